@@ -1,14 +1,37 @@
-use crate::deposits;
+use crate::{deposits, slashing_protection, validator_create, validator_exit, wallet};
 use clap::{App, Arg, SubCommand};
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new("account_manager")
         .visible_aliases(&["a", "am", "account", "account_manager"])
         .about("Utilities for generating and managing Ethereum 2.0 accounts.")
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIR")
+                .help(
+                    "The path where the validator keystore passwords will be stored. \
+                    Defaults to ~/.lighthouse/secrets",
+                )
+                .global(true)
+                .takes_value(true),
+        )
+        .subcommand(wallet::cli_app())
         .subcommand(
             SubCommand::with_name("validator")
                 .about("Generate or manage Ethereum 2.0 validators.")
                 .subcommand(deposits::cli_app())
+                .subcommand(validator_create::cli_app())
+                .subcommand(validator_exit::cli_app())
+                .subcommand(slashing_protection::cli_app())
+                .subcommand(
+                    SubCommand::with_name("migrate")
+                        .about("Converts each validator directory containing unencrypted SSZ \
+                                keypair files into EIP-2335 keystores. A random password is \
+                                generated for each keystore and stored in --secrets-dir. The \
+                                unencrypted files are deleted once the keystores have been \
+                                verified.")
+                )
                 .subcommand(
                     SubCommand::with_name("new")
                         .about("Create a new Ethereum 2.0 validator.")
@@ -11,7 +11,7 @@ use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use tokio::timer::Delay;
 use types::EthSpec;
-use validator_client::validator_directory::ValidatorDirectoryBuilder;
+use validator_client::validator_directory::{ValidatorDirectoryBuilder, DEFAULT_SECRETS_DIR};
 use web3::{
     transports::Ipc,
     types::{Address, SyncInfo, SyncState},
@@ -89,6 +89,11 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
         "validator_dir",
         PathBuf::new().join(".lighthouse").join("validators"),
     )?;
+    let secrets_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        "secrets-dir",
+        PathBuf::from(DEFAULT_SECRETS_DIR),
+    )?;
     let eth1_ipc_path: PathBuf = clap_utils::parse_required(matches, "eth1-ipc")?;
     let from_address: Address = clap_utils::parse_required(matches, "from-address")?;
     let deposit_gwei = clap_utils::parse_optional(matches, "deposit-gwei")?
@@ -155,7 +160,7 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Res
                     }),
             )?
             .create_directory(validator_dir.clone())?
-            .write_keypair_files()?
+            .write_keystore_files(secrets_dir.clone())?
             .write_eth1_data_file()?
             .build()?;
     }
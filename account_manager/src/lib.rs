@@ -1,5 +1,9 @@
 mod cli;
 mod deposits;
+mod slashing_protection;
+mod validator_create;
+mod validator_exit;
+mod wallet;
 
 use clap::ArgMatches;
 use deposit_contract::DEPOSIT_GAS;
@@ -13,7 +17,9 @@ use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use types::{ChainSpec, EthSpec};
-use validator_client::validator_directory::{ValidatorDirectory, ValidatorDirectoryBuilder};
+use validator_client::validator_directory::{
+    ValidatorDirectory, ValidatorDirectoryBuilder, DEFAULT_SECRETS_DIR,
+};
 use web3::{
     transports::Http,
     types::{Address, TransactionRequest, U256},
@@ -41,6 +47,12 @@ pub fn run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Result<
 
     fs::create_dir_all(&datadir).map_err(|e| format!("Failed to create datadir: {}", e))?;
 
+    let secrets_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        "secrets-dir",
+        PathBuf::from(DEFAULT_SECRETS_DIR),
+    )?;
+
     info!(
         log,
         "Located data directory";
@@ -50,11 +62,24 @@ pub fn run<T: EthSpec>(matches: &ArgMatches, mut env: Environment<T>) -> Result<
     match matches.subcommand() {
         ("validator", Some(matches)) => match matches.subcommand() {
             ("deposited", Some(matches)) => deposits::cli_run(matches, env)?,
-            ("new", Some(matches)) => run_new_validator_subcommand(matches, datadir, env)?,
+            (validator_create::CMD, Some(matches)) => {
+                validator_create::cli_run(matches, env, datadir, secrets_dir)?
+            }
+            (validator_exit::CMD, Some(matches)) => {
+                validator_exit::cli_run(matches, env, secrets_dir)?
+            }
+            ("new", Some(matches)) => {
+                run_new_validator_subcommand(matches, datadir, secrets_dir, env)?
+            }
+            ("migrate", Some(_)) => run_migrate_subcommand(datadir, secrets_dir, &log)?,
+            (slashing_protection::CMD, Some(matches)) => {
+                slashing_protection::cli_run(matches, env, datadir)?
+            }
             _ => {
                 return Err("Invalid 'validator new' command. See --help.".to_string());
             }
         },
+        (wallet::CMD, Some(matches)) => wallet::cli_run(matches, &log)?,
         _ => {
             return Err("Invalid 'validator' command. See --help.".to_string());
         }
@@ -75,6 +100,7 @@ enum KeygenMethod {
 fn run_new_validator_subcommand<T: EthSpec>(
     matches: &ArgMatches,
     datadir: PathBuf,
+    secrets_dir: PathBuf,
     mut env: Environment<T>,
 ) -> Result<(), String> {
     let mut context = env.core_context();
@@ -161,6 +187,7 @@ fn run_new_validator_subcommand<T: EthSpec>(
 
     let validators = make_validators(
         datadir.clone(),
+        secrets_dir,
         &methods,
         deposit_value,
         &context.eth2_config.spec,
@@ -248,6 +275,7 @@ fn run_new_validator_subcommand<T: EthSpec>(
 /// Produces a validator directory for each of the key generation methods provided in `methods`.
 fn make_validators(
     datadir: PathBuf,
+    secrets_dir: PathBuf,
     methods: &[KeygenMethod],
     deposit_value: u64,
     spec: &ChainSpec,
@@ -267,7 +295,7 @@ fn make_validators(
 
             let validator = builder
                 .create_directory(datadir.clone())?
-                .write_keypair_files()?
+                .write_keystore_files(secrets_dir.clone())?
                 .write_eth1_data_file()?
                 .build()?;
 
@@ -288,6 +316,46 @@ fn make_validators(
         .collect()
 }
 
+/// Converts every validator directory in `datadir` that contains unencrypted keypair files into
+/// one that contains EIP-2335 keystores.
+fn run_migrate_subcommand(
+    datadir: PathBuf,
+    secrets_dir: PathBuf,
+    log: &Logger,
+) -> Result<(), String> {
+    let mut migrated = 0;
+
+    for entry in fs::read_dir(&datadir)
+        .map_err(|e| format!("Failed to read datadir {:?}: {}", datadir, e))?
+    {
+        let path = entry
+            .map_err(|e| format!("Failed to read datadir entry: {}", e))?
+            .path();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        if ValidatorDirectory::migrate_to_keystores(&path, &secrets_dir)? {
+            info!(
+                log,
+                "Migrated validator to keystores";
+                "path" => format!("{:?}", path)
+            );
+            migrated += 1;
+        }
+    }
+
+    info!(
+        log,
+        "Migration complete";
+        "migrated" => migrated,
+        "secrets_dir" => format!("{:?}", secrets_dir),
+    );
+
+    Ok(())
+}
+
 /// For each `ValidatorDirectory`, submit a deposit transaction to the `eth1_endpoint`.
 ///
 /// Returns success as soon as the eth1 endpoint accepts the transaction (i.e., does not wait for
@@ -0,0 +1,241 @@
+use crate::wallet::{find_wallet, save_wallet, wallets_dir, WALLETS_DIR_FLAG};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use eth2_wallet::{PlainText, Wallet};
+use slog::info;
+use std::path::{Path, PathBuf};
+use types::{ChainSpec, EthSpec};
+use validator_client::validator_directory::{
+    random_password, read_password, ValidatorDirectory, ValidatorDirectoryBuilder,
+};
+
+pub const CMD: &str = "create";
+pub const WALLET_NAME_FLAG: &str = "wallet-name";
+pub const WALLET_PASSWORD_FLAG: &str = "wallet-password";
+pub const DEPOSIT_GWEI_FLAG: &str = "deposit-gwei";
+pub const COUNT_FLAG: &str = "count";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Creates new validators from an existing EIP-2386 wallet using the EIP-2334 format. \
+            The wallet's `nextaccount` is incremented for each validator created.",
+        )
+        .arg(
+            Arg::with_name(WALLET_NAME_FLAG)
+                .long(WALLET_NAME_FLAG)
+                .value_name("WALLET_NAME")
+                .help("Use the wallet identified by this name")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(WALLET_PASSWORD_FLAG)
+                .long(WALLET_PASSWORD_FLAG)
+                .value_name("WALLET_PASSWORD_PATH")
+                .help("A path to a file containing the password which will unlock the wallet.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(WALLETS_DIR_FLAG)
+                .long(WALLETS_DIR_FLAG)
+                .value_name("WALLETS_DIRECTORY")
+                .help("A path containing Eth2 EIP-2386 wallets. Defaults to ~/.lighthouse/wallets")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(DEPOSIT_GWEI_FLAG)
+                .long(DEPOSIT_GWEI_FLAG)
+                .value_name("DEPOSIT_GWEI")
+                .help(
+                    "The GWEI value of the deposit amount. Defaults to the minimum amount \
+                    required for an active validator (MAX_EFFECTIVE_BALANCE)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(COUNT_FLAG)
+                .long(COUNT_FLAG)
+                .value_name("VALIDATOR_COUNT")
+                .help("The number of validators to create")
+                .takes_value(true)
+                .default_value("1"),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(
+    matches: &ArgMatches,
+    mut env: Environment<T>,
+    validator_dir: PathBuf,
+    secrets_dir: PathBuf,
+) -> Result<(), String> {
+    let context = env.core_context();
+    let log = context.log.clone();
+    let spec = context.eth2_config.spec;
+
+    let name: String = clap_utils::parse_required(matches, WALLET_NAME_FLAG)?;
+    let wallet_password_path: PathBuf = clap_utils::parse_required(matches, WALLET_PASSWORD_FLAG)?;
+    let deposit_gwei = clap_utils::parse_optional(matches, DEPOSIT_GWEI_FLAG)?
+        .unwrap_or_else(|| spec.max_effective_balance);
+    let count: usize = clap_utils::parse_required(matches, COUNT_FLAG)?;
+
+    let wallets_dir = wallets_dir(matches)?;
+    let (wallet_path, mut wallet) = find_wallet(&wallets_dir, &name)?
+        .ok_or_else(|| format!("No wallet named {} in {:?}", name, wallets_dir))?;
+    let wallet_password = read_password(&wallet_password_path)?;
+
+    for _ in 0..count {
+        let index = wallet.nextaccount();
+        let validator = create_validator(
+            &mut wallet,
+            &wallet_path,
+            &wallet_password,
+            &spec,
+            deposit_gwei,
+            &validator_dir,
+            &secrets_dir,
+        )?;
+
+        info!(
+            log,
+            "Created validator";
+            "wallet_index" => index,
+            "voting_pubkey" => validator
+                .voting_keypair
+                .as_ref()
+                .map(|kp| format!("{:?}", kp.pk))
+                .unwrap_or_else(|| "None".to_string()),
+            "directory" => validator
+                .directory
+                .to_str()
+                .unwrap_or("<unknown>"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Derives the next validator from `wallet`, writing its keystores to a new directory in
+/// `validator_dir` and their passwords to `secrets_dir`.
+///
+/// The incremented `nextaccount` of `wallet` is saved to `wallet_path` before any keys are
+/// written.
+fn create_validator(
+    wallet: &mut Wallet,
+    wallet_path: &Path,
+    wallet_password: &PlainText,
+    spec: &ChainSpec,
+    deposit_gwei: u64,
+    validator_dir: &Path,
+    secrets_dir: &Path,
+) -> Result<ValidatorDirectory, String> {
+    let voting_password = random_password();
+    let withdrawal_password = random_password();
+
+    let keystores = wallet
+        .next_validator(
+            wallet_password.as_bytes(),
+            voting_password.as_bytes(),
+            withdrawal_password.as_bytes(),
+        )
+        .map_err(|e| format!("Unable to derive validator keys: {:?}", e))?;
+
+    // Persist the incremented `nextaccount` before writing any keys to disk. If we crash between
+    // these steps then an index is skipped, which is far safer than the same keys being
+    // generated twice.
+    save_wallet(wallet, wallet_path)?;
+
+    ValidatorDirectoryBuilder::default()
+        .spec(spec.clone())
+        .custom_deposit_amount(deposit_gwei)
+        .keystores(
+            keystores.voting,
+            voting_password,
+            keystores.withdrawal,
+            withdrawal_password,
+        )?
+        .create_directory(validator_dir.to_path_buf())?
+        .write_keystore_files(secrets_dir.to_path_buf())?
+        .write_eth1_data_file()?
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::wallet_path;
+    use eth2_keystore::Keystore;
+    use eth2_wallet::{
+        bip39::{Language, Mnemonic},
+        WalletBuilder,
+    };
+    use std::fs::File;
+    use tempdir::TempDir;
+    use types::MinimalEthSpec;
+
+    const MNEMONIC: &str =
+        "enemy fog enlist laundry nurse hungry discover turkey holiday resemble glad discover";
+
+    #[test]
+    fn create_validators_from_wallet() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+        let validator_dir = TempDir::new("validators").expect("should create test dir");
+        let secrets_dir = TempDir::new("secrets").expect("should create test dir");
+        let spec = MinimalEthSpec::default_spec();
+        let wallet_password = PlainText::from(b"password".to_vec());
+
+        let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+        let mut wallet =
+            WalletBuilder::from_mnemonic(&mnemonic, wallet_password.as_bytes(), "a".into())
+                .unwrap()
+                .build()
+                .unwrap();
+        let wallet_path = wallet_path(wallets_dir.path(), &wallet);
+        save_wallet(&wallet, &wallet_path).expect("should save wallet");
+
+        let validators = (0..2)
+            .map(|_| {
+                create_validator(
+                    &mut wallet,
+                    &wallet_path,
+                    &wallet_password,
+                    &spec,
+                    spec.max_effective_balance,
+                    validator_dir.path(),
+                    secrets_dir.path(),
+                )
+                .expect("should create validator")
+            })
+            .collect::<Vec<_>>();
+
+        let (_, saved) = find_wallet(wallets_dir.path(), "a")
+            .expect("should load wallets")
+            .expect("should find wallet");
+        assert_eq!(saved.nextaccount(), 2, "should persist nextaccount");
+
+        assert_ne!(
+            validators[0].voting_keypair, validators[1].voting_keypair,
+            "should derive a new key for each validator"
+        );
+
+        for (index, validator) in validators.into_iter().enumerate() {
+            let keystore = File::open(validator.directory.join("voting-keystore.json"))
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|file| Keystore::from_json_reader(file).map_err(|e| format!("{:?}", e)))
+                .expect("should read voting keystore");
+            assert_eq!(
+                keystore.path(),
+                format!("m/12381/3600/{}/0/0", index),
+                "should preserve the derivation path"
+            );
+
+            let loaded = ValidatorDirectory::load_for_signing(
+                validator.directory.clone(),
+                secrets_dir.path(),
+            )
+            .expect("should load validator");
+            assert_eq!(loaded.voting_keypair, validator.voting_keypair);
+        }
+    }
+}
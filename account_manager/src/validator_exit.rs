@@ -0,0 +1,151 @@
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use futures::Future;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::info;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use types::{ChainSpec, Epoch, EthSpec, SignedVoluntaryExit, VoluntaryExit};
+use validator_client::validator_directory::ValidatorDirectory;
+
+pub const CMD: &str = "exit";
+pub const VALIDATOR_DIR_FLAG: &str = "validator-dir";
+pub const BEACON_NODE_FLAG: &str = "beacon-node";
+
+/// The phrase the user must type to confirm that they want to exit their validator.
+pub const CONFIRMATION_PHRASE: &str = "Exit my validator";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Signs a voluntary exit for a validator and submits it to a beacon node. An exited \
+            validator can never rejoin the validator set and its funds cannot be transferred \
+            until withdrawals are enabled.",
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help("The directory of the validator to exit, containing its voting keystore.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(BEACON_NODE_FLAG)
+                .long(BEACON_NODE_FLAG)
+                .value_name("NETWORK_ADDRESS")
+                .help("Address to a beacon node HTTP API")
+                .default_value("http://localhost:5052")
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run<T: EthSpec>(
+    matches: &ArgMatches,
+    mut env: Environment<T>,
+    secrets_dir: PathBuf,
+) -> Result<(), String> {
+    let log = env.core_context().log;
+    let spec = env.core_context().eth2_config.spec;
+
+    let validator_dir: PathBuf = clap_utils::parse_required(matches, VALIDATOR_DIR_FLAG)?;
+    let server: String = clap_utils::parse_required(matches, BEACON_NODE_FLAG)?;
+
+    let validator = ValidatorDirectory::load_for_signing(validator_dir.clone(), &secrets_dir)?;
+    let keypair = validator
+        .voting_keypair
+        .ok_or_else(|| format!("No voting keypair in {:?}", validator_dir))?;
+
+    let remote_node = RemoteBeaconNode::<T>::new(server.clone())?;
+    let beacon = remote_node.http.beacon();
+
+    let (head, fork, genesis_validators_root, validators) = env
+        .runtime()
+        .block_on(beacon.get_head().join4(
+            beacon.get_fork(),
+            beacon.get_genesis_validators_root(),
+            beacon.get_validators(vec![keypair.pk.clone()], None),
+        ))
+        .map_err(|e| format!("Unable to query beacon node at {}: {:?}", server, e))?;
+
+    let validator_response = validators
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Beacon node did not return the validator".to_string())?;
+    let validator_index = validator_response.validator_index.ok_or_else(|| {
+        format!(
+            "Validator {:?} is unknown to the beacon node, has its deposit been processed?",
+            keypair.pk
+        )
+    })?;
+    if let Some(validator) = validator_response.validator {
+        if validator.exit_epoch != spec.far_future_epoch {
+            return Err(format!(
+                "Validator {:?} is already exiting at epoch {}",
+                keypair.pk, validator.exit_epoch
+            ));
+        }
+    }
+
+    let epoch = head.slot.epoch(T::slots_per_epoch());
+
+    print_warning(&keypair.pk.as_hex_string(), validator_index, epoch, &spec);
+    if !confirm()? {
+        return Err("Exit not confirmed, no exit was submitted".to_string());
+    }
+
+    let signed_exit: SignedVoluntaryExit = VoluntaryExit {
+        epoch,
+        validator_index: validator_index as u64,
+    }
+    .sign(&keypair.sk, &fork, genesis_validators_root, &spec);
+
+    env.runtime()
+        .block_on(beacon.voluntary_exit(signed_exit))
+        .map_err(|e| format!("Beacon node did not accept the exit: {:?}", e))?;
+
+    info!(
+        log,
+        "Submitted voluntary exit";
+        "epoch" => epoch,
+        "validator_index" => validator_index,
+        "voting_pubkey" => keypair.pk.as_hex_string(),
+    );
+
+    Ok(())
+}
+
+/// Describes the consequences of exiting to the user.
+fn print_warning(pubkey: &str, validator_index: usize, epoch: Epoch, spec: &ChainSpec) {
+    println!();
+    println!("Publishing a voluntary exit for validator:");
+    println!();
+    println!("\tpubkey: {}", pubkey);
+    println!("\tindex:  {}", validator_index);
+    println!("\tepoch:  {}", epoch);
+    println!();
+    println!("WARNING: THIS IS AN IRREVERSIBLE OPERATION.");
+    println!();
+    println!("Once exited, the validator can never rejoin the validator set. The validator ");
+    println!("must keep performing its duties until it has exited, which takes at least ");
+    println!(
+        "{} epochs. Its funds cannot be withdrawn until withdrawals are enabled.",
+        spec.max_seed_lookahead
+    );
+    println!();
+    println!("To confirm, type the phrase: {}", CONFIRMATION_PHRASE);
+}
+
+/// Reads a line from stdin, returning `true` if it matches `CONFIRMATION_PHRASE`.
+fn confirm() -> Result<bool, String> {
+    io::stdout()
+        .flush()
+        .map_err(|e| format!("Unable to flush stdout: {}", e))?;
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Unable to read from stdin: {}", e))?;
+
+    Ok(input.trim() == CONFIRMATION_PHRASE)
+}
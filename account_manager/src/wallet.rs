@@ -0,0 +1,490 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use eth2_wallet::{
+    bip39::{Language, Mnemonic, MnemonicType},
+    Wallet, WalletBuilder,
+};
+use slog::{info, warn, Logger};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use validator_client::validator_directory::{create_private_file, random_password, read_password};
+
+pub const CMD: &str = "wallet";
+pub const CREATE_CMD: &str = "create";
+pub const LIST_CMD: &str = "list";
+pub const RECOVER_CMD: &str = "recover";
+
+pub const NAME_FLAG: &str = "name";
+pub const PASSWORD_FLAG: &str = "wallet-password";
+pub const MNEMONIC_FLAG: &str = "mnemonic-path";
+pub const WALLETS_DIR_FLAG: &str = "wallets-dir";
+
+/// The default location of wallets, relative to the home directory.
+pub const DEFAULT_WALLETS_DIR: &str = ".lighthouse/wallets";
+
+/// The file extension of a temporary wallet file, used whilst atomically updating a wallet.
+const TEMP_WALLET_EXTENSION: &str = "json.tmp";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    let name_arg = Arg::with_name(NAME_FLAG)
+        .long(NAME_FLAG)
+        .value_name("WALLET_NAME")
+        .help("The wallet will be created with this name. It is not allowed to create two wallets with the same name for the same --wallets-dir.")
+        .takes_value(true)
+        .required(true);
+    let password_arg = Arg::with_name(PASSWORD_FLAG)
+        .long(PASSWORD_FLAG)
+        .value_name("WALLET_PASSWORD_PATH")
+        .help("A path to a file containing the password which will unlock the wallet. If the file does not exist, a random password will be generated and saved at that path. To avoid confusion, if the file does not already exist it must include a '.pass' suffix.")
+        .takes_value(true)
+        .required(true);
+
+    App::new(CMD)
+        .about("Create, list or recover EIP-2386 hierarchical-deterministic wallets.")
+        .arg(
+            Arg::with_name(WALLETS_DIR_FLAG)
+                .long(WALLETS_DIR_FLAG)
+                .value_name("WALLETS_DIRECTORY")
+                .help("A path containing Eth2 EIP-2386 wallets. Defaults to ~/.lighthouse/wallets")
+                .takes_value(true)
+                .global(true),
+        )
+        .subcommand(
+            SubCommand::with_name(CREATE_CMD)
+                .about("Creates a new wallet from a randomly generated BIP-39 mnemonic.")
+                .arg(name_arg.clone())
+                .arg(password_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name(LIST_CMD)
+                .about("Lists the names and UUIDs of all wallets in --wallets-dir."),
+        )
+        .subcommand(
+            SubCommand::with_name(RECOVER_CMD)
+                .about("Recovers a wallet from a BIP-39 mnemonic. The recovered wallet will derive the same validator keys as the original, however its `nextaccount` is reset to zero.")
+                .arg(name_arg)
+                .arg(password_arg)
+                .arg(
+                    Arg::with_name(MNEMONIC_FLAG)
+                        .long(MNEMONIC_FLAG)
+                        .value_name("MNEMONIC_PATH")
+                        .help("If present, the mnemonic will be read from this file. Otherwise, it will be read from stdin.")
+                        .takes_value(true),
+                ),
+        )
+}
+
+pub fn cli_run(matches: &ArgMatches, log: &Logger) -> Result<(), String> {
+    let wallets_dir = wallets_dir(matches)?;
+
+    match matches.subcommand() {
+        (CREATE_CMD, Some(matches)) => {
+            let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+            let wallet = create_wallet(matches, &wallets_dir, &mnemonic, log)?;
+
+            println!();
+            println!("Your wallet's 24-word BIP-39 mnemonic is:");
+            println!();
+            println!("\t{}", mnemonic.phrase());
+            println!();
+            println!("This mnemonic can be used to fully restore your wallet, should ");
+            println!("you lose the JSON file or your password. ");
+            println!();
+            println!("It is very important that you DO NOT SHARE this mnemonic as it will ");
+            println!("reveal the private keys of all validators and keys generated with ");
+            println!("this wallet. That would be catastrophic.");
+            println!();
+            println!("It is also important to store a backup of this mnemonic so you can ");
+            println!("recover your private keys in the case of data loss. Writing it on ");
+            println!("a piece of paper and storing it in a safe place would be prudent.");
+            println!();
+            println!("Your wallet's UUID is:");
+            println!();
+            println!("\t{}", wallet.uuid());
+            println!();
+            println!("You do not need to backup your UUID or keep it secret.");
+
+            Ok(())
+        }
+        (LIST_CMD, Some(_)) => {
+            for (path, wallet) in load_wallets(&wallets_dir)? {
+                println!(
+                    "{}\t{}\t{}\tnextaccount: {}",
+                    wallet.name(),
+                    wallet.uuid(),
+                    path.display(),
+                    wallet.nextaccount()
+                );
+            }
+
+            Ok(())
+        }
+        (RECOVER_CMD, Some(matches)) => {
+            let phrase = if let Some(path) = matches.value_of(MNEMONIC_FLAG) {
+                fs::read_to_string(path)
+                    .map_err(|e| format!("Unable to read mnemonic file {}: {}", path, e))?
+            } else {
+                println!("Enter the mnemonic phrase:");
+                let mut phrase = String::new();
+                io::stdin()
+                    .read_line(&mut phrase)
+                    .map_err(|e| format!("Unable to read mnemonic from stdin: {}", e))?;
+                phrase
+            };
+
+            let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+                .map_err(|e| format!("Unable to parse mnemonic: {}", e))?;
+
+            let wallet = create_wallet(matches, &wallets_dir, &mnemonic, log)?;
+
+            warn!(
+                log,
+                "Wallet nextaccount reset to zero";
+                "msg" => "validators created with the original wallet must not be created again",
+                "uuid" => format!("{}", wallet.uuid()),
+            );
+
+            Ok(())
+        }
+        ("", _) => Err("No subcommand provided, see --help for options".to_string()),
+        (command, _) => Err(format!("No such subcommand `{}`", command)),
+    }
+}
+
+/// Parses the `--wallets-dir` flag, falling back to `DEFAULT_WALLETS_DIR`.
+pub fn wallets_dir(matches: &ArgMatches) -> Result<PathBuf, String> {
+    clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        WALLETS_DIR_FLAG,
+        PathBuf::from(DEFAULT_WALLETS_DIR),
+    )
+}
+
+/// Creates a new wallet in `wallets_dir` from `mnemonic`, using the name and password specified
+/// in `matches`.
+fn create_wallet(
+    matches: &ArgMatches,
+    wallets_dir: &Path,
+    mnemonic: &Mnemonic,
+    log: &Logger,
+) -> Result<Wallet, String> {
+    let name: String = clap_utils::parse_required(matches, NAME_FLAG)?;
+    let password_path: PathBuf = clap_utils::parse_required(matches, PASSWORD_FLAG)?;
+
+    if find_wallet(wallets_dir, &name)?.is_some() {
+        return Err(format!("A wallet named {} already exists", name));
+    }
+
+    // Create a random password if the file does not exist.
+    if !password_path.exists() {
+        // To prevent users from accidentally supplying their password to the PASSWORD_FLAG and
+        // create a file with that name, we require that the password has a .pass suffix.
+        if password_path.extension() != Some(OsStr::new("pass")) {
+            return Err(format!(
+                "Only creates a password file if that file ends in .pass: {:?}",
+                password_path
+            ));
+        }
+
+        create_private_file(&password_path)?
+            .write_all(random_password().as_bytes())
+            .map_err(|e| format!("Unable to write to {:?}: {:?}", password_path, e))?;
+    }
+
+    let password = read_password(&password_path)?;
+
+    let wallet = WalletBuilder::from_mnemonic(mnemonic, password.as_bytes(), name)
+        .map_err(|e| format!("Unable to create wallet builder: {:?}", e))?
+        .build()
+        .map_err(|e| format!("Unable to create wallet: {:?}", e))?;
+
+    fs::create_dir_all(wallets_dir)
+        .map_err(|e| format!("Unable to create wallets directory: {}", e))?;
+
+    let path = wallet_path(wallets_dir, &wallet);
+    if path.exists() {
+        return Err(format!("Wallet file already exists at {:?}", path));
+    }
+    save_wallet(&wallet, &path)?;
+
+    info!(
+        log,
+        "Created wallet";
+        "name" => wallet.name(),
+        "uuid" => format!("{}", wallet.uuid()),
+        "path" => format!("{:?}", path),
+    );
+
+    Ok(wallet)
+}
+
+/// Returns the path of the JSON file for `wallet`.
+///
+/// Wallets are stored as `<uuid>.json`, so renaming a wallet does not require moving its file.
+pub fn wallet_path(wallets_dir: &Path, wallet: &Wallet) -> PathBuf {
+    wallets_dir.join(format!("{}.json", wallet.uuid()))
+}
+
+/// Loads all wallets from `wallets_dir`, alongside the path to each wallet file.
+///
+/// Returns an empty list if `wallets_dir` does not exist.
+pub fn load_wallets(wallets_dir: &Path) -> Result<Vec<(PathBuf, Wallet)>, String> {
+    if !wallets_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut wallets = fs::read_dir(wallets_dir)
+        .map_err(|e| format!("Unable to read wallets directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "json"))
+        .map(|path| {
+            File::open(&path)
+                .map_err(|e| format!("Unable to open wallet {:?}: {}", path, e))
+                .and_then(|file| {
+                    Wallet::from_json_reader(file)
+                        .map_err(|e| format!("Unable to parse wallet {:?}: {:?}", path, e))
+                })
+                .map(|wallet| (path, wallet))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    wallets.sort_by(|a, b| a.1.name().cmp(b.1.name()));
+
+    Ok(wallets)
+}
+
+/// Finds the wallet named `name` in `wallets_dir`, if any.
+pub fn find_wallet(wallets_dir: &Path, name: &str) -> Result<Option<(PathBuf, Wallet)>, String> {
+    Ok(load_wallets(wallets_dir)?
+        .into_iter()
+        .find(|(_, wallet)| wallet.name() == name))
+}
+
+/// Atomically writes `wallet` to `path`.
+///
+/// The wallet is first written to a temporary file which is then renamed over `path`, so a crash
+/// never leaves a partially written wallet (or a wallet with a stale `nextaccount`) on disk.
+pub fn save_wallet(wallet: &Wallet, path: &Path) -> Result<(), String> {
+    let temp_path = path.with_extension(TEMP_WALLET_EXTENSION);
+
+    if temp_path.exists() {
+        fs::remove_file(&temp_path)
+            .map_err(|e| format!("Unable to remove stale temp wallet {:?}: {}", temp_path, e))?;
+    }
+
+    let mut file = create_private_file(&temp_path)?;
+    wallet
+        .to_json_writer(&mut file)
+        .map_err(|e| format!("Unable to write wallet to {:?}: {:?}", temp_path, e))?;
+    file.sync_all()
+        .map_err(|e| format!("Unable to sync wallet file {:?}: {}", temp_path, e))?;
+
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Unable to move wallet into {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempdir::TempDir;
+
+    const MNEMONIC: &str =
+        "enemy fog enlist laundry nurse hungry discover turkey holiday resemble glad discover";
+
+    fn matches<'a>(args: &[&str]) -> ArgMatches<'a> {
+        cli_app()
+            .get_matches_from_safe(std::iter::once(CMD).chain(args.iter().cloned()))
+            .expect("should parse arguments")
+    }
+
+    fn run(args: &[&str]) -> Result<(), String> {
+        let log = environment::null_logger().expect("should build logger");
+        cli_run(&matches(args), &log)
+    }
+
+    #[test]
+    fn create_wallet_with_random_password() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+        let password_dir = TempDir::new("passwords").expect("should create test dir");
+        let wallets = wallets_dir.path().to_str().unwrap();
+        let password_path = password_dir.path().join("wallet.pass");
+        let password = password_path.to_str().unwrap();
+
+        run(&[
+            "--wallets-dir",
+            wallets,
+            CREATE_CMD,
+            "--name",
+            "a",
+            "--wallet-password",
+            password,
+        ])
+        .expect("should create wallet");
+
+        let mode = fs::metadata(&password_path)
+            .expect("should create password file")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600, "password file should be private");
+
+        let (path, wallet) = find_wallet(wallets_dir.path(), "a")
+            .expect("should load wallets")
+            .expect("should find wallet");
+        assert_eq!(path, wallet_path(wallets_dir.path(), &wallet));
+        assert_eq!(wallet.nextaccount(), 0);
+        wallet
+            .decrypt_seed(read_password(&password_path).unwrap().as_bytes())
+            .expect("should decrypt with the generated password");
+
+        assert!(
+            run(&[
+                "--wallets-dir",
+                wallets,
+                CREATE_CMD,
+                "--name",
+                "a",
+                "--wallet-password",
+                password,
+            ])
+            .is_err(),
+            "should not create two wallets with the same name"
+        );
+    }
+
+    #[test]
+    fn create_wallet_requires_pass_suffix() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+        let password_dir = TempDir::new("passwords").expect("should create test dir");
+        let password_path = password_dir.path().join("hunter2");
+
+        assert!(
+            run(&[
+                "--wallets-dir",
+                wallets_dir.path().to_str().unwrap(),
+                CREATE_CMD,
+                "--name",
+                "a",
+                "--wallet-password",
+                password_path.to_str().unwrap(),
+            ])
+            .is_err(),
+            "should not create a password file without a .pass suffix"
+        );
+        assert!(!password_path.exists(), "should not create password file");
+        assert!(load_wallets(wallets_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn recover_wallet_derives_the_same_keys() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+        let other_dir = TempDir::new("passwords").expect("should create test dir");
+        let password_path = other_dir.path().join("wallet.pass");
+        let mnemonic_path = other_dir.path().join("mnemonic");
+        fs::write(&mnemonic_path, format!("{}\n", MNEMONIC)).expect("should write mnemonic");
+
+        run(&[
+            "--wallets-dir",
+            wallets_dir.path().to_str().unwrap(),
+            RECOVER_CMD,
+            "--name",
+            "recovered",
+            "--wallet-password",
+            password_path.to_str().unwrap(),
+            "--mnemonic-path",
+            mnemonic_path.to_str().unwrap(),
+        ])
+        .expect("should recover wallet");
+
+        let (_, mut recovered) = find_wallet(wallets_dir.path(), "recovered")
+            .expect("should load wallets")
+            .expect("should find wallet");
+        assert_eq!(recovered.nextaccount(), 0);
+
+        let password = read_password(&password_path).unwrap();
+        let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+        let mut original = WalletBuilder::from_mnemonic(&mnemonic, b"original", "original".into())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let recovered_keystores = recovered
+            .next_validator(password.as_bytes(), b"voting", b"withdrawal")
+            .expect("should derive from recovered wallet");
+        let original_keystores = original
+            .next_validator(b"original", b"voting", b"withdrawal")
+            .expect("should derive from original wallet");
+
+        assert_eq!(
+            recovered_keystores.voting.pubkey(),
+            original_keystores.voting.pubkey()
+        );
+        assert_eq!(
+            recovered_keystores.withdrawal.pubkey(),
+            original_keystores.withdrawal.pubkey()
+        );
+    }
+
+    #[test]
+    fn list_wallets() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+
+        assert!(
+            load_wallets(&wallets_dir.path().join("missing"))
+                .expect("should not fail on a missing directory")
+                .is_empty(),
+            "should list no wallets"
+        );
+
+        let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+        for name in &["b", "a"] {
+            let wallet = WalletBuilder::from_mnemonic(&mnemonic, b"password", name.to_string())
+                .unwrap()
+                .build()
+                .unwrap();
+            save_wallet(&wallet, &wallet_path(wallets_dir.path(), &wallet))
+                .expect("should save wallet");
+        }
+        fs::write(wallets_dir.path().join("notes.txt"), "not a wallet").unwrap();
+
+        let names = load_wallets(wallets_dir.path())
+            .expect("should load wallets")
+            .into_iter()
+            .map(|(_, wallet)| wallet.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+
+        run(&[
+            "--wallets-dir",
+            wallets_dir.path().to_str().unwrap(),
+            LIST_CMD,
+        ])
+        .expect("should list wallets");
+    }
+
+    #[test]
+    fn save_wallet_replaces_existing_file() {
+        let wallets_dir = TempDir::new("wallets").expect("should create test dir");
+        let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+        let mut wallet = WalletBuilder::from_mnemonic(&mnemonic, b"password", "a".into())
+            .unwrap()
+            .build()
+            .unwrap();
+        let path = wallet_path(wallets_dir.path(), &wallet);
+
+        save_wallet(&wallet, &path).expect("should save wallet");
+        wallet
+            .next_validator(b"password", b"voting", b"withdrawal")
+            .unwrap();
+        save_wallet(&wallet, &path).expect("should save wallet again");
+
+        let (_, loaded) = find_wallet(wallets_dir.path(), "a").unwrap().unwrap();
+        assert_eq!(loaded.nextaccount(), 1);
+        assert!(
+            !path.with_extension(TEMP_WALLET_EXTENSION).exists(),
+            "should not leave a temp file behind"
+        );
+    }
+}
@@ -17,6 +17,7 @@ use crate::naive_aggregation_pool::{Error as NaiveAggregationError, NaiveAggrega
 use crate::observed_attestations::{Error as AttestationObservationError, ObservedAttestations};
 use crate::observed_attesters::{ObservedAggregators, ObservedAttesters};
 use crate::observed_block_producers::ObservedBlockProducers;
+use crate::observed_operations::{ObservationOutcome, ObservedOperations, VerifiedOperation};
 use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::shuffling_cache::ShufflingCache;
 use crate::snapshot_cache::SnapshotCache;
@@ -42,7 +43,7 @@ use store::iter::{
     BlockRootsIterator, ParentRootBlockIterator, ReverseBlockRootIterator,
     ReverseStateRootIterator, StateRootsIterator,
 };
-use store::{Error as DBError, Store};
+use store::{Error as DBError, SimpleStoreItem, Store};
 use types::*;
 
 // Text included in blocks.
@@ -186,6 +187,13 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub observed_aggregators: ObservedAggregators<T::EthSpec>,
     /// Maintains a record of which validators have proposed blocks for each slot.
     pub observed_block_producers: ObservedBlockProducers<T::EthSpec>,
+    /// Maintains a record of which validators have been seen in valid gossip voluntary exits.
+    pub(crate) observed_voluntary_exits: ObservedOperations<SignedVoluntaryExit, T::EthSpec>,
+    /// Maintains a record of which validators have been seen in valid gossip proposer slashings.
+    pub(crate) observed_proposer_slashings: ObservedOperations<ProposerSlashing, T::EthSpec>,
+    /// Maintains a record of which validators have been seen in valid gossip attester slashings.
+    pub(crate) observed_attester_slashings:
+        ObservedOperations<AttesterSlashing<T::EthSpec>, T::EthSpec>,
     /// Provides information from the Ethereum 1 (PoW) chain.
     pub eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec, T::Store>>,
     /// Stores a "snapshot" of the chain at the time the head-of-the-chain block was received.
@@ -220,12 +228,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// ## Notes:
     ///
-    /// In this function we first obtain the head, persist fork choice, then persist the head. We
-    /// do it in this order to ensure that the persisted head is always from a time prior to fork
-    /// choice.
+    /// In this function we first obtain the head, then the fork choice. We do it in this order to
+    /// ensure that the persisted head is always from a time prior to fork choice.
     ///
     /// We want to ensure that the head never out dates the fork choice to avoid having references
-    /// to blocks that do not exist in fork choice.
+    /// to blocks that do not exist in fork choice. Both are written in a single batch, so that
+    /// the database never contains one without the other.
     pub fn persist_head_and_fork_choice(&self) -> Result<(), Error> {
         let canonical_head_block_root = self
             .canonical_head
@@ -241,16 +249,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         let fork_choice_timer = metrics::start_timer(&metrics::PERSIST_FORK_CHOICE);
 
-        self.store.put(
-            &Hash256::from_slice(&FORK_CHOICE_DB_KEY),
-            &self.fork_choice.as_ssz_container(),
-        )?;
+        let fork_choice_op = self
+            .fork_choice
+            .as_ssz_container()
+            .as_kv_store_op(&Hash256::from_slice(&FORK_CHOICE_DB_KEY));
 
         metrics::stop_timer(fork_choice_timer);
         let head_timer = metrics::start_timer(&metrics::PERSIST_HEAD);
 
-        self.store
-            .put(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY), &persisted_head)?;
+        let head_op = persisted_head.as_kv_store_op(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY));
+        self.store.do_atomically(vec![fork_choice_op, head_op])?;
 
         metrics::stop_timer(head_timer);
 
@@ -738,6 +746,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.naive_aggregation_pool.get(data).map_err(Into::into)
     }
 
+    /// Returns an aggregated `Attestation`, if any, at `slot` whose `attestation.data` has the
+    /// given tree hash root.
+    ///
+    /// The attestation will be obtained from `self.naive_aggregation_pool`.
+    pub fn get_aggregated_attestation_by_slot_and_root(
+        &self,
+        slot: Slot,
+        attestation_data_root: Hash256,
+    ) -> Option<Attestation<T::EthSpec>> {
+        self.naive_aggregation_pool
+            .get_by_slot_and_root(slot, attestation_data_root)
+    }
+
     /// Produce an unaggregated `Attestation` that is valid for the given `slot` and `index`.
     ///
     /// The produced `Attestation` will not be valid until it has been signed by exactly one
@@ -910,13 +931,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let attestation = unaggregated_attestation.attestation();
 
         match self.naive_aggregation_pool.insert(attestation) {
-            Ok(outcome) => trace!(
-                self.log,
-                "Stored unaggregated attestation";
-                "outcome" => format!("{:?}", outcome),
-                "index" => attestation.data.index,
-                "slot" => attestation.data.slot.as_u64(),
-            ),
+            Ok(outcome) => {
+                trace!(
+                    self.log,
+                    "Stored unaggregated attestation";
+                    "outcome" => format!("{:?}", outcome),
+                    "index" => attestation.data.index,
+                    "slot" => attestation.data.slot.as_u64(),
+                );
+                self.register_attestation_imported(attestation);
+            }
             Err(NaiveAggregationError::SlotTooLow {
                 slot,
                 lowest_permissible_slot,
@@ -972,9 +996,23 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .map_err(Error::from)?;
         }
 
+        self.register_attestation_imported(signed_aggregate.attestation());
+
         Ok(signed_aggregate)
     }
 
+    /// Registers a `BeaconAttestationImported` event, without cloning the attestation if there is
+    /// nothing listening for events.
+    fn register_attestation_imported(&self, attestation: &Attestation<T::EthSpec>) {
+        if self.event_handler.is_enabled() {
+            let _ = self
+                .event_handler
+                .register(EventKind::BeaconAttestationImported {
+                    attestation: Box::new(attestation.clone()),
+                });
+        }
+    }
+
     /// Check that the shuffling at `block_root` is equal to one of the shufflings of `state`.
     ///
     /// The `target_epoch` argument determines which shuffling to check compatibility with, it
@@ -1045,6 +1083,68 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Returns `true` if the validator has been observed on gossip publishing an attestation,
+    /// aggregate or block in `epoch`.
+    ///
+    /// Observations are only retained for the current and previous epochs.
+    pub fn validator_seen_at_epoch(&self, validator_index: usize, epoch: Epoch) -> bool {
+        self.observed_attesters
+            .index_seen_at_epoch(validator_index, epoch)
+            || self
+                .observed_aggregators
+                .index_seen_at_epoch(validator_index, epoch)
+            || self
+                .observed_block_producers
+                .index_seen_at_epoch(validator_index as u64, epoch)
+    }
+
+    /// Verify a voluntary exit received on gossip against the wall-clock state.
+    ///
+    /// Returns `ObservationOutcome::AlreadyKnown` if a valid exit has already been seen for the
+    /// validator, in which case the exit should not be propagated.
+    pub fn verify_voluntary_exit_for_gossip(
+        &self,
+        exit: SignedVoluntaryExit,
+    ) -> Result<ObservationOutcome<SignedVoluntaryExit>, Error> {
+        let wall_clock_state = self.wall_clock_state()?;
+        Ok(self
+            .observed_voluntary_exits
+            .verify_and_observe(exit, &wall_clock_state, &self.spec)?)
+    }
+
+    /// Verify a proposer slashing received on gossip against the wall-clock state.
+    ///
+    /// Returns `ObservationOutcome::AlreadyKnown` if a valid proposer slashing has already been
+    /// seen for the proposer, in which case the slashing should not be propagated.
+    pub fn verify_proposer_slashing_for_gossip(
+        &self,
+        proposer_slashing: ProposerSlashing,
+    ) -> Result<ObservationOutcome<ProposerSlashing>, Error> {
+        let wall_clock_state = self.wall_clock_state()?;
+        Ok(self.observed_proposer_slashings.verify_and_observe(
+            proposer_slashing,
+            &wall_clock_state,
+            &self.spec,
+        )?)
+    }
+
+    /// Verify an attester slashing received on gossip against the wall-clock state.
+    ///
+    /// Returns `ObservationOutcome::AlreadyKnown` if valid attester slashings have already been
+    /// seen for all of the slashable validators, in which case the slashing should not be
+    /// propagated.
+    pub fn verify_attester_slashing_for_gossip(
+        &self,
+        attester_slashing: AttesterSlashing<T::EthSpec>,
+    ) -> Result<ObservationOutcome<AttesterSlashing<T::EthSpec>>, Error> {
+        let wall_clock_state = self.wall_clock_state()?;
+        Ok(self.observed_attester_slashings.verify_and_observe(
+            attester_slashing,
+            &wall_clock_state,
+            &self.spec,
+        )?)
+    }
+
     /// Accept some exit and queue it for inclusion in an appropriate block.
     pub fn process_voluntary_exit(
         &self,
@@ -1122,6 +1222,42 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Import an exit that has already been verified (e.g., on gossip) into the operation pool,
+    /// queueing it for inclusion in an appropriate block.
+    pub fn import_voluntary_exit(&self, exit: VerifiedOperation<SignedVoluntaryExit>) {
+        if self.eth1_chain.is_some() {
+            self.op_pool
+                .insert_verified_voluntary_exit(exit.into_inner())
+        }
+    }
+
+    /// Import a proposer slashing that has already been verified (e.g., on gossip) into the
+    /// operation pool, queueing it for inclusion in an appropriate block.
+    pub fn import_proposer_slashing(&self, proposer_slashing: VerifiedOperation<ProposerSlashing>) {
+        if self.eth1_chain.is_some() {
+            self.op_pool
+                .insert_verified_proposer_slashing(proposer_slashing.into_inner())
+        }
+    }
+
+    /// Import an attester slashing that has already been verified (e.g., on gossip) into the
+    /// operation pool, queueing it for inclusion in an appropriate block.
+    pub fn import_attester_slashing(
+        &self,
+        attester_slashing: VerifiedOperation<AttesterSlashing<T::EthSpec>>,
+    ) -> Result<(), Error> {
+        if self.eth1_chain.is_some() {
+            // The state is only used to compute the slashing's identifier in the pool.
+            let state = self.wall_clock_state()?;
+            self.op_pool.insert_verified_attester_slashing(
+                attester_slashing.into_inner(),
+                &state,
+                &self.spec,
+            )
+        }
+        Ok(())
+    }
+
     /// Attempt to verify and import a chain of blocks to `self`.
     ///
     /// The provided blocks _must_ each reference the previous block via `block.parent_root` (i.e.,
@@ -1398,8 +1534,8 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Accepts a fully-verified block and imports it into the chain without performing any
     /// additional verification.
     ///
-    /// An error is returned if the block was unable to be imported. It may be partially imported
-    /// (i.e., this function is not atomic).
+    /// An error is returned if the block was unable to be imported. The block and its states are
+    /// written to the database atomically, but the in-memory caches may be partially updated.
     fn import_block(
         &self,
         fully_verified_block: FullyVerifiedBlock<T>,
@@ -1490,18 +1626,15 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         let db_write_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_WRITE);
 
-        // Store all the states between the parent block state and this block's slot before storing
-        // the final state.
-        intermediate_states.commit(&*self.store)?;
-
-        // Store the block and state.
-        // NOTE: we store the block *after* the state to guard against inconsistency in the event of
-        // a crash, as states are usually looked up from blocks, not the other way around. A better
-        // solution would be to use a database transaction (once our choice of database and API
-        // settles down).
-        // See: https://github.com/sigp/lighthouse/issues/692
-        self.store.put_state(&block.state_root, &state)?;
-        self.store.put_block(&block_root, signed_block.clone())?;
+        // Store all the states between the parent block state and this block's slot, the
+        // block's state and the block itself in a single batch, so that a crash can't leave a
+        // block without its states.
+        let mut ops = vec![];
+        intermediate_states.into_kv_store_ops(&*self.store, &mut ops)?;
+        self.store
+            .state_as_kv_store_ops(&block.state_root, &state, &mut ops)?;
+        self.store
+            .put_block_with_ops(&block_root, signed_block.clone(), ops)?;
 
         self.snapshot_cache
             .try_write_for(BLOCK_PROCESSING_CACHE_LOCK_TIMEOUT)
@@ -1739,6 +1872,22 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .map(|root| *root)
                 .unwrap_or_else(|_| Hash256::random());
 
+        // A failure only prevents the re-org event from being registered.
+        let reorg_depth = if is_reorg {
+            let depth = self.reorg_depth(current_head.block_root, beacon_block_root);
+            if depth.is_none() {
+                warn!(
+                    self.log,
+                    "Unable to determine re-org depth";
+                    "previous_head" => format!("{}", current_head.block_root),
+                    "new_head" => format!("{}", beacon_block_root),
+                );
+            }
+            depth
+        } else {
+            None
+        };
+
         if is_reorg {
             metrics::inc_counter(&metrics::FORK_CHOICE_REORG_COUNT);
             warn!(
@@ -1785,6 +1934,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             self.persist_head_and_fork_choice()?;
         }
 
+        let new_head_slot = new_head.beacon_block.slot();
+        let new_head_state_root = new_head.beacon_state_root;
+
         let update_head_timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
 
         // Update the snapshot that stores the head of the chain at the time it received the
@@ -1818,15 +1970,60 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             )?;
         }
 
+        let new_head_epoch = new_head_slot.epoch(T::EthSpec::slots_per_epoch());
+
+        if let Some(depth) = reorg_depth {
+            let _ = self.event_handler.register(EventKind::BeaconChainReorg {
+                slot: new_head_slot,
+                depth,
+                old_head_beacon_block_root: current_head.block_root,
+                new_head_beacon_block_root: beacon_block_root,
+                old_head_state_root: current_head.state_root,
+                new_head_state_root,
+                epoch: new_head_epoch,
+            });
+        }
+
         let _ = self.event_handler.register(EventKind::BeaconHeadChanged {
             reorg: is_reorg,
             previous_head_beacon_block_root: current_head.block_root,
             current_head_beacon_block_root: beacon_block_root,
+            slot: new_head_slot,
+            current_head_state_root: new_head_state_root,
+            epoch_transition: current_head.slot.epoch(T::EthSpec::slots_per_epoch())
+                < new_head_epoch,
         });
 
         Ok(())
     }
 
+    /// Returns the number of slots between `old_head` and its common ancestor with `new_head`.
+    ///
+    /// Both chains are walked in fork choice, so no blocks or states are read from the database.
+    /// Returns `None` if either block is unknown to fork choice, or the chains do not meet before
+    /// the finalized block.
+    fn reorg_depth(&self, old_head: Hash256, new_head: Hash256) -> Option<u64> {
+        let proto_array = self.fork_choice.core_proto_array();
+        let mut old_chain = proto_array.iter_block_roots(&old_head).peekable();
+        let mut new_chain = proto_array.iter_block_roots(&new_head).peekable();
+        let old_head_slot = old_chain.peek()?.1;
+
+        // Both iterators descend in slot, so step back along whichever chain is ahead until they
+        // reach the same block.
+        loop {
+            let (old_root, old_slot) = *old_chain.peek()?;
+            let (new_root, new_slot) = *new_chain.peek()?;
+
+            if old_root == new_root {
+                return Some(old_head_slot.saturating_sub(old_slot).as_u64());
+            } else if old_slot >= new_slot {
+                old_chain.next();
+            } else {
+                new_chain.next();
+            }
+        }
+    }
+
     /// Called by the timer on every slot.
     ///
     /// Performs slot-based pruning.
@@ -1899,6 +2096,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             let _ = self.event_handler.register(EventKind::BeaconFinalization {
                 epoch: new_finalized_epoch,
                 root: finalized_block_root,
+                state_root: finalized_block.state_root,
             });
 
             Ok(())
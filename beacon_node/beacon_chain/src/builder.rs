@@ -20,6 +20,7 @@ use operation_pool::{OperationPool, PersistedOperationPool};
 use proto_array_fork_choice::ProtoArrayForkChoice;
 use slog::{info, Logger};
 use slot_clock::{SlotClock, TestingSlotClock};
+use state_processing::per_slot_processing;
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -259,7 +260,20 @@ where
                 .unwrap_or_else(|| OperationPool::new()),
         );
 
-        let finalized_block_root = head_state.finalized_checkpoint.root;
+        // If the chain was started from an anchor state and has not finalized since, the finalized
+        // checkpoint of the head precedes the anchor. Use the anchor block instead.
+        let finalized_block_root = match store.get_anchor_info() {
+            Some(anchor_info)
+                if head_state
+                    .finalized_checkpoint
+                    .epoch
+                    .start_slot(TEthSpec::slots_per_epoch())
+                    < anchor_info.anchor_slot =>
+            {
+                anchor_info.anchor_block_root
+            }
+            _ => head_state.finalized_checkpoint.root,
+        };
         let finalized_block = store
             .get::<SignedBeaconBlock<TEthSpec>>(&finalized_block_root)
             .map_err(|e| format!("DB error when reading finalized block: {:?}", e))?
@@ -338,6 +352,111 @@ where
         Ok(self.empty_op_pool())
     }
 
+    /// Starts a new chain from a trusted, finalized (weak subjectivity) `anchor_state` and the
+    /// `anchor_block` that produced it, rather than from genesis.
+    ///
+    /// If the slots between `anchor_block` and the next epoch boundary were skipped, the
+    /// `anchor_state` is advanced through them so that the chain starts on the epoch boundary. The
+    /// finalized snapshot then holds the advanced state and its root.
+    ///
+    /// The `genesis_state` is required to determine the genesis block root, however no blocks or
+    /// states between genesis and the anchor are required. They may be backfilled later.
+    pub fn weak_subjectivity_state(
+        mut self,
+        mut anchor_state: BeaconState<TEthSpec>,
+        anchor_block: SignedBeaconBlock<TEthSpec>,
+        mut genesis_state: BeaconState<TEthSpec>,
+    ) -> Result<Self, String> {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "weak_subjectivity_state requires a store")?;
+
+        if anchor_state.slot != anchor_block.slot() {
+            return Err(format!(
+                "Weak subjectivity state is at slot {} but the block is at slot {}",
+                anchor_state.slot,
+                anchor_block.slot()
+            ));
+        }
+
+        if anchor_state.genesis_validators_root != genesis_state.genesis_validators_root {
+            return Err(format!(
+                "Weak subjectivity state has genesis validators root {:?} but the genesis state \
+                 has {:?}, are they from the same network?",
+                anchor_state.genesis_validators_root, genesis_state.genesis_validators_root
+            ));
+        }
+
+        let beacon_state_root = anchor_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Error hashing weak subjectivity state: {:?}", e))?;
+
+        if anchor_block.state_root() != beacon_state_root {
+            return Err(format!(
+                "Weak subjectivity block has state root {:?} but the state has root {:?}",
+                anchor_block.state_root(),
+                beacon_state_root
+            ));
+        }
+
+        // If the first slots of the finalized epoch were skipped, `anchor_block` is older than
+        // the epoch boundary. Advance its state through the skipped slots.
+        let mut anchor_state_root = beacon_state_root;
+        while anchor_state.slot % TEthSpec::slots_per_epoch() != 0 {
+            per_slot_processing(&mut anchor_state, Some(anchor_state_root), &self.spec)
+                .map_err(|e| format!("Error advancing weak subjectivity state: {:?}", e))?;
+            anchor_state_root = anchor_state
+                .update_tree_hash_cache()
+                .map_err(|e| format!("Error hashing weak subjectivity state: {:?}", e))?;
+        }
+
+        anchor_state
+            .build_all_caches(&self.spec)
+            .map_err(|e| format!("Failed to build weak subjectivity state caches: {:?}", e))?;
+
+        let beacon_block_root = anchor_block.canonical_root();
+
+        // The anchor must be stored first, so that the store places the genesis state in the
+        // freezer. The store also makes an advanced anchor state available under
+        // `beacon_state_root`, which is how the children of `anchor_block` load their parent state.
+        store
+            .init_anchor_state(&anchor_state_root, &anchor_state)
+            .map_err(|e| format!("Failed to store weak subjectivity state: {:?}", e))?;
+        store
+            .put(&beacon_block_root, &anchor_block)
+            .map_err(|e| format!("Failed to store weak subjectivity block: {:?}", e))?;
+
+        let genesis_block = genesis_block(&mut genesis_state, &self.spec)?;
+        let genesis_block_root = genesis_block.canonical_root();
+
+        store
+            .put_state(&genesis_block.message.state_root, &genesis_state)
+            .map_err(|e| format!("Failed to store genesis state: {:?}", e))?;
+        store
+            .put(&genesis_block_root, &genesis_block)
+            .map_err(|e| format!("Failed to store genesis block: {:?}", e))?;
+
+        // Store the genesis block under the `ZERO_HASH` key.
+        store.put(&Hash256::zero(), &genesis_block).map_err(|e| {
+            format!(
+                "Failed to store genesis block under 0x00..00 alias: {:?}",
+                e
+            )
+        })?;
+
+        self.genesis_block_root = Some(genesis_block_root);
+
+        self.finalized_snapshot = Some(BeaconSnapshot {
+            beacon_block_root,
+            beacon_block: anchor_block,
+            beacon_state_root: anchor_state_root,
+            beacon_state: anchor_state,
+        });
+
+        Ok(self.empty_op_pool())
+    }
+
     /// Sets the `BeaconChain` eth1 backend.
     pub fn eth1_backend(mut self, backend: Option<TEth1Backend>) -> Self {
         self.eth1_chain = backend.map(Eth1Chain::new);
@@ -399,8 +518,15 @@ where
             .build_all_caches(&self.spec)
             .map_err(|e| format!("Failed to build state caches: {:?}", e))?;
 
-        if canonical_head.beacon_block.state_root() != canonical_head.beacon_state_root {
-            return Err("beacon_block.state_root != beacon_state".to_string());
+        // The head state may have been advanced through skipped slots after the head block (e.g.,
+        // a weak subjectivity anchor), so check that the head block is the latest block of the
+        // state rather than comparing state roots.
+        if canonical_head
+            .beacon_state
+            .get_latest_block_root(canonical_head.beacon_state_root)
+            != canonical_head.beacon_block_root
+        {
+            return Err("beacon_block is not the latest block of beacon_state".to_string());
         }
 
         let pubkey_cache_path = self
@@ -439,6 +565,10 @@ where
             observed_aggregators: <_>::default(),
             // TODO: allow for persisting and loading the pool from disk.
             observed_block_producers: <_>::default(),
+            // TODO: allow for persisting and loading the pool from disk.
+            observed_voluntary_exits: <_>::default(),
+            observed_proposer_slashings: <_>::default(),
+            observed_attester_slashings: <_>::default(),
             eth1_chain: self.eth1_chain,
             genesis_validators_root: canonical_head.beacon_state.genesis_validators_root,
             canonical_head: TimeoutRwLock::new(canonical_head.clone()),
@@ -512,9 +642,6 @@ where
                 .finalized_snapshot
                 .as_ref()
                 .ok_or_else(|| "fork_choice_backend requires a finalized_snapshot")?;
-            let genesis_block_root = self
-                .genesis_block_root
-                .ok_or_else(|| "fork_choice_backend requires a genesis_block_root")?;
 
             let backend = ProtoArrayForkChoice::new(
                 finalized_snapshot.beacon_block.message.slot,
@@ -528,9 +655,11 @@ where
                 finalized_snapshot.beacon_block_root,
             )?;
 
+            // The finalized block is used in place of genesis, since it may be a weak
+            // subjectivity anchor that has no ancestors in the store.
             ForkChoice::new(
                 backend,
-                genesis_block_root,
+                finalized_snapshot.beacon_block_root,
                 &finalized_snapshot.beacon_state,
             )
         };
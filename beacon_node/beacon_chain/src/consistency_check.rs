@@ -0,0 +1,480 @@
+//! Checks that the blocks and states referenced by the persisted beacon chain, fork choice and
+//! split point are present in the database.
+//!
+//! An unclean shutdown can leave the `HeadTracker` or fork choice referring to blocks or states
+//! which never reached the disk, which prevents the `BeaconChain` from being resumed. Heads with
+//! missing data can be pruned with `prune_bad_heads`.
+use crate::beacon_chain::{BEACON_CHAIN_DB_KEY, FORK_CHOICE_DB_KEY};
+use crate::fork_choice::SszForkChoice;
+use crate::head_tracker::HeadTracker;
+use crate::persisted_beacon_chain::PersistedBeaconChain;
+use slog::{info, warn, Logger};
+use std::collections::HashMap;
+use std::fmt;
+use store::{get_key_for_col, DBColumn, DiskStore, KeyValueStoreOp, SimpleStoreItem, Store};
+use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// A reference to a block or state which is missing from the database.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    /// The canonical head block is missing.
+    MissingCanonicalHeadBlock { block_root: Hash256 },
+    /// The state of the canonical head block is missing.
+    MissingCanonicalHeadState {
+        block_root: Hash256,
+        state_root: Hash256,
+        slot: Slot,
+    },
+    /// The block of a head in the `HeadTracker` is missing.
+    MissingHeadBlock { block_root: Hash256, slot: Slot },
+    /// The state of a head in the `HeadTracker` is missing.
+    MissingHeadState {
+        block_root: Hash256,
+        state_root: Hash256,
+        slot: Slot,
+    },
+    /// The block of a fork choice node on the chain of a head is missing.
+    MissingForkChoiceBlock { block_root: Hash256, slot: Slot },
+    /// The state of a fork choice node on the chain of a head is missing.
+    MissingForkChoiceState {
+        block_root: Hash256,
+        state_root: Hash256,
+        slot: Slot,
+    },
+    /// The canonical head descends from a fork choice node with a missing block or state.
+    MissingCanonicalHeadAncestor {
+        block_root: Hash256,
+        ancestor_root: Hash256,
+    },
+    /// A head in the `HeadTracker` descends from a fork choice node with a missing block or
+    /// state.
+    MissingHeadAncestor {
+        block_root: Hash256,
+        slot: Slot,
+        ancestor_root: Hash256,
+    },
+    /// The state at the split point between the hot and cold databases is missing.
+    MissingSplitState { state_root: Hash256, slot: Slot },
+}
+
+impl Inconsistency {
+    /// Returns `true` if `prune_bad_heads` is able to repair `self`.
+    pub fn is_repairable(&self) -> bool {
+        match self {
+            Inconsistency::MissingSplitState { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inconsistency::MissingCanonicalHeadBlock { block_root } => {
+                write!(f, "canonical head block {:?} is missing", block_root)
+            }
+            Inconsistency::MissingCanonicalHeadState {
+                block_root,
+                state_root,
+                slot,
+            } => write!(
+                f,
+                "state {:?} of canonical head block {:?} at slot {} is missing",
+                state_root, block_root, slot
+            ),
+            Inconsistency::MissingHeadBlock { block_root, slot } => {
+                write!(f, "head block {:?} at slot {} is missing", block_root, slot)
+            }
+            Inconsistency::MissingHeadState {
+                block_root,
+                state_root,
+                slot,
+            } => write!(
+                f,
+                "state {:?} of head block {:?} at slot {} is missing",
+                state_root, block_root, slot
+            ),
+            Inconsistency::MissingForkChoiceBlock { block_root, slot } => write!(
+                f,
+                "fork choice block {:?} at slot {} is missing",
+                block_root, slot
+            ),
+            Inconsistency::MissingForkChoiceState {
+                block_root,
+                state_root,
+                slot,
+            } => write!(
+                f,
+                "state {:?} of fork choice block {:?} at slot {} is missing",
+                state_root, block_root, slot
+            ),
+            Inconsistency::MissingCanonicalHeadAncestor {
+                block_root,
+                ancestor_root,
+            } => write!(
+                f,
+                "canonical head block {:?} descends from fork choice block {:?} with missing data",
+                block_root, ancestor_root
+            ),
+            Inconsistency::MissingHeadAncestor {
+                block_root,
+                slot,
+                ancestor_root,
+            } => write!(
+                f,
+                "head block {:?} at slot {} descends from fork choice block {:?} with missing data",
+                block_root, slot, ancestor_root
+            ),
+            Inconsistency::MissingSplitState { state_root, slot } => write!(
+                f,
+                "split state {:?} at slot {} is missing",
+                state_root, slot
+            ),
+        }
+    }
+}
+
+/// The outcome of `check_consistency`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConsistencyReport {
+    /// The number of heads in the `HeadTracker`.
+    pub heads_checked: usize,
+    /// The number of fork choice nodes on the chains of those heads.
+    pub fork_choice_nodes_checked: usize,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if no references to missing data were found.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+
+    /// Returns `true` if every inconsistency can be repaired by `prune_bad_heads`.
+    pub fn is_repairable(&self) -> bool {
+        self.inconsistencies
+            .iter()
+            .all(Inconsistency::is_repairable)
+    }
+
+    /// Returns the roots of the heads in the `HeadTracker` whose block or state is missing, or
+    /// which descend from a fork choice node with a missing block or state.
+    pub fn bad_heads(&self) -> Vec<Hash256> {
+        let mut bad_heads = vec![];
+        for inconsistency in &self.inconsistencies {
+            match inconsistency {
+                Inconsistency::MissingHeadBlock { block_root, .. }
+                | Inconsistency::MissingHeadState { block_root, .. }
+                | Inconsistency::MissingHeadAncestor { block_root, .. } => {
+                    if !bad_heads.contains(block_root) {
+                        bad_heads.push(*block_root)
+                    }
+                }
+                _ => {}
+            }
+        }
+        bad_heads
+    }
+
+    /// Returns `true` if the canonical head block or state is missing, or if it descends from a
+    /// fork choice node with a missing block or state.
+    pub fn canonical_head_is_bad(&self) -> bool {
+        self.inconsistencies
+            .iter()
+            .any(|inconsistency| match inconsistency {
+                Inconsistency::MissingCanonicalHeadBlock { .. }
+                | Inconsistency::MissingCanonicalHeadState { .. }
+                | Inconsistency::MissingCanonicalHeadAncestor { .. } => true,
+                _ => false,
+            })
+    }
+
+    /// Returns `true` if any fork choice node refers to a missing block or state.
+    pub fn fork_choice_is_bad(&self) -> bool {
+        self.inconsistencies
+            .iter()
+            .any(|inconsistency| match inconsistency {
+                Inconsistency::MissingForkChoiceBlock { .. }
+                | Inconsistency::MissingForkChoiceState { .. } => true,
+                _ => false,
+            })
+    }
+}
+
+/// Check that the blocks and states referenced by the persisted beacon chain, fork choice and
+/// split point are present in `store`.
+///
+/// Only the fork choice nodes on the chains of the canonical head and the heads in the
+/// `HeadTracker` are checked, as the blocks of abandoned forks are deleted from the database
+/// before they are pruned from fork choice.
+///
+/// Returns `None` if the database does not contain a beacon chain.
+pub fn check_consistency<E: EthSpec>(
+    store: &DiskStore<E>,
+) -> Result<Option<ConsistencyReport>, String> {
+    let persisted_chain = match load_persisted_chain(store)? {
+        Some(persisted_chain) => persisted_chain,
+        None => return Ok(None),
+    };
+    let head_tracker = HeadTracker::from_ssz_container(&persisted_chain.ssz_head_tracker)
+        .map_err(|e| format!("Failed to decode head tracker: {:?}", e))?;
+    let mut heads = head_tracker.heads();
+    heads.sort_by_key(|(_, slot)| *slot);
+
+    let mut report = ConsistencyReport {
+        heads_checked: heads.len(),
+        ..ConsistencyReport::default()
+    };
+
+    let canonical_head_root = persisted_chain.canonical_head_block_root;
+    match get_block(store, &canonical_head_root)? {
+        Some(block) => {
+            if !state_exists(store, &block.state_root())? {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingCanonicalHeadState {
+                        block_root: canonical_head_root,
+                        state_root: block.state_root(),
+                        slot: block.slot(),
+                    });
+            }
+        }
+        None => report
+            .inconsistencies
+            .push(Inconsistency::MissingCanonicalHeadBlock {
+                block_root: canonical_head_root,
+            }),
+    }
+
+    for &(block_root, slot) in &heads {
+        match get_block(store, &block_root)? {
+            Some(block) => {
+                if !state_exists(store, &block.state_root())? {
+                    report
+                        .inconsistencies
+                        .push(Inconsistency::MissingHeadState {
+                            block_root,
+                            state_root: block.state_root(),
+                            slot,
+                        });
+                }
+            }
+            None => report
+                .inconsistencies
+                .push(Inconsistency::MissingHeadBlock { block_root, slot }),
+        }
+    }
+
+    let persisted_fork_choice = store
+        .get::<SszForkChoice>(&Hash256::from_slice(&FORK_CHOICE_DB_KEY))
+        .map_err(|e| format!("DB error when reading persisted fork choice: {:?}", e))?;
+    if let Some(persisted_fork_choice) = persisted_fork_choice {
+        let fork_choice = persisted_fork_choice
+            .backend()
+            .map_err(|e| format!("Failed to decode fork choice: {:?}", e))?;
+        let proto_array = fork_choice.core_proto_array();
+
+        // The nearest node with a missing block or state on the chain of each checked node, so
+        // that every head which descends from missing data is reported.
+        let mut missing_ancestors: HashMap<Hash256, Option<Hash256>> = HashMap::new();
+        let head_roots = std::iter::once(canonical_head_root).chain(heads.iter().map(|h| h.0));
+        for head_root in head_roots {
+            let mut unchecked = vec![];
+            let mut missing_ancestor = None;
+            for (block_root, slot) in proto_array.iter_block_roots(&head_root) {
+                // Chains of different heads share their ancestors, only check them once.
+                if let Some(ancestor) = missing_ancestors.get(&block_root) {
+                    missing_ancestor = *ancestor;
+                    break;
+                }
+
+                let is_missing = if !store
+                    .exists::<SignedBeaconBlock<E>>(&block_root)
+                    .map_err(|e| format!("DB error when reading block: {:?}", e))?
+                {
+                    report
+                        .inconsistencies
+                        .push(Inconsistency::MissingForkChoiceBlock { block_root, slot });
+                    true
+                } else {
+                    let state_root = proto_array
+                        .indices
+                        .get(&block_root)
+                        .and_then(|&index| proto_array.nodes.get(index))
+                        .map(|node| node.state_root)
+                        .ok_or_else(|| format!("Fork choice node {:?} is missing", block_root))?;
+                    if !state_exists(store, &state_root)? {
+                        report
+                            .inconsistencies
+                            .push(Inconsistency::MissingForkChoiceState {
+                                block_root,
+                                state_root,
+                                slot,
+                            });
+                        true
+                    } else {
+                        false
+                    }
+                };
+                unchecked.push((block_root, is_missing));
+            }
+
+            for (block_root, is_missing) in unchecked.into_iter().rev() {
+                if is_missing {
+                    missing_ancestor = Some(block_root);
+                }
+                missing_ancestors.insert(block_root, missing_ancestor);
+            }
+        }
+        report.fork_choice_nodes_checked = missing_ancestors.len();
+
+        // A head with its own block or state missing has already been reported.
+        let missing_ancestor_of = |head_root: &Hash256| {
+            missing_ancestors
+                .get(head_root)
+                .and_then(|ancestor| *ancestor)
+                .filter(|ancestor_root| ancestor_root != head_root)
+        };
+        if let Some(ancestor_root) = missing_ancestor_of(&canonical_head_root) {
+            report
+                .inconsistencies
+                .push(Inconsistency::MissingCanonicalHeadAncestor {
+                    block_root: canonical_head_root,
+                    ancestor_root,
+                });
+        }
+        for &(block_root, slot) in &heads {
+            if let Some(ancestor_root) = missing_ancestor_of(&block_root) {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingHeadAncestor {
+                        block_root,
+                        slot,
+                        ancestor_root,
+                    });
+            }
+        }
+    }
+
+    let split_slot = store.get_split_slot();
+    let split_state_root = store.get_split_state_root();
+    if split_state_root != Hash256::zero() && !state_exists(store, &split_state_root)? {
+        report
+            .inconsistencies
+            .push(Inconsistency::MissingSplitState {
+                state_root: split_state_root,
+                slot: split_slot,
+            });
+    }
+
+    Ok(Some(report))
+}
+
+/// Remove the heads with missing blocks or states, and the heads which descend from them, from the
+/// `HeadTracker`, so that the `BeaconChain` can be resumed from the database.
+///
+/// If the canonical head is bad, the remaining head with the highest slot becomes the canonical
+/// head. If fork choice refers to missing data, it is deleted so that it is rebuilt from the
+/// finalized checkpoint when the beacon chain is resumed.
+///
+/// Returns the roots of the pruned heads, or an error if `report` contains an inconsistency which
+/// can't be repaired.
+pub fn prune_bad_heads<E: EthSpec>(
+    store: &DiskStore<E>,
+    report: &ConsistencyReport,
+    log: &Logger,
+) -> Result<Vec<Hash256>, String> {
+    if let Some(inconsistency) = report
+        .inconsistencies
+        .iter()
+        .find(|inconsistency| !inconsistency.is_repairable())
+    {
+        return Err(format!(
+            "Unable to repair the database, {}. Remove the database with --purge-db",
+            inconsistency
+        ));
+    }
+
+    let mut persisted_chain = load_persisted_chain(store)?
+        .ok_or_else(|| "No persisted beacon chain found in the database".to_string())?;
+    let head_tracker = HeadTracker::from_ssz_container(&persisted_chain.ssz_head_tracker)
+        .map_err(|e| format!("Failed to decode head tracker: {:?}", e))?;
+
+    let bad_heads = report.bad_heads();
+    for head_root in &bad_heads {
+        warn!(
+            log,
+            "Pruning head with missing data";
+            "block_root" => format!("{:?}", head_root)
+        );
+        head_tracker.remove_head(*head_root);
+    }
+
+    if report.canonical_head_is_bad() {
+        let (new_head_root, new_head_slot) = head_tracker
+            .heads()
+            .into_iter()
+            .max_by_key(|(_, slot)| *slot)
+            .ok_or_else(|| {
+                "Unable to repair the database, no heads remain. Remove the database with \
+                 --purge-db"
+                    .to_string()
+            })?;
+        warn!(
+            log,
+            "Replacing canonical head with missing data";
+            "old_head" => format!("{:?}", persisted_chain.canonical_head_block_root),
+            "new_head" => format!("{:?}", new_head_root),
+            "new_head_slot" => new_head_slot.as_u64(),
+        );
+        persisted_chain.canonical_head_block_root = new_head_root;
+    }
+    persisted_chain.ssz_head_tracker = head_tracker.to_ssz_container();
+
+    let mut ops = vec![persisted_chain.as_kv_store_op(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY))];
+    if report.fork_choice_is_bad() {
+        warn!(
+            log,
+            "Resetting fork choice with missing data";
+            "info" => "fork choice will be rebuilt from the finalized checkpoint"
+        );
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::ForkChoice.into(),
+            &FORK_CHOICE_DB_KEY,
+        )));
+    }
+    store
+        .do_atomically(ops)
+        .map_err(|e| format!("DB error when writing repaired beacon chain: {:?}", e))?;
+
+    info!(
+        log,
+        "Database repaired";
+        "pruned_heads" => bad_heads.len(),
+    );
+
+    Ok(bad_heads)
+}
+
+fn load_persisted_chain<E: EthSpec>(
+    store: &DiskStore<E>,
+) -> Result<Option<PersistedBeaconChain>, String> {
+    store
+        .get::<PersistedBeaconChain>(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY))
+        .map_err(|e| format!("DB error when reading persisted beacon chain: {:?}", e))
+}
+
+fn get_block<E: EthSpec>(
+    store: &DiskStore<E>,
+    block_root: &Hash256,
+) -> Result<Option<SignedBeaconBlock<E>>, String> {
+    store
+        .get_block(block_root)
+        .map_err(|e| format!("DB error when reading block {:?}: {:?}", block_root, e))
+}
+
+fn state_exists<E: EthSpec>(store: &DiskStore<E>, state_root: &Hash256) -> Result<bool, String> {
+    store
+        .state_exists(state_root)
+        .map_err(|e| format!("DB error when reading state {:?}: {:?}", state_root, e))
+}
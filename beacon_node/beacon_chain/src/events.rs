@@ -1,27 +1,148 @@
+use futures::sync::mpsc;
+use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
 use std::marker::PhantomData;
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock};
+use std::sync::Arc;
+use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
 pub use websocket_server::WebSocketSender;
 
 pub trait EventHandler<T: EthSpec>: Sized + Send + Sync {
     fn register(&self, kind: EventKind<T>) -> Result<(), String>;
+
+    /// Returns `false` if registered events are certain to be discarded, allowing callers to skip
+    /// building events which are expensive to construct.
+    fn is_enabled(&self) -> bool;
 }
 
 pub struct NullEventHandler<T: EthSpec>(PhantomData<T>);
 
 impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
     fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        // Avoid serializing events which will never be sent.
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
         self.send_string(
             serde_json::to_string(&kind)
                 .map_err(|e| format!("Unable to serialize event: {:?}", e))?,
         )
     }
+
+    fn is_enabled(&self) -> bool {
+        WebSocketSender::is_enabled(self)
+    }
+}
+
+/// Forwards events to the subscribers of the HTTP API event stream.
+///
+/// Each subscriber has a bounded buffer. If a subscriber is not keeping up and its buffer is full,
+/// the event is dropped for that subscriber rather than waiting, so a slow consumer can never
+/// delay the `BeaconChain`.
+pub struct ServerSentEventHandler<T: EthSpec> {
+    subscribers: Mutex<Vec<mpsc::Sender<Arc<EventKind<T>>>>>,
+}
+
+impl<T: EthSpec> ServerSentEventHandler<T> {
+    /// Returns a stream of all events registered after this call, buffering up to
+    /// `buffer_size` events which have not yet been read.
+    ///
+    /// The subscription is removed once the receiver is dropped.
+    pub fn subscribe(&self, buffer_size: usize) -> mpsc::Receiver<Arc<EventKind<T>>> {
+        let (sender, receiver) = mpsc::channel(buffer_size);
+        self.subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Returns the number of subscribers, including those which have disconnected since the
+    /// last event.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for ServerSentEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let mut subscribers = self.subscribers.lock();
+
+        if subscribers.is_empty() {
+            return Ok(());
+        }
+
+        let event = Arc::new(kind);
+        let retained: Vec<_> = subscribers
+            .drain(..)
+            .filter_map(|mut sender| match sender.try_send(event.clone()) {
+                Ok(()) => Some(sender),
+                // The subscriber is lagging, it misses this event.
+                Err(e) if e.is_full() => Some(sender),
+                // The subscriber has gone away.
+                Err(_) => None,
+            })
+            .collect();
+        *subscribers = retained;
+
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.subscriber_count() > 0
+    }
+}
+
+impl<T: EthSpec> Default for ServerSentEventHandler<T> {
+    fn default() -> Self {
+        Self {
+            subscribers: Mutex::new(vec![]),
+        }
+    }
+}
+
+/// Registers each event with both the WebSocket server and the HTTP API event stream.
+pub struct TeeEventHandler<T: EthSpec> {
+    websocket_handler: WebSocketSender<T>,
+    sse_handler: Arc<ServerSentEventHandler<T>>,
+}
+
+impl<T: EthSpec> TeeEventHandler<T> {
+    pub fn new(
+        websocket_handler: WebSocketSender<T>,
+        sse_handler: Arc<ServerSentEventHandler<T>>,
+    ) -> Self {
+        Self {
+            websocket_handler,
+            sse_handler,
+        }
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for TeeEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let websocket_result = if self.websocket_handler.is_enabled() {
+            serde_json::to_string(&kind)
+                .map_err(|e| format!("Unable to serialize event: {:?}", e))
+                .and_then(|string| self.websocket_handler.send_string(string))
+        } else {
+            Ok(())
+        };
+
+        // Always pass the event to the event stream, even if the WebSocket server failed.
+        self.sse_handler.register(kind).and(websocket_result)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.websocket_handler.is_enabled() || self.sse_handler.is_enabled()
+    }
 }
 
 impl<T: EthSpec> EventHandler<T> for NullEventHandler<T> {
     fn register(&self, _kind: EventKind<T>) -> Result<(), String> {
         Ok(())
     }
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
 }
 
 impl<T: EthSpec> Default for NullEventHandler<T> {
@@ -42,10 +163,27 @@ pub enum EventKind<T: EthSpec> {
         reorg: bool,
         current_head_beacon_block_root: Hash256,
         previous_head_beacon_block_root: Hash256,
+        slot: Slot,
+        current_head_state_root: Hash256,
+        /// True if the new head is in a later epoch than the previous head.
+        epoch_transition: bool,
+    },
+    /// Registered before `BeaconHeadChanged` when the new head is not a descendant of the
+    /// previous head.
+    BeaconChainReorg {
+        slot: Slot,
+        /// The number of slots between the previous head and the common ancestor of both heads.
+        depth: u64,
+        old_head_beacon_block_root: Hash256,
+        new_head_beacon_block_root: Hash256,
+        old_head_state_root: Hash256,
+        new_head_state_root: Hash256,
+        epoch: Epoch,
     },
     BeaconFinalization {
         epoch: Epoch,
         root: Hash256,
+        state_root: Hash256,
     },
     BeaconBlockImported {
         block_root: Hash256,
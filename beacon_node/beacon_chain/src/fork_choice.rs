@@ -261,6 +261,13 @@ pub struct SszForkChoice {
     backend_bytes: Vec<u8>,
 }
 
+impl SszForkChoice {
+    /// Decode the persisted `ProtoArrayForkChoice`, without the rest of the `ForkChoice`.
+    pub fn backend(&self) -> std::result::Result<ProtoArrayForkChoice, String> {
+        ProtoArrayForkChoice::from_bytes(&self.backend_bytes)
+    }
+}
+
 impl From<BeaconStateError> for Error {
     fn from(e: BeaconStateError) -> Error {
         Error::BeaconStateError(e)
@@ -0,0 +1,121 @@
+//! Provides `BeaconChain::import_historical_block_batch`, which backfills the blocks prior to the
+//! anchor of a database that was started from a weak subjectivity checkpoint.
+//!
+//! Historical blocks are authenticated by the hash chain leading back from the anchor block, so
+//! they are stored without verifying signatures or replaying any state.
+
+use crate::{metrics, BeaconChain, BeaconChainTypes};
+use store::{Error as StoreError, Store};
+use types::{Hash256, SignedBeaconBlock, Slot};
+
+#[derive(Debug, PartialEq)]
+pub enum HistoricalBlockError {
+    /// The database was started from genesis, there are no historical blocks to import.
+    NoAnchorInfo,
+    /// A block does not have the root that its child (or the oldest known block) expects.
+    MismatchedBlockRoot {
+        slot: Slot,
+        block_root: Hash256,
+        expected_block_root: Hash256,
+    },
+    /// The blocks are not in strictly ascending slot order.
+    NonLinearSlots,
+    StoreError(StoreError),
+}
+
+impl From<StoreError> for HistoricalBlockError {
+    fn from(e: StoreError) -> Self {
+        HistoricalBlockError::StoreError(e)
+    }
+}
+
+impl<T: BeaconChainTypes> BeaconChain<T> {
+    /// Store a batch of blocks that precede the oldest block in the database, returning the number
+    /// of blocks that were stored.
+    ///
+    /// The `blocks` must be in ascending slot order, as returned by a `BlocksByRange` request.
+    /// Blocks at or after the oldest known block are ignored. The remaining blocks must form a
+    /// chain that ends at the parent of the oldest known block, otherwise an error is returned
+    /// and nothing is stored. An empty batch (e.g., a range of skip slots) is always accepted.
+    ///
+    /// Once the parent of the oldest block is the genesis block, backfill is complete and the
+    /// `AnchorInfo` records an `oldest_block_slot` of zero.
+    pub fn import_historical_block_batch(
+        &self,
+        blocks: Vec<SignedBeaconBlock<T::EthSpec>>,
+    ) -> Result<usize, HistoricalBlockError> {
+        let anchor_info = self
+            .store
+            .get_anchor_info()
+            .ok_or_else(|| HistoricalBlockError::NoAnchorInfo)?;
+
+        if anchor_info.backfill_complete() {
+            return Ok(0);
+        }
+
+        let _timer = metrics::start_timer(&metrics::BACKFILL_BATCH_TIMES);
+
+        let blocks = blocks
+            .into_iter()
+            .filter(|block| block.slot() < anchor_info.oldest_block_slot)
+            .collect::<Vec<_>>();
+
+        if blocks
+            .windows(2)
+            .any(|pair| pair[0].slot() >= pair[1].slot())
+        {
+            return Err(HistoricalBlockError::NonLinearSlots);
+        }
+
+        // Verify the whole batch before storing any of it, walking backwards from the parent of
+        // the oldest known block.
+        let mut expected_block_root = anchor_info.oldest_block_parent;
+        let mut oldest_block_slot = anchor_info.oldest_block_slot;
+        let mut verified_blocks = Vec::with_capacity(blocks.len());
+
+        for block in blocks.into_iter().rev() {
+            if expected_block_root == self.genesis_block_root {
+                // The genesis block is already stored, ignore it if the peer returned it.
+                break;
+            }
+
+            let block_root = block.canonical_root();
+            if block_root != expected_block_root {
+                return Err(HistoricalBlockError::MismatchedBlockRoot {
+                    slot: block.slot(),
+                    block_root,
+                    expected_block_root,
+                });
+            }
+
+            expected_block_root = block.parent_root();
+            oldest_block_slot = block.slot();
+            verified_blocks.push((block_root, block));
+        }
+
+        let imported_blocks = verified_blocks.len();
+        for (block_root, block) in verified_blocks {
+            self.store.put_block(&block_root, block)?;
+        }
+
+        // The anchor is only updated once the blocks are stored, so a crash part-way through this
+        // function results in redundant writes rather than a gap in the chain.
+        let mut new_anchor_info = anchor_info;
+        if expected_block_root == self.genesis_block_root {
+            new_anchor_info.oldest_block_slot = Slot::new(0);
+            new_anchor_info.oldest_block_parent = Hash256::zero();
+        } else {
+            new_anchor_info.oldest_block_slot = oldest_block_slot;
+            new_anchor_info.oldest_block_parent = expected_block_root;
+        }
+        self.store.set_anchor_info(new_anchor_info)?;
+
+        metrics::inc_counter_by(&metrics::BACKFILL_BLOCKS_IMPORTED, imported_blocks as i64);
+        metrics::set_gauge(
+            &metrics::BACKFILL_OLDEST_BLOCK_SLOT,
+            new_anchor_info.oldest_block_slot.as_u64() as i64,
+        );
+
+        Ok(imported_blocks)
+    }
+}
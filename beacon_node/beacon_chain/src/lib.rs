@@ -7,17 +7,20 @@ mod beacon_chain;
 mod beacon_snapshot;
 mod block_verification;
 pub mod builder;
+pub mod consistency_check;
 mod errors;
 pub mod eth1_chain;
 pub mod events;
 mod fork_choice;
 mod head_tracker;
+mod historical_blocks;
 mod metrics;
 pub mod migrate;
 mod naive_aggregation_pool;
 mod observed_attestations;
 mod observed_attesters;
 mod observed_block_producers;
+mod observed_operations;
 mod persisted_beacon_chain;
 mod shuffling_cache;
 mod snapshot_cache;
@@ -36,7 +39,9 @@ pub use block_verification::{BlockError, BlockProcessingOutcome, GossipVerifiedB
 pub use eth1_chain::{Eth1Chain, Eth1ChainBackend};
 pub use events::EventHandler;
 pub use fork_choice::ForkChoice;
+pub use historical_blocks::HistoricalBlockError;
 pub use metrics::scrape_for_metrics;
+pub use observed_operations::{ObservationOutcome, VerifiedOperation};
 pub use parking_lot;
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
@@ -1,5 +1,6 @@
 use crate::{BeaconChain, BeaconChainTypes};
 pub use lighthouse_metrics::*;
+use store::Store;
 use types::{BeaconState, Epoch, Hash256, Slot};
 
 lazy_static! {
@@ -251,6 +252,22 @@ lazy_static! {
         try_create_int_gauge("beacon_op_pool_proposer_slashings_total", "Count of proposer slashings in the op pool");
     pub static ref OP_POOL_NUM_VOLUNTARY_EXITS: Result<IntGauge> =
         try_create_int_gauge("beacon_op_pool_voluntary_exits_total", "Count of voluntary exits in the op pool");
+
+    /*
+     * Historical block backfill
+     */
+    pub static ref BACKFILL_BLOCKS_IMPORTED: Result<IntCounter> = try_create_int_counter(
+        "beacon_backfill_blocks_imported_total",
+        "Count of historical blocks stored by backfill sync"
+    );
+    pub static ref BACKFILL_BATCH_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_backfill_batch_seconds",
+        "Time taken to verify and store a batch of historical blocks"
+    );
+    pub static ref BACKFILL_OLDEST_BLOCK_SLOT: Result<IntGauge> = try_create_int_gauge(
+        "beacon_backfill_oldest_block_slot",
+        "Slot of the oldest block in the database, zero once backfill is complete"
+    );
 }
 
 /// Scrape the `beacon_chain` for metrics that are not constantly updated (e.g., the present slot,
@@ -276,6 +293,10 @@ pub fn scrape_for_metrics<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) {
         &OP_POOL_NUM_VOLUNTARY_EXITS,
         beacon_chain.op_pool.num_voluntary_exits(),
     );
+
+    if let Some(anchor_info) = beacon_chain.store.get_anchor_info() {
+        set_gauge_by_slot(&BACKFILL_OLDEST_BLOCK_SLOT, anchor_info.oldest_block_slot);
+    }
 }
 
 /// Scrape the given `state` assuming it's the head state, updating the `DEFAULT_REGISTRY`.
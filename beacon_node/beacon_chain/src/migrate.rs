@@ -42,10 +42,17 @@ pub trait Migrate<S: Store<E>, E: EthSpec>: Send + Sync + 'static {
         new_finalized_block_hash: SignedBeaconBlockHash,
         new_finalized_slot: Slot,
     ) -> Result<(), BeaconChainError> {
-        let old_finalized_slot = store
-            .get_block(&old_finalized_block_hash.into())?
-            .ok_or_else(|| BeaconChainError::MissingBeaconBlock(old_finalized_block_hash.into()))?
-            .slot();
+        let old_finalized_slot = match store.get_block(&old_finalized_block_hash.into())? {
+            Some(block) => block.slot(),
+            // The previous finalized block precedes the anchor of a chain that was started from a
+            // weak subjectivity state. Skip pruning until the next finalization.
+            None if store.get_anchor_info().is_some() => return Ok(()),
+            None => {
+                return Err(BeaconChainError::MissingBeaconBlock(
+                    old_finalized_block_hash.into(),
+                ))
+            }
+        };
 
         // Collect hashes from new_finalized_block back to old_finalized_block (inclusive)
         let mut found_block = false; // hack for `take_until`
@@ -141,14 +148,15 @@ pub trait Migrate<S: Store<E>, E: EthSpec>: Send + Sync + 'static {
             }
         }
 
-        // XXX Should be performed atomically, see
-        // https://github.com/sigp/lighthouse/issues/692
+        let mut ops = vec![];
         for block_hash in abandoned_blocks.into_iter() {
-            store.delete_block(&block_hash.into())?;
+            ops.push(store.delete_block_as_kv_store_op(&block_hash.into()));
         }
         for (slot, state_hash) in abandoned_states.into_iter() {
-            store.delete_state(&state_hash.into(), slot)?;
+            store.delete_state_as_kv_store_ops(&state_hash.into(), slot, &mut ops);
         }
+        store.do_atomically(ops)?;
+
         for head_hash in abandoned_heads.into_iter() {
             head_tracker.remove_head(head_hash);
         }
@@ -1,6 +1,7 @@
 use parking_lot::RwLock;
 use std::collections::HashMap;
-use types::{Attestation, AttestationData, EthSpec, Slot};
+use tree_hash::TreeHash;
+use types::{Attestation, AttestationData, EthSpec, Hash256, Slot};
 
 /// The number of slots that will be stored in the pool.
 ///
@@ -115,6 +116,14 @@ impl<E: EthSpec> AggregatedAttestationMap<E> {
         Ok(self.map.get(data).cloned())
     }
 
+    /// Returns an aggregated `Attestation` whose `data` has the given tree hash root, if any.
+    pub fn get_by_root(&self, root: Hash256) -> Option<Attestation<E>> {
+        self.map
+            .iter()
+            .find(|(data, _attestation)| data.tree_hash_root() == root)
+            .map(|(_data, attestation)| attestation.clone())
+    }
+
     pub fn len(&self) -> usize {
         self.map.len()
     }
@@ -216,6 +225,16 @@ impl<E: EthSpec> NaiveAggregationPool<E> {
             .unwrap_or_else(|| Ok(None))
     }
 
+    /// Returns an aggregated `Attestation` at `slot` whose `data` has the given tree hash root, if
+    /// any.
+    pub fn get_by_slot_and_root(&self, slot: Slot, root: Hash256) -> Option<Attestation<E>> {
+        self.maps
+            .read()
+            .iter()
+            .find(|(map_slot, _map)| **map_slot == slot)
+            .and_then(|(_slot, map)| map.get_by_root(root))
+    }
+
     /// Removes any attestations with a slot lower than `current_slot` and bars any future
     /// attestations with a slot lower than `current_slot - SLOTS_RETAINED`.
     pub fn prune(&self, current_slot: Slot) {
@@ -312,6 +331,16 @@ mod tests {
             retrieved, a,
             "retrieved attestation should equal the one inserted"
         );
+        assert_eq!(
+            pool.get_by_slot_and_root(a.data.slot, a.data.tree_hash_root()),
+            Some(a.clone()),
+            "should get the attestation by its data root"
+        );
+        assert_eq!(
+            pool.get_by_slot_and_root(a.data.slot + 1, a.data.tree_hash_root()),
+            None,
+            "should not get the attestation from another slot"
+        );
 
         sign(&mut a, 1, Hash256::random());
 
@@ -219,6 +219,15 @@ impl<T: Item, E: EthSpec> AutoPruningContainer<T, E> {
         Ok(exists)
     }
 
+    /// Returns `true` if `validator_index` has been observed producing an attestation with a
+    /// target in `epoch`. Always returns `false` for epochs that have been pruned.
+    pub fn index_seen_at_epoch(&self, validator_index: usize, epoch: Epoch) -> bool {
+        self.items
+            .read()
+            .get(&epoch)
+            .map_or(false, |item| item.contains(validator_index))
+    }
+
     fn sanitize_request(&self, a: &Attestation<E>, validator_index: usize) -> Result<(), Error> {
         if validator_index > E::ValidatorRegistryLimit::to_usize() {
             return Err(Error::ValidatorIndexTooHigh(validator_index));
@@ -285,6 +294,11 @@ mod tests {
                     let attesters = [0, 1, 2, 3, 5, 6, 7, 18, 22];
                     let a = &get_attestation(epoch);
 
+                    assert!(
+                        !store.index_seen_at_epoch(4, epoch),
+                        "should not indicate an unseen validator was seen"
+                    );
+
                     for &i in &attesters {
                         assert_eq!(
                             store.validator_has_been_observed(a, i),
@@ -304,6 +318,10 @@ mod tests {
                             Ok(true),
                             "should indicate a known attestation is known"
                         );
+                        assert!(
+                            store.index_seen_at_epoch(i, epoch),
+                            "should indicate the validator was seen in the epoch"
+                        );
                         assert_eq!(
                             store.observe_validator(a, i),
                             Ok(true),
@@ -4,7 +4,7 @@
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
-use types::{BeaconBlock, EthSpec, Slot, Unsigned};
+use types::{BeaconBlock, Epoch, EthSpec, Slot, Unsigned};
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -86,6 +86,14 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
         Ok(exists)
     }
 
+    /// Returns `true` if `validator_index` has been observed proposing a block at any slot in
+    /// `epoch`.
+    pub fn index_seen_at_epoch(&self, validator_index: u64, epoch: Epoch) -> bool {
+        self.items.read().iter().any(|(slot, producers)| {
+            slot.epoch(E::slots_per_epoch()) == epoch && producers.contains(&validator_index)
+        })
+    }
+
     /// Returns `Ok(())` if the given `block` is sane.
     fn sanitize_block(&self, block: &BeaconBlock<E>) -> Result<(), Error> {
         if block.proposer_index > E::ValidatorRegistryLimit::to_u64() {
@@ -308,6 +316,18 @@ mod tests {
             Ok(true),
             "observed block is indicated as true"
         );
+        assert!(
+            cache.index_seen_at_epoch(0, Epoch::new(0)),
+            "proposer is seen in the block's epoch"
+        );
+        assert!(
+            !cache.index_seen_at_epoch(0, Epoch::new(1)),
+            "proposer is not seen in a later epoch"
+        );
+        assert!(
+            !cache.index_seen_at_epoch(1, Epoch::new(0)),
+            "other proposer is not seen"
+        );
         assert_eq!(
             cache.observe_proposer(block_a),
             Ok(true),
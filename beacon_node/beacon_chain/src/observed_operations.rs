@@ -0,0 +1,254 @@
+//! Provides the `ObservedOperations` struct which allows for rejecting gossip voluntary exits and
+//! slashings for validators that have already been seen in a valid operation of the same kind.
+
+use parking_lot::Mutex;
+use state_processing::per_block_processing::errors::{
+    AttesterSlashingValidationError, ExitValidationError, ProposerSlashingValidationError,
+};
+use state_processing::per_block_processing::{
+    verify_attester_slashing, verify_exit, verify_proposer_slashing, VerifySignatures,
+};
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use types::{
+    AttesterSlashing, BeaconState, ChainSpec, EthSpec, ProposerSlashing, SignedVoluntaryExit,
+};
+
+/// The result of verifying and observing an operation.
+#[derive(Debug, PartialEq)]
+pub enum ObservationOutcome<T> {
+    /// The operation is valid and concerns at least one validator that has not been observed
+    /// before. It should be propagated and imported.
+    New(VerifiedOperation<T>),
+    /// Every validator in the operation has already been observed in a valid operation of the same
+    /// kind. The operation should be neither propagated nor imported.
+    AlreadyKnown,
+}
+
+/// An operation that has been verified (including signatures) by `ObservedOperations`.
+///
+/// Can only be created by `ObservedOperations::verify_and_observe`, so it may be imported into the
+/// operation pool without verifying it again.
+#[derive(Debug, PartialEq)]
+pub struct VerifiedOperation<T>(T);
+
+impl<T> VerifiedOperation<T> {
+    /// Returns a reference to the verified operation.
+    pub fn as_inner(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the verified operation.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// An operation which can be verified against a `BeaconState` and that is about some set of
+/// validators.
+pub trait ObservableOperation<E: EthSpec>: Sized {
+    type Error;
+
+    /// The indices of the validators that this operation would exit or slash.
+    fn observed_validators(&self) -> Vec<u64>;
+
+    /// Verify `self` (including signatures) against `state`.
+    fn verify(&self, state: &BeaconState<E>, spec: &ChainSpec) -> Result<(), Self::Error>;
+}
+
+impl<E: EthSpec> ObservableOperation<E> for SignedVoluntaryExit {
+    type Error = ExitValidationError;
+
+    fn observed_validators(&self) -> Vec<u64> {
+        vec![self.message.validator_index]
+    }
+
+    fn verify(&self, state: &BeaconState<E>, spec: &ChainSpec) -> Result<(), Self::Error> {
+        verify_exit(state, self, VerifySignatures::True, spec)
+    }
+}
+
+impl<E: EthSpec> ObservableOperation<E> for ProposerSlashing {
+    type Error = ProposerSlashingValidationError;
+
+    fn observed_validators(&self) -> Vec<u64> {
+        vec![self.signed_header_1.message.proposer_index]
+    }
+
+    fn verify(&self, state: &BeaconState<E>, spec: &ChainSpec) -> Result<(), Self::Error> {
+        verify_proposer_slashing(self, state, VerifySignatures::True, spec)
+    }
+}
+
+impl<E: EthSpec> ObservableOperation<E> for AttesterSlashing<E> {
+    type Error = AttesterSlashingValidationError;
+
+    /// Returns the validators that attested to both attestations, i.e., the validators that could
+    /// be slashed by `self`.
+    fn observed_validators(&self) -> Vec<u64> {
+        let attestation_1_indices = self
+            .attestation_1
+            .attesting_indices
+            .iter()
+            .copied()
+            .collect::<HashSet<u64>>();
+
+        self.attestation_2
+            .attesting_indices
+            .iter()
+            .filter(|index| attestation_1_indices.contains(index))
+            .copied()
+            .collect()
+    }
+
+    fn verify(&self, state: &BeaconState<E>, spec: &ChainSpec) -> Result<(), Self::Error> {
+        verify_attester_slashing(state, self, VerifySignatures::True, spec)
+    }
+}
+
+/// Maintains a cache of the indices of validators that have been seen in a valid operation of type
+/// `T`.
+///
+/// Since a validator can only exit or be slashed once, there is no need to prune the cache. Its
+/// size is bounded by the number of validators.
+pub struct ObservedOperations<T: ObservableOperation<E>, E: EthSpec> {
+    observed_validator_indices: Mutex<HashSet<u64>>,
+    _phantom: PhantomData<(T, E)>,
+}
+
+impl<T: ObservableOperation<E>, E: EthSpec> Default for ObservedOperations<T, E> {
+    fn default() -> Self {
+        Self {
+            observed_validator_indices: Mutex::new(HashSet::new()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ObservableOperation<E>, E: EthSpec> ObservedOperations<T, E> {
+    /// Verify `op` against `state`, observing its validators if it is valid.
+    ///
+    /// Returns `ObservationOutcome::AlreadyKnown` without verifying `op` if all of its validators
+    /// have already been observed. Validators are only observed once `op` has been verified, so an
+    /// invalid operation cannot prevent a later valid one from being accepted.
+    ///
+    /// An operation about no validators at all (e.g., an attester slashing whose attestations
+    /// share no attesters) is never known, so it is always verified.
+    pub fn verify_and_observe(
+        &self,
+        op: T,
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Result<ObservationOutcome<T>, T::Error> {
+        let validator_indices = op.observed_validators();
+
+        if all_observed(&self.observed_validator_indices.lock(), &validator_indices) {
+            return Ok(ObservationOutcome::AlreadyKnown);
+        }
+
+        // Verify without holding the lock, since signature verification is expensive.
+        op.verify(state, spec)?;
+
+        // Check again, since another copy of `op` may have been verified in the meantime.
+        let mut observed_validator_indices = self.observed_validator_indices.lock();
+
+        if all_observed(&observed_validator_indices, &validator_indices) {
+            return Ok(ObservationOutcome::AlreadyKnown);
+        }
+
+        observed_validator_indices.extend(validator_indices);
+
+        Ok(ObservationOutcome::New(VerifiedOperation(op)))
+    }
+
+    /// Returns the number of validators that have been observed.
+    pub fn len(&self) -> usize {
+        self.observed_validator_indices.lock().len()
+    }
+
+    /// Returns `true` if no validators have been observed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Returns `true` if `validator_indices` is non-empty and every validator in it is in
+/// `observed_validator_indices`.
+fn all_observed(observed_validator_indices: &HashSet<u64>, validator_indices: &[u64]) -> bool {
+    !validator_indices.is_empty()
+        && validator_indices
+            .iter()
+            .all(|index| observed_validator_indices.contains(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MainnetEthSpec;
+
+    type E = MainnetEthSpec;
+
+    /// An operation about `indices` which only passes verification if `valid`.
+    struct MockOp {
+        indices: Vec<u64>,
+        valid: bool,
+    }
+
+    impl ObservableOperation<E> for MockOp {
+        type Error = ();
+
+        fn observed_validators(&self) -> Vec<u64> {
+            self.indices.clone()
+        }
+
+        fn verify(&self, _state: &BeaconState<E>, _spec: &ChainSpec) -> Result<(), ()> {
+            if self.valid {
+                Ok(())
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    #[test]
+    fn observe() {
+        let spec = E::default_spec();
+        let state = BeaconState::<E>::new(0, <_>::default(), &spec);
+        let cache = ObservedOperations::<MockOp, E>::default();
+
+        // Returns `Ok(true)` if the op is new, `Ok(false)` if it is known and `Err(())` if invalid.
+        let observe = |indices: Vec<u64>, valid: bool| {
+            cache
+                .verify_and_observe(MockOp { indices, valid }, &state, &spec)
+                .map(|outcome| match outcome {
+                    ObservationOutcome::New(_) => true,
+                    ObservationOutcome::AlreadyKnown => false,
+                })
+        };
+
+        assert_eq!(observe(vec![1], false), Err(()), "invalid op is rejected");
+        assert!(cache.is_empty(), "invalid op should not be observed");
+
+        assert_eq!(observe(vec![1], true), Ok(true), "first op is new");
+        assert_eq!(observe(vec![1], true), Ok(false), "second op is known");
+        assert_eq!(
+            observe(vec![1], false),
+            Ok(false),
+            "known op should not be verified"
+        );
+
+        assert_eq!(
+            observe(vec![1, 2], true),
+            Ok(true),
+            "op with a new validator should be new"
+        );
+        assert_eq!(observe(vec![2, 1], true), Ok(false));
+        assert_eq!(cache.len(), 2);
+
+        assert_eq!(
+            observe(vec![], false),
+            Err(()),
+            "op about no validators should be verified"
+        );
+    }
+}
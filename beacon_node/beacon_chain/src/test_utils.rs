@@ -99,11 +99,17 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
         keypairs: Vec<Keypair>,
         target_aggregators_per_committee: u64,
     ) -> Self {
-        let data_dir = tempdir().expect("should create temporary data_dir");
         let mut spec = E::default_spec();
 
         spec.target_aggregators_per_committee = target_aggregators_per_committee;
 
+        Self::new_with_spec(eth_spec_instance, keypairs, spec)
+    }
+
+    /// Instantiate a new harness with `validator_count` initial validators and a custom `spec`.
+    pub fn new_with_spec(eth_spec_instance: E, keypairs: Vec<Keypair>, spec: ChainSpec) -> Self {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+
         let log = NullLoggerBuilder.build().expect("logger should build");
 
         let chain = BeaconChainBuilder::new(eth_spec_instance)
@@ -180,6 +186,54 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
         }
     }
 
+    /// Instantiate a new harness which starts from the given weak subjectivity state, rather than
+    /// genesis.
+    pub fn new_from_weak_subjectivity_state(
+        eth_spec_instance: E,
+        store: Arc<DiskStore<E>>,
+        keypairs: Vec<Keypair>,
+        anchor_state: BeaconState<E>,
+        anchor_block: SignedBeaconBlock<E>,
+    ) -> Self {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+        let spec = E::default_spec();
+
+        let log = NullLoggerBuilder.build().expect("logger should build");
+
+        let chain = BeaconChainBuilder::new(eth_spec_instance)
+            .logger(log.clone())
+            .custom_spec(spec.clone())
+            .store(store.clone())
+            .store_migrator(<BlockingMigrator<_> as Migrate<_, E>>::new(
+                store,
+                log.clone(),
+            ))
+            .data_dir(data_dir.path().to_path_buf())
+            .weak_subjectivity_state(
+                anchor_state,
+                anchor_block,
+                interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &spec)
+                    .expect("should generate interop state"),
+            )
+            .expect("should build state using weak subjectivity state")
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .null_event_handler()
+            .testing_slot_clock(HARNESS_SLOT_TIME)
+            .expect("should configure testing slot clock")
+            .reduced_tree_fork_choice()
+            .expect("should add fork choice to builder")
+            .build()
+            .expect("should build");
+
+        Self {
+            spec: chain.spec.clone(),
+            chain,
+            keypairs,
+            data_dir,
+        }
+    }
+
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn resume_from_disk_store(
         eth_spec_instance: E,
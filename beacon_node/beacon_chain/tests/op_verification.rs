@@ -0,0 +1,252 @@
+//! Tests for gossip verification of voluntary exits, proposer slashings and attester slashings.
+
+#![cfg(not(debug_assertions))]
+
+#[macro_use]
+extern crate lazy_static;
+
+use beacon_chain::{
+    test_utils::{BeaconChainHarness, HarnessType},
+    BeaconChainError, ObservationOutcome,
+};
+use types::{
+    test_utils::{
+        AttesterSlashingTestTask, ProposerSlashingTestTask, TestingAttesterSlashingBuilder,
+        TestingProposerSlashingBuilder, TestingVoluntaryExitBuilder,
+    },
+    AttesterSlashing, Epoch, EthSpec, Keypair, MainnetEthSpec, ProposerSlashing, Signature,
+    SignedVoluntaryExit,
+};
+
+pub type E = MainnetEthSpec;
+
+pub const VALIDATOR_COUNT: usize = 24;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+fn get_harness() -> BeaconChainHarness<HarnessType<E>> {
+    let mut spec = E::default_spec();
+
+    // Allow validators to exit immediately after genesis.
+    spec.persistent_committee_period = 0;
+
+    let harness = BeaconChainHarness::new_with_spec(MainnetEthSpec, KEYPAIRS.to_vec(), spec);
+
+    harness.advance_slot();
+
+    harness
+}
+
+fn voluntary_exit(
+    harness: &BeaconChainHarness<HarnessType<E>>,
+    validator_index: u64,
+    signer_index: usize,
+) -> SignedVoluntaryExit {
+    let state = harness.get_head_state();
+
+    TestingVoluntaryExitBuilder::new(state.current_epoch(), validator_index).build(
+        &KEYPAIRS[signer_index].sk,
+        &state.fork,
+        state.genesis_validators_root,
+        &harness.spec,
+    )
+}
+
+fn attester_slashing(
+    harness: &BeaconChainHarness<HarnessType<E>>,
+    test_task: AttesterSlashingTestTask,
+    validator_indices: &[u64],
+) -> AttesterSlashing<E> {
+    let state = harness.get_head_state();
+    let signer = |validator_index: u64, message: &[u8]| {
+        Signature::new(message, &KEYPAIRS[validator_index as usize].sk)
+    };
+
+    TestingAttesterSlashingBuilder::double_vote(
+        test_task,
+        validator_indices,
+        signer,
+        &state.fork,
+        state.genesis_validators_root,
+        &harness.spec,
+    )
+}
+
+fn proposer_slashing(
+    harness: &BeaconChainHarness<HarnessType<E>>,
+    test_task: ProposerSlashingTestTask,
+    validator_index: u64,
+) -> ProposerSlashing {
+    let state = harness.get_head_state();
+
+    TestingProposerSlashingBuilder::double_vote::<E>(
+        test_task,
+        validator_index,
+        &KEYPAIRS[validator_index as usize].sk,
+        &state.fork,
+        state.genesis_validators_root,
+        &harness.spec,
+    )
+}
+
+#[test]
+fn proposer_slashing_gossip_verification() {
+    let harness = get_harness();
+    let chain = &harness.chain;
+
+    let invalid = proposer_slashing(&harness, ProposerSlashingTestTask::BadProposal1Signature, 1);
+    assert!(
+        match chain.verify_proposer_slashing_for_gossip(invalid) {
+            Err(BeaconChainError::ProposerSlashingValidationError(_)) => true,
+            _ => false,
+        },
+        "should reject slashing with invalid signature"
+    );
+
+    let valid = proposer_slashing(&harness, ProposerSlashingTestTask::Valid, 1);
+    let verified = match chain
+        .verify_proposer_slashing_for_gossip(valid.clone())
+        .expect("should verify valid slashing")
+    {
+        ObservationOutcome::New(slashing) => slashing,
+        ObservationOutcome::AlreadyKnown => panic!("first slashing should be new"),
+    };
+    assert_eq!(verified.as_inner(), &valid);
+
+    assert_eq!(
+        chain
+            .verify_proposer_slashing_for_gossip(valid)
+            .expect("should not error on known slashing"),
+        ObservationOutcome::AlreadyKnown,
+        "second slashing for the same proposer should be known"
+    );
+
+    let other = proposer_slashing(&harness, ProposerSlashingTestTask::Valid, 2);
+    assert!(
+        match chain.verify_proposer_slashing_for_gossip(other) {
+            Ok(ObservationOutcome::New(_)) => true,
+            _ => false,
+        },
+        "slashing for another proposer should be new"
+    );
+
+    chain.import_proposer_slashing(verified);
+    assert_eq!(chain.op_pool.num_proposer_slashings(), 1);
+}
+
+#[test]
+fn voluntary_exit_gossip_verification() {
+    let harness = get_harness();
+    let chain = &harness.chain;
+
+    let invalid = voluntary_exit(&harness, 1, 2);
+    assert!(
+        match chain.verify_voluntary_exit_for_gossip(invalid) {
+            Err(BeaconChainError::ExitValidationError(_)) => true,
+            _ => false,
+        },
+        "should reject exit with invalid signature"
+    );
+
+    let valid = voluntary_exit(&harness, 1, 1);
+    let verified = match chain
+        .verify_voluntary_exit_for_gossip(valid.clone())
+        .expect("should verify valid exit")
+    {
+        ObservationOutcome::New(exit) => exit,
+        ObservationOutcome::AlreadyKnown => panic!("first exit should be new"),
+    };
+    assert_eq!(verified.as_inner(), &valid);
+
+    assert_eq!(
+        chain
+            .verify_voluntary_exit_for_gossip(valid)
+            .expect("should not error on known exit"),
+        ObservationOutcome::AlreadyKnown,
+        "second exit for the same validator should be known"
+    );
+
+    let other = voluntary_exit(&harness, 2, 2);
+    assert!(
+        match chain.verify_voluntary_exit_for_gossip(other) {
+            Ok(ObservationOutcome::New(_)) => true,
+            _ => false,
+        },
+        "exit for another validator should be new"
+    );
+
+    chain.import_voluntary_exit(verified);
+    assert_eq!(chain.op_pool.num_voluntary_exits(), 1);
+}
+
+#[test]
+fn attester_slashing_gossip_verification() {
+    let harness = get_harness();
+    let chain = &harness.chain;
+
+    let invalid = attester_slashing(&harness, AttesterSlashingTestTask::NotSlashable, &[1, 2]);
+    assert!(
+        match chain.verify_attester_slashing_for_gossip(invalid) {
+            Err(BeaconChainError::AttesterSlashingValidationError(_)) => true,
+            _ => false,
+        },
+        "should reject slashing that is not slashable"
+    );
+
+    // Two valid attestations which share no attesters slash nobody.
+    let disjoint = AttesterSlashing {
+        attestation_1: attester_slashing(&harness, AttesterSlashingTestTask::Valid, &[1])
+            .attestation_1,
+        attestation_2: attester_slashing(&harness, AttesterSlashingTestTask::Valid, &[2])
+            .attestation_2,
+    };
+    assert!(
+        match chain.verify_attester_slashing_for_gossip(disjoint) {
+            Err(BeaconChainError::AttesterSlashingValidationError(_)) => true,
+            _ => false,
+        },
+        "should reject slashing without shared attesters"
+    );
+
+    let valid = attester_slashing(&harness, AttesterSlashingTestTask::Valid, &[1, 2]);
+    let verified = match chain
+        .verify_attester_slashing_for_gossip(valid.clone())
+        .expect("should verify valid slashing")
+    {
+        ObservationOutcome::New(slashing) => slashing,
+        ObservationOutcome::AlreadyKnown => panic!("first slashing should be new"),
+    };
+    assert_eq!(verified.as_inner(), &valid);
+
+    assert_eq!(
+        chain
+            .verify_attester_slashing_for_gossip(attester_slashing(
+                &harness,
+                AttesterSlashingTestTask::Valid,
+                &[2],
+            ))
+            .expect("should not error on known slashing"),
+        ObservationOutcome::AlreadyKnown,
+        "slashing for already slashed attesters should be known"
+    );
+
+    assert!(
+        match chain.verify_attester_slashing_for_gossip(attester_slashing(
+            &harness,
+            AttesterSlashingTestTask::Valid,
+            &[2, 3],
+        )) {
+            Ok(ObservationOutcome::New(_)) => true,
+            _ => false,
+        },
+        "slashing for another attester should be new"
+    );
+
+    chain
+        .import_attester_slashing(verified)
+        .expect("should import slashing");
+    assert_eq!(chain.op_pool.num_attester_slashings(), 1);
+}
@@ -4,12 +4,13 @@
 extern crate lazy_static;
 
 use beacon_chain::{
+    consistency_check::{check_consistency, prune_bad_heads},
     test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy},
     BeaconChain, BeaconChainTypes,
 };
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::sync::Arc;
-use store::{DiskStore, StoreConfig};
+use store::{DiskStore, Store, StoreConfig};
 use tempfile::{tempdir, TempDir};
 use types::{EthSpec, Keypair, MinimalEthSpec};
 
@@ -137,6 +138,186 @@ fn finalizes_after_resuming_from_db() {
     );
 }
 
+#[test]
+fn resumes_from_db_after_pruning_bad_heads() {
+    let honest_validators: Vec<usize> = (0..VALIDATOR_COUNT * 2 / 3).collect();
+    let faulty_validators: Vec<usize> = (VALIDATOR_COUNT * 2 / 3..VALIDATOR_COUNT).collect();
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let log = NullLoggerBuilder.build().expect("logger should build");
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        2,
+        2,
+    );
+
+    assert_eq!(
+        harness
+            .chain
+            .head()
+            .expect("should read head")
+            .beacon_block_root,
+        honest_head,
+        "the honest fork should be the canonical head"
+    );
+
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+
+    let report = check_consistency(&store)
+        .expect("should check database")
+        .expect("database should contain a beacon chain");
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(report.heads_checked, 2);
+
+    // Lose the faulty head block, as if the node crashed before it was written.
+    store
+        .delete_block(&faulty_head)
+        .expect("should delete block");
+
+    let report = check_consistency(&store)
+        .expect("should check database")
+        .expect("database should contain a beacon chain");
+    assert_eq!(report.bad_heads(), vec![faulty_head]);
+    assert!(report.fork_choice_is_bad());
+    assert!(!report.canonical_head_is_bad());
+    assert!(report.is_repairable());
+
+    assert_eq!(
+        prune_bad_heads(&store, &report, &log),
+        Ok(vec![faulty_head])
+    );
+
+    let report = check_consistency(&store)
+        .expect("should check database")
+        .expect("database should contain a beacon chain");
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(report.heads_checked, 1);
+    assert_eq!(
+        report.fork_choice_nodes_checked, 0,
+        "fork choice should have been deleted"
+    );
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        harness.data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness
+            .chain
+            .head()
+            .expect("should read head")
+            .beacon_block_root,
+        honest_head
+    );
+    assert_eq!(resumed_harness.chain.heads().len(), 1);
+}
+
+#[test]
+fn prunes_heads_descending_from_missing_blocks() {
+    let honest_validators: Vec<usize> = (0..VALIDATOR_COUNT * 2 / 3).collect();
+    let faulty_validators: Vec<usize> = (VALIDATOR_COUNT * 2 / 3..VALIDATOR_COUNT).collect();
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let log = NullLoggerBuilder.build().expect("logger should build");
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        2,
+        2,
+    );
+
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+
+    // Lose the parent of the faulty head block, leaving the head itself intact.
+    let faulty_parent = store
+        .get_block(&faulty_head)
+        .expect("should read block")
+        .expect("faulty head block should exist")
+        .parent_root();
+    store
+        .delete_block(&faulty_parent)
+        .expect("should delete block");
+
+    let report = check_consistency(&store)
+        .expect("should check database")
+        .expect("database should contain a beacon chain");
+    assert_eq!(
+        report.bad_heads(),
+        vec![faulty_head],
+        "should report the head descending from the missing block"
+    );
+    assert!(report.fork_choice_is_bad());
+    assert!(!report.canonical_head_is_bad());
+
+    assert_eq!(
+        prune_bad_heads(&store, &report, &log),
+        Ok(vec![faulty_head])
+    );
+
+    let report = check_consistency(&store)
+        .expect("should check database")
+        .expect("database should contain a beacon chain");
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(report.heads_checked, 1);
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        harness.data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness
+            .chain
+            .head()
+            .expect("should read head")
+            .beacon_block_root,
+        honest_head
+    );
+    assert_eq!(resumed_harness.chain.heads().len(), 1);
+}
+
 /// Checks that two chains are the same, for the purpose of this tests.
 ///
 /// Several fields that are hard/impossible to check are ignored (e.g., the store).
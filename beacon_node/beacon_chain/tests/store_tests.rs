@@ -13,10 +13,13 @@ use rand::Rng;
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::Cursor;
 use std::sync::Arc;
 use store::{
+    era::{EraChunk, EraError, EraReader, EraWriter},
+    hot_cold_store::HotColdDBError,
     iter::{BlockRootsIterator, StateRootsIterator},
-    DiskStore, Store, StoreConfig,
+    DatabaseBackend, DiskStore, Store, StoreConfig,
 };
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
@@ -36,15 +39,31 @@ type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
 
 fn get_store(db_path: &TempDir) -> Arc<DiskStore<E>> {
+    Arc::new(open_store(db_path, StoreConfig::default()).expect("disk store should initialize"))
+}
+
+/// The database backend to run the tests against, chosen by the `STORE_TESTS_DB_BACKEND`
+/// environment variable.
+fn test_db_backend() -> DatabaseBackend {
+    std::env::var("STORE_TESTS_DB_BACKEND")
+        .map(|backend| {
+            backend
+                .parse()
+                .expect("STORE_TESTS_DB_BACKEND should be a valid backend")
+        })
+        .unwrap_or_default()
+}
+
+fn open_store(db_path: &TempDir, config: StoreConfig) -> Result<DiskStore<E>, store::Error> {
+    let config = StoreConfig {
+        backend: test_db_backend(),
+        ..config
+    };
     let spec = MinimalEthSpec::default_spec();
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
-    let config = StoreConfig::default();
     let log = NullLoggerBuilder.build().expect("logger should build");
-    Arc::new(
-        DiskStore::open(&hot_path, &cold_path, config, spec, log)
-            .expect("disk store should initialize"),
-    )
+    DiskStore::open(&hot_path, &cold_path, config, spec, log)
 }
 
 fn get_harness(store: Arc<DiskStore<E>>, validator_count: usize) -> TestHarness {
@@ -250,6 +269,749 @@ fn split_slot_restore() {
     assert_eq!(store.get_split_slot(), split_slot);
 }
 
+// Check that the states loaded from the historic state cache, or replayed from a cached state,
+// match the states replayed from restore points.
+#[test]
+fn historic_state_cache() {
+    let db_path = tempdir().unwrap();
+    let config = StoreConfig {
+        historic_state_cache_size: 4,
+        ..StoreConfig::default()
+    };
+    let store = Arc::new(open_store(&db_path, config.clone()).unwrap());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    harness.extend_chain(
+        (2 * config.slots_per_restore_point) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let split_slot = store.get_split_slot();
+    assert!(split_slot > config.slots_per_restore_point);
+
+    let head_state = harness.chain.head().expect("should get head").beacon_state;
+    let state_roots = StateRootsIterator::new(store.clone(), &head_state)
+        .filter(|(_, slot)| *slot < split_slot)
+        .map(|(state_root, slot)| (slot, state_root))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(state_roots.len() as u64, split_slot.as_u64());
+
+    // Descending slots are replayed from restore points, ascending slots from the previous slot,
+    // and each ascending slot is loaded a second time from the cache.
+    let slots = (0..split_slot.as_u64())
+        .rev()
+        .chain((0..split_slot.as_u64()).flat_map(|slot| vec![slot, slot]))
+        .map(Slot::new);
+    for slot in slots {
+        let state = store
+            .load_cold_state_by_slot(slot)
+            .expect("should load cold state");
+        assert_eq!(state.slot, slot);
+        assert_eq!(state.canonical_root(), state_roots[&slot], "slot {}", slot);
+    }
+}
+
+// Check that pruning the freezer DB keeps only the genesis restore point.
+#[test]
+fn prune_historic_states() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+    let num_blocks = 3 * slots_per_restore_point;
+
+    harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let latest_restore_point_slot = store.get_latest_restore_point_slot();
+    assert!(latest_restore_point_slot >= slots_per_restore_point);
+    assert!(store
+        .load_cold_state_by_slot(latest_restore_point_slot)
+        .is_ok());
+
+    let num_pruned = store.prune_historic_states().expect("should prune");
+    assert_eq!(
+        num_pruned as u64,
+        latest_restore_point_slot.as_u64() / slots_per_restore_point
+    );
+
+    assert!(store.load_cold_state_by_slot(Slot::new(0)).is_ok());
+    assert!(store
+        .load_cold_state_by_slot(latest_restore_point_slot)
+        .is_err());
+    assert_eq!(store.prune_historic_states(), Ok(0));
+}
+
+// Check that the restore points can be re-indexed at a new interval, and that every frozen state
+// is unchanged afterwards.
+#[test]
+fn reindex_restore_points() {
+    let db_path = tempdir().unwrap();
+    let old_config = StoreConfig::default();
+    let new_config = StoreConfig {
+        slots_per_restore_point: old_config.slots_per_restore_point / 4,
+        ..StoreConfig::default()
+    };
+
+    let (split_slot, state_roots) = {
+        let store = get_store(&db_path);
+        let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+        harness.extend_chain(
+            (3 * old_config.slots_per_restore_point) as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let split_slot = store.get_split_slot();
+        let state_roots = (0..split_slot.as_u64())
+            .map(|slot| {
+                store
+                    .load_cold_state_by_slot(Slot::new(slot))
+                    .expect("should load cold state")
+                    .canonical_root()
+            })
+            .collect::<Vec<_>>();
+        (split_slot, state_roots)
+    };
+    assert_ne!(split_slot, Slot::new(0));
+
+    {
+        let store = Arc::new(open_store(&db_path, old_config.clone()).unwrap());
+        let restore_points =
+            DiskStore::reindex_restore_points(store, new_config.slots_per_restore_point)
+                .expect("should re-index restore points");
+        assert_eq!(
+            restore_points as u64,
+            (split_slot.as_u64() - 1) / new_config.slots_per_restore_point + 1
+        );
+    }
+
+    // The database can no longer be opened with the old interval.
+    assert_eq!(
+        open_store(&db_path, old_config.clone()).err(),
+        Some(store::Error::HotColdDBError(
+            HotColdDBError::SlotsPerRestorePointMismatch {
+                database: new_config.slots_per_restore_point,
+                config: old_config.slots_per_restore_point,
+            }
+        ))
+    );
+
+    let store = open_store(&db_path, new_config).unwrap();
+    for (slot, state_root) in state_roots.into_iter().enumerate() {
+        let state = store
+            .load_cold_state_by_slot(Slot::new(slot as u64))
+            .expect("should load re-indexed cold state");
+        assert_eq!(state.canonical_root(), state_root, "slot {}", slot);
+    }
+}
+
+// Check that re-indexing a database started from an anchor moves the anchor forwards when its
+// restore point is not a multiple of the new interval.
+#[test]
+fn reindex_restore_points_after_anchor() {
+    let old_config = StoreConfig {
+        slots_per_restore_point: StoreConfig::default().slots_per_restore_point / 4,
+        ..StoreConfig::default()
+    };
+    let new_config = StoreConfig::default();
+    let anchor_slot = Slot::new(old_config.slots_per_restore_point);
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    harness.extend_chain(
+        (3 * new_config.slots_per_restore_point) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let anchor_state = store
+        .load_cold_state_by_slot(anchor_slot)
+        .expect("should load anchor state");
+    let anchor_block = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .into_iter()
+        .map(|snapshot| snapshot.beacon_block)
+        .find(|block| block.slot() == anchor_slot)
+        .expect("should have a block at the anchor slot");
+
+    // Start a second database from the anchor, with the old interval, and sync it.
+    let ws_db_path = tempdir().unwrap();
+    let ws_split_slot = {
+        let ws_store = Arc::new(open_store(&ws_db_path, old_config.clone()).unwrap());
+        let ws_harness = BeaconChainHarness::new_from_weak_subjectivity_state(
+            MinimalEthSpec,
+            ws_store.clone(),
+            KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+            anchor_state,
+            anchor_block,
+        );
+        ws_harness
+            .chain
+            .slot_clock
+            .set_slot(harness.chain.slot().unwrap().as_u64());
+        for snapshot in harness.chain.chain_dump().unwrap() {
+            if snapshot.beacon_block.slot() > anchor_slot {
+                ws_harness
+                    .chain
+                    .process_block(snapshot.beacon_block)
+                    .expect("should import block after the anchor");
+            }
+        }
+        ws_harness
+            .chain
+            .fork_choice()
+            .expect("should run fork choice");
+        ws_store.get_split_slot()
+    };
+    assert!(ws_split_slot > new_config.slots_per_restore_point);
+
+    let ws_store = Arc::new(open_store(&ws_db_path, old_config).unwrap());
+    DiskStore::reindex_restore_points(ws_store.clone(), new_config.slots_per_restore_point)
+        .expect("should re-index restore points");
+    assert_eq!(
+        ws_store.get_anchor_info().map(|info| info.anchor_slot),
+        Some(Slot::new(new_config.slots_per_restore_point)),
+        "should move the anchor to the first new restore point"
+    );
+    drop(ws_store);
+
+    let ws_store = open_store(&ws_db_path, new_config.clone()).unwrap();
+    assert!(ws_store.load_cold_state_by_slot(anchor_slot).is_err());
+    for slot in new_config.slots_per_restore_point..ws_split_slot.as_u64() {
+        let slot = Slot::new(slot);
+        assert_eq!(
+            ws_store
+                .load_cold_state_by_slot(slot)
+                .expect("should load re-indexed cold state")
+                .canonical_root(),
+            store
+                .load_cold_state_by_slot(slot)
+                .expect("should load cold state")
+                .canonical_root(),
+            "slot {}",
+            slot
+        );
+    }
+}
+
+#[test]
+fn weak_subjectivity_sync() {
+    let num_initial_blocks = E::slots_per_epoch() * 5;
+
+    // Build a finalized chain from genesis.
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_initial_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let finalized_root = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .finalized_checkpoint
+        .root;
+    let anchor_block = harness
+        .chain
+        .get_block(&finalized_root)
+        .expect("should get block")
+        .expect("finalized block should exist");
+    let anchor_state = harness
+        .chain
+        .get_state(&anchor_block.state_root(), Some(anchor_block.slot()))
+        .expect("should get state")
+        .expect("finalized state should exist");
+
+    // Start a second chain from the finalized checkpoint.
+    let ws_db_path = tempdir().unwrap();
+    let ws_store = get_store(&ws_db_path);
+    let ws_harness = BeaconChainHarness::new_from_weak_subjectivity_state(
+        MinimalEthSpec,
+        ws_store.clone(),
+        KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+        anchor_state,
+        anchor_block.clone(),
+    );
+    ws_harness
+        .chain
+        .slot_clock
+        .set_slot(harness.chain.slot().unwrap().as_u64());
+
+    assert_eq!(
+        ws_harness.chain.genesis_block_root,
+        harness.chain.genesis_block_root
+    );
+    assert_eq!(ws_store.get_split_slot(), anchor_block.slot());
+    assert_eq!(
+        ws_store
+            .get_anchor_info()
+            .map(|info| info.anchor_block_root),
+        Some(finalized_root)
+    );
+
+    // Import the blocks that were produced after the anchor.
+    for snapshot in harness.chain.chain_dump().unwrap() {
+        if snapshot.beacon_block.slot() > anchor_block.slot() {
+            ws_harness
+                .chain
+                .process_block(snapshot.beacon_block)
+                .expect("should import block after the anchor");
+        }
+    }
+    ws_harness
+        .chain
+        .fork_choice()
+        .expect("should run fork choice");
+
+    assert_eq!(
+        ws_harness.chain.head_info().unwrap().block_root,
+        harness.chain.head_info().unwrap().block_root
+    );
+
+    // The chain should continue to finalize and migrate states past the anchor.
+    let num_blocks = E::slots_per_epoch() * 4;
+    ws_harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    check_finalization(&ws_harness, num_initial_blocks + num_blocks);
+    check_split_slot(&ws_harness, ws_store.clone());
+
+    // Genesis is available, but states between genesis and the anchor are not.
+    assert!(ws_store.load_cold_state_by_slot(Slot::new(0)).is_ok());
+    assert!(ws_store
+        .load_cold_state_by_slot(anchor_block.slot() - 1)
+        .is_err());
+
+    // Backfill the blocks prior to the anchor, one epoch at a time, newest first.
+    let historical_blocks = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .into_iter()
+        .map(|snapshot| snapshot.beacon_block)
+        .filter(|block| block.slot() < anchor_block.slot())
+        .collect::<Vec<_>>();
+
+    // A batch that doesn't end at the parent of the oldest block should be rejected.
+    let anchor_info = ws_store.get_anchor_info().unwrap();
+    assert_eq!(anchor_info.oldest_block_slot, anchor_block.slot());
+    assert!(ws_harness
+        .chain
+        .import_historical_block_batch(historical_blocks[1..2].to_vec())
+        .is_err());
+    assert_eq!(ws_store.get_anchor_info(), Some(anchor_info));
+
+    for batch in historical_blocks.rchunks(E::slots_per_epoch() as usize) {
+        ws_harness
+            .chain
+            .import_historical_block_batch(batch.to_vec())
+            .expect("should import historical blocks");
+    }
+
+    assert!(ws_store.get_anchor_info().unwrap().backfill_complete());
+    for block in &historical_blocks {
+        assert_eq!(
+            ws_store
+                .get_block(&block.canonical_root())
+                .expect("should read block")
+                .as_ref(),
+            Some(block)
+        );
+    }
+}
+
+// Check that a chain can be started from a finalized checkpoint whose epoch boundary slot was
+// skipped, so that the checkpoint block and its state are from an earlier slot.
+#[test]
+fn weak_subjectivity_sync_skipped_boundary_slot() {
+    let boundary_slot = Slot::new(3 * E::slots_per_epoch());
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    // Produce a block in every slot up to the boundary, skip the boundary slot, and then continue
+    // until the boundary is finalized.
+    harness.extend_chain(
+        (boundary_slot.as_u64() - 1) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.advance_slot();
+    harness.extend_chain(
+        (E::slots_per_epoch() * 4) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = harness.chain.head_info().expect("should get head info");
+    assert!(
+        head_info
+            .finalized_checkpoint
+            .epoch
+            .start_slot(E::slots_per_epoch())
+            > boundary_slot
+    );
+
+    // The checkpoint block for the boundary is the block prior to the skipped slot, and its state is
+    // fetched by the block's state root, as checkpoint sync does.
+    let anchor_block = harness
+        .chain
+        .chain_dump()
+        .unwrap()
+        .into_iter()
+        .map(|snapshot| snapshot.beacon_block)
+        .filter(|block| block.slot() < boundary_slot)
+        .last()
+        .expect("should have a block prior to the boundary");
+    assert_eq!(anchor_block.slot(), boundary_slot - 1);
+    let anchor_block_root = anchor_block.canonical_root();
+    let anchor_state = harness
+        .chain
+        .get_state(&anchor_block.state_root(), Some(anchor_block.slot()))
+        .expect("should get state")
+        .expect("anchor state should exist");
+
+    let ws_db_path = tempdir().unwrap();
+    let ws_store = get_store(&ws_db_path);
+    let ws_harness = BeaconChainHarness::new_from_weak_subjectivity_state(
+        MinimalEthSpec,
+        ws_store.clone(),
+        KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+        anchor_state,
+        anchor_block.clone(),
+    );
+    ws_harness
+        .chain
+        .slot_clock
+        .set_slot(harness.chain.slot().unwrap().as_u64());
+
+    // The anchor state is advanced to the boundary, but remains available by the block's state
+    // root.
+    assert_eq!(ws_store.get_split_slot(), anchor_block.slot());
+    let anchor_info = ws_store.get_anchor_info().expect("should have anchor info");
+    assert_eq!(anchor_info.anchor_block_root, anchor_block_root);
+    assert_eq!(anchor_info.anchor_slot, boundary_slot);
+    assert_eq!(
+        ws_store
+            .get_state(&anchor_block.state_root(), Some(anchor_block.slot()))
+            .expect("should read state")
+            .map(|state| state.slot),
+        Some(boundary_slot)
+    );
+
+    // The head is the anchor block, with the advanced state and its root.
+    let ws_head = ws_harness.chain.head().expect("should get head");
+    assert_eq!(ws_head.beacon_block_root, anchor_block_root);
+    assert_eq!(ws_head.beacon_state.slot, boundary_slot);
+    assert_eq!(
+        ws_head.beacon_state_root,
+        ws_head.beacon_state.canonical_root()
+    );
+
+    // Import the blocks that were produced after the anchor, the first of which is a child of the
+    // anchor block.
+    for snapshot in harness.chain.chain_dump().unwrap() {
+        if snapshot.beacon_block.slot() > anchor_block.slot() {
+            ws_harness
+                .chain
+                .process_block(snapshot.beacon_block)
+                .expect("should import block after the anchor");
+        }
+    }
+    ws_harness
+        .chain
+        .fork_choice()
+        .expect("should run fork choice");
+
+    assert_eq!(
+        ws_harness.chain.head_info().unwrap().block_root,
+        head_info.block_root
+    );
+
+    // The chain should continue to finalize and migrate states past the anchor.
+    let num_blocks = E::slots_per_epoch() * 4;
+    ws_harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    check_split_slot(&ws_harness, ws_store);
+}
+
+// Check that an archive exported from one database can be imported into a database started from
+// an anchor, backfilling its blocks, and that an archive with a missing block is rejected.
+#[test]
+fn era_export_import() {
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    harness.extend_chain(
+        (3 * slots_per_restore_point) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let to_slot = store.get_latest_restore_point_slot();
+    assert!(to_slot >= 2 * slots_per_restore_point);
+
+    let mut archive = vec![];
+    let chunks = store
+        .export_era(Slot::new(0), to_slot, &mut archive)
+        .expect("should export archive");
+    assert_eq!(chunks as u64, to_slot.as_u64() / slots_per_restore_point);
+
+    // Ranges which don't start and end on frozen restore points are rejected.
+    assert!(store
+        .export_era(Slot::new(1), to_slot, &mut vec![])
+        .is_err());
+    assert!(store
+        .export_era(Slot::new(0), to_slot + slots_per_restore_point, &mut vec![])
+        .is_err());
+
+    // Start a second database from the restore point at the end of the archive.
+    let anchor_state = store
+        .load_cold_state_by_slot(to_slot)
+        .expect("should load anchor state");
+    let anchor_block_root = anchor_state.get_latest_block_root(anchor_state.canonical_root());
+    let anchor_block = store
+        .get_block(&anchor_block_root)
+        .expect("should read block")
+        .expect("anchor block should exist");
+
+    let ws_db_path = tempdir().unwrap();
+    let ws_store = get_store(&ws_db_path);
+    let _ws_harness = BeaconChainHarness::new_from_weak_subjectivity_state(
+        MinimalEthSpec,
+        ws_store.clone(),
+        KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+        anchor_state.clone(),
+        anchor_block,
+    );
+    assert_eq!(ws_store.get_split_slot(), to_slot);
+
+    // A chunk with a missing block is rejected before any of it is stored.
+    let mut chunk = EraReader::new(&archive[..])
+        .unwrap()
+        .next_chunk::<E>()
+        .expect("should read chunk")
+        .expect("archive should contain a chunk");
+    let removed_block = chunk.blocks.remove(1);
+    let mut writer = EraWriter::new(vec![]).unwrap();
+    writer.write_chunk(&chunk).unwrap();
+    let bad_archive = writer.finish().unwrap();
+
+    assert_eq!(
+        ws_store.import_era(Cursor::new(&bad_archive)),
+        Err(store::Error::EraError(EraError::MissingBlock {
+            slot: removed_block.slot()
+        }))
+    );
+    assert_eq!(
+        ws_store
+            .get_block(&chunk.blocks[2].canonical_root())
+            .expect("should read block"),
+        None
+    );
+
+    let imported = ws_store
+        .import_era(Cursor::new(&archive))
+        .expect("should import archive");
+    assert_eq!(imported.chunks, chunks);
+    assert!(ws_store.get_anchor_info().unwrap().backfill_complete());
+
+    let mut imported_blocks = 0;
+    for slot in 0..to_slot.as_u64() {
+        let block_root = *anchor_state.get_block_root(Slot::new(slot)).unwrap();
+        let block = ws_store
+            .get_block(&block_root)
+            .expect("should read block")
+            .expect("imported block should exist");
+        assert_eq!(Some(&block), store.get_block(&block_root).unwrap().as_ref());
+        if block.slot() == slot {
+            imported_blocks += 1;
+        }
+    }
+    assert_eq!(imported.blocks, imported_blocks);
+
+    // The restore points prior to the anchor are stored.
+    for slot in
+        (slots_per_restore_point..to_slot.as_u64()).step_by(slots_per_restore_point as usize)
+    {
+        let state_root = store
+            .load_cold_state_by_slot(Slot::new(slot))
+            .expect("should load cold state")
+            .canonical_root();
+        assert_eq!(
+            ws_store.state_exists(&state_root),
+            Ok(true),
+            "slot {}",
+            slot
+        );
+    }
+}
+
+// Check that an archive of another chain, or a tampered archive, is rejected before any of it is
+// stored.
+#[test]
+fn era_import_rejects_foreign_and_tampered_archives() {
+    let slots_per_restore_point = StoreConfig::default().slots_per_restore_point;
+
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    harness.extend_chain(
+        (4 * slots_per_restore_point) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // The split state only reaches back one restore point interval, so the states of the first
+    // chunks are unknown to a database started from the end of the archive.
+    let to_slot = store.get_latest_restore_point_slot();
+    assert!(to_slot >= 3 * slots_per_restore_point);
+
+    let mut archive = vec![];
+    store
+        .export_era(Slot::new(0), to_slot, &mut archive)
+        .expect("should export archive");
+
+    let mut reader = EraReader::new(&archive[..]).unwrap();
+    let mut chunks = vec![];
+    while let Some(chunk) = reader.next_chunk::<E>().expect("should read chunk") {
+        chunks.push(chunk);
+    }
+
+    let write_archive = |chunks: &[EraChunk<E>]| {
+        let mut writer = EraWriter::new(vec![]).unwrap();
+        for chunk in chunks {
+            writer.write_chunk(chunk).unwrap();
+        }
+        writer.finish().unwrap()
+    };
+
+    // Start a second database from the restore point at the end of the archive.
+    let anchor_state = store
+        .load_cold_state_by_slot(to_slot)
+        .expect("should load anchor state");
+    let anchor_block_root = anchor_state.get_latest_block_root(anchor_state.canonical_root());
+    let anchor_block = store
+        .get_block(&anchor_block_root)
+        .expect("should read block")
+        .expect("anchor block should exist");
+
+    let ws_db_path = tempdir().unwrap();
+    let ws_store = get_store(&ws_db_path);
+    let _ws_harness = BeaconChainHarness::new_from_weak_subjectivity_state(
+        MinimalEthSpec,
+        ws_store.clone(),
+        KEYPAIRS[0..LOW_VALIDATOR_COUNT].to_vec(),
+        anchor_state,
+        anchor_block,
+    );
+
+    let genesis_block_root = chunks[0].blocks[0].canonical_root();
+    let assert_nothing_imported = || {
+        assert_eq!(
+            ws_store
+                .get_block(&genesis_block_root)
+                .expect("should read block"),
+            None,
+            "should not store any block"
+        );
+        assert_eq!(
+            ws_store.get_anchor_info().unwrap().oldest_block_slot,
+            to_slot
+        );
+    };
+
+    // A chunk missing the block at its start slot.
+    let mut missing_first_block = chunks.clone();
+    let start_slot = missing_first_block[1].start_slot;
+    let removed_block = missing_first_block[1].blocks.remove(0);
+    assert_eq!(removed_block.slot(), start_slot);
+    assert_eq!(
+        ws_store.import_era(Cursor::new(write_archive(&missing_first_block))),
+        Err(store::Error::EraError(EraError::MissingBlock {
+            slot: start_slot
+        }))
+    );
+    assert_nothing_imported();
+
+    // A chunk with a tampered state is rejected by the next chunk, or by a database which has the
+    // state root for its slot.
+    let mut tampered = chunks.clone();
+    tampered[0].state.balances[0] += 1;
+    let tampered_error = Err(store::Error::EraError(EraError::StateRootMismatch {
+        slot: tampered[0].state.slot,
+        state_root: tampered[0].state.canonical_root(),
+        expected_state_root: chunks[0].state.canonical_root(),
+    }));
+    assert_eq!(
+        ws_store.import_era(Cursor::new(write_archive(&tampered))),
+        tampered_error
+    );
+    assert_nothing_imported();
+    assert_eq!(
+        store.import_era(Cursor::new(write_archive(&tampered[0..1]))),
+        tampered_error
+    );
+
+    // A chunk which is not followed by a chunk, and whose state root is unknown to the database.
+    assert_eq!(
+        ws_store.import_era(Cursor::new(write_archive(&chunks[0..1]))),
+        Err(store::Error::EraError(EraError::UnknownStateRoot {
+            slot: chunks[0].state.slot
+        }))
+    );
+    assert_nothing_imported();
+
+    // An archive of another chain.
+    let foreign_db_path = tempdir().unwrap();
+    let foreign_store = get_store(&foreign_db_path);
+    let foreign_harness = get_harness(foreign_store.clone(), HIGH_VALIDATOR_COUNT);
+    foreign_harness.extend_chain(
+        (3 * slots_per_restore_point) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let mut foreign_archive = vec![];
+    foreign_store
+        .export_era(
+            Slot::new(0),
+            foreign_store.get_latest_restore_point_slot(),
+            &mut foreign_archive,
+        )
+        .expect("should export archive");
+    assert!(ws_store.import_era(Cursor::new(&foreign_archive)).is_err());
+    assert!(store.import_era(Cursor::new(&foreign_archive)).is_err());
+    assert_nothing_imported();
+
+    // The genuine archive is still accepted.
+    ws_store
+        .import_era(Cursor::new(&archive))
+        .expect("should import archive");
+    assert!(ws_store.get_anchor_info().unwrap().backfill_complete());
+}
+
 // Check attestation processing and `load_epoch_boundary_state` in the presence of a split DB.
 // This is a bit of a monster test in that it tests lots of different things, but until they're
 // tested elsewhere, this is as good a place as any.
@@ -1,9 +1,11 @@
-use crate::config::{ClientGenesis, Config as ClientConfig};
+use crate::config::{ClientGenesis, Config as ClientConfig, WeakSubjectivityAnchor};
 use crate::notifier::spawn_notifier;
 use crate::Client;
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
+    consistency_check::{check_consistency, prune_bad_heads},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
+    events::{ServerSentEventHandler, TeeEventHandler},
     migrate::{BackgroundMigrator, Migrate, NullMigrator},
     slot_clock::{SlotClock, SystemTimeSlotClock},
     store::{DiskStore, MemoryStore, SimpleDiskStore, Store, StoreConfig},
@@ -16,19 +18,26 @@ use eth2_libp2p::NetworkGlobals;
 use futures::{future, Future, IntoFuture};
 use genesis::{interop_genesis_state, Eth1GenesisService};
 use network::{NetworkConfig, NetworkMessage, NetworkService};
-use slog::info;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{info, warn};
 use ssz::Decode;
 use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
+use store::hot_cold_store::HotColdDBError;
 use tokio::sync::mpsc::UnboundedSender;
-use types::{test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec};
+use types::{
+    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec, SignedBeaconBlock,
+};
 use websocket_server::{Config as WebSocketConfig, WebSocketSender};
 
 /// Interval between polling the eth1 node for genesis information.
 pub const ETH1_GENESIS_UPDATE_INTERVAL_MILLIS: u64 = 7_000;
 
+/// Timeout for downloading a weak subjectivity state from a remote beacon node.
+pub const WEAK_SUBJECTIVITY_DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+
 /// Builds a `Client` instance.
 ///
 /// ## Notes
@@ -53,6 +62,7 @@ pub struct ClientBuilder<T: BeaconChainTypes> {
     eth1_service: Option<Eth1Service>,
     exit_channels: Vec<tokio::sync::oneshot::Sender<()>>,
     event_handler: Option<T::EventHandler>,
+    sse_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     network_globals: Option<Arc<NetworkGlobals<T::EthSpec>>>,
     network_send: Option<UnboundedSender<NetworkMessage<T::EthSpec>>>,
     http_listen_addr: Option<SocketAddr>,
@@ -87,6 +97,7 @@ where
             eth1_service: None,
             exit_channels: vec![],
             event_handler: None,
+            sse_handler: None,
             network_globals: None,
             network_send: None,
             http_listen_addr: None,
@@ -203,6 +214,67 @@ where
 
                             Box::new(future)
                         }
+                        ClientGenesis::WeakSubjectivityState {
+                            genesis_state_bytes,
+                            anchor,
+                        } => {
+                            let log = context.log.clone();
+
+                            let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                                .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e));
+
+                            let anchor_future: Box<dyn Future<Item = _, Error = _> + Send> =
+                                match anchor {
+                                    WeakSubjectivityAnchor::SszBytes {
+                                        anchor_state_bytes,
+                                        anchor_block_bytes,
+                                    } => {
+                                        info!(
+                                            log,
+                                            "Starting from weak subjectivity state";
+                                            "source" => "file"
+                                        );
+
+                                        Box::new(
+                                            decode_weak_subjectivity_anchor(
+                                                &anchor_state_bytes,
+                                                &anchor_block_bytes,
+                                            )
+                                            .into_future(),
+                                        )
+                                    }
+                                    WeakSubjectivityAnchor::RemoteBeaconNode { url } => {
+                                        info!(
+                                            log,
+                                            "Starting from weak subjectivity state";
+                                            "source" => &url
+                                        );
+
+                                        Box::new(download_weak_subjectivity_anchor(url))
+                                    }
+                                };
+
+                            let future = genesis_state
+                                .into_future()
+                                .join(anchor_future)
+                                .and_then(move |(genesis_state, (anchor_state, anchor_block))| {
+                                    info!(
+                                        log,
+                                        "Loaded weak subjectivity state";
+                                        "slot" => anchor_state.slot,
+                                        "block_root" => format!("{:?}", anchor_block.canonical_root()),
+                                    );
+
+                                    builder.weak_subjectivity_state(
+                                        anchor_state,
+                                        anchor_block,
+                                        genesis_state,
+                                    )
+                                })
+                                .map(|v| (v, None));
+
+                            Box::new(future)
+                        }
                         ClientGenesis::DepositContract => {
                             info!(
                                 context.log,
@@ -328,6 +400,7 @@ where
             &context.executor,
             beacon_chain,
             network_info,
+            self.sse_handler.clone(),
             client_config
                 .create_db_path()
                 .map_err(|_| "unable to read data dir")?,
@@ -345,6 +418,31 @@ where
         Ok(self)
     }
 
+    /// Starts the WebSocket server if it is enabled, returning a sender which broadcasts to its
+    /// clients (or a dummy sender if it is disabled).
+    fn websocket_sender(
+        &mut self,
+        config: WebSocketConfig,
+    ) -> Result<WebSocketSender<TEthSpec>, String> {
+        if !config.enabled {
+            return Ok(WebSocketSender::dummy());
+        }
+
+        let context = self
+            .runtime_context
+            .as_ref()
+            .ok_or_else(|| "websocket server requires a runtime_context")?
+            .service_context("ws".into());
+
+        let (sender, exit_channel, listening_addr) =
+            websocket_server::start_server(&config, &context.executor, &context.log)?;
+
+        self.exit_channels.push(exit_channel);
+        self.websocket_listen_addr = Some(listening_addr);
+
+        Ok(sender)
+    }
+
     /// Immediately starts the service that periodically logs information each slot.
     pub fn notifier(mut self) -> Result<Self, String> {
         let context = self
@@ -457,29 +555,39 @@ where
 {
     /// Specifies that the `BeaconChain` should publish events using the WebSocket server.
     pub fn websocket_event_handler(mut self, config: WebSocketConfig) -> Result<Self, String> {
-        let context = self
-            .runtime_context
-            .as_ref()
-            .ok_or_else(|| "websocket_event_handler requires a runtime_context")?
-            .service_context("ws".into());
+        let sender = self.websocket_sender(config)?;
+        self.event_handler = Some(sender);
 
-        let (sender, exit_channel, listening_addr): (
-            WebSocketSender<TEthSpec>,
-            Option<_>,
-            Option<_>,
-        ) = if config.enabled {
-            let (sender, exit, listening_addr) =
-                websocket_server::start_server(&config, &context.executor, &context.log)?;
-            (sender, Some(exit), Some(listening_addr))
-        } else {
-            (WebSocketSender::dummy(), None, None)
-        };
+        Ok(self)
+    }
+}
 
-        if let Some(channel) = exit_channel {
-            self.exit_channels.push(channel);
-        }
-        self.event_handler = Some(sender);
-        self.websocket_listen_addr = listening_addr;
+impl<TStore, TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec>
+    ClientBuilder<
+        Witness<
+            TStore,
+            TStoreMigrator,
+            TSlotClock,
+            TEth1Backend,
+            TEthSpec,
+            TeeEventHandler<TEthSpec>,
+        >,
+    >
+where
+    TStore: Store<TEthSpec> + 'static,
+    TStoreMigrator: Migrate<TStore, TEthSpec>,
+    TSlotClock: SlotClock + 'static,
+    TEth1Backend: Eth1ChainBackend<TEthSpec, TStore> + 'static,
+    TEthSpec: EthSpec + 'static,
+{
+    /// Specifies that the `BeaconChain` should publish events using both the WebSocket server and
+    /// the event stream of the HTTP API.
+    pub fn tee_event_handler(mut self, config: WebSocketConfig) -> Result<Self, String> {
+        let websocket_sender = self.websocket_sender(config)?;
+        let sse_handler = Arc::new(ServerSentEventHandler::default());
+
+        self.event_handler = Some(TeeEventHandler::new(websocket_sender, sse_handler.clone()));
+        self.sse_handler = Some(sse_handler);
 
         Ok(self)
     }
@@ -520,11 +628,94 @@ where
             .clone()
             .ok_or_else(|| "disk_store requires a chain spec".to_string())?;
 
-        let store = DiskStore::open(hot_path, cold_path, config, spec, context.log)
-            .map_err(|e| format!("Unable to open database: {:?}", e))?;
+        let store = DiskStore::open(hot_path, cold_path, config, spec, context.log).map_err(
+            |e| match e {
+                store::Error::HotColdDBError(HotColdDBError::SchemaDowngrade {
+                    database_version,
+                    software_version,
+                }) => format!(
+                    "The database has schema version {}, which is newer than version {} \
+                     supported by this version of Lighthouse. Upgrade Lighthouse or remove the \
+                     database with --purge-db",
+                    database_version, software_version
+                ),
+                store::Error::HotColdDBError(HotColdDBError::SlotsPerRestorePointMismatch {
+                    database,
+                    config,
+                }) => format!(
+                    "The database was created with --slots-per-restore-point {}, not {}. Use \
+                     `lighthouse db reindex --new-slots-per-restore-point {}` to change it",
+                    database, config, config
+                ),
+                store::Error::DatabaseBackendMismatch { database, config } => format!(
+                    "The database was created with --db-backend {}, not {}",
+                    database, config
+                ),
+                e => format!("Unable to open database: {:?}", e),
+            },
+        )?;
         self.store = Some(Arc::new(store));
         Ok(self)
     }
+
+    /// Checks that the blocks and states referenced by the persisted beacon chain are present in
+    /// the database.
+    ///
+    /// If `prune_bad_heads_enabled` is true, the heads with missing data are pruned. Otherwise, an
+    /// error is returned if any data is missing.
+    pub fn check_database(self, prune_bad_heads_enabled: bool) -> Result<Self, String> {
+        let log = self
+            .runtime_context
+            .as_ref()
+            .ok_or_else(|| "check_database requires a log".to_string())?
+            .service_context("db_check".into())
+            .log;
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "check_database requires a store".to_string())?;
+
+        let report = match check_consistency(&store)? {
+            Some(report) => report,
+            // The database is empty, there is nothing to check.
+            None => return Ok(self),
+        };
+
+        if report.is_consistent() {
+            info!(
+                log,
+                "Database check passed";
+                "heads" => report.heads_checked,
+                "fork_choice_nodes" => report.fork_choice_nodes_checked,
+            );
+            return Ok(self);
+        }
+
+        for inconsistency in &report.inconsistencies {
+            warn!(
+                log,
+                "Database inconsistency";
+                "error" => inconsistency.to_string()
+            );
+        }
+
+        if prune_bad_heads_enabled {
+            prune_bad_heads(&store, &report, &log)?;
+            Ok(self)
+        } else if report.is_repairable() {
+            Err(format!(
+                "The database refers to {} missing blocks or states. Restart with \
+                 --prune-bad-heads to prune the heads with missing data",
+                report.inconsistencies.len()
+            ))
+        } else {
+            Err(format!(
+                "The database refers to {} missing blocks or states and can't be repaired. \
+                 Remove the database with --purge-db",
+                report.inconsistencies.len()
+            ))
+        }
+    }
 }
 
 impl<TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec, TEventHandler>
@@ -766,3 +957,50 @@ where
         Ok(self)
     }
 }
+
+/// Decodes the state and block for `ClientGenesis::WeakSubjectivityState` from SSZ bytes.
+fn decode_weak_subjectivity_anchor<E: EthSpec>(
+    state_bytes: &[u8],
+    block_bytes: &[u8],
+) -> Result<(BeaconState<E>, SignedBeaconBlock<E>), String> {
+    let state = BeaconState::from_ssz_bytes(state_bytes)
+        .map_err(|e| format!("Unable to parse weak subjectivity state SSZ: {:?}", e))?;
+    let block = SignedBeaconBlock::from_ssz_bytes(block_bytes)
+        .map_err(|e| format!("Unable to parse weak subjectivity block SSZ: {:?}", e))?;
+
+    Ok((state, block))
+}
+
+/// Downloads the latest finalized block, and the state it produced, from the beacon node HTTP API
+/// at `server`.
+///
+/// If the first slots of the finalized epoch were skipped, the state is prior to the epoch boundary.
+/// The `BeaconChainBuilder` advances it to the boundary.
+fn download_weak_subjectivity_anchor<E: EthSpec>(
+    server: String,
+) -> impl Future<Item = (BeaconState<E>, SignedBeaconBlock<E>), Error = String> {
+    RemoteBeaconNode::new_with_timeout(
+        server.clone(),
+        Duration::from_secs(WEAK_SUBJECTIVITY_DOWNLOAD_TIMEOUT_SECS),
+    )
+    .into_future()
+    .and_then(move |remote_node| {
+        let beacon = remote_node.http.beacon();
+        let state_beacon = beacon.clone();
+
+        beacon
+            .get_head()
+            .and_then(move |head| beacon.get_block_by_root(head.finalized_block_root))
+            .and_then(move |(block, _)| {
+                state_beacon
+                    .get_state_by_root(block.state_root())
+                    .map(move |(state, _)| (state, block))
+            })
+            .map_err(move |e| {
+                format!(
+                    "Unable to download weak subjectivity state from {}: {:?}",
+                    server, e
+                )
+            })
+    })
+}
@@ -29,6 +29,27 @@ pub enum ClientGenesis {
     /// We include the bytes instead of the `BeaconState<E>` because the `EthSpec` type
     /// parameter would be very annoying.
     SszBytes { genesis_state_bytes: Vec<u8> },
+    /// Starts from a recent, trusted finalized state instead of genesis. Blocks and states prior
+    /// to the anchor are not downloaded.
+    ///
+    /// The genesis state is still required to determine the genesis block root.
+    WeakSubjectivityState {
+        genesis_state_bytes: Vec<u8>,
+        anchor: WeakSubjectivityAnchor,
+    },
+}
+
+/// The source of the state and block used by `ClientGenesis::WeakSubjectivityState`.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum WeakSubjectivityAnchor {
+    /// SSZ-encoded `BeaconState` and `SignedBeaconBlock` bytes. The block must have been applied
+    /// to produce the state.
+    SszBytes {
+        anchor_state_bytes: Vec<u8>,
+        anchor_block_bytes: Vec<u8>,
+    },
+    /// Downloads the latest finalized state and block from a trusted beacon node HTTP API.
+    RemoteBeaconNode { url: String },
 }
 
 impl Default for ClientGenesis {
@@ -60,6 +81,12 @@ pub struct Config {
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
     pub genesis: ClientGenesis,
     pub store: store::StoreConfig,
+    /// If true, check that the blocks and states referenced by the persisted beacon chain are
+    /// present in the database before resuming it.
+    pub check_db: bool,
+    /// If true, prune the heads with missing blocks or states found by the database check,
+    /// instead of refusing to start.
+    pub prune_bad_heads: bool,
     pub network: network::NetworkConfig,
     pub rest_api: rest_api::Config,
     pub websocket_server: websocket_server::Config,
@@ -76,6 +103,8 @@ impl Default for Config {
             log_file: PathBuf::from(""),
             genesis: <_>::default(),
             store: <_>::default(),
+            check_db: false,
+            prune_bad_heads: false,
             network: NetworkConfig::default(),
             rest_api: <_>::default(),
             websocket_server: <_>::default(),
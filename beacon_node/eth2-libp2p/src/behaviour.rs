@@ -1,5 +1,5 @@
 use crate::discovery::{enr::Eth2Enr, Discovery};
-use crate::peer_manager::{PeerManager, PeerManagerEvent};
+use crate::peer_manager::{MessageAcceptance, PeerManager, PeerManagerEvent, PeerScoreParams};
 use crate::rpc::*;
 use crate::types::{GossipEncoding, GossipKind, GossipTopic};
 use crate::{error, Enr, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash};
@@ -99,11 +99,11 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         };
 
         Ok(Behaviour {
-            eth2_rpc: RPC::new(log.clone()),
+            eth2_rpc: RPC::new(&net_conf.rpc_quotas, log.clone()),
             gossipsub: Gossipsub::new(local_peer_id, net_conf.gs_config.clone()),
             discovery: Discovery::new(local_key, net_conf, network_globals.clone(), log)?,
             identify,
-            peer_manager: PeerManager::new(network_globals.clone(), log),
+            peer_manager: PeerManager::new(network_globals.clone(), net_conf, log),
             events: Vec::new(),
             seen_gossip_messages: LruCache::new(100_000),
             meta_data,
@@ -212,6 +212,25 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
             .propagate_message(&message_id, propagation_source);
     }
 
+    /// Scores the peer that propagated a gossip message on the `kind` topic by the result of
+    /// validating the message.
+    ///
+    /// Returns true if the peer's gossip score has fallen below the disconnect threshold.
+    pub fn report_gossip_validation(
+        &mut self,
+        propagation_source: &PeerId,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+    ) -> bool {
+        self.peer_manager
+            .gossip_validation(propagation_source, kind, acceptance)
+    }
+
+    /// Replaces the parameters used to score the gossip of peers.
+    pub fn update_gossip_score_params(&mut self, params: PeerScoreParams) {
+        self.peer_manager.update_gossip_score_params(params);
+    }
+
     /* Eth2 RPC behaviour functions */
 
     /// Sends an RPC Request/Response via the RPC protocol.
@@ -318,6 +337,26 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> Behaviour<TSubstream, T
         self.send_rpc(peer_id, metadata_request);
     }
 
+    /// Scores a peer that propagated a gossip message which could not be decoded as a rejected
+    /// message on each of its topics.
+    fn reject_undecodable_message(&mut self, propagation_source: PeerId, topics: &[TopicHash]) {
+        let low_score = topics
+            .iter()
+            .filter_map(|topic| GossipTopic::decode(topic.as_str()).ok())
+            .fold(false, |low_score, topic| {
+                self.peer_manager.gossip_validation(
+                    &propagation_source,
+                    topic.kind(),
+                    MessageAcceptance::Reject,
+                ) || low_score
+            });
+
+        if low_score {
+            self.events
+                .push(BehaviourEvent::LowGossipScore(propagation_source));
+        }
+    }
+
     /// Sends a METADATA response to a peer.
     fn send_meta_data_response(&mut self, id: RequestId, peer_id: PeerId) {
         let metadata_response = RPCEvent::Response(
@@ -335,12 +374,22 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
     fn inject_event(&mut self, event: GossipsubEvent) {
         match event {
             GossipsubEvent::Message(propagation_source, id, gs_msg) => {
+                // Ignore the messages of peers whose gossip score is too low. The gossipsub
+                // implementation cannot remove a peer from its meshes, so graylisted peers remain
+                // in them until their score falls below the disconnect threshold.
+                if self.peer_manager.is_graylisted(&propagation_source) {
+                    debug!(self.log, "Ignoring gossip from graylisted peer"; "peer_id" => propagation_source.to_string());
+                    return;
+                }
+
                 // Note: We are keeping track here of the peer that sent us the message, not the
                 // peer that originally published the message.
                 if self.seen_gossip_messages.put(id.clone(), ()).is_none() {
                     match PubsubMessage::decode(&gs_msg.topics, &gs_msg.data) {
                         Err(e) => {
-                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
+                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e));
+                            // Invalid messages are the fault of the peer that propagated them.
+                            self.reject_undecodable_message(propagation_source, &gs_msg.topics);
                         }
                         Ok(msg) => {
                             // if this message isn't a duplicate, notify the network
@@ -420,6 +469,10 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec>
             RPCMessage::PeerDisconnected(peer_id) => {
                 self.events.push(BehaviourEvent::PeerDisconnected(peer_id))
             }
+            RPCMessage::RateLimited(peer_id, protocol, err) => {
+                self.peer_manager
+                    .handle_rate_limited(&peer_id, protocol, &err);
+            }
             RPCMessage::RPC(peer_id, rpc_event) => {
                 // The METADATA and PING RPC responses are handled within the behaviour and not
                 // propagated
@@ -530,6 +583,8 @@ impl<TSubstream: AsyncRead + AsyncWrite, TSpec: EthSpec> NetworkBehaviourEventPr
                 }
                 // send peer info to the peer manager.
                 self.peer_manager.identify(&peer_id, &info);
+                // let discovery learn our external IPv6 address
+                self.discovery.observed_address(&peer_id, &observed_addr);
 
                 debug!(self.log, "Identified Peer"; "peer" => format!("{}", peer_id),
                 "protocol_version" => info.protocol_version,
@@ -577,4 +632,7 @@ pub enum BehaviourEvent<TSpec: EthSpec> {
     PeerSubscribed(PeerId, TopicHash),
     /// Inform the network to send a Status to this peer.
     StatusPeer(PeerId),
+    /// The gossip score of the peer has fallen below the disconnect threshold. The peer should be
+    /// disconnected and banned.
+    LowGossipScore(PeerId),
 }
@@ -1,3 +1,5 @@
+use crate::peer_manager::PeerScoreParams;
+use crate::rpc::RateLimiterConfig;
 use crate::types::GossipKind;
 use crate::Enr;
 use libp2p::discv5::{Discv5Config, Discv5ConfigBuilder};
@@ -5,6 +7,7 @@ use libp2p::gossipsub::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessag
 use libp2p::Multiaddr;
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::net::Ipv6Addr;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -20,12 +23,22 @@ pub struct Config {
     /// IP address to listen on.
     pub listen_address: std::net::IpAddr,
 
+    /// An IPv6 address on which libp2p and discovery listen in addition to an IPv4
+    /// `listen_address`, making the node dual-stack.
+    pub listen_address6: Option<Ipv6Addr>,
+
     /// The TCP port that libp2p listens on.
     pub libp2p_port: u16,
 
+    /// The TCP port that libp2p listens on for `listen_address6`.
+    pub libp2p_port6: u16,
+
     /// UDP port that discovery listens on.
     pub discovery_port: u16,
 
+    /// The UDP port that discovery listens on for `listen_address6`.
+    pub discovery_port6: u16,
+
     /// The address to broadcast to peers about which address we are listening on. None indicates
     /// that no discovery address has been set in the CLI args.
     pub enr_address: Option<std::net::IpAddr>,
@@ -36,7 +49,17 @@ pub struct Config {
     /// The tcp port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp_port: Option<u16>,
 
-    /// Target number of connected peers.
+    /// The IPv6 address to broadcast to peers alongside the IPv4 `enr_address` of a dual-stack
+    /// node. An IPv6-only node broadcasts its address in `enr_address` instead.
+    pub enr_address6: Option<Ipv6Addr>,
+
+    /// The udp port to broadcast to peers for `enr_address6`.
+    pub enr_udp6_port: Option<u16>,
+
+    /// The tcp port to broadcast to peers for `enr_address6`.
+    pub enr_tcp6_port: Option<u16>,
+
+    /// Target number of connected peers, excluding trusted peers.
     pub max_peers: usize,
 
     /// A secp256k1 secret key, as bytes in ASCII-encoded hex.
@@ -59,6 +82,28 @@ pub struct Config {
     /// List of libp2p nodes to initially connect to.
     pub libp2p_nodes: Vec<Multiaddr>,
 
+    /// List of addresses of peers which we always stay connected to. Each address must end with
+    /// the `/p2p/` id of the peer.
+    ///
+    /// Trusted peers are exempt from reputation changes, banning, pruning and `max_peers`, and are
+    /// redialled when they disconnect.
+    pub trusted_peers: Vec<Multiaddr>,
+
+    /// Gossip messages from peers with a gossip score below this threshold are ignored.
+    pub gossip_graylist_threshold: f64,
+
+    /// Peers with a gossip score below this threshold are disconnected and banned. Must not be
+    /// greater than `gossip_graylist_threshold`.
+    pub gossip_disconnect_threshold: f64,
+
+    /// The parameters used to score the gossip messages of peers, derived from the `ChainSpec`
+    /// when the network starts and refreshed every epoch. `None` disables gossip scoring.
+    #[serde(skip)]
+    pub gossip_score_params: Option<PeerScoreParams>,
+
+    /// The quota of the inbound RPC requests of each peer, for each protocol.
+    pub rpc_quotas: RateLimiterConfig,
+
     /// Client version
     pub client_version: String,
 
@@ -124,20 +169,43 @@ impl Default for Config {
         Config {
             network_dir,
             listen_address: "0.0.0.0".parse().expect("valid ip address"),
+            listen_address6: None,
             libp2p_port: 9000,
+            libp2p_port6: 9090,
             discovery_port: 9000,
+            discovery_port6: 9090,
             enr_address: None,
             enr_udp_port: None,
             enr_tcp_port: None,
+            enr_address6: None,
+            enr_udp6_port: None,
+            enr_tcp6_port: None,
             max_peers: 50,
             secret_key_hex: None,
             gs_config,
             discv5_config,
             boot_nodes: vec![],
             libp2p_nodes: vec![],
+            trusted_peers: vec![],
+            gossip_graylist_threshold: -400.0,
+            gossip_disconnect_threshold: -900.0,
+            gossip_score_params: None,
+            rpc_quotas: RateLimiterConfig::default(),
             client_version: version::version(),
             topics,
             propagation_percentage: None,
         }
     }
 }
+
+impl Config {
+    /// Returns true if libp2p listens on an IPv4 address.
+    pub fn listens_on_ipv4(&self) -> bool {
+        self.listen_address.is_ipv4()
+    }
+
+    /// Returns true if libp2p listens on an IPv6 address, either alone or alongside IPv4.
+    pub fn listens_on_ipv6(&self) -> bool {
+        self.listen_address.is_ipv6() || self.listen_address6.is_some()
+    }
+}
@@ -12,6 +12,7 @@ use ssz_types::BitVector;
 use std::convert::TryInto;
 use std::fs::File;
 use std::io::prelude::*;
+use std::net::IpAddr;
 use std::path::Path;
 use std::str::FromStr;
 use types::{EnrForkId, EthSpec};
@@ -112,16 +113,37 @@ pub fn build_enr<T: EthSpec>(
     enr_fork_id: EnrForkId,
 ) -> Result<Enr, String> {
     let mut builder = EnrBuilder::new("v4");
+    // `ip` sets the `ip` or `ip6` field depending on the address family
     if let Some(enr_address) = config.enr_address {
         builder.ip(enr_address);
     }
-    if let Some(udp_port) = config.enr_udp_port {
-        builder.udp(udp_port);
+    if let Some(enr_address6) = config.enr_address6 {
+        builder.ip(IpAddr::V6(enr_address6));
     }
     // we always give it our listening tcp port
     // TODO: Add uPnP support to map udp and tcp ports
     let tcp_port = config.enr_tcp_port.unwrap_or_else(|| config.libp2p_port);
-    builder.tcp(tcp_port);
+    // the ports of the listen address belong to the fields of its address family
+    if config.listen_address.is_ipv6() {
+        builder.tcp6(tcp_port);
+        if let Some(udp_port) = config.enr_udp_port {
+            builder.udp6(udp_port);
+        }
+    } else {
+        builder.tcp(tcp_port);
+        if let Some(udp_port) = config.enr_udp_port {
+            builder.udp(udp_port);
+        }
+        // a dual-stack node also listens for libp2p connections and discovery over IPv6
+        if config.listen_address6.is_some() {
+            builder.tcp6(config.enr_tcp6_port.unwrap_or_else(|| config.libp2p_port6));
+            builder.udp6(
+                config
+                    .enr_udp6_port
+                    .unwrap_or_else(|| config.discovery_port6),
+            );
+        }
+    }
 
     // set the `eth2` field on our ENR
     builder.add_value(ETH2_ENR_KEY.into(), enr_fork_id.as_ssz_bytes());
@@ -132,7 +154,6 @@ pub fn build_enr<T: EthSpec>(
     builder.add_value(BITFIELD_ENR_KEY.into(), bitfield.as_ssz_bytes());
 
     builder
-        .tcp(config.libp2p_port)
         .build(enr_key)
         .map_err(|e| format!("Could not build Local ENR: {:?}", e))
 }
@@ -142,12 +163,15 @@ pub fn build_enr<T: EthSpec>(
 fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
     // take preference over disk_enr address if one is not specified
     (local_enr.ip().is_none() || local_enr.ip() == disk_enr.ip())
+        && (local_enr.ip6().is_none() || local_enr.ip6() == disk_enr.ip6())
         // tcp ports must match
         && local_enr.tcp() == disk_enr.tcp()
+        && local_enr.tcp6() == disk_enr.tcp6()
         // must match on the same fork
         && local_enr.get(ETH2_ENR_KEY) == disk_enr.get(ETH2_ENR_KEY)
         // take preference over disk udp port if one is not specified
         && (local_enr.udp().is_none() || local_enr.udp() == disk_enr.udp())
+        && (local_enr.udp6().is_none() || local_enr.udp6() == disk_enr.udp6())
         // we need the BITFIELD_ENR_KEY key to match, otherwise we use a new ENR. This will likely only
         // be true for non-validating nodes
         && local_enr.get(BITFIELD_ENR_KEY) == disk_enr.get(BITFIELD_ENR_KEY)
@@ -170,3 +194,79 @@ pub fn save_enr_to_disk(dir: &Path, enr: &Enr, log: &slog::Logger) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+    use types::MinimalEthSpec;
+
+    fn build(config: &NetworkConfig) -> Enr {
+        let enr_key: CombinedKey = Keypair::generate_secp256k1().try_into().unwrap();
+        build_enr::<MinimalEthSpec>(&enr_key, config, EnrForkId::default()).unwrap()
+    }
+
+    #[test]
+    fn ipv4_fields() {
+        let mut config = NetworkConfig::default();
+        config.libp2p_port = 9000;
+        config.enr_address = Some("10.0.0.1".parse().unwrap());
+        config.enr_udp_port = Some(9001);
+        let enr = build(&config);
+
+        assert_eq!(enr.ip(), Some("10.0.0.1".parse().unwrap()));
+        assert_eq!(enr.udp(), Some(9001));
+        assert_eq!(enr.tcp(), Some(9000), "should use the libp2p port");
+        assert_eq!(enr.ip6(), None);
+        assert_eq!(enr.udp6(), None);
+        assert_eq!(enr.tcp6(), None);
+
+        config.enr_tcp_port = Some(9002);
+        assert_eq!(
+            build(&config).tcp(),
+            Some(9002),
+            "should prefer the ENR tcp port"
+        );
+    }
+
+    #[test]
+    fn ipv6_only_fields() {
+        let mut config = NetworkConfig::default();
+        config.listen_address = "::".parse().unwrap();
+        config.libp2p_port = 9000;
+        config.enr_address = Some("fd00::1".parse().unwrap());
+        config.enr_udp_port = Some(9001);
+        let enr = build(&config);
+
+        assert_eq!(enr.ip6(), Some("fd00::1".parse().unwrap()));
+        assert_eq!(enr.udp6(), Some(9001));
+        assert_eq!(enr.tcp6(), Some(9000));
+        assert_eq!(enr.ip(), None);
+        assert_eq!(enr.udp(), None, "should not use the IPv4 fields");
+        assert_eq!(enr.tcp(), None, "should not use the IPv4 fields");
+    }
+
+    #[test]
+    fn dual_stack_fields() {
+        let mut config = NetworkConfig::default();
+        config.listen_address6 = Some(Ipv6Addr::UNSPECIFIED);
+        config.libp2p_port = 9000;
+        config.libp2p_port6 = 9090;
+        config.discovery_port6 = 9091;
+        config.enr_address = Some("10.0.0.1".parse().unwrap());
+        config.enr_address6 = Some("fd00::1".parse().unwrap());
+        let enr = build(&config);
+
+        assert_eq!(enr.ip(), Some("10.0.0.1".parse().unwrap()));
+        assert_eq!(enr.tcp(), Some(9000));
+        assert_eq!(enr.ip6(), Some("fd00::1".parse().unwrap()));
+        assert_eq!(enr.tcp6(), Some(9090), "should use the IPv6 libp2p port");
+        assert_eq!(enr.udp6(), Some(9091), "should use the IPv6 discovery port");
+
+        config.enr_tcp6_port = Some(9092);
+        config.enr_udp6_port = Some(9093);
+        let enr = build(&config);
+        assert_eq!(enr.tcp6(), Some(9092), "should prefer the ENR tcp6 port");
+        assert_eq!(enr.udp6(), Some(9093), "should prefer the ENR udp6 port");
+    }
+}
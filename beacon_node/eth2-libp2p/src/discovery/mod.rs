@@ -16,8 +16,8 @@ use libp2p::swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters, Pr
 use slog::{crit, debug, info, warn};
 use ssz::{Decode, Encode};
 use ssz_types::BitVector;
-use std::collections::{HashSet, VecDeque};
-use std::net::SocketAddr;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -43,7 +43,7 @@ pub struct Discovery<TSubstream, TSpec: EthSpec> {
     /// The currently banned peers.
     banned_peers: HashSet<PeerId>,
 
-    /// The target number of connected peers on the libp2p interface.
+    /// The target number of connected peers on the libp2p interface, excluding trusted peers.
     max_peers: usize,
 
     /// The directory where the ENR is stored.
@@ -61,9 +61,28 @@ pub struct Discovery<TSubstream, TSpec: EthSpec> {
     //TODO: Improve NAT handling limit the above restriction
     tcp_port: u16,
 
+    /// The TCP port for libp2p over IPv6 on a dual-stack node.
+    tcp_port6: u16,
+
+    /// Whether libp2p listens on IPv4 and IPv6 respectively. Peers are preferably dialled on the
+    /// address families we listen on, as these are the ones we are likely able to reach.
+    listens_on_ipv4: bool,
+    listens_on_ipv6: bool,
+
     /// The discovery behaviour used to discover new peers.
     discovery: Discv5<TSubstream>,
 
+    /// The discovery behaviour listening on the IPv6 address of a dual-stack node. Its local ENR
+    /// is kept identical to the local ENR of `discovery`.
+    discovery6: Option<Discv5<TSubstream>>,
+
+    /// The IPv6 address each connected peer observes us on, used to update the `ip6` field of the
+    /// ENR of a dual-stack node. `None` if the ENR is not updated.
+    ip6_votes: Option<HashMap<PeerId, Ipv6Addr>>,
+
+    /// The number of peers that must agree on our IPv6 address before the ENR is updated.
+    ip6_votes_min: usize,
+
     /// A collection of network constants that can be read from other threads.
     network_globals: Arc<NetworkGlobals<TSpec>>,
 
@@ -87,50 +106,77 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
 
         let local_enr = network_globals.local_enr.read().clone();
 
-        info!(log, "ENR Initialised"; "enr" => local_enr.to_base64(), "seq" => local_enr.seq(), "id"=> format!("{}",local_enr.node_id()), "ip" => format!("{:?}", local_enr.ip()), "udp"=> format!("{:?}", local_enr.udp()), "tcp" => format!("{:?}", local_enr.tcp()));
+        info!(log, "ENR Initialised"; "enr" => local_enr.to_base64(), "seq" => local_enr.seq(), "id"=> format!("{}",local_enr.node_id()), "ip" => format!("{:?}", local_enr.ip()), "udp"=> format!("{:?}", local_enr.udp()), "tcp" => format!("{:?}", local_enr.tcp()), "ip6" => format!("{:?}", local_enr.ip6()), "udp6"=> format!("{:?}", local_enr.udp6()), "tcp6" => format!("{:?}", local_enr.tcp6()));
 
         let listen_socket = SocketAddr::new(config.listen_address, config.discovery_port);
 
-        let mut discovery = Discv5::new(
-            local_enr,
+        let discovery = Discv5::new(
+            local_enr.clone(),
             local_key.clone(),
             config.discv5_config.clone(),
             listen_socket,
         )
         .map_err(|e| format!("Discv5 service failed. Error: {:?}", e))?;
 
-        // Add bootnodes to routing table
-        for bootnode_enr in config.boot_nodes.clone() {
-            debug!(
-                log,
-                "Adding node to routing table";
-                "node_id" => format!("{}", bootnode_enr.node_id()),
-                "peer_id" => format!("{}", bootnode_enr.peer_id()),
-                "ip" => format!("{:?}", bootnode_enr.ip()),
-                "udp" => format!("{:?}", bootnode_enr.udp()),
-                "tcp" => format!("{:?}", bootnode_enr.tcp())
-            );
-            let _ = discovery.add_enr(bootnode_enr).map_err(|e| {
-                warn!(
-                    log,
-                    "Could not add peer to the local routing table";
-                    "error" => format!("{}", e)
+        // A dual-stack node runs a second discv5 service on its IPv6 address. Discv5 compares the
+        // socket voted for by its peers with the IPv4 socket of the ENR if there is one, so the
+        // IPv6 service cannot update the ENR itself. Instead, its `ip6` field is updated from
+        // the addresses libp2p peers observe us on.
+        let discovery6 = match config.listen_address6 {
+            Some(listen_address6) => {
+                let mut discv5_config6 = config.discv5_config.clone();
+                discv5_config6.enr_update = false;
+                let listen_socket6 =
+                    SocketAddr::new(IpAddr::V6(listen_address6), config.discovery_port6);
+                Some(
+                    Discv5::new(local_enr, local_key.clone(), discv5_config6, listen_socket6)
+                        .map_err(|e| format!("IPv6 Discv5 service failed. Error: {:?}", e))?,
                 )
-            });
-        }
+            }
+            None => None,
+        };
+        let ip6_votes = if discovery6.is_some() && config.discv5_config.enr_update {
+            Some(HashMap::new())
+        } else {
+            None
+        };
 
-        Ok(Self {
+        let mut discovery = Self {
             events: VecDeque::with_capacity(16),
             banned_peers: HashSet::new(),
             max_peers: config.max_peers,
             peer_discovery_delay: Delay::new(Instant::now()),
             past_discovery_delay: INITIAL_SEARCH_DELAY,
             tcp_port: config.libp2p_port,
+            tcp_port6: config.libp2p_port6,
+            listens_on_ipv4: config.listens_on_ipv4(),
+            listens_on_ipv6: config.listens_on_ipv6(),
             discovery,
+            discovery6,
+            ip6_votes,
+            ip6_votes_min: config.discv5_config.enr_peer_update_min,
             network_globals,
             log,
             enr_dir,
-        })
+        };
+
+        // Add bootnodes to routing table
+        for bootnode_enr in config.boot_nodes.clone() {
+            debug!(
+                discovery.log,
+                "Adding node to routing table";
+                "node_id" => format!("{}", bootnode_enr.node_id()),
+                "peer_id" => format!("{}", bootnode_enr.peer_id()),
+                "ip" => format!("{:?}", bootnode_enr.ip()),
+                "udp" => format!("{:?}", bootnode_enr.udp()),
+                "tcp" => format!("{:?}", bootnode_enr.tcp()),
+                "ip6" => format!("{:?}", bootnode_enr.ip6()),
+                "udp6" => format!("{:?}", bootnode_enr.udp6()),
+            );
+            discovery.add_enr(bootnode_enr);
+        }
+
+        Ok(discovery)
     }
 
     /// Return the nodes local ENR.
@@ -146,8 +192,15 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
     }
 
     /// Add an ENR to the routing table of the discovery mechanism.
+    ///
+    /// On a dual-stack node, an ENR whose discovery socket is an IPv6 socket is added to the
+    /// routing table of the IPv6 service, as the IPv4 service cannot reach it.
     pub fn add_enr(&mut self, enr: Enr) {
-        let _ = self.discovery.add_enr(enr).map_err(|e| {
+        let discovery = match (&mut self.discovery6, enr.udp_socket()) {
+            (Some(discovery6), Some(SocketAddr::V6(_))) => discovery6,
+            _ => &mut self.discovery,
+        };
+        let _ = discovery.add_enr(enr).map_err(|e| {
             warn!(
                 self.log,
                 "Could not add peer to the local routing table";
@@ -169,12 +222,51 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
 
     /// Returns an iterator over all enr entries in the DHT.
     pub fn enr_entries(&mut self) -> impl Iterator<Item = &Enr> {
-        self.discovery.enr_entries()
+        self.discovery
+            .enr_entries()
+            .chain(self.discovery6.iter_mut().flat_map(|d| d.enr_entries()))
     }
 
     /// Returns the ENR of a known peer if it exists.
     pub fn enr_of_peer(&mut self, peer_id: &PeerId) -> Option<Enr> {
-        self.discovery.enr_of_peer(peer_id)
+        match self.discovery.enr_of_peer(peer_id) {
+            Some(enr) => Some(enr),
+            None => self
+                .discovery6
+                .as_mut()
+                .and_then(|discovery6| discovery6.enr_of_peer(peer_id)),
+        }
+    }
+
+    /// Records the address a connected peer observes us on. Once enough peers agree on a new IPv6
+    /// address, it is set in the `ip6` field of the ENR of a dual-stack node.
+    pub fn observed_address(&mut self, peer_id: &PeerId, address: &Multiaddr) {
+        let ip6_votes = match &mut self.ip6_votes {
+            Some(ip6_votes) => ip6_votes,
+            None => return,
+        };
+        match address.iter().next() {
+            Some(Protocol::Ip6(ip6)) if !ip6.is_loopback() && !ip6.is_unspecified() => {
+                ip6_votes.insert(peer_id.clone(), ip6);
+            }
+            _ => return,
+        }
+
+        let mut votes = HashMap::new();
+        for ip6 in ip6_votes.values() {
+            *votes.entry(*ip6).or_insert(0) += 1;
+        }
+        let majority = votes
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| *count >= self.ip6_votes_min)
+            .map(|(ip6, _)| ip6);
+
+        if let Some(ip6) = majority {
+            if self.local_enr().ip6() != Some(ip6) {
+                self.update_ip6(ip6);
+            }
+        }
     }
 
     /// Adds/Removes a subnet from the ENR Bitfield
@@ -209,9 +301,7 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
             .map_err(|_| String::from("Subnet ID out of bounds, could not set subnet ID"))?;
 
         // insert the bitfield into the ENR record
-        let _ = self
-            .discovery
-            .enr_insert(BITFIELD_ENR_KEY, current_bitfield.as_ssz_bytes());
+        let _ = self.enr_insert(BITFIELD_ENR_KEY, current_bitfield.as_ssz_bytes());
 
         // replace the global version
         *self.network_globals.local_enr.write() = self.discovery.local_enr().clone();
@@ -235,13 +325,12 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         );
 
         let _ = self
-            .discovery
-            .enr_insert(ETH2_ENR_KEY.into(), enr_fork_id.as_ssz_bytes())
+            .enr_insert(ETH2_ENR_KEY, enr_fork_id.as_ssz_bytes())
             .map_err(|e| {
                 warn!(
                     self.log,
                     "Could not update eth2 ENR field";
-                    "error" => format!("{:?}", e)
+                    "error" => e
                 )
             });
 
@@ -305,6 +394,49 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
 
     /* Internal Functions */
 
+    /// Inserts a field into the local ENR of each discv5 service.
+    fn enr_insert(&mut self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        if let Some(discovery6) = &mut self.discovery6 {
+            discovery6
+                .enr_insert(key, value.clone())
+                .map_err(|e| format!("{:?}", e))?;
+        }
+        self.discovery
+            .enr_insert(key, value)
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    /// Sets the `ip6` field of the local ENR of a dual-stack node, and reports the new IPv6
+    /// address to libp2p.
+    fn update_ip6(&mut self, ip6: Ipv6Addr) {
+        let udp6_port = match self.local_enr().udp6() {
+            Some(port) => port,
+            None => return,
+        };
+        let socket = SocketAddr::new(IpAddr::V6(ip6), udp6_port);
+        self.discovery.update_local_enr_socket(socket, false);
+        if let Some(discovery6) = &mut self.discovery6 {
+            discovery6.update_local_enr_socket(socket, false);
+        }
+
+        info!(self.log, "IPv6 address updated"; "ip6" => format!("{}", ip6), "udp6_port" => udp6_port);
+        metrics::inc_counter(&metrics::ADDRESS_UPDATE_COUNT);
+        self.local_enr_updated();
+
+        let mut address = Multiaddr::from(ip6);
+        address.push(Protocol::Tcp(self.tcp_port6));
+        self.events
+            .push_back(NetworkBehaviourAction::ReportObservedAddr { address });
+    }
+
+    /// Saves the local ENR to disk and replaces the global version after it was updated.
+    fn local_enr_updated(&self) {
+        let enr = self.discovery.local_enr();
+        enr::save_enr_to_disk(Path::new(&self.enr_dir), enr, &self.log);
+        *self.network_globals.local_enr.write() = enr.clone();
+    }
+
     /// Run a standard query to search for more peers.
     ///
     /// This searches for the standard kademlia bucket size (16) peers.
@@ -337,9 +469,95 @@ impl<TSubstream, TSpec: EthSpec> Discovery<TSubstream, TSpec> {
         let predicate = move |enr: &Enr| eth2_fork_predicate(enr) && enr_predicate(enr);
 
         // general predicate
+        if let Some(discovery6) = &mut self.discovery6 {
+            discovery6.find_enr_predicate(random_node.clone(), predicate.clone(), num_nodes);
+        }
         self.discovery
             .find_enr_predicate(random_node, predicate, num_nodes);
     }
+
+    /// Handles an event of either discv5 service, returning an action for the swarm if any.
+    fn on_discv5_event(
+        &mut self,
+        event: Discv5Event,
+    ) -> Option<NetworkBehaviourAction<void::Void, Discv5Event>> {
+        match event {
+            Discv5Event::Discovered(_enr) => {
+                // peers that get discovered during a query but are not contactable or
+                // don't match a predicate can end up here. For debugging purposes we
+                // log these to see if we are unnecessarily dropping discovered peers
+                /*
+                if enr.eth2() == self.local_enr().eth2() {
+                    trace!(self.log, "Peer found in process of query"; "peer_id" => format!("{}", enr.peer_id()), "tcp_socket" => enr.tcp_socket());
+                } else {
+                    // this is temporary warning for debugging the DHT
+                    warn!(self.log, "Found peer during discovery not on correct fork"; "peer_id" => format!("{}", enr.peer_id()), "tcp_socket" => enr.tcp_socket());
+                }
+                */
+            }
+            Discv5Event::SocketUpdated(socket) => {
+                // only the IPv4 (or single-stack) service updates the ENR, keep the IPv6 service
+                // in sync with it
+                info!(self.log, "Address updated"; "ip" => format!("{}",socket.ip()), "udp_port" => format!("{}", socket.port()));
+                metrics::inc_counter(&metrics::ADDRESS_UPDATE_COUNT);
+                if let Some(discovery6) = &mut self.discovery6 {
+                    discovery6.update_local_enr_socket(socket, false);
+                }
+                self.local_enr_updated();
+
+                let mut address = Multiaddr::from(socket.ip());
+                address.push(Protocol::Tcp(self.tcp_port));
+                return Some(NetworkBehaviourAction::ReportObservedAddr { address });
+            }
+            Discv5Event::FindNodeResult { closer_peers, .. } => {
+                debug!(self.log, "Discovery query completed"; "peers_found" => closer_peers.len());
+                // update the time to the next query
+                if self.past_discovery_delay < MAX_TIME_BETWEEN_PEER_SEARCHES {
+                    self.past_discovery_delay *= 2;
+                }
+                let delay =
+                    std::cmp::max(self.past_discovery_delay, MAX_TIME_BETWEEN_PEER_SEARCHES);
+                self.peer_discovery_delay
+                    .reset(Instant::now() + Duration::from_secs(delay));
+
+                for peer_id in closer_peers {
+                    // if we need more peers, attempt a connection
+
+                    if self.network_globals.connected_or_dialing_untrusted_peers() < self.max_peers
+                        && !self
+                            .network_globals
+                            .peers
+                            .read()
+                            .is_connected_or_dialing(&peer_id)
+                        && !self.banned_peers.contains(&peer_id)
+                    {
+                        debug!(self.log, "Connecting to discovered peer"; "peer_id"=> format!("{:?}", peer_id));
+                        self.network_globals.peers.write().dialing_peer(&peer_id);
+                        self.events
+                            .push_back(NetworkBehaviourAction::DialPeer { peer_id });
+                    }
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Returns true if `address` is of an IP address family we listen on. Addresses without an IP
+/// address are assumed to be reachable.
+fn is_reachable(address: &Multiaddr, ipv4: bool, ipv6: bool) -> bool {
+    match address.iter().next() {
+        Some(Protocol::Ip4(_)) => ipv4,
+        Some(Protocol::Ip6(_)) => ipv6,
+        _ => true,
+    }
+}
+
+/// Sorts `addresses` such that those of the IP address families we listen on are dialled first. The
+/// sort is stable, so the order of discovery is kept otherwise.
+fn sort_by_reachability(addresses: &mut [Multiaddr], ipv4: bool, ipv6: bool) {
+    addresses.sort_by_key(|address| !is_reachable(address, ipv4, ipv6));
 }
 
 // Redirect all behaviour events to underlying discovery behaviour.
@@ -356,12 +574,21 @@ where
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
         // Let discovery track possible known peers.
-        self.discovery.addresses_of_peer(peer_id)
+        let mut addresses = self.discovery.addresses_of_peer(peer_id);
+        if let Some(discovery6) = &mut self.discovery6 {
+            addresses.extend(discovery6.addresses_of_peer(peer_id));
+        }
+        sort_by_reachability(&mut addresses, self.listens_on_ipv4, self.listens_on_ipv6);
+        addresses
     }
 
     fn inject_connected(&mut self, _peer_id: PeerId, _endpoint: ConnectedPoint) {}
 
-    fn inject_disconnected(&mut self, _peer_id: &PeerId, _endpoint: ConnectedPoint) {}
+    fn inject_disconnected(&mut self, peer_id: &PeerId, _endpoint: ConnectedPoint) {
+        if let Some(ip6_votes) = &mut self.ip6_votes {
+            ip6_votes.remove(peer_id);
+        }
+    }
 
     fn inject_replaced(
         &mut self,
@@ -393,7 +620,7 @@ where
         loop {
             match self.peer_discovery_delay.poll() {
                 Ok(Async::Ready(_)) => {
-                    if self.network_globals.connected_peers() < self.max_peers {
+                    if self.network_globals.connected_untrusted_peers() < self.max_peers {
                         self.find_peers();
                     }
                     // Set to maximum, and update to earlier, once we get our results back.
@@ -412,65 +639,8 @@ where
         loop {
             match self.discovery.poll(params) {
                 Async::Ready(NetworkBehaviourAction::GenerateEvent(event)) => {
-                    match event {
-                        Discv5Event::Discovered(_enr) => {
-                            // peers that get discovered during a query but are not contactable or
-                            // don't match a predicate can end up here. For debugging purposes we
-                            // log these to see if we are unnecessarily dropping discovered peers
-                            /*
-                            if enr.eth2() == self.local_enr().eth2() {
-                                trace!(self.log, "Peer found in process of query"; "peer_id" => format!("{}", enr.peer_id()), "tcp_socket" => enr.tcp_socket());
-                            } else {
-                                // this is temporary warning for debugging the DHT
-                                warn!(self.log, "Found peer during discovery not on correct fork"; "peer_id" => format!("{}", enr.peer_id()), "tcp_socket" => enr.tcp_socket());
-                            }
-                            */
-                        }
-                        Discv5Event::SocketUpdated(socket) => {
-                            info!(self.log, "Address updated"; "ip" => format!("{}",socket.ip()), "udp_port" => format!("{}", socket.port()));
-                            metrics::inc_counter(&metrics::ADDRESS_UPDATE_COUNT);
-                            let mut address = Multiaddr::from(socket.ip());
-                            address.push(Protocol::Tcp(self.tcp_port));
-                            let enr = self.discovery.local_enr();
-                            enr::save_enr_to_disk(Path::new(&self.enr_dir), enr, &self.log);
-
-                            return Async::Ready(NetworkBehaviourAction::ReportObservedAddr {
-                                address,
-                            });
-                        }
-                        Discv5Event::FindNodeResult { closer_peers, .. } => {
-                            debug!(self.log, "Discovery query completed"; "peers_found" => closer_peers.len());
-                            // update the time to the next query
-                            if self.past_discovery_delay < MAX_TIME_BETWEEN_PEER_SEARCHES {
-                                self.past_discovery_delay *= 2;
-                            }
-                            let delay = std::cmp::max(
-                                self.past_discovery_delay,
-                                MAX_TIME_BETWEEN_PEER_SEARCHES,
-                            );
-                            self.peer_discovery_delay
-                                .reset(Instant::now() + Duration::from_secs(delay));
-
-                            for peer_id in closer_peers {
-                                // if we need more peers, attempt a connection
-
-                                if self.network_globals.connected_or_dialing_peers()
-                                    < self.max_peers
-                                    && !self
-                                        .network_globals
-                                        .peers
-                                        .read()
-                                        .is_connected_or_dialing(&peer_id)
-                                    && !self.banned_peers.contains(&peer_id)
-                                {
-                                    debug!(self.log, "Connecting to discovered peer"; "peer_id"=> format!("{:?}", peer_id));
-                                    self.network_globals.peers.write().dialing_peer(&peer_id);
-                                    self.events
-                                        .push_back(NetworkBehaviourAction::DialPeer { peer_id });
-                                }
-                            }
-                        }
-                        _ => {}
+                    if let Some(action) = self.on_discv5_event(event) {
+                        return Async::Ready(action);
                     }
                 }
                 // discv5 does not output any other NetworkBehaviourAction
@@ -479,6 +649,19 @@ where
             }
         }
 
+        // Poll the IPv6 discovery of a dual-stack node
+        while let Some(discovery6) = &mut self.discovery6 {
+            match discovery6.poll(params) {
+                Async::Ready(NetworkBehaviourAction::GenerateEvent(event)) => {
+                    if let Some(action) = self.on_discv5_event(event) {
+                        return Async::Ready(action);
+                    }
+                }
+                Async::Ready(_) => {}
+                Async::NotReady => break,
+            }
+        }
+
         // process any queued events
         if let Some(event) = self.events.pop_front() {
             return Async::Ready(event);
@@ -487,3 +670,62 @@ where
         Async::NotReady
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses(addresses: &[&str]) -> Vec<Multiaddr> {
+        addresses.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn reachable_addresses_are_dialled_first() {
+        let discovered = addresses(&[
+            "/ip6/fd00::1/tcp/9000",
+            "/ip4/10.0.0.1/tcp/9000",
+            "/ip6/fd00::2/tcp/9000",
+            "/ip4/10.0.0.2/tcp/9000",
+        ]);
+
+        let mut ipv4_only = discovered.clone();
+        sort_by_reachability(&mut ipv4_only, true, false);
+        assert_eq!(
+            ipv4_only,
+            addresses(&[
+                "/ip4/10.0.0.1/tcp/9000",
+                "/ip4/10.0.0.2/tcp/9000",
+                "/ip6/fd00::1/tcp/9000",
+                "/ip6/fd00::2/tcp/9000",
+            ]),
+            "should dial IPv4 first, keeping the order of discovery"
+        );
+
+        let mut ipv6_only = discovered.clone();
+        sort_by_reachability(&mut ipv6_only, false, true);
+        assert_eq!(
+            ipv6_only,
+            addresses(&[
+                "/ip6/fd00::1/tcp/9000",
+                "/ip6/fd00::2/tcp/9000",
+                "/ip4/10.0.0.1/tcp/9000",
+                "/ip4/10.0.0.2/tcp/9000",
+            ]),
+            "should dial IPv6 first, keeping the order of discovery"
+        );
+
+        let mut dual_stack = discovered.clone();
+        sort_by_reachability(&mut dual_stack, true, true);
+        assert_eq!(dual_stack, discovered, "should keep the order of discovery");
+    }
+
+    #[test]
+    fn addresses_without_ip_are_reachable() {
+        let mut discovered = addresses(&["/ip6/fd00::1/tcp/9000", "/dns4/localhost/tcp/9000"]);
+        sort_by_reachability(&mut discovered, true, false);
+        assert_eq!(
+            discovered,
+            addresses(&["/dns4/localhost/tcp/9000", "/ip6/fd00::1/tcp/9000"])
+        );
+    }
+}
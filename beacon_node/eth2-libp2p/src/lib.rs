@@ -20,6 +20,9 @@ pub use config::Config as NetworkConfig;
 pub use libp2p::gossipsub::{MessageId, Topic, TopicHash};
 pub use libp2p::{multiaddr, Multiaddr};
 pub use libp2p::{PeerId, Swarm};
-pub use peer_manager::{PeerDB, PeerInfo, PeerSyncStatus, SyncInfo};
+pub use peer_manager::{
+    MessageAcceptance, PeerDB, PeerInfo, PeerScoreParams, PeerSyncStatus, PersistedPeer,
+    PersistedPeers, SyncInfo,
+};
 pub use rpc::RPCEvent;
 pub use service::{Service, NETWORK_KEY_FILENAME};
@@ -1,9 +1,11 @@
 //! Implementation of a Lighthouse's peer management system.
 
 pub use self::peerdb::*;
+use self::score::GossipScoring;
 use crate::metrics;
-use crate::rpc::{MetaData, Protocol, RPCError, RPCResponseErrorCode};
-use crate::{NetworkGlobals, PeerId};
+use crate::rpc::{MetaData, Protocol, RPCError, RPCResponseErrorCode, RateLimitedErr};
+use crate::types::GossipKind;
+use crate::{NetworkConfig, NetworkGlobals, PeerId};
 use futures::prelude::*;
 use futures::Stream;
 use hashmap_delay::HashSetDelay;
@@ -13,15 +15,18 @@ use smallvec::SmallVec;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::timer::Interval;
 use types::EthSpec;
 
 mod client;
 mod peer_info;
 mod peer_sync_status;
 mod peerdb;
+mod score;
 
 pub use peer_info::{PeerConnectionStatus::*, PeerInfo};
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
+pub use score::{MessageAcceptance, PeerScoreParams};
 /// The minimum reputation before a peer is disconnected.
 // Most likely this needs tweaking.
 const MIN_REP_BEFORE_BAN: Rep = 10;
@@ -43,6 +48,10 @@ pub struct PeerManager<TSpec: EthSpec> {
     status_peers: HashSetDelay<PeerId>,
     /// Last updated moment.
     last_updated: Instant,
+    /// The parameters and thresholds of gossip scores, if gossip scoring is enabled.
+    gossip_scoring: Option<GossipScoring>,
+    /// The interval at which gossip scores decay.
+    gossip_score_decay: Option<Interval>,
     /// The logger associated with the `PeerManager`.
     log: slog::Logger,
 }
@@ -100,11 +109,29 @@ pub enum PeerManagerEvent {
 }
 
 impl<TSpec: EthSpec> PeerManager<TSpec> {
-    pub fn new(network_globals: Arc<NetworkGlobals<TSpec>>, log: &slog::Logger) -> Self {
+    pub fn new(
+        network_globals: Arc<NetworkGlobals<TSpec>>,
+        net_conf: &NetworkConfig,
+        log: &slog::Logger,
+    ) -> Self {
+        let gossip_scoring = net_conf
+            .gossip_score_params
+            .clone()
+            .map(|params| GossipScoring {
+                params,
+                graylist_threshold: net_conf.gossip_graylist_threshold,
+                disconnect_threshold: net_conf.gossip_disconnect_threshold,
+            });
+        let gossip_score_decay = gossip_scoring
+            .as_ref()
+            .map(|scoring| Interval::new_interval(scoring.params.decay_interval));
+
         PeerManager {
             network_globals,
             events: SmallVec::new(),
             last_updated: Instant::now(),
+            gossip_scoring,
+            gossip_score_decay,
             ping_peers: HashSetDelay::new(Duration::from_secs(PING_INTERVAL)),
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
             log: log.clone(),
@@ -244,6 +271,63 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.update_reputations();
     }
 
+    /// Scores a peer by the result of validating one of its gossip messages.
+    ///
+    /// Returns true if the gossip score of the peer has fallen below the disconnect threshold, in
+    /// which case the peer should be disconnected and banned. Trusted peers are never disconnected.
+    pub fn gossip_validation(
+        &mut self,
+        peer_id: &PeerId,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+    ) -> bool {
+        let scoring = match &self.gossip_scoring {
+            Some(scoring) => scoring,
+            None => return false,
+        };
+
+        let mut pdb = self.network_globals.peers.write();
+        let score = pdb.add_gossip_validation(peer_id, kind, acceptance, scoring);
+        if score < scoring.disconnect_threshold && !pdb.is_trusted(peer_id) {
+            debug!(self.log, "Peer gossip score below the disconnect threshold";
+                "peer_id" => peer_id.to_string(), "score" => score);
+            return true;
+        }
+        false
+    }
+
+    /// Replaces the gossip score parameters, e.g. as the number of active validators changes.
+    ///
+    /// Has no effect if gossip scoring is disabled.
+    pub fn update_gossip_score_params(&mut self, params: PeerScoreParams) {
+        let scoring = match &mut self.gossip_scoring {
+            Some(scoring) => scoring,
+            None => return,
+        };
+
+        if scoring.params.decay_interval != params.decay_interval {
+            self.gossip_score_decay = Some(Interval::new_interval(params.decay_interval));
+        }
+        scoring.params = params;
+    }
+
+    /// Returns true if the gossip messages of a peer should be ignored, because its gossip score is
+    /// below the graylist threshold.
+    pub fn is_graylisted(&self, peer_id: &PeerId) -> bool {
+        let scoring = match &self.gossip_scoring {
+            Some(scoring) => scoring,
+            None => return false,
+        };
+
+        self.network_globals
+            .peers
+            .read()
+            .peer_info(peer_id)
+            .map_or(false, |info| {
+                !info.is_trusted && info.gossip_score < scoring.graylist_threshold
+            })
+    }
+
     /// Updates `PeerInfo` with `identify` information.
     pub fn identify(&mut self, peer_id: &PeerId, info: &IdentifyInfo) {
         if let Some(peer_info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
@@ -254,6 +338,25 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         }
     }
 
+    /// Penalises a peer for a request which was refused because it exceeded the quota of its
+    /// protocol.
+    pub fn handle_rate_limited(
+        &mut self,
+        peer_id: &PeerId,
+        protocol: Protocol,
+        err: &RateLimitedErr,
+    ) {
+        debug!(self.log, "Peer exceeded RPC quota"; "peer_id" => peer_id.to_string(), "protocol" => protocol.to_string(), "err" => format!("{:?}", err));
+
+        let peer_action = match err {
+            // Requests are never larger than the protocol allows, unless the peer is misbehaving.
+            RateLimitedErr::TooLarge => PeerAction::LowToleranceError,
+            // Occasionally exceeding the quota is tolerated, persistently doing so is not.
+            RateLimitedErr::TooSoon(_) => PeerAction::MidToleranceError,
+        };
+        self.report_peer(peer_id, peer_action);
+    }
+
     pub fn handle_rpc_error(&mut self, peer_id: &PeerId, protocol: Protocol, err: &RPCError) {
         debug!(self.log, "RPCError"; "protocol" => protocol.to_string(), "err" => err.to_string());
 
@@ -278,6 +381,8 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             RPCError::ErrorResponse(code) => match code {
                 RPCResponseErrorCode::Unknown => PeerAction::HighToleranceError,
                 RPCResponseErrorCode::ServerError => PeerAction::MidToleranceError,
+                // We exceeded the quota of the peer, which is not the peer's fault.
+                RPCResponseErrorCode::RateLimited => return,
                 RPCResponseErrorCode::InvalidRequest => PeerAction::LowToleranceError,
             },
             RPCError::SSZDecodeError(_) => PeerAction::Fatal,
@@ -383,6 +488,11 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         let mut pdb = self.network_globals.peers.write();
 
         for (id, info) in pdb.peers_mut() {
+            // Trusted peers keep their reputation and are never banned
+            if info.is_trusted {
+                continue;
+            }
+
             // Update reputations
             match info.connection_status {
                 Connected { .. } => {
@@ -390,7 +500,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                 }
                 Disconnected { since } | Banned { since } => {
                     // For disconnected peers, lower their reputation by 1 for every hour they
-                    // stay disconnected. This helps us slowly forget disconnected peers.
+                    // stay disconnected.
                     // In the same way, slowly allow banned peers back again.
                     let dc_hours = now
                         .checked_duration_since(since)
@@ -489,6 +599,19 @@ impl<TSpec: EthSpec> Stream for PeerManager<TSpec> {
             self.status_peers.insert(peer);
         }
 
+        // decay the gossip scores of all peers
+        if let (Some(scoring), Some(decay)) = (&self.gossip_scoring, &mut self.gossip_score_decay) {
+            let log = &self.log;
+            while let Async::Ready(Some(_)) = decay.poll().map_err(|e| {
+                error!(log, "Failed to check for gossip score decay"; "error" => e.to_string());
+            })? {
+                self.network_globals
+                    .peers
+                    .write()
+                    .decay_gossip_scores(scoring);
+            }
+        }
+
         if !self.events.is_empty() {
             return Ok(Async::Ready(Some(self.events.remove(0))));
         } else {
@@ -1,5 +1,6 @@
 use super::client::Client;
 use super::peerdb::{Rep, DEFAULT_REPUTATION};
+use super::score::GossipStats;
 use super::PeerSyncStatus;
 use crate::rpc::MetaData;
 use crate::Multiaddr;
@@ -19,10 +20,18 @@ pub struct PeerInfo<T: EthSpec> {
     _status: PeerStatus,
     /// The peers reputation
     pub reputation: Rep,
+    /// The score of the peer's gossip messages. Its contribution is included in `reputation`.
+    pub gossip_score: f64,
+    /// The counters from which `gossip_score` is computed.
+    #[serde(skip)]
+    pub gossip_stats: GossipStats,
     /// Client managing this peer
     pub client: Client,
     /// Connection status of this peer
     pub connection_status: PeerConnectionStatus,
+    /// Whether the peer is trusted. Trusted peers are exempt from reputation changes, banning,
+    /// pruning and the peer limit.
+    pub is_trusted: bool,
     /// The known listening addresses of this peer.
     pub listening_addresses: Vec<Multiaddr>,
     /// The current syncing state of the peer. The state may be determined after it's initial
@@ -38,8 +47,11 @@ impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
         PeerInfo {
             _status: Default::default(),
             reputation: DEFAULT_REPUTATION,
+            gossip_score: 0.0,
+            gossip_stats: GossipStats::default(),
             client: Client::default(),
             connection_status: Default::default(),
+            is_trusted: false,
             listening_addresses: vec![],
             sync_status: PeerSyncStatus::Unknown,
             meta_data: None,
@@ -1,10 +1,16 @@
+use super::client::Client;
 use super::peer_info::{PeerConnectionStatus, PeerInfo};
 use super::peer_sync_status::PeerSyncStatus;
+use super::score::{GossipScoring, MessageAcceptance};
+use super::MIN_REP_BEFORE_BAN;
 use crate::rpc::methods::MetaData;
+use crate::types::GossipKind;
 use crate::PeerId;
-use slog::{crit, warn};
+use slog::{crit, debug, warn};
+use ssz_derive::{Decode, Encode};
 use std::collections::HashMap;
-use std::time::Instant;
+use std::convert::TryInto;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use types::{EthSpec, SubnetId};
 
 /// A peer's reputation (perceived potential usefulness)
@@ -32,6 +38,29 @@ pub struct PeerDB<TSpec: EthSpec> {
     log: slog::Logger,
 }
 
+/// The information about a peer which is kept across restarts.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct PersistedPeer {
+    /// The bytes of the peer's `PeerId`.
+    pub peer_id: Vec<u8>,
+    pub reputation: Rep,
+    pub banned: bool,
+    /// The time the peer was disconnected or banned, in seconds since the UNIX epoch.
+    pub since: u64,
+    /// The UTF-8 identify agent version of the peer. Empty if unknown.
+    pub agent_version: Vec<u8>,
+    /// The UTF-8 libp2p protocol version of the peer.
+    pub protocol_version: Vec<u8>,
+}
+
+/// A snapshot of the `PeerDB`, taken when the node shuts down.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct PersistedPeers {
+    /// The time the snapshot was taken, in seconds since the UNIX epoch.
+    pub saved_at: u64,
+    pub peers: Vec<PersistedPeer>,
+}
+
 impl RepChange {
     pub fn good(diff: Rep) -> Self {
         RepChange {
@@ -97,6 +126,13 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         self.peers.get_mut(peer_id)
     }
 
+    /// Returns true if the peer is a trusted peer.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .map_or(false, |info| info.is_trusted)
+    }
+
     /// Returns true if the peer is synced at least to our current head.
     pub fn peer_synced(&self, peer_id: &PeerId) -> bool {
         match self.peers.get(peer_id).map(|info| &info.sync_status) {
@@ -223,6 +259,11 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 
     /* Setters */
 
+    /// Marks a peer as trusted, adding it to the db if it is unknown.
+    pub fn add_trusted_peer(&mut self, peer_id: &PeerId) {
+        self.peers.entry(peer_id.clone()).or_default().is_trusted = true;
+    }
+
     /// A peer is being dialed.
     pub fn dialing_peer(&mut self, peer_id: &PeerId) {
         let info = self.peers.entry(peer_id.clone()).or_default();
@@ -271,25 +312,37 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         self.shrink_to_fit();
     }
 
-    /// Drops the peers with the lowest reputation so that the number of
-    /// disconnected peers is less than MAX_DC_PEERS
+    /// Drops the peers with the highest reputation so that the number of
+    /// disconnected peers is less than MAX_DC_PEERS. Trusted peers are never dropped.
+    ///
+    /// A dropped peer returns with the default reputation, so the peers with the lowest reputation
+    /// are kept to prevent them from shedding their penalties.
     pub fn shrink_to_fit(&mut self) {
         // for caution, but the difference should never be > 1
         while self.n_dc > MAX_DC_PEERS {
-            let to_drop = self
+            let to_drop = match self
                 .peers
                 .iter()
-                .filter(|(_, info)| info.connection_status.is_disconnected())
-                .min_by_key(|(_, info)| info.reputation)
+                .filter(|(_, info)| info.connection_status.is_disconnected() && !info.is_trusted)
+                .max_by_key(|(_, info)| info.reputation)
                 .map(|(id, _)| id.clone())
-                .unwrap(); // should be safe since n_dc > MAX_DC_PEERS > 0
+            {
+                Some(to_drop) => to_drop,
+                // only trusted peers are disconnected
+                None => break,
+            };
             self.peers.remove(&to_drop);
             self.n_dc -= 1;
         }
     }
 
-    /// Sets a peer as banned
+    /// Sets a peer as banned. Trusted peers are never banned.
     pub fn ban(&mut self, peer_id: &PeerId) {
+        if self.is_trusted(peer_id) {
+            debug!(self.log, "Not banning trusted peer"; "peer_id" => peer_id.to_string());
+            return;
+        }
+
         let log_ref = &self.log;
         let info = self.peers.entry(peer_id.clone()).or_insert_with(|| {
             warn!(log_ref, "Banning unknown peer";
@@ -332,6 +385,8 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 
     /// Adds to a peer's reputation by `change`. If the reputation exceeds Rep's
     /// upper (lower) bounds, it stays at the maximum (minimum) value.
+    ///
+    /// The reputation of trusted peers does not change.
     pub(super) fn add_reputation(&mut self, peer_id: &PeerId, change: RepChange) {
         let log_ref = &self.log;
         let info = self.peers.entry(peer_id.clone()).or_insert_with(|| {
@@ -339,6 +394,9 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
                     "peer_id" => peer_id.to_string());
             PeerInfo::default()
         });
+        if info.is_trusted {
+            return;
+        }
 
         info.reputation = if change.is_good {
             info.reputation.saturating_add(change.diff)
@@ -346,13 +404,181 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             info.reputation.saturating_sub(change.diff)
         };
     }
+
+    /* Gossip scores */
+
+    /// Records the result of validating a gossip message from a peer and returns the updated
+    /// gossip score of the peer.
+    ///
+    /// The reputation of the peer changes by the change of the score's contribution to it.
+    pub(super) fn add_gossip_validation(
+        &mut self,
+        peer_id: &PeerId,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+        scoring: &GossipScoring,
+    ) -> f64 {
+        let log_ref = &self.log;
+        let info = self.peers.entry(peer_id.clone()).or_insert_with(|| {
+            warn!(log_ref, "Scoring the gossip of an unknown peer";
+                    "peer_id" => peer_id.to_string());
+            PeerInfo::default()
+        });
+        info.gossip_stats.add(&scoring.params, kind, acceptance);
+        update_gossip_score(info, scoring);
+        info.gossip_score
+    }
+
+    /// Decays the gossip scores of all peers by one decay interval.
+    pub(super) fn decay_gossip_scores(&mut self, scoring: &GossipScoring) {
+        for info in self.peers.values_mut() {
+            info.gossip_stats.decay(&scoring.params);
+            update_gossip_score(info, scoring);
+        }
+    }
+
+    /* Persistence */
+
+    /// Returns a snapshot of all known peers, to be restored with `restore` after a restart.
+    ///
+    /// Connected and dialing peers are recorded as disconnected from now, since the snapshot is
+    /// taken when the node shuts down.
+    pub fn persist(&self) -> PersistedPeers {
+        let now = unix_time_secs();
+        let peers = self
+            .peers
+            .iter()
+            .map(|(peer_id, info)| {
+                let (banned, elapsed) = match &info.connection_status {
+                    PeerConnectionStatus::Banned { since } => (true, since.elapsed()),
+                    PeerConnectionStatus::Disconnected { since } => (false, since.elapsed()),
+                    PeerConnectionStatus::Connected { .. }
+                    | PeerConnectionStatus::Dialing { .. } => (false, Duration::from_secs(0)),
+                };
+
+                PersistedPeer {
+                    peer_id: peer_id.as_bytes().to_vec(),
+                    reputation: info.reputation,
+                    banned,
+                    since: now.saturating_sub(elapsed.as_secs()),
+                    agent_version: info
+                        .client
+                        .agent_string
+                        .clone()
+                        .unwrap_or_default()
+                        .into_bytes(),
+                    protocol_version: info.client.protocol_version.clone().into_bytes(),
+                }
+            })
+            .collect();
+
+        PersistedPeers {
+            saved_at: now,
+            peers,
+        }
+    }
+
+    /// Adds the peers of a snapshot taken by `persist` to the db as disconnected or banned peers.
+    ///
+    /// Reputations are updated for the time the node was offline, in the same way as they are
+    /// while it is running: disconnected peers lose 1 rep and banned peers gain 1 rep for every
+    /// hour since they were disconnected or banned. Peers which are already known are ignored.
+    pub fn restore(&mut self, persisted: PersistedPeers) {
+        let now = unix_time_secs();
+        let instant_now = Instant::now();
+
+        for peer in persisted.peers {
+            let peer_id = match PeerId::from_bytes(peer.peer_id) {
+                Ok(peer_id) => peer_id,
+                Err(_) => {
+                    warn!(self.log, "Ignoring persisted peer with invalid id");
+                    continue;
+                }
+            };
+            if self.peers.contains_key(&peer_id) {
+                continue;
+            }
+
+            let hours_since = |time: u64| time.saturating_sub(peer.since) / 3600;
+            let offline_hours: Rep = hours_since(now)
+                .saturating_sub(hours_since(persisted.saved_at))
+                .try_into()
+                .unwrap_or(Rep::max_value());
+            let reputation = if peer.banned {
+                peer.reputation.saturating_add(offline_hours)
+            } else {
+                peer.reputation.saturating_sub(offline_hours)
+            };
+
+            let since = instant_now
+                .checked_sub(Duration::from_secs(now.saturating_sub(peer.since)))
+                .unwrap_or(instant_now);
+            // The ban of a peer expires once its reputation has recovered.
+            let connection_status = if peer.banned && reputation < MIN_REP_BEFORE_BAN {
+                PeerConnectionStatus::Banned { since }
+            } else {
+                self.n_dc += 1;
+                PeerConnectionStatus::Disconnected { since }
+            };
+
+            let protocol_version = String::from_utf8(peer.protocol_version)
+                .unwrap_or_else(|_| Client::default().protocol_version);
+            let client = match String::from_utf8(peer.agent_version) {
+                Ok(agent_version) if !agent_version.is_empty() => {
+                    Client::from_agent_version(&agent_version, protocol_version)
+                }
+                _ => Client {
+                    protocol_version,
+                    ..Client::default()
+                },
+            };
+
+            debug!(self.log, "Restored persisted peer"; "peer_id" => peer_id.to_string(), "reputation" => reputation, "banned" => connection_status.is_banned());
+            let mut info = PeerInfo::default();
+            info.reputation = reputation;
+            info.client = client;
+            info.connection_status = connection_status;
+            self.peers.insert(peer_id, info);
+        }
+
+        self.shrink_to_fit();
+    }
+}
+
+/// Returns the current time in seconds since the UNIX epoch.
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Recomputes the gossip score of a peer from its gossip stats, and changes its reputation by the
+/// change of the score's contribution to it.
+fn update_gossip_score<TSpec: EthSpec>(info: &mut PeerInfo<TSpec>, scoring: &GossipScoring) {
+    let score = info.gossip_stats.score(&scoring.params);
+    let change = scoring.reputation(score) - scoring.reputation(info.gossip_score);
+    info.gossip_score = score;
+
+    // The reputation of trusted peers does not change.
+    if info.is_trusted {
+        return;
+    }
+
+    let diff = change.abs().try_into().unwrap_or(Rep::max_value());
+    info.reputation = if change >= 0 {
+        info.reputation.saturating_add(diff)
+    } else {
+        info.reputation.saturating_sub(diff)
+    };
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::score::PeerScoreParams;
     use super::*;
     use slog::{o, Drain};
-    use types::MinimalEthSpec;
+    use types::{ChainSpec, MinimalEthSpec};
     type M = MinimalEthSpec;
 
     pub fn build_log(level: slog::Level, enabled: bool) -> slog::Logger {
@@ -462,6 +688,25 @@ mod tests {
         assert_eq!(pdb.n_dc, MAX_DC_PEERS);
     }
 
+    #[test]
+    fn test_lowest_reputation_disconnected_are_kept() {
+        let mut pdb = get_db();
+
+        let bad_peer = PeerId::random();
+        pdb.connect_ingoing(&bad_peer);
+        pdb.set_reputation(&bad_peer, MIN_REP_BEFORE_BAN + 1);
+        pdb.disconnect(&bad_peer);
+
+        for _ in 0..MAX_DC_PEERS {
+            let p = PeerId::random();
+            pdb.connect_ingoing(&p);
+            pdb.disconnect(&p);
+        }
+
+        assert_eq!(pdb.n_dc, MAX_DC_PEERS);
+        assert_eq!(pdb.reputation(&bad_peer), MIN_REP_BEFORE_BAN + 1);
+    }
+
     #[test]
     fn test_best_peers() {
         let mut pdb = get_db();
@@ -532,4 +777,168 @@ mod tests {
         pdb.disconnect(&random_peer);
         assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
     }
+
+    #[test]
+    fn test_trusted_peers() {
+        let mut pdb = get_db();
+        let trusted_peer = PeerId::random();
+        pdb.add_trusted_peer(&trusted_peer);
+        pdb.connect_ingoing(&trusted_peer);
+
+        // the reputation of trusted peers doesn't change
+        pdb.add_reputation(&trusted_peer, RepChange::worst());
+        assert_eq!(pdb.reputation(&trusted_peer), DEFAULT_REPUTATION);
+
+        // trusted peers are never banned
+        pdb.ban(&trusted_peer);
+        assert!(pdb.is_trusted(&trusted_peer));
+        assert!(pdb.connection_status(&trusted_peer).unwrap().is_connected());
+
+        // trusted peers are never pruned
+        pdb.disconnect(&trusted_peer);
+        for _ in 0..MAX_DC_PEERS + 1 {
+            let p = PeerId::random();
+            pdb.connect_ingoing(&p);
+            pdb.disconnect(&p);
+        }
+        assert!(pdb.is_trusted(&trusted_peer));
+        assert_eq!(pdb.disconnected_peers().count(), MAX_DC_PEERS);
+    }
+
+    #[test]
+    fn test_gossip_scores() {
+        let mut pdb = get_db();
+        let scoring = GossipScoring {
+            params: PeerScoreParams::new::<M>(&ChainSpec::minimal(), 64),
+            graylist_threshold: -400.0,
+            disconnect_threshold: -900.0,
+        };
+        let peer = PeerId::random();
+        let trusted_peer = PeerId::random();
+        pdb.connect_ingoing(&peer);
+        pdb.add_trusted_peer(&trusted_peer);
+        pdb.connect_ingoing(&trusted_peer);
+
+        // a score at the disconnect threshold lowers the reputation to the ban threshold
+        let mut score = 0.0;
+        for _ in 0..3 {
+            score = pdb.add_gossip_validation(
+                &peer,
+                &GossipKind::BeaconBlock,
+                MessageAcceptance::Reject,
+                &scoring,
+            );
+            pdb.add_gossip_validation(
+                &trusted_peer,
+                &GossipKind::BeaconBlock,
+                MessageAcceptance::Reject,
+                &scoring,
+            );
+        }
+        assert!((score - scoring.disconnect_threshold).abs() < 1e-6);
+        assert_eq!(pdb.reputation(&peer), MIN_REP_BEFORE_BAN);
+        assert_eq!(pdb.reputation(&trusted_peer), DEFAULT_REPUTATION);
+
+        // the reputation recovers as the score decays
+        for _ in 0..100 * M::slots_per_epoch() {
+            pdb.decay_gossip_scores(&scoring);
+        }
+        assert!(pdb.peer_info(&peer).unwrap().gossip_score.abs() < 1e-6);
+        assert_eq!(pdb.reputation(&peer), DEFAULT_REPUTATION);
+    }
+
+    #[test]
+    fn test_persist_and_restore() {
+        let mut pdb = get_db();
+
+        let connected = PeerId::random();
+        let banned = PeerId::random();
+        pdb.connect_ingoing(&connected);
+        pdb.set_reputation(&connected, 80);
+        pdb.connect_outgoing(&banned);
+        pdb.set_reputation(&banned, 0);
+        pdb.ban(&banned);
+
+        let persisted = pdb.persist();
+        assert_eq!(persisted.peers.len(), 2);
+
+        let mut restored = get_db();
+        restored.restore(persisted);
+
+        // connected peers are restored as disconnected
+        assert_eq!(restored.reputation(&connected), 80);
+        assert!(restored
+            .connection_status(&connected)
+            .unwrap()
+            .is_disconnected());
+        assert_eq!(restored.reputation(&banned), 0);
+        assert!(restored.connection_status(&banned).unwrap().is_banned());
+        assert_eq!(restored.n_dc, restored.disconnected_peers().count());
+    }
+
+    #[test]
+    fn test_restore_applies_offline_decay() {
+        let disconnected = PeerId::random();
+        let banned = PeerId::random();
+        let five_hours_ago = unix_time_secs() - 5 * 3600;
+        let persisted_peer = |peer_id: &PeerId, reputation: Rep, banned: bool| PersistedPeer {
+            peer_id: peer_id.as_bytes().to_vec(),
+            reputation,
+            banned,
+            since: five_hours_ago,
+            agent_version: b"Lighthouse/v0.2.0/x86_64-linux".to_vec(),
+            protocol_version: b"lighthouse/libp2p".to_vec(),
+        };
+        let persisted = PersistedPeers {
+            saved_at: five_hours_ago,
+            peers: vec![
+                persisted_peer(&disconnected, 50, false),
+                persisted_peer(&banned, MIN_REP_BEFORE_BAN - 5, true),
+            ],
+        };
+
+        let mut pdb = get_db();
+        pdb.restore(persisted);
+
+        assert_eq!(pdb.reputation(&disconnected), 45);
+        // the ban has expired while the node was offline
+        assert_eq!(pdb.reputation(&banned), MIN_REP_BEFORE_BAN);
+        assert!(pdb.connection_status(&banned).unwrap().is_disconnected());
+        assert_eq!(
+            pdb.peer_info(&banned).unwrap().client.to_string(),
+            "Lighthouse: version: v0.2.0, os_version: x86_64-linux"
+        );
+    }
+
+    #[test]
+    fn test_restore_keeps_low_reputation_peers() {
+        let now = unix_time_secs();
+        let persisted_peer = |peer_id: &PeerId, reputation: Rep| PersistedPeer {
+            peer_id: peer_id.as_bytes().to_vec(),
+            reputation,
+            banned: false,
+            since: now,
+            agent_version: vec![],
+            protocol_version: vec![],
+        };
+
+        let bad_peer = PeerId::random();
+        let mut peers = vec![persisted_peer(&bad_peer, MIN_REP_BEFORE_BAN + 1)];
+        for _ in 0..MAX_DC_PEERS {
+            peers.push(persisted_peer(&PeerId::random(), DEFAULT_REPUTATION));
+        }
+
+        let mut pdb = get_db();
+        pdb.restore(PersistedPeers {
+            saved_at: now,
+            peers,
+        });
+
+        assert_eq!(pdb.n_dc, MAX_DC_PEERS);
+        assert_eq!(
+            pdb.reputation(&bad_peer),
+            MIN_REP_BEFORE_BAN + 1,
+            "should keep the penalised peer"
+        );
+    }
 }
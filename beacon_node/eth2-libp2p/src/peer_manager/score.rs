@@ -0,0 +1,494 @@
+//! Gossipsub v1.1-style scoring of peers by the validation results of their gossip messages.
+//!
+//! The score of a peer is computed from counters which decay over time:
+//!
+//! - The first deliveries of valid messages on each topic, which increase the score up to a cap
+//!   (P2 of the gossipsub v1.1 specification).
+//! - The invalid messages on each topic, which decrease the score by their square (P4).
+//! - The duplicate messages (i.e., messages which are already known) in excess of a threshold,
+//!   which decrease the score by the square of the excess (P7). Other ignored messages do not
+//!   change the score, since the peer is not at fault.
+//!
+//! The mesh and IP colocation counters of the specification (P1, P3 and P6) are only known to
+//! gossipsub itself and are not used.
+//!
+//! The gossipsub implementation of our libp2p fork predates gossipsub v1.1: it does not score peers
+//! and has no means of removing a connected peer from a mesh. A graylisted peer therefore stays in
+//! the meshes it has joined, and only its messages are ignored, until its score falls below the
+//! disconnect threshold and it is disconnected.
+
+use super::peerdb::{Rep, DEFAULT_REPUTATION};
+use super::MIN_REP_BEFORE_BAN;
+use crate::types::GossipKind;
+use std::collections::HashMap;
+use std::time::Duration;
+use types::{ChainSpec, EthSpec, SubnetId};
+
+/// The maximum positive score a peer obtains from delivering valid messages.
+const MAX_POSITIVE_SCORE: f64 = 100.0;
+/// The fraction of its value after which a decaying counter is set to zero.
+const DECAY_TO_ZERO: f64 = 0.01;
+
+/// The share of `MAX_POSITIVE_SCORE` each topic contributes when all of its messages are delivered
+/// first. The shares add up to more than one, so a peer need not deliver on every topic to reach
+/// the cap.
+const BEACON_BLOCK_WEIGHT: f64 = 0.5;
+const BEACON_AGGREGATE_PROOF_WEIGHT: f64 = 0.5;
+/// Shared between all attestation subnets.
+const BEACON_ATTESTATION_SUBNETS_WEIGHT: f64 = 1.0;
+const VOLUNTARY_EXIT_WEIGHT: f64 = 0.05;
+const PROPOSER_SLASHING_WEIGHT: f64 = 0.05;
+const ATTESTER_SLASHING_WEIGHT: f64 = 0.05;
+
+/// The result of validating a gossip message, as defined by gossipsub v1.1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageAcceptance {
+    /// The message is valid and is propagated.
+    Accept,
+    /// The message is not propagated, but the peer that sent it is not necessarily at fault (e.g.,
+    /// the message refers to a block we do not know yet).
+    Ignore,
+    /// The message is not propagated because it, or an equivalent message, is already known. The
+    /// peer is not at fault for a single duplicate, but forwarding many of them is penalized.
+    Duplicate,
+    /// The message is invalid and the peer that sent it is at fault.
+    Reject,
+}
+
+/// The scoring parameters of a single topic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TopicScoreParams {
+    /// The weight of the score of the topic in the score of a peer.
+    pub topic_weight: f64,
+    /// The weight of each first delivery of a valid message.
+    pub first_message_deliveries_weight: f64,
+    /// The factor the first deliveries counter is multiplied by every decay interval.
+    pub first_message_deliveries_decay: f64,
+    /// The maximum value of the first deliveries counter.
+    pub first_message_deliveries_cap: f64,
+    /// The weight of the square of the invalid messages counter. Always negative.
+    pub invalid_message_deliveries_weight: f64,
+    /// The factor the invalid messages counter is multiplied by every decay interval.
+    pub invalid_message_deliveries_decay: f64,
+}
+
+/// The parameters used to score the gossip messages of peers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerScoreParams {
+    /// The parameters of each scored topic. Messages on other topics are not scored.
+    pub topics: HashMap<GossipKind, TopicScoreParams>,
+    /// The maximum positive contribution of all topics to the score of a peer.
+    pub topic_score_cap: f64,
+    /// The weight of the square of the duplicate messages in excess of
+    /// `behaviour_penalty_threshold`. Always negative.
+    pub behaviour_penalty_weight: f64,
+    /// The number of duplicate messages which are not penalized.
+    pub behaviour_penalty_threshold: f64,
+    /// The factor the duplicate messages counter is multiplied by every decay interval.
+    pub behaviour_penalty_decay: f64,
+    /// The interval at which all counters decay.
+    pub decay_interval: Duration,
+    /// Counters which decay below this value are set to zero.
+    pub decay_to_zero: f64,
+}
+
+impl PeerScoreParams {
+    /// Builds the parameters for a network with `active_validator_count` active validators.
+    ///
+    /// The counters decay every slot, and the caps and thresholds are derived from the number of
+    /// messages expected on each topic per slot.
+    pub fn new<E: EthSpec>(spec: &ChainSpec, active_validator_count: usize) -> Self {
+        let slot = Duration::from_millis(spec.milliseconds_per_slot);
+        let slots_per_epoch = E::slots_per_epoch();
+        let epoch = slot * slots_per_epoch as u32;
+        let epochs = |n: u32| epoch * n;
+
+        let committees_per_slot =
+            E::get_committee_count_per_slot(active_validator_count, spec).unwrap_or(1) as f64;
+        let subnet_count = spec.attestation_subnet_count.max(1);
+
+        // The expected number of messages per slot on each topic.
+        let blocks_per_slot = 1.0;
+        let aggregates_per_slot =
+            committees_per_slot * spec.target_aggregators_per_committee as f64;
+        let attestations_per_subnet_per_slot =
+            active_validator_count as f64 / slots_per_epoch as f64 / subnet_count as f64;
+        // Exits and slashings are rare, allow a peer to deliver a few of them.
+        let operations_per_slot = 1.0 / slots_per_epoch as f64;
+
+        let topic = |weight, messages_per_slot, first_decay_time, invalid_decay_time| {
+            topic_score_params(
+                weight,
+                messages_per_slot,
+                score_parameter_decay(first_decay_time, slot),
+                score_parameter_decay(invalid_decay_time, slot),
+            )
+        };
+
+        let mut topics = HashMap::new();
+        topics.insert(
+            GossipKind::BeaconBlock,
+            topic(BEACON_BLOCK_WEIGHT, blocks_per_slot, epochs(20), epochs(50)),
+        );
+        topics.insert(
+            GossipKind::BeaconAggregateAndProof,
+            topic(
+                BEACON_AGGREGATE_PROOF_WEIGHT,
+                aggregates_per_slot,
+                epochs(1),
+                epochs(5),
+            ),
+        );
+        for subnet_id in 0..subnet_count {
+            topics.insert(
+                GossipKind::CommitteeIndex(SubnetId::new(subnet_id)),
+                topic(
+                    BEACON_ATTESTATION_SUBNETS_WEIGHT / subnet_count as f64,
+                    attestations_per_subnet_per_slot,
+                    epochs(1),
+                    epochs(5),
+                ),
+            );
+        }
+        topics.insert(
+            GossipKind::VoluntaryExit,
+            topic(
+                VOLUNTARY_EXIT_WEIGHT,
+                operations_per_slot,
+                epochs(100),
+                epochs(50),
+            ),
+        );
+        topics.insert(
+            GossipKind::ProposerSlashing,
+            topic(
+                PROPOSER_SLASHING_WEIGHT,
+                operations_per_slot,
+                epochs(100),
+                epochs(50),
+            ),
+        );
+        topics.insert(
+            GossipKind::AttesterSlashing,
+            topic(
+                ATTESTER_SLASHING_WEIGHT,
+                operations_per_slot,
+                epochs(100),
+                epochs(50),
+            ),
+        );
+
+        // A peer may forward an epoch's worth of known blocks and aggregates before it is
+        // penalized. Exceeding the threshold by as much again costs `MAX_POSITIVE_SCORE`.
+        let behaviour_penalty_threshold =
+            ((blocks_per_slot + aggregates_per_slot) * slots_per_epoch as f64).max(1.0);
+
+        PeerScoreParams {
+            topics,
+            topic_score_cap: MAX_POSITIVE_SCORE,
+            behaviour_penalty_weight: -MAX_POSITIVE_SCORE / behaviour_penalty_threshold.powi(2),
+            behaviour_penalty_threshold,
+            behaviour_penalty_decay: score_parameter_decay(epoch, slot),
+            decay_interval: slot,
+            decay_to_zero: DECAY_TO_ZERO,
+        }
+    }
+}
+
+/// Builds the parameters of a topic which is expected to receive `messages_per_slot` messages.
+///
+/// A peer which delivers every message of the topic first reaches the cap of its first deliveries
+/// counter, which is worth `topic_weight * MAX_POSITIVE_SCORE`. Every invalid message costs
+/// `MAX_POSITIVE_SCORE` times the square of the number of invalid messages.
+fn topic_score_params(
+    topic_weight: f64,
+    messages_per_slot: f64,
+    first_message_deliveries_decay: f64,
+    invalid_message_deliveries_decay: f64,
+) -> TopicScoreParams {
+    // The steady state of the counter when `messages_per_slot` are delivered every slot.
+    let first_message_deliveries_cap =
+        (messages_per_slot / (1.0 - first_message_deliveries_decay)).max(1.0);
+
+    TopicScoreParams {
+        topic_weight,
+        first_message_deliveries_weight: MAX_POSITIVE_SCORE / first_message_deliveries_cap,
+        first_message_deliveries_decay,
+        first_message_deliveries_cap,
+        invalid_message_deliveries_weight: -MAX_POSITIVE_SCORE / topic_weight,
+        invalid_message_deliveries_decay,
+    }
+}
+
+/// Returns the factor a counter is multiplied by every `decay_interval`, such that it decays to
+/// `DECAY_TO_ZERO` of its value after `decay_time`.
+fn score_parameter_decay(decay_time: Duration, decay_interval: Duration) -> f64 {
+    let ticks = decay_time.as_secs_f64() / decay_interval.as_secs_f64();
+    DECAY_TO_ZERO.powf(1.0 / ticks)
+}
+
+/// The gossip score parameters together with the thresholds at which a peer's gossip is ignored
+/// and at which the peer is disconnected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GossipScoring {
+    pub params: PeerScoreParams,
+    /// Gossip messages from peers with a score below this threshold are ignored.
+    pub graylist_threshold: f64,
+    /// Peers with a score below this threshold are disconnected and banned.
+    pub disconnect_threshold: f64,
+}
+
+impl GossipScoring {
+    /// Returns the contribution of a gossip score to the reputation of a peer.
+    ///
+    /// The score is scaled such that a peer at the disconnect threshold loses the reputation
+    /// between `DEFAULT_REPUTATION` and `MIN_REP_BEFORE_BAN`.
+    pub fn reputation(&self, score: f64) -> i16 {
+        let scale =
+            f64::from(DEFAULT_REPUTATION - MIN_REP_BEFORE_BAN) / self.disconnect_threshold.abs();
+        let max = f64::from(Rep::max_value());
+        (score * scale).round().max(-max).min(max) as i16
+    }
+}
+
+/// The counters of the gossip messages of a peer from which its score is computed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GossipStats {
+    topics: HashMap<GossipKind, TopicStats>,
+    behaviour_penalty: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct TopicStats {
+    first_message_deliveries: f64,
+    invalid_message_deliveries: f64,
+}
+
+impl GossipStats {
+    /// Records the result of validating a message which the peer delivered on the `kind` topic.
+    pub fn add(
+        &mut self,
+        params: &PeerScoreParams,
+        kind: &GossipKind,
+        acceptance: MessageAcceptance,
+    ) {
+        let topic_params = match params.topics.get(kind) {
+            Some(topic_params) => topic_params,
+            None => return,
+        };
+
+        match acceptance {
+            MessageAcceptance::Accept => {
+                let stats = self.topics.entry(kind.clone()).or_default();
+                stats.first_message_deliveries = (stats.first_message_deliveries + 1.0)
+                    .min(topic_params.first_message_deliveries_cap);
+            }
+            MessageAcceptance::Ignore => {}
+            MessageAcceptance::Duplicate => self.behaviour_penalty += 1.0,
+            MessageAcceptance::Reject => {
+                self.topics
+                    .entry(kind.clone())
+                    .or_default()
+                    .invalid_message_deliveries += 1.0
+            }
+        }
+    }
+
+    /// Returns the score of the peer.
+    pub fn score(&self, params: &PeerScoreParams) -> f64 {
+        let topic_score: f64 = self
+            .topics
+            .iter()
+            .filter_map(|(kind, stats)| {
+                params.topics.get(kind).map(|topic_params| {
+                    topic_params.topic_weight
+                        * (stats.first_message_deliveries
+                            * topic_params.first_message_deliveries_weight
+                            + stats.invalid_message_deliveries.powi(2)
+                                * topic_params.invalid_message_deliveries_weight)
+                })
+            })
+            .sum();
+
+        let mut score = topic_score.min(params.topic_score_cap);
+
+        let excess_penalty = self.behaviour_penalty - params.behaviour_penalty_threshold;
+        if excess_penalty > 0.0 {
+            score += excess_penalty.powi(2) * params.behaviour_penalty_weight;
+        }
+
+        score
+    }
+
+    /// Decays the counters by one decay interval.
+    pub fn decay(&mut self, params: &PeerScoreParams) {
+        let decay = |value: f64, factor: f64| {
+            let decayed = value * factor;
+            if decayed < params.decay_to_zero {
+                0.0
+            } else {
+                decayed
+            }
+        };
+
+        for (kind, stats) in self.topics.iter_mut() {
+            if let Some(topic_params) = params.topics.get(kind) {
+                stats.first_message_deliveries = decay(
+                    stats.first_message_deliveries,
+                    topic_params.first_message_deliveries_decay,
+                );
+                stats.invalid_message_deliveries = decay(
+                    stats.invalid_message_deliveries,
+                    topic_params.invalid_message_deliveries_decay,
+                );
+            }
+        }
+        self.topics.retain(|_, stats| {
+            stats.first_message_deliveries > 0.0 || stats.invalid_message_deliveries > 0.0
+        });
+
+        self.behaviour_penalty = decay(self.behaviour_penalty, params.behaviour_penalty_decay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{MainnetEthSpec, MinimalEthSpec};
+
+    fn params() -> PeerScoreParams {
+        PeerScoreParams::new::<MainnetEthSpec>(&ChainSpec::mainnet(), 100_000)
+    }
+
+    #[test]
+    fn test_first_deliveries_are_capped() {
+        let params = params();
+        let mut stats = GossipStats::default();
+
+        for _ in 0..10_000 {
+            stats.add(&params, &GossipKind::BeaconBlock, MessageAcceptance::Accept);
+        }
+        let block_score = stats.score(&params);
+        assert!((block_score - BEACON_BLOCK_WEIGHT * MAX_POSITIVE_SCORE).abs() < 1e-6);
+
+        for _ in 0..100_000 {
+            stats.add(
+                &params,
+                &GossipKind::BeaconAggregateAndProof,
+                MessageAcceptance::Accept,
+            );
+            stats.add(
+                &params,
+                &GossipKind::CommitteeIndex(SubnetId::new(0)),
+                MessageAcceptance::Accept,
+            );
+        }
+        assert!((stats.score(&params) - params.topic_score_cap).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_messages_cost_their_square() {
+        let params = params();
+        let mut stats = GossipStats::default();
+
+        for kind in &[
+            GossipKind::BeaconBlock,
+            GossipKind::CommitteeIndex(SubnetId::new(3)),
+            GossipKind::AttesterSlashing,
+        ] {
+            stats.add(&params, kind, MessageAcceptance::Reject);
+            stats.add(&params, kind, MessageAcceptance::Reject);
+        }
+        assert!((stats.score(&params) + 3.0 * 4.0 * MAX_POSITIVE_SCORE).abs() < 1e-6);
+
+        // Messages on unscored topics are ignored.
+        let mut stats = GossipStats::default();
+        stats.add(
+            &params,
+            &GossipKind::CommitteeIndex(SubnetId::new(1_000)),
+            MessageAcceptance::Reject,
+        );
+        assert_eq!(stats, GossipStats::default());
+    }
+
+    #[test]
+    fn test_duplicate_messages_above_threshold() {
+        let params = params();
+        let mut stats = GossipStats::default();
+        let threshold = params.behaviour_penalty_threshold as usize;
+
+        for _ in 0..threshold {
+            stats.add(
+                &params,
+                &GossipKind::BeaconBlock,
+                MessageAcceptance::Duplicate,
+            );
+        }
+        assert!(stats.score(&params).abs() < 1e-6);
+
+        for _ in 0..threshold {
+            stats.add(
+                &params,
+                &GossipKind::BeaconBlock,
+                MessageAcceptance::Duplicate,
+            );
+        }
+        assert!((stats.score(&params) + MAX_POSITIVE_SCORE).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ignored_messages_are_neutral() {
+        let params = params();
+        let mut stats = GossipStats::default();
+
+        for _ in 0..10 * params.behaviour_penalty_threshold as usize {
+            stats.add(&params, &GossipKind::BeaconBlock, MessageAcceptance::Ignore);
+        }
+        assert_eq!(stats, GossipStats::default());
+    }
+
+    #[test]
+    fn test_counters_decay_to_zero() {
+        let params = PeerScoreParams::new::<MinimalEthSpec>(&ChainSpec::minimal(), 64);
+        let mut stats = GossipStats::default();
+
+        stats.add(&params, &GossipKind::BeaconBlock, MessageAcceptance::Reject);
+        stats.add(
+            &params,
+            &GossipKind::BeaconAggregateAndProof,
+            MessageAcceptance::Accept,
+        );
+        stats.add(
+            &params,
+            &GossipKind::BeaconBlock,
+            MessageAcceptance::Duplicate,
+        );
+        let score = stats.score(&params);
+
+        stats.decay(&params);
+        assert!(stats.score(&params) > score);
+
+        // Invalid blocks decay to zero over 50 epochs.
+        for _ in 0..50 * MinimalEthSpec::slots_per_epoch() {
+            stats.decay(&params);
+        }
+        assert_eq!(stats, GossipStats::default());
+    }
+
+    #[test]
+    fn test_reputation_at_thresholds() {
+        let scoring = GossipScoring {
+            params: params(),
+            graylist_threshold: -400.0,
+            disconnect_threshold: -900.0,
+        };
+
+        assert_eq!(scoring.reputation(0.0), 0);
+        assert_eq!(
+            scoring.reputation(-900.0),
+            -i16::from(DEFAULT_REPUTATION - MIN_REP_BEFORE_BAN)
+        );
+        assert_eq!(scoring.reputation(-1e12), -i16::from(Rep::max_value()));
+        assert!(scoring.reputation(MAX_POSITIVE_SCORE) > 0);
+    }
+}
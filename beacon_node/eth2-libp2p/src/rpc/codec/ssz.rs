@@ -51,6 +51,7 @@ impl<TSpec: EthSpec> Encoder for SSZInboundCodec<TSpec> {
             },
             RPCCodedResponse::InvalidRequest(err) => err.as_ssz_bytes(),
             RPCCodedResponse::ServerError(err) => err.as_ssz_bytes(),
+            RPCCodedResponse::RateLimited(err) => err.as_ssz_bytes(),
             RPCCodedResponse::Unknown(err) => err.as_ssz_bytes(),
             RPCCodedResponse::StreamTermination(_) => {
                 unreachable!("Code error - attempting to encode a stream termination")
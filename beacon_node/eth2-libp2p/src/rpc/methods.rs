@@ -186,6 +186,9 @@ pub enum RPCCodedResponse<T: EthSpec> {
     /// The response indicates a server error.
     ServerError(ErrorMessage),
 
+    /// The request was rejected because the peer exceeded its rate limit.
+    RateLimited(ErrorMessage),
+
     /// There was an unknown response.
     Unknown(ErrorMessage),
 
@@ -198,6 +201,7 @@ pub enum RPCCodedResponse<T: EthSpec> {
 pub enum RPCResponseErrorCode {
     InvalidRequest,
     ServerError,
+    RateLimited,
     Unknown,
 }
 
@@ -208,6 +212,8 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             RPCCodedResponse::Success(_) => Some(0),
             RPCCodedResponse::InvalidRequest(_) => Some(1),
             RPCCodedResponse::ServerError(_) => Some(2),
+            // A code in the range reserved for extensions by the spec.
+            RPCCodedResponse::RateLimited(_) => Some(139),
             RPCCodedResponse::Unknown(_) => Some(255),
             RPCCodedResponse::StreamTermination(_) => None,
         }
@@ -226,6 +232,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
         match response_code {
             1 => RPCCodedResponse::InvalidRequest(err),
             2 => RPCCodedResponse::ServerError(err),
+            139 => RPCCodedResponse::RateLimited(err),
             _ => RPCCodedResponse::Unknown(err),
         }
     }
@@ -242,6 +249,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             },
             RPCCodedResponse::InvalidRequest(_) => true,
             RPCCodedResponse::ServerError(_) => true,
+            RPCCodedResponse::RateLimited(_) => true,
             RPCCodedResponse::Unknown(_) => true,
             // Stream terminations are part of responses that have chunks
             RPCCodedResponse::StreamTermination(_) => true,
@@ -263,6 +271,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             RPCCodedResponse::StreamTermination(_) => None,
             RPCCodedResponse::InvalidRequest(_) => Some(RPCResponseErrorCode::InvalidRequest),
             RPCCodedResponse::ServerError(_) => Some(RPCResponseErrorCode::ServerError),
+            RPCCodedResponse::RateLimited(_) => Some(RPCResponseErrorCode::RateLimited),
             RPCCodedResponse::Unknown(_) => Some(RPCResponseErrorCode::Unknown),
         }
     }
@@ -285,6 +294,7 @@ impl std::fmt::Display for RPCResponseErrorCode {
         let repr = match self {
             RPCResponseErrorCode::InvalidRequest => "The request was invalid",
             RPCResponseErrorCode::ServerError => "Server error occurred",
+            RPCResponseErrorCode::RateLimited => "Rate limited",
             RPCResponseErrorCode::Unknown => "Unknown error occurred",
         };
         f.write_str(repr)
@@ -319,6 +329,7 @@ impl<T: EthSpec> std::fmt::Display for RPCCodedResponse<T> {
             RPCCodedResponse::Success(res) => write!(f, "{}", res),
             RPCCodedResponse::InvalidRequest(err) => write!(f, "Invalid Request: {:?}", err),
             RPCCodedResponse::ServerError(err) => write!(f, "Server Error: {:?}", err),
+            RPCCodedResponse::RateLimited(err) => write!(f, "Rate Limited: {:?}", err),
             RPCCodedResponse::Unknown(err) => write!(f, "Unknown Error: {:?}", err),
             RPCCodedResponse::StreamTermination(_) => write!(f, "Stream Termination"),
         }
@@ -17,6 +17,7 @@ pub use methods::{
     ResponseTermination, StatusMessage,
 };
 pub use protocol::{Protocol, RPCError, RPCProtocol, RPCRequest};
+pub use rate_limiter::{Quota, RPCRateLimiter, RateLimitedErr, RateLimiterConfig};
 use slog::{debug, o};
 use std::marker::PhantomData;
 use std::time::Duration;
@@ -27,6 +28,7 @@ pub(crate) mod codec;
 mod handler;
 pub mod methods;
 mod protocol;
+mod rate_limiter;
 
 /// The return type used in the behaviour and the resultant event from the protocols handler.
 #[derive(Debug)]
@@ -71,6 +73,8 @@ impl<T: EthSpec> std::fmt::Display for RPCEvent<T> {
 pub struct RPC<TSubstream, TSpec: EthSpec> {
     /// Queue of events to processed.
     events: Vec<NetworkBehaviourAction<RPCEvent<TSpec>, RPCMessage<TSpec>>>,
+    /// Limits the rate of inbound requests of each peer.
+    limiter: RPCRateLimiter,
     /// Pins the generic substream.
     marker: PhantomData<TSubstream>,
     /// Slog logger for RPC behaviour.
@@ -78,10 +82,11 @@ pub struct RPC<TSubstream, TSpec: EthSpec> {
 }
 
 impl<TSubstream, TSpec: EthSpec> RPC<TSubstream, TSpec> {
-    pub fn new(log: slog::Logger) -> Self {
+    pub fn new(rate_limiter_config: &RateLimiterConfig, log: slog::Logger) -> Self {
         let log = log.new(o!("service" => "libp2p_rpc"));
         RPC {
             events: Vec::new(),
+            limiter: RPCRateLimiter::new(rate_limiter_config),
             marker: PhantomData,
             log,
         }
@@ -160,6 +165,37 @@ where
         source: PeerId,
         event: <Self::ProtocolsHandler as ProtocolsHandler>::OutEvent,
     ) {
+        if let RPCEvent::Request(id, request) = &event {
+            if let Err(err) = self.limiter.allows(&source, request) {
+                let error_message = match err {
+                    // The request can never be served within the quota.
+                    RateLimitedErr::TooLarge => {
+                        debug!(self.log, "Request too large to be served"; "peer_id" => format!("{}", source), "request" => format!("{}", request));
+                        "Rate limited. Request too large".to_string()
+                    }
+                    RateLimitedErr::TooSoon(wait_time) => {
+                        debug!(self.log, "Request rate limited"; "peer_id" => format!("{}", source), "request" => format!("{}", request), "wait_time" => format!("{:?}", wait_time));
+                        format!("Rate limited. Wait {} ms", wait_time.as_millis())
+                    }
+                };
+
+                // inform the behaviour, which penalises the peer
+                self.events.push(NetworkBehaviourAction::GenerateEvent(
+                    RPCMessage::RateLimited(source.clone(), request.protocol(), err),
+                ));
+                self.send_rpc(
+                    source,
+                    RPCEvent::Response(
+                        *id,
+                        RPCCodedResponse::RateLimited(ErrorMessage {
+                            error_message: error_message.into_bytes(),
+                        }),
+                    ),
+                );
+                return;
+            }
+        }
+
         // send the event to the user
         self.events
             .push(NetworkBehaviourAction::GenerateEvent(RPCMessage::RPC(
@@ -188,6 +224,8 @@ pub enum RPCMessage<TSpec: EthSpec> {
     RPC(PeerId, RPCEvent<TSpec>),
     PeerDialed(PeerId),
     PeerDisconnected(PeerId),
+    /// A request of the peer was refused because it exceeded the quota of its protocol.
+    RateLimited(PeerId, Protocol, RateLimitedErr),
     // TODO: This is a hack to give access to connections to peer manager. Remove this once
     // behaviour is re-written
     PeerConnectedHack(PeerId, ConnectedPoint),
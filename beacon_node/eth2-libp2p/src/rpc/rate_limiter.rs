@@ -0,0 +1,351 @@
+//! Rate limiting of inbound RPC requests.
+//!
+//! Each protocol has a `Quota`, which is enforced separately for every peer by a token bucket.
+//! Requests consume a number of tokens proportional to the work required to answer them (e.g., the
+//! number of blocks requested), and the tokens of a bucket are replenished at a constant rate.
+
+use super::protocol::{Protocol, RPCRequest};
+use fnv::FnvHashMap;
+use libp2p::PeerId;
+use serde_derive::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use types::EthSpec;
+
+/// The minimum time between removing the buckets of peers which have not made requests recently.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The number of tokens which may be consumed within a period of time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Quota {
+    /// The time it takes for an empty bucket to be entirely replenished.
+    pub replenish_all_every: Duration,
+    /// The size of the bucket, i.e., the maximum number of tokens consumed at once.
+    pub max_tokens: u64,
+}
+
+impl Quota {
+    /// Allows `n` tokens to be consumed every `seconds`.
+    pub fn n_every(n: u64, seconds: u64) -> Self {
+        Quota {
+            replenish_all_every: Duration::from_secs(seconds),
+            max_tokens: n,
+        }
+    }
+}
+
+impl FromStr for Quota {
+    type Err = String;
+
+    /// Parses a quota of the form `tokens/seconds`, e.g., `1024/10`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        let (tokens, seconds) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(tokens), Some(seconds), None) => (tokens, seconds),
+            _ => return Err(format!("Quota must be of the form tokens/seconds: {}", s)),
+        };
+        let tokens = tokens
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid number of tokens in quota {}: {:?}", s, e))?;
+        let seconds = seconds
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid number of seconds in quota {}: {:?}", s, e))?;
+
+        if tokens == 0 || seconds == 0 {
+            return Err(format!("Quota must be non-zero: {}", s));
+        }
+
+        Ok(Quota::n_every(tokens, seconds))
+    }
+}
+
+/// The quota of each rate limited protocol.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RateLimiterConfig {
+    pub status: Quota,
+    pub blocks_by_range: Quota,
+    pub blocks_by_root: Quota,
+    pub ping: Quota,
+    pub meta_data: Quota,
+}
+
+impl Default for RateLimiterConfig {
+    /// Allows a peer to sync from us at a reasonable rate (the maximum of 1024 blocks per request,
+    /// every 10 seconds), and enough of the other requests for the regular status, ping and meta
+    /// data exchanges.
+    fn default() -> Self {
+        Self {
+            status: Quota::n_every(5, 15),
+            blocks_by_range: Quota::n_every(1024, 10),
+            blocks_by_root: Quota::n_every(128, 10),
+            ping: Quota::n_every(2, 10),
+            meta_data: Quota::n_every(2, 5),
+        }
+    }
+}
+
+impl RateLimiterConfig {
+    /// Replaces the quotas of the protocols listed in `quotas`, a comma-separated list of
+    /// `protocol:tokens/seconds` (e.g., `beacon_blocks_by_range:512/10,ping:4/10`). The protocols
+    /// are named as in their protocol ids.
+    pub fn apply_quotas(&mut self, quotas: &str) -> Result<(), String> {
+        for entry in quotas.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let mut parts = entry.splitn(2, ':');
+            let protocol = parts.next().unwrap_or("");
+            let quota = parts
+                .next()
+                .ok_or_else(|| format!("Missing quota for protocol: {}", entry))?
+                .parse::<Quota>()?;
+
+            match protocol {
+                "status" => self.status = quota,
+                "beacon_blocks_by_range" => self.blocks_by_range = quota,
+                "beacon_blocks_by_root" => self.blocks_by_root = quota,
+                "ping" => self.ping = quota,
+                "metadata" => self.meta_data = quota,
+                other => return Err(format!("Unknown rate limited protocol: {}", other)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The reason a request was not allowed.
+#[derive(Debug, PartialEq)]
+pub enum RateLimitedErr {
+    /// The request requires more tokens than the quota allows at once. It will never be allowed.
+    TooLarge,
+    /// The bucket of the peer does not have enough tokens. The request would be allowed after the
+    /// given duration.
+    TooSoon(Duration),
+}
+
+/// Rate limits the inbound requests of every peer, separately for each protocol.
+///
+/// Goodbye requests are not limited, since they have no response and the peer disconnects.
+pub struct RPCRateLimiter {
+    /// The time from which the buckets measure time.
+    init_time: Instant,
+    /// The last time the buckets were pruned.
+    last_pruned: Instant,
+    status: Limiter<PeerId>,
+    blocks_by_range: Limiter<PeerId>,
+    blocks_by_root: Limiter<PeerId>,
+    ping: Limiter<PeerId>,
+    meta_data: Limiter<PeerId>,
+}
+
+impl RPCRateLimiter {
+    pub fn new(config: &RateLimiterConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            init_time: now,
+            last_pruned: now,
+            status: Limiter::new(config.status),
+            blocks_by_range: Limiter::new(config.blocks_by_range),
+            blocks_by_root: Limiter::new(config.blocks_by_root),
+            ping: Limiter::new(config.ping),
+            meta_data: Limiter::new(config.meta_data),
+        }
+    }
+
+    /// Checks whether `request` from `peer_id` is within quota, consuming its tokens if it is.
+    pub fn allows<T: EthSpec>(
+        &mut self,
+        peer_id: &PeerId,
+        request: &RPCRequest<T>,
+    ) -> Result<(), RateLimitedErr> {
+        let now = Instant::now();
+        if now.duration_since(self.last_pruned) >= PRUNE_INTERVAL {
+            self.prune(now);
+        }
+
+        let time_since_start = now.duration_since(self.init_time);
+        let tokens = request_cost(request);
+        let limiter = match request.protocol() {
+            Protocol::Status => &mut self.status,
+            Protocol::Goodbye => return Ok(()),
+            Protocol::BlocksByRange => &mut self.blocks_by_range,
+            Protocol::BlocksByRoot => &mut self.blocks_by_root,
+            Protocol::Ping => &mut self.ping,
+            Protocol::MetaData => &mut self.meta_data,
+        };
+
+        limiter.allows(time_since_start, peer_id, tokens)
+    }
+
+    /// Removes the buckets which are full. A full bucket is equivalent to no bucket.
+    fn prune(&mut self, now: Instant) {
+        let time_since_start = now.duration_since(self.init_time);
+        self.status.prune(time_since_start);
+        self.blocks_by_range.prune(time_since_start);
+        self.blocks_by_root.prune(time_since_start);
+        self.ping.prune(time_since_start);
+        self.meta_data.prune(time_since_start);
+        self.last_pruned = now;
+    }
+}
+
+/// The number of tokens consumed by `request`.
+fn request_cost<T: EthSpec>(request: &RPCRequest<T>) -> u64 {
+    let cost = match request {
+        RPCRequest::BlocksByRange(req) => req.count,
+        RPCRequest::BlocksByRoot(req) => req.block_roots.len() as u64,
+        _ => 1,
+    };
+    // Even an empty request requires some work.
+    cost.max(1)
+}
+
+/// A token bucket for each key, implemented with the generic cell rate algorithm (GCRA).
+///
+/// Rather than counting tokens, the bucket of each key is represented by its _theoretical arrival
+/// time_ (TAT): the time at which the bucket would be full again. Times are measured in nanoseconds
+/// from the creation of the `RPCRateLimiter`.
+struct Limiter<Key: Hash + Eq + Clone> {
+    /// The time it takes to replenish a single token, in nanoseconds.
+    token_time: u64,
+    /// The time it takes to replenish the entire bucket, in nanoseconds.
+    bucket_time: u64,
+    tat_per_key: FnvHashMap<Key, u64>,
+}
+
+impl<Key: Hash + Eq + Clone> Limiter<Key> {
+    fn new(quota: Quota) -> Self {
+        let bucket_time = quota.replenish_all_every.as_nanos() as u64;
+        Self {
+            token_time: bucket_time / quota.max_tokens.max(1),
+            bucket_time,
+            tat_per_key: FnvHashMap::default(),
+        }
+    }
+
+    fn allows(
+        &mut self,
+        time_since_start: Duration,
+        key: &Key,
+        tokens: u64,
+    ) -> Result<(), RateLimitedErr> {
+        let additional_time = self.token_time.saturating_mul(tokens);
+        if additional_time > self.bucket_time {
+            return Err(RateLimitedErr::TooLarge);
+        }
+
+        let now = time_since_start.as_nanos() as u64;
+        let tat = self.tat_per_key.entry(key.clone()).or_insert(now);
+        // The earliest time at which the bucket holds enough tokens for the request.
+        let earliest_time = (*tat + additional_time).saturating_sub(self.bucket_time);
+
+        if now < earliest_time {
+            Err(RateLimitedErr::TooSoon(Duration::from_nanos(
+                earliest_time - now,
+            )))
+        } else {
+            *tat = std::cmp::max(*tat, now) + additional_time;
+            Ok(())
+        }
+    }
+
+    fn prune(&mut self, time_since_start: Duration) {
+        let now = time_since_start.as_nanos() as u64;
+        self.tat_per_key.retain(|_, tat| *tat > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_replenishes() {
+        // 4 tokens every 4 seconds, i.e., one token per second.
+        let mut limiter = Limiter::new(Quota::n_every(4, 4));
+        let key = 1u64;
+
+        // A full bucket allows a burst of all its tokens.
+        assert_eq!(limiter.allows(secs(0), &key, 2), Ok(()));
+        assert_eq!(limiter.allows(secs(0), &key, 2), Ok(()));
+        assert_eq!(
+            limiter.allows(secs(0), &key, 1),
+            Err(RateLimitedErr::TooSoon(secs(1)))
+        );
+
+        // One token is replenished each second.
+        assert_eq!(limiter.allows(secs(1), &key, 1), Ok(()));
+        assert_eq!(
+            limiter.allows(secs(1), &key, 1),
+            Err(RateLimitedErr::TooSoon(secs(1)))
+        );
+        assert_eq!(
+            limiter.allows(secs(2), &key, 2),
+            Err(RateLimitedErr::TooSoon(secs(1)))
+        );
+        assert_eq!(limiter.allows(secs(3), &key, 2), Ok(()));
+
+        // Tokens do not accumulate beyond the size of the bucket.
+        assert_eq!(limiter.allows(secs(100), &key, 4), Ok(()));
+        assert!(limiter.allows(secs(100), &key, 1).is_err());
+    }
+
+    #[test]
+    fn buckets_are_per_key() {
+        let mut limiter = Limiter::new(Quota::n_every(1, 10));
+
+        assert_eq!(limiter.allows(secs(0), &1u64, 1), Ok(()));
+        assert!(limiter.allows(secs(0), &1u64, 1).is_err());
+        assert_eq!(limiter.allows(secs(0), &2u64, 1), Ok(()));
+    }
+
+    #[test]
+    fn requests_larger_than_bucket_are_rejected() {
+        let mut limiter = Limiter::new(Quota::n_every(4, 4));
+
+        assert_eq!(
+            limiter.allows(secs(0), &1u64, 5),
+            Err(RateLimitedErr::TooLarge)
+        );
+        // The rejected request does not consume any tokens.
+        assert_eq!(limiter.allows(secs(0), &1u64, 4), Ok(()));
+    }
+
+    #[test]
+    fn prune_removes_full_buckets() {
+        let mut limiter = Limiter::new(Quota::n_every(4, 4));
+
+        assert_eq!(limiter.allows(secs(0), &1u64, 4), Ok(()));
+        assert_eq!(limiter.allows(secs(0), &2u64, 1), Ok(()));
+
+        limiter.prune(secs(2));
+        assert_eq!(limiter.tat_per_key.len(), 1);
+        limiter.prune(secs(4));
+        assert!(limiter.tat_per_key.is_empty());
+    }
+
+    #[test]
+    fn quotas_are_applied_by_protocol() {
+        let mut config = RateLimiterConfig::default();
+        config
+            .apply_quotas("beacon_blocks_by_range:512/20, ping:4/10")
+            .expect("should apply valid quotas");
+
+        assert_eq!(config.blocks_by_range, Quota::n_every(512, 20));
+        assert_eq!(config.ping, Quota::n_every(4, 10));
+        assert_eq!(
+            config.status,
+            RateLimiterConfig::default().status,
+            "should not change other protocols"
+        );
+
+        assert!(config.apply_quotas("goodbye:1/10").is_err());
+        assert!(config.apply_quotas("ping").is_err());
+        assert!(config.apply_quotas("ping:4").is_err());
+        assert!(config.apply_quotas("ping:0/10").is_err());
+        assert!(config.apply_quotas("ping:4/10/1").is_err());
+    }
+}
@@ -5,6 +5,7 @@ use crate::types::{error, GossipKind};
 use crate::{NetworkConfig, NetworkGlobals};
 use futures::prelude::*;
 use futures::Stream;
+use hashmap_delay::HashSetDelay;
 use libp2p::core::{
     identity::Keypair,
     multiaddr::Multiaddr,
@@ -16,6 +17,7 @@ use libp2p::core::{
 };
 use libp2p::{core, noise, secio, swarm::NetworkBehaviour, PeerId, Swarm, Transport};
 use slog::{crit, debug, error, info, trace, warn};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{Error, ErrorKind};
@@ -31,6 +33,10 @@ pub const NETWORK_KEY_FILENAME: &str = "key";
 /// The time in milliseconds to wait before banning a peer. This allows for any Goodbye messages to be
 /// flushed and protocols to be negotiated.
 const BAN_PEER_WAIT_TIMEOUT: u64 = 200;
+/// The time in seconds to wait before redialling a trusted peer which has disconnected.
+const TRUSTED_PEER_INITIAL_BACKOFF: u64 = 5;
+/// The maximum time in seconds between attempts to redial a trusted peer.
+const TRUSTED_PEER_MAX_BACKOFF: u64 = 300;
 
 /// The configuration and state of the libp2p components for the beacon node.
 pub struct Service<TSpec: EthSpec> {
@@ -47,6 +53,15 @@ pub struct Service<TSpec: EthSpec> {
     /// A list of timeouts after which peers become unbanned.
     peer_ban_timeout: DelayQueue<PeerId>,
 
+    /// The trusted peers, which are never banned and are redialled when they disconnect.
+    trusted_peers: HashMap<PeerId, TrustedPeer>,
+
+    /// The trusted peers to redial once their backoff expires.
+    trusted_peer_redials: HashSetDelay<PeerId>,
+
+    /// A collection of variables accessible outside of the network crate.
+    network_globals: Arc<NetworkGlobals<TSpec>>,
+
     /// The libp2p logger handle.
     pub log: slog::Logger,
 }
@@ -80,7 +95,7 @@ impl<TSpec: EthSpec> Service<TSpec> {
         ));
 
         info!(log, "Libp2p Service"; "peer_id" => format!("{:?}", enr.peer_id()));
-        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port);
+        debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => config.discovery_port, "address6" => format!("{:?}", config.listen_address6), "tcp_port6" => config.libp2p_port6, "udp_port6" => config.discovery_port6);
 
         let mut swarm = {
             // Set up the transport - tcp/ws with noise/secio and mplex/yamux
@@ -90,29 +105,36 @@ impl<TSpec: EthSpec> Service<TSpec> {
             Swarm::new(transport, behaviour, local_peer_id.clone())
         };
 
-        // listen on the specified address
-        let listen_multiaddr = {
+        // listen on the specified addresses, over both IPv4 and IPv6 on a dual-stack node
+        let mut listen_multiaddrs = vec![{
             let mut m = Multiaddr::from(config.listen_address);
             m.push(Protocol::Tcp(config.libp2p_port));
             m
-        };
+        }];
+        if let Some(listen_address6) = config.listen_address6 {
+            let mut m = Multiaddr::from(listen_address6);
+            m.push(Protocol::Tcp(config.libp2p_port6));
+            listen_multiaddrs.push(m);
+        }
 
-        match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
-            Ok(_) => {
-                let mut log_address = listen_multiaddr;
-                log_address.push(Protocol::P2p(local_peer_id.clone().into()));
-                info!(log, "Listening established"; "address" => format!("{}", log_address));
-            }
-            Err(err) => {
-                crit!(
-                    log,
-                    "Unable to listen on libp2p address";
-                    "error" => format!("{:?}", err),
-                    "listen_multiaddr" => format!("{}", listen_multiaddr),
-                );
-                return Err("Libp2p was unable to listen on the given listen address.".into());
-            }
-        };
+        for listen_multiaddr in listen_multiaddrs {
+            match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
+                Ok(_) => {
+                    let mut log_address = listen_multiaddr;
+                    log_address.push(Protocol::P2p(local_peer_id.clone().into()));
+                    info!(log, "Listening established"; "address" => format!("{}", log_address));
+                }
+                Err(err) => {
+                    crit!(
+                        log,
+                        "Unable to listen on libp2p address";
+                        "error" => format!("{:?}", err),
+                        "listen_multiaddr" => format!("{}", listen_multiaddr),
+                    );
+                    return Err("Libp2p was unable to listen on the given listen address.".into());
+                }
+            };
+        }
 
         // helper closure for dialing peers
         let mut dial_addr = |multiaddr: &Multiaddr| {
@@ -130,6 +152,41 @@ impl<TSpec: EthSpec> Service<TSpec> {
             dial_addr(multiaddr);
         }
 
+        // attempt to connect to the trusted peers
+        let mut trusted_peers: HashMap<PeerId, TrustedPeer> = HashMap::new();
+        for multiaddr in &config.trusted_peers {
+            let mut address = multiaddr.clone();
+            let peer_id = match address.pop() {
+                Some(Protocol::P2p(multihash)) => PeerId::from_multihash(multihash).ok(),
+                _ => None,
+            };
+            match peer_id {
+                Some(peer_id) => trusted_peers
+                    .entry(peer_id)
+                    .or_insert_with(TrustedPeer::default)
+                    .addresses
+                    .push(address),
+                None => {
+                    warn!(log, "Trusted peer address has no peer id"; "address" => format!("{}", multiaddr))
+                }
+            }
+        }
+        let mut trusted_peer_redials =
+            HashSetDelay::new(Duration::from_secs(TRUSTED_PEER_INITIAL_BACKOFF));
+        for (peer_id, trusted_peer) in &trusted_peers {
+            {
+                let mut peers = network_globals.peers.write();
+                peers.add_trusted_peer(peer_id);
+                peers.dialing_peer(peer_id);
+            }
+
+            for address in &trusted_peer.addresses {
+                dial_addr(address);
+            }
+            // check that the peer has connected once the backoff expires
+            trusted_peer_redials.insert_at(peer_id.clone(), trusted_peer.backoff);
+        }
+
         // attempt to connect to any specified boot-nodes
         for bootnode_enr in &config.boot_nodes {
             for multiaddr in &bootnode_enr.multiaddr() {
@@ -162,6 +219,9 @@ impl<TSpec: EthSpec> Service<TSpec> {
             swarm,
             peers_to_ban: DelayQueue::new(),
             peer_ban_timeout: DelayQueue::new(),
+            trusted_peers,
+            trusted_peer_redials,
+            network_globals: network_globals.clone(),
             log,
         };
 
@@ -169,7 +229,13 @@ impl<TSpec: EthSpec> Service<TSpec> {
     }
 
     /// Adds a peer to be banned for a period of time, specified by a timeout.
+    ///
+    /// Trusted peers are never banned.
     pub fn disconnect_and_ban_peer(&mut self, peer_id: PeerId, timeout: Duration) {
+        if self.trusted_peers.contains_key(&peer_id) {
+            debug!(self.log, "Not banning trusted peer"; "peer_id" => format!("{:?}", peer_id));
+            return;
+        }
         error!(self.log, "Disconnecting and banning peer"; "peer_id" => format!("{:?}", peer_id), "timeout" => format!("{:?}", timeout));
         self.peers_to_ban.insert(
             peer_id.clone(),
@@ -177,6 +243,65 @@ impl<TSpec: EthSpec> Service<TSpec> {
         );
         self.peer_ban_timeout.insert(peer_id, timeout);
     }
+
+    /// Dials a trusted peer if it is not connected, doubling the time until the next attempt.
+    ///
+    /// Once the peer is connected its backoff is reset, and it is redialled from the
+    /// `PeerDisconnected` event.
+    fn redial_trusted_peer(&mut self, peer_id: PeerId) {
+        let trusted_peer = match self.trusted_peers.get_mut(&peer_id) {
+            Some(trusted_peer) => trusted_peer,
+            None => return,
+        };
+
+        if self.network_globals.peers.read().is_connected(&peer_id) {
+            trusted_peer.reset_backoff();
+            return;
+        }
+
+        debug!(self.log, "Redialling trusted peer"; "peer_id" => format!("{:?}", peer_id), "backoff" => format!("{:?}", trusted_peer.backoff));
+        self.network_globals.peers.write().dialing_peer(&peer_id);
+        for address in &trusted_peer.addresses {
+            if let Err(e) = Swarm::dial_addr(&mut self.swarm, address.clone()) {
+                debug!(self.log, "Could not redial trusted peer"; "address" => format!("{}", address), "error" => format!("{:?}", e));
+            }
+        }
+
+        let backoff = trusted_peer.increase_backoff();
+        self.trusted_peer_redials.insert_at(peer_id, backoff);
+    }
+}
+
+/// The known addresses of a trusted peer and the time to wait before it is next redialled.
+struct TrustedPeer {
+    addresses: Vec<Multiaddr>,
+    backoff: Duration,
+}
+
+impl TrustedPeer {
+    /// Doubles the backoff, up to `TRUSTED_PEER_MAX_BACKOFF`, returning the new backoff.
+    fn increase_backoff(&mut self) -> Duration {
+        self.backoff = std::cmp::min(
+            self.backoff * 2,
+            Duration::from_secs(TRUSTED_PEER_MAX_BACKOFF),
+        );
+        self.backoff
+    }
+
+    /// Resets the backoff to `TRUSTED_PEER_INITIAL_BACKOFF`, returning the new backoff.
+    fn reset_backoff(&mut self) -> Duration {
+        self.backoff = Duration::from_secs(TRUSTED_PEER_INITIAL_BACKOFF);
+        self.backoff
+    }
+}
+
+impl Default for TrustedPeer {
+    fn default() -> Self {
+        TrustedPeer {
+            addresses: vec![],
+            backoff: Duration::from_secs(TRUSTED_PEER_INITIAL_BACKOFF),
+        }
+    }
 }
 
 impl<TSpec: EthSpec> Stream for Service<TSpec> {
@@ -187,6 +312,14 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
         loop {
             match self.swarm.poll() {
                 Ok(Async::Ready(Some(event))) => {
+                    if let BehaviourEvent::PeerDisconnected(peer_id) = &event {
+                        if let Some(trusted_peer) = self.trusted_peers.get_mut(peer_id) {
+                            // the peer was connected, so start redialling from the initial backoff
+                            let backoff = trusted_peer.reset_backoff();
+                            self.trusted_peer_redials
+                                .insert_at(peer_id.clone(), backoff);
+                        }
+                    }
                     return Ok(Async::Ready(Some(event)));
                 }
                 Ok(Async::Ready(None)) => unreachable!("Swarm stream shouldn't end"),
@@ -236,6 +369,18 @@ impl<TSpec: EthSpec> Stream for Service<TSpec> {
             }
         }
 
+        // redial trusted peers whose backoff has expired
+        loop {
+            match self.trusted_peer_redials.poll() {
+                Ok(Async::Ready(Some(peer_id))) => self.redial_trusted_peer(peer_id),
+                Ok(Async::NotReady) | Ok(Async::Ready(None)) => break,
+                Err(e) => {
+                    warn!(self.log, "Trusted peer redial queue failed"; "error" => e);
+                    break;
+                }
+            }
+        }
+
         Ok(Async::NotReady)
     }
 }
@@ -374,3 +519,43 @@ fn generate_noise_config(
         .expect("signing can fail only once during starting a node");
     noise::NoiseConfig::xx(static_dh_keys).into_authenticated()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn trusted_peer_backoff() {
+        let mut trusted_peer = TrustedPeer::default();
+        assert_eq!(trusted_peer.backoff, secs(TRUSTED_PEER_INITIAL_BACKOFF));
+
+        // each failed redial doubles the backoff, up to the maximum
+        let backoffs = (0..8)
+            .map(|_| trusted_peer.increase_backoff())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backoffs,
+            vec![
+                secs(10),
+                secs(20),
+                secs(40),
+                secs(80),
+                secs(160),
+                secs(TRUSTED_PEER_MAX_BACKOFF),
+                secs(TRUSTED_PEER_MAX_BACKOFF),
+                secs(TRUSTED_PEER_MAX_BACKOFF),
+            ]
+        );
+
+        // a connection starts the next redials from the initial backoff
+        assert_eq!(
+            trusted_peer.reset_backoff(),
+            secs(TRUSTED_PEER_INITIAL_BACKOFF)
+        );
+        assert_eq!(trusted_peer.increase_backoff(), secs(10));
+    }
+}
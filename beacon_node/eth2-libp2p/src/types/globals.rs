@@ -1,7 +1,7 @@
 //! A collection of variables that are accessible outside of the network thread itself.
 use crate::peer_manager::PeerDB;
 use crate::rpc::methods::MetaData;
-use crate::types::SyncState;
+use crate::types::{BackFillState, SyncState};
 use crate::{discovery::enr::Eth2Enr, Enr, GossipTopic, Multiaddr, PeerId};
 use parking_lot::RwLock;
 use std::collections::HashSet;
@@ -27,6 +27,8 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
     pub gossipsub_subscriptions: RwLock<HashSet<GossipTopic>>,
     /// The current sync status of the node.
     pub sync_state: RwLock<SyncState>,
+    /// The current state of the download of historical blocks.
+    pub backfill_state: RwLock<BackFillState>,
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
@@ -49,6 +51,7 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             peers: RwLock::new(PeerDB::new(log)),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
+            backfill_state: RwLock::new(BackFillState::NotRequired),
         }
     }
 
@@ -88,6 +91,26 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         self.peers.read().connected_or_dialing_peers().count()
     }
 
+    /// Returns the number of libp2p connected peers which count toward the peer limit, i.e., which
+    /// are not trusted.
+    pub fn connected_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_peer_ids()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
+    /// Returns the number of libp2p peers that are either connected or being dialed and which
+    /// count toward the peer limit, i.e., which are not trusted.
+    pub fn connected_or_dialing_untrusted_peers(&self) -> usize {
+        let peers = self.peers.read();
+        peers
+            .connected_or_dialing_peers()
+            .filter(|peer_id| !peers.is_trusted(peer_id))
+            .count()
+    }
+
     /// Returns in the node is syncing.
     pub fn is_syncing(&self) -> bool {
         self.sync_state.read().is_syncing()
@@ -98,6 +121,11 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         self.sync_state.read().clone()
     }
 
+    /// Returns the current state of the historical block download.
+    pub fn backfill_state(&self) -> BackFillState {
+        self.backfill_state.read().clone()
+    }
+
     /// Updates the syncing state of the node.
     ///
     /// If there is a new state, the old state and the new states are returned.
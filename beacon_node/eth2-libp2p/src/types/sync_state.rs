@@ -64,3 +64,35 @@ impl std::fmt::Display for SyncState {
         }
     }
 }
+
+/// The state of the download of historical blocks, prior to the state the node was started from.
+///
+/// This is tracked separately from `SyncState`, since backfilling runs alongside forward sync.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BackFillState {
+    /// The node was started from genesis, there are no historical blocks to download.
+    NotRequired,
+    /// Historical blocks are being downloaded from synced peers.
+    Syncing {
+        oldest_block_slot: Slot,
+        anchor_slot: Slot,
+    },
+    /// Historical blocks are required but no synced peers are connected.
+    Stalled {
+        oldest_block_slot: Slot,
+        anchor_slot: Slot,
+    },
+    /// All blocks back to genesis have been downloaded.
+    Completed,
+}
+
+impl std::fmt::Display for BackFillState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackFillState::NotRequired => write!(f, "Not Required"),
+            BackFillState::Syncing { .. } => write!(f, "Syncing Historical Blocks"),
+            BackFillState::Stalled { .. } => write!(f, "Stalled"),
+            BackFillState::Completed => write!(f, "Completed"),
+        }
+    }
+}
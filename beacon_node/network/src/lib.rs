@@ -4,6 +4,7 @@ pub mod service;
 
 mod attestation_service;
 mod persisted_dht;
+mod persisted_peers;
 mod router;
 mod sync;
 
@@ -0,0 +1,78 @@
+use eth2_libp2p::PersistedPeers;
+use ssz::{Decode, Encode};
+use std::sync::Arc;
+use store::{DBColumn, Error as StoreError, SimpleStoreItem, Store};
+use types::{EthSpec, Hash256};
+
+/// 32-byte key for accessing the `PersistedPeerDB`.
+pub const PEER_DB_KEY: &str = "PERSISTEDPEERDBPERSISTEDPEERDBPE";
+
+/// Load the peers of the `PeerDB` persisted by the last run of the node, if any.
+pub fn load_peers<T: Store<E>, E: EthSpec>(store: Arc<T>) -> Option<PersistedPeers> {
+    let key = Hash256::from_slice(&PEER_DB_KEY.as_bytes());
+    match store.get(&key) {
+        Ok(Some(p)) => {
+            let p: PersistedPeerDB = p;
+            Some(p.peers)
+        }
+        _ => None,
+    }
+}
+
+/// Attempt to persist the peers of the `PeerDB` to `store`.
+pub fn persist_peers<T: Store<E>, E: EthSpec>(
+    store: Arc<T>,
+    peers: PersistedPeers,
+) -> Result<(), store::Error> {
+    let key = Hash256::from_slice(&PEER_DB_KEY.as_bytes());
+    store.put(&key, &PersistedPeerDB { peers })?;
+    Ok(())
+}
+
+/// Wrapper around the peers of the `PeerDB` for persistence to disk.
+pub struct PersistedPeerDB {
+    pub peers: PersistedPeers,
+}
+
+impl SimpleStoreItem for PersistedPeerDB {
+    fn db_column() -> DBColumn {
+        DBColumn::PeerDB
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.peers.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(PersistedPeerDB {
+            peers: PersistedPeers::from_ssz_bytes(bytes)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth2_libp2p::{PeerId, PersistedPeer};
+    use store::MemoryStore;
+    use types::MinimalEthSpec;
+
+    #[test]
+    fn test_persisted_peers() {
+        let store = Arc::new(MemoryStore::<MinimalEthSpec>::open());
+        let peers = PersistedPeers {
+            saved_at: 42,
+            peers: vec![PersistedPeer {
+                peer_id: PeerId::random().as_bytes().to_vec(),
+                reputation: 5,
+                banned: true,
+                since: 40,
+                agent_version: b"Lighthouse/v0.2.0/x86_64-linux".to_vec(),
+                protocol_version: b"lighthouse/libp2p".to_vec(),
+            }],
+        };
+
+        persist_peers(store.clone(), peers.clone()).unwrap();
+        assert_eq!(load_peers(store), Some(peers));
+    }
+}
@@ -14,11 +14,12 @@ use eth2_libp2p::{
         RPCCodedResponse, RPCError, RPCRequest, RPCResponse, RPCResponseErrorCode, RequestId,
         ResponseTermination,
     },
-    MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
+    types::GossipKind,
+    MessageAcceptance, MessageId, NetworkGlobals, PeerId, PubsubMessage, RPCEvent,
 };
 use futures::future::Future;
 use futures::stream::Stream;
-use processor::Processor;
+use processor::{block_error_acceptance, Processor};
 use slog::{debug, o, trace, warn};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -187,6 +188,14 @@ impl<T: BeaconChainTypes> Router<T> {
                     RPCError::ErrorResponse(RPCResponseErrorCode::ServerError),
                 );
             }
+            RPCCodedResponse::RateLimited(error) => {
+                warn!(self.log, "Peer rate limited our request"; "peer" => format!("{:?}", peer_id), "error" => error.as_string());
+                self.handle_rpc_error(
+                    peer_id,
+                    request_id,
+                    RPCError::ErrorResponse(RPCResponseErrorCode::RateLimited),
+                );
+            }
             RPCCodedResponse::Unknown(error) => {
                 warn!(self.log, "Unknown peer error"; "peer" => format!("{:?}", peer_id), "error" => error.as_string());
                 self.handle_rpc_error(
@@ -249,65 +258,101 @@ impl<T: BeaconChainTypes> Router<T> {
         peer_id: PeerId,
         gossip_message: PubsubMessage<T::EthSpec>,
     ) {
-        match gossip_message {
+        let kind = gossip_message.kind();
+        let acceptance = match gossip_message {
             // Attestations should never reach the router.
             PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => {
-                if let Some(gossip_verified) =
-                    self.processor.verify_aggregated_attestation_for_gossip(
-                        peer_id.clone(),
-                        *aggregate_and_proof.clone(),
-                    )
-                {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_aggregated_attestation(peer_id, gossip_verified);
+                match self.processor.verify_aggregated_attestation_for_gossip(
+                    peer_id.clone(),
+                    *aggregate_and_proof.clone(),
+                ) {
+                    Ok(gossip_verified) => {
+                        self.propagate_message(id, peer_id.clone());
+                        self.processor
+                            .import_aggregated_attestation(peer_id.clone(), gossip_verified);
+                        MessageAcceptance::Accept
+                    }
+                    Err(acceptance) => acceptance,
                 }
             }
             PubsubMessage::Attestation(subnet_attestation) => {
-                if let Some(gossip_verified) =
-                    self.processor.verify_unaggregated_attestation_for_gossip(
-                        peer_id.clone(),
-                        subnet_attestation.1.clone(),
-                    )
-                {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_unaggregated_attestation(peer_id, gossip_verified);
+                match self.processor.verify_unaggregated_attestation_for_gossip(
+                    peer_id.clone(),
+                    subnet_attestation.1.clone(),
+                ) {
+                    Ok(gossip_verified) => {
+                        self.propagate_message(id, peer_id.clone());
+                        self.processor
+                            .import_unaggregated_attestation(peer_id.clone(), gossip_verified);
+                        MessageAcceptance::Accept
+                    }
+                    Err(acceptance) => acceptance,
                 }
             }
             PubsubMessage::BeaconBlock(block) => {
                 match self.processor.should_forward_block(&peer_id, block) {
                     Ok(verified_block) => {
                         self.propagate_message(id, peer_id.clone());
-                        self.processor.on_block_gossip(peer_id, verified_block);
+                        self.processor
+                            .on_block_gossip(peer_id.clone(), verified_block);
+                        MessageAcceptance::Accept
                     }
-                    Err(BlockError::ParentUnknown { .. }) => {} // performing a parent lookup
+                    // performing a parent lookup
+                    Err(BlockError::ParentUnknown { .. }) => MessageAcceptance::Ignore,
                     Err(e) => {
-                        // performing a parent lookup
                         warn!(self.log, "Could not verify block for gossip";
                             "error" => format!("{:?}", e));
+                        block_error_acceptance(&e)
                     }
                 }
             }
-            PubsubMessage::VoluntaryExit(_exit) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle exits
-                debug!(self.log, "Received a voluntary exit"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::VoluntaryExit(exit) => {
+                debug!(self.log, "Received a voluntary exit"; "peer_id" => format!("{}", peer_id));
+                match self
+                    .processor
+                    .verify_voluntary_exit_for_gossip(&peer_id, *exit)
+                {
+                    Ok(verified_exit) => {
+                        self.propagate_message(id, peer_id.clone());
+                        self.processor.import_voluntary_exit(verified_exit);
+                        MessageAcceptance::Accept
+                    }
+                    Err(acceptance) => acceptance,
+                }
             }
-            PubsubMessage::ProposerSlashing(_proposer_slashing) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle proposer slashings
-                debug!(self.log, "Received a proposer slashing"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::ProposerSlashing(proposer_slashing) => {
+                debug!(self.log, "Received a proposer slashing"; "peer_id" => format!("{}", peer_id));
+                match self
+                    .processor
+                    .verify_proposer_slashing_for_gossip(&peer_id, *proposer_slashing)
+                {
+                    Ok(verified_proposer_slashing) => {
+                        self.propagate_message(id, peer_id.clone());
+                        self.processor
+                            .import_proposer_slashing(verified_proposer_slashing);
+                        MessageAcceptance::Accept
+                    }
+                    Err(acceptance) => acceptance,
+                }
             }
-            PubsubMessage::AttesterSlashing(_attester_slashing) => {
-                // TODO: Apply more sophisticated validation
-                self.propagate_message(id, peer_id.clone());
-                // TODO: Handle attester slashings
-                debug!(self.log, "Received an attester slashing"; "peer_id" => format!("{}", peer_id) );
+            PubsubMessage::AttesterSlashing(attester_slashing) => {
+                debug!(self.log, "Received an attester slashing"; "peer_id" => format!("{}", peer_id));
+                match self
+                    .processor
+                    .verify_attester_slashing_for_gossip(&peer_id, *attester_slashing)
+                {
+                    Ok(verified_attester_slashing) => {
+                        self.propagate_message(id, peer_id.clone());
+                        self.processor
+                            .import_attester_slashing(verified_attester_slashing);
+                        MessageAcceptance::Accept
+                    }
+                    Err(acceptance) => acceptance,
+                }
             }
-        }
+        };
+
+        self.report_gossip_validation(peer_id, kind, acceptance);
     }
 
     /// Informs the network service that the message should be forwarded to other peers.
@@ -324,4 +369,26 @@ impl<T: BeaconChainTypes> Router<T> {
                 )
             });
     }
+
+    /// Informs the network service of the result of validating a gossip message, to score the peer
+    /// that propagated it.
+    fn report_gossip_validation(
+        &mut self,
+        propagation_source: PeerId,
+        kind: GossipKind,
+        acceptance: MessageAcceptance,
+    ) {
+        self.network_send
+            .try_send(NetworkMessage::GossipValidation {
+                propagation_source,
+                kind,
+                acceptance,
+            })
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send gossip validation result to the network service"
+                )
+            });
+    }
 }
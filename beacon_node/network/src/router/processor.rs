@@ -5,19 +5,20 @@ use beacon_chain::{
         Error as AttnError, IntoForkChoiceVerifiedAttestation, VerifiedAggregatedAttestation,
         VerifiedUnaggregatedAttestation,
     },
-    BeaconChain, BeaconChainTypes, BlockError, BlockProcessingOutcome, GossipVerifiedBlock,
+    BeaconChain, BeaconChainError, BeaconChainTypes, BlockError, BlockProcessingOutcome,
+    GossipVerifiedBlock, ObservationOutcome, VerifiedOperation,
 };
 use eth2_libp2p::rpc::methods::*;
 use eth2_libp2p::rpc::{RPCCodedResponse, RPCEvent, RPCRequest, RPCResponse, RequestId};
-use eth2_libp2p::{NetworkGlobals, PeerId};
+use eth2_libp2p::{MessageAcceptance, NetworkGlobals, PeerId};
 use slog::{debug, error, o, trace, warn};
 use ssz::Encode;
 use std::sync::Arc;
 use store::Store;
 use tokio::sync::{mpsc, oneshot};
 use types::{
-    Attestation, ChainSpec, Epoch, EthSpec, Hash256, SignedAggregateAndProof, SignedBeaconBlock,
-    Slot,
+    Attestation, AttesterSlashing, ChainSpec, Epoch, EthSpec, Hash256, ProposerSlashing,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 
 //TODO: Rate limit requests
@@ -553,13 +554,16 @@ impl<T: BeaconChainTypes> Processor<T> {
 
     /// Handle an error whilst verifying an `Attestation` or `SignedAggregateAndProof` from the
     /// network.
+    ///
+    /// Returns whether the peer that sent the attestation is at fault (`Reject`), sent an
+    /// attestation which is already known (`Duplicate`) or is not necessarily at fault (`Ignore`).
     pub fn handle_attestation_verification_failure(
         &mut self,
         peer_id: PeerId,
         beacon_block_root: Hash256,
         attestation_type: &str,
         error: AttnError,
-    ) {
+    ) -> MessageAcceptance {
         debug!(
             self.log,
             "Invalid attestation from network";
@@ -579,6 +583,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message, _only_ if we trust our own clock.
                  */
+                MessageAcceptance::Ignore
             }
             AttnError::InvalidSelectionProof { .. } | AttnError::InvalidSignature => {
                 /*
@@ -586,6 +591,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::EmptyAggregationBitfield => {
                 /*
@@ -598,6 +604,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * https://github.com/ethereum/eth2.0-specs/pull/1732
                  */
+                MessageAcceptance::Ignore
             }
             AttnError::AggregatorPubkeyUnknown(_) => {
                 /*
@@ -613,6 +620,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::AggregatorNotInCommittee { .. } => {
                 /*
@@ -628,6 +636,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::AttestationAlreadyKnown { .. } => {
                 /*
@@ -636,6 +645,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer is not necessarily faulty.
                  */
+                MessageAcceptance::Duplicate
             }
             AttnError::AggregatorAlreadyKnown(_) => {
                 /*
@@ -644,6 +654,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer is not necessarily faulty.
                  */
+                MessageAcceptance::Duplicate
             }
             AttnError::PriorAttestationKnown { .. } => {
                 /*
@@ -651,6 +662,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer is not necessarily faulty.
                  */
+                MessageAcceptance::Duplicate
             }
             AttnError::ValidatorIndexTooHigh(_) => {
                 /*
@@ -659,6 +671,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::UnknownHeadBlock { beacon_block_root } => {
                 // Note: its a little bit unclear as to whether or not this block is unknown or
@@ -675,6 +688,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                 );
                 // we don't know the block, get the sync manager to handle the block lookup
                 self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
+                MessageAcceptance::Ignore
             }
             AttnError::UnknownTargetRoot(_) => {
                 /*
@@ -693,6 +707,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::BadTargetEpoch => {
                 /*
@@ -701,6 +716,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::NoCommitteeForSlotAndIndex { .. } => {
                 /*
@@ -708,6 +724,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::NotExactlyOneAggregationBitSet(_) => {
                 /*
@@ -715,6 +732,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::AttestsToFutureBlock { .. } => {
                 /*
@@ -722,6 +740,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::Invalid(_) => {
                 /*
@@ -729,6 +748,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                  *
                  * The peer has published an invalid consensus message.
                  */
+                MessageAcceptance::Reject
             }
             AttnError::BeaconChainError(e) => {
                 /*
@@ -744,6 +764,7 @@ impl<T: BeaconChainTypes> Processor<T> {
                     "peer_id" => format!("{:?}", peer_id),
                     "error" => format!("{:?}", e),
                 );
+                MessageAcceptance::Ignore
             }
         }
     }
@@ -752,7 +773,7 @@ impl<T: BeaconChainTypes> Processor<T> {
         &mut self,
         peer_id: PeerId,
         aggregate_and_proof: SignedAggregateAndProof<T::EthSpec>,
-    ) -> Option<VerifiedAggregatedAttestation<T>> {
+    ) -> Result<VerifiedAggregatedAttestation<T>, MessageAcceptance> {
         // This is provided to the error handling function to assist with debugging.
         let beacon_block_root = aggregate_and_proof.message.aggregate.data.beacon_block_root;
 
@@ -766,7 +787,6 @@ impl<T: BeaconChainTypes> Processor<T> {
                     e,
                 )
             })
-            .ok()
     }
 
     pub fn import_aggregated_attestation(
@@ -798,7 +818,7 @@ impl<T: BeaconChainTypes> Processor<T> {
         &mut self,
         peer_id: PeerId,
         unaggregated_attestation: Attestation<T::EthSpec>,
-    ) -> Option<VerifiedUnaggregatedAttestation<T>> {
+    ) -> Result<VerifiedUnaggregatedAttestation<T>, MessageAcceptance> {
         // This is provided to the error handling function to assist with debugging.
         let beacon_block_root = unaggregated_attestation.data.beacon_block_root;
 
@@ -812,7 +832,6 @@ impl<T: BeaconChainTypes> Processor<T> {
                     e,
                 )
             })
-            .ok()
     }
 
     pub fn import_unaggregated_attestation(
@@ -843,6 +862,184 @@ impl<T: BeaconChainTypes> Processor<T> {
         }
     }
 
+    /// Verify a gossip voluntary exit, returning it if it is valid and for a validator that has not
+    /// already been seen exiting. Only exits returned by this function should be propagated.
+    pub fn verify_voluntary_exit_for_gossip(
+        &mut self,
+        peer_id: &PeerId,
+        exit: SignedVoluntaryExit,
+    ) -> Result<VerifiedOperation<SignedVoluntaryExit>, MessageAcceptance> {
+        let validator_index = exit.message.validator_index;
+
+        match self.chain.verify_voluntary_exit_for_gossip(exit) {
+            Ok(ObservationOutcome::New(exit)) => Ok(exit),
+            Ok(ObservationOutcome::AlreadyKnown) => {
+                debug!(
+                    self.log,
+                    "Dropping exit for already exiting validator";
+                    "validator_index" => validator_index,
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Duplicate)
+            }
+            Err(BeaconChainError::ExitValidationError(e)) => {
+                debug!(
+                    self.log,
+                    "Dropping invalid voluntary exit";
+                    "reason" => format!("{:?}", e),
+                    "validator_index" => validator_index,
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Reject)
+            }
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Unable to verify voluntary exit";
+                    "error" => format!("{:?}", e),
+                    "validator_index" => validator_index,
+                );
+                Err(MessageAcceptance::Ignore)
+            }
+        }
+    }
+
+    /// Import a voluntary exit that has been verified for gossip into the operation pool.
+    pub fn import_voluntary_exit(&mut self, exit: VerifiedOperation<SignedVoluntaryExit>) {
+        let validator_index = exit.as_inner().message.validator_index;
+
+        self.chain.import_voluntary_exit(exit);
+
+        debug!(
+            self.log,
+            "Successfully imported voluntary exit";
+            "validator_index" => validator_index,
+        )
+    }
+
+    /// Verify a gossip proposer slashing, returning it if it is valid and for a proposer that has
+    /// not already been seen slashed. Only slashings returned by this function should be
+    /// propagated.
+    pub fn verify_proposer_slashing_for_gossip(
+        &mut self,
+        peer_id: &PeerId,
+        proposer_slashing: ProposerSlashing,
+    ) -> Result<VerifiedOperation<ProposerSlashing>, MessageAcceptance> {
+        let validator_index = proposer_slashing.signed_header_1.message.proposer_index;
+
+        match self
+            .chain
+            .verify_proposer_slashing_for_gossip(proposer_slashing)
+        {
+            Ok(ObservationOutcome::New(proposer_slashing)) => Ok(proposer_slashing),
+            Ok(ObservationOutcome::AlreadyKnown) => {
+                debug!(
+                    self.log,
+                    "Dropping proposer slashing for already slashed proposer";
+                    "validator_index" => validator_index,
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Duplicate)
+            }
+            Err(BeaconChainError::ProposerSlashingValidationError(e)) => {
+                debug!(
+                    self.log,
+                    "Dropping invalid proposer slashing";
+                    "reason" => format!("{:?}", e),
+                    "validator_index" => validator_index,
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Reject)
+            }
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Unable to verify proposer slashing";
+                    "error" => format!("{:?}", e),
+                    "validator_index" => validator_index,
+                );
+                Err(MessageAcceptance::Ignore)
+            }
+        }
+    }
+
+    /// Import a proposer slashing that has been verified for gossip into the operation pool.
+    pub fn import_proposer_slashing(
+        &mut self,
+        proposer_slashing: VerifiedOperation<ProposerSlashing>,
+    ) {
+        let validator_index = proposer_slashing
+            .as_inner()
+            .signed_header_1
+            .message
+            .proposer_index;
+
+        self.chain.import_proposer_slashing(proposer_slashing);
+
+        debug!(
+            self.log,
+            "Successfully imported proposer slashing";
+            "validator_index" => validator_index,
+        )
+    }
+
+    /// Verify a gossip attester slashing, returning it if it is valid and slashes at least one
+    /// validator that has not already been seen slashed. Only slashings returned by this function
+    /// should be propagated.
+    pub fn verify_attester_slashing_for_gossip(
+        &mut self,
+        peer_id: &PeerId,
+        attester_slashing: AttesterSlashing<T::EthSpec>,
+    ) -> Result<VerifiedOperation<AttesterSlashing<T::EthSpec>>, MessageAcceptance> {
+        match self
+            .chain
+            .verify_attester_slashing_for_gossip(attester_slashing)
+        {
+            Ok(ObservationOutcome::New(attester_slashing)) => Ok(attester_slashing),
+            Ok(ObservationOutcome::AlreadyKnown) => {
+                debug!(
+                    self.log,
+                    "Dropping attester slashing for already slashed attesters";
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Duplicate)
+            }
+            Err(BeaconChainError::AttesterSlashingValidationError(e)) => {
+                debug!(
+                    self.log,
+                    "Dropping invalid attester slashing";
+                    "reason" => format!("{:?}", e),
+                    "peer" => format!("{:?}", peer_id)
+                );
+                Err(MessageAcceptance::Reject)
+            }
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Unable to verify attester slashing";
+                    "error" => format!("{:?}", e),
+                );
+                Err(MessageAcceptance::Ignore)
+            }
+        }
+    }
+
+    /// Import an attester slashing that has been verified for gossip into the operation pool.
+    pub fn import_attester_slashing(
+        &mut self,
+        attester_slashing: VerifiedOperation<AttesterSlashing<T::EthSpec>>,
+    ) {
+        if let Err(e) = self.chain.import_attester_slashing(attester_slashing) {
+            error!(
+                self.log,
+                "Unable to import attester slashing";
+                "error" => format!("{:?}", e),
+            )
+        } else {
+            debug!(self.log, "Successfully imported attester slashing");
+        }
+    }
+
     /// Apply the attestation to fork choice, suppressing errors.
     ///
     /// We suppress the errors when adding an attestation to fork choice since the spec
@@ -889,6 +1086,35 @@ pub(crate) fn status_message<T: BeaconChainTypes>(
     })
 }
 
+/// Returns whether the peer that sent a gossip block which failed verification with `error` is at
+/// fault (`Reject`), sent a block which is already known (`Duplicate`) or is not necessarily at
+/// fault (`Ignore`).
+pub(crate) fn block_error_acceptance(error: &BlockError) -> MessageAcceptance {
+    match error {
+        // The block, or another block of its proposer for the slot, is already known.
+        BlockError::BlockIsAlreadyKnown | BlockError::RepeatProposal { .. } => {
+            MessageAcceptance::Duplicate
+        }
+        // The block may be valid, but we are not able to verify or import it at the moment.
+        BlockError::ParentUnknown(_)
+        | BlockError::FutureSlot { .. }
+        | BlockError::WouldRevertFinalizedSlot { .. }
+        | BlockError::BlockSlotLimitReached
+        | BlockError::UnknownValidator(_)
+        | BlockError::BeaconChainError(_) => MessageAcceptance::Ignore,
+        // The peer has published an invalid block.
+        BlockError::StateRootMismatch { .. }
+        | BlockError::GenesisBlock
+        | BlockError::IncorrectBlockProposer { .. }
+        | BlockError::ProposalSignatureInvalid
+        | BlockError::InvalidSignature
+        | BlockError::BlockIsNotLaterThanParent { .. }
+        | BlockError::NonLinearParentRoots
+        | BlockError::NonLinearSlots
+        | BlockError::PerBlockProcessingError(_) => MessageAcceptance::Reject,
+    }
+}
+
 /// Wraps a Network Channel to employ various RPC related network functionality for the
 /// processor.
 /// The Processor doesn't manage it's own request Id's and can therefore only send
@@ -1,23 +1,25 @@
 use crate::error;
 use crate::persisted_dht::{load_dht, persist_dht};
+use crate::persisted_peers::{load_peers, persist_peers};
 use crate::router::{Router, RouterMessage};
 use crate::{
     attestation_service::{AttServiceMessage, AttestationService},
     NetworkConfig,
 };
 use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2_libp2p::types::GossipKind;
 use eth2_libp2p::Service as LibP2PService;
 use eth2_libp2p::{rpc::RPCRequest, BehaviourEvent, Enr, MessageId, NetworkGlobals, PeerId, Swarm};
-use eth2_libp2p::{PubsubMessage, RPCEvent};
+use eth2_libp2p::{MessageAcceptance, PeerScoreParams, PubsubMessage, RPCEvent};
 use futures::prelude::*;
 use futures::Stream;
 use rest_types::ValidatorSubscription;
-use slog::{debug, error, info, trace};
+use slog::{debug, error, info, trace, warn};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::runtime::TaskExecutor;
 use tokio::sync::{mpsc, oneshot};
-use tokio::timer::Delay;
+use tokio::timer::{Delay, Interval};
 use types::EthSpec;
 
 mod tests;
@@ -38,7 +40,7 @@ pub struct NetworkService<T: BeaconChainTypes> {
     /// The sending channel for the network service to send messages to be routed throughout
     /// lighthouse.
     router_send: mpsc::UnboundedSender<RouterMessage<T::EthSpec>>,
-    /// A reference to lighthouse's database to persist the DHT.
+    /// A reference to lighthouse's database to persist the DHT and the `PeerDB`.
     store: Arc<T::Store>,
     /// A collection of global variables, accessible outside of the network service.
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
@@ -46,6 +48,9 @@ pub struct NetworkService<T: BeaconChainTypes> {
     initial_delay: Delay,
     /// A delay that expires when a new fork takes place.
     next_fork_update: Option<Delay>,
+    /// An interval at which the gossip scoring parameters are recomputed, if gossip scoring is
+    /// enabled.
+    gossip_score_update: Option<Interval>,
     /// The logger for the network service.
     log: slog::Logger,
     /// A probability of propagation.
@@ -76,14 +81,45 @@ impl<T: BeaconChainTypes> NetworkService<T> {
         // keep track of when our fork_id needs to be updated
         let next_fork_update = next_fork_delay(&beacon_chain);
 
+        // derive the gossip scoring parameters from the spec and the size of the validator set
+        let mut config = config.clone();
+        let mut gossip_score_update = None;
+        match gossip_score_params(&beacon_chain) {
+            Ok(params) => {
+                config.gossip_score_params = Some(params);
+                // the parameters depend on the size of the validator set, refresh them every epoch
+                let epoch_duration = Duration::from_millis(
+                    beacon_chain.spec.milliseconds_per_slot * T::EthSpec::slots_per_epoch(),
+                );
+                gossip_score_update = Some(Interval::new(
+                    Instant::now() + epoch_duration,
+                    epoch_duration,
+                ));
+            }
+            Err(e) => warn!(
+                network_log,
+                "Gossip scoring disabled";
+                "reason" => e,
+            ),
+        }
+
         // launch libp2p service
         let (network_globals, mut libp2p) =
-            LibP2PService::new(config, enr_fork_id, network_log.clone())?;
+            LibP2PService::new(&config, enr_fork_id, network_log.clone())?;
 
         for enr in load_dht::<T::Store, T::EthSpec>(store.clone()) {
             libp2p.swarm.add_enr(enr);
         }
 
+        if let Some(peers) = load_peers::<T::Store, T::EthSpec>(store.clone()) {
+            debug!(
+                network_log,
+                "Restoring persisted peers";
+                "Number of peers" => peers.peers.len(),
+            );
+            network_globals.peers.write().restore(peers);
+        }
+
         // A delay used to initialise code after the network has started
         // This is currently used to obtain the listening addresses from the libp2p service.
         let initial_delay = Delay::new(Instant::now() + Duration::from_secs(1));
@@ -114,6 +150,7 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             network_globals: network_globals.clone(),
             initial_delay,
             next_fork_update,
+            gossip_score_update,
             log: network_log,
             propagation_percentage,
         };
@@ -166,6 +203,25 @@ fn spawn_service<T: BeaconChainTypes>(
                         ),
                     }
 
+                    let peers = service.network_globals.peers.read().persist();
+                    debug!(
+                        log,
+                        "Persisting peers to store";
+                        "Number of peers" => peers.peers.len(),
+                    );
+
+                    match persist_peers::<T::Store, T::EthSpec>(service.store.clone(), peers) {
+                        Err(e) => error!(
+                            log,
+                            "Failed to persist peers on drop";
+                            "error" => format!("{:?}", e)
+                        ),
+                        Ok(_) => info!(
+                            log,
+                            "Saved peer reputations";
+                        ),
+                    }
+
                     info!(log.clone(), "Network service shutdown");
                     return Ok(Async::Ready(()));
         }
@@ -231,6 +287,21 @@ fn spawn_service<T: BeaconChainTypes>(
                             service.libp2p.swarm.publish(messages);
                         }
                     }
+                    NetworkMessage::GossipValidation {
+                        propagation_source,
+                        kind,
+                        acceptance,
+                    } => {
+                        if service.libp2p.swarm.report_gossip_validation(&propagation_source, &kind, acceptance) {
+                            debug!(log, "Disconnecting peer with a low gossip score";
+                                "peer_id" => propagation_source.to_string(),
+                            );
+                            service.libp2p.disconnect_and_ban_peer(
+                                propagation_source,
+                                std::time::Duration::from_secs(BAN_PEER_TIMEOUT),
+                            );
+                        }
+                    }
                     NetworkMessage::Disconnect { peer_id } => {
                         service.libp2p.disconnect_and_ban_peer(
                             peer_id,
@@ -339,6 +410,12 @@ fn spawn_service<T: BeaconChainTypes>(
                         }
                     }
                     BehaviourEvent::PeerSubscribed(_, _) => {}
+                    BehaviourEvent::LowGossipScore(peer_id) => {
+                        debug!(log, "Disconnecting peer with a low gossip score";
+                            "peer_id" => peer_id.to_string(),
+                        );
+                        peers_to_ban.push(peer_id);
+                    }
                 },
                 Ok(Async::Ready(None)) => unreachable!("Stream never ends"),
                 Ok(Async::NotReady) => break,
@@ -346,7 +423,7 @@ fn spawn_service<T: BeaconChainTypes>(
             }
         }
 
-        // ban and disconnect any peers that sent Goodbye requests
+        // ban and disconnect any peers that sent Goodbye requests or have a low gossip score
         while let Some(peer_id) = peers_to_ban.pop() {
             service.libp2p.disconnect_and_ban_peer(
                 peer_id.clone(),
@@ -364,6 +441,16 @@ fn spawn_service<T: BeaconChainTypes>(
             }
         }
 
+        // recompute the gossip scoring parameters as the validator set changes
+        if let Some(gossip_score_update) = service.gossip_score_update.as_mut() {
+            while let Ok(Async::Ready(Some(_))) = gossip_score_update.poll() {
+                match gossip_score_params(&service.beacon_chain) {
+                    Ok(params) => service.libp2p.swarm.update_gossip_score_params(params),
+                    Err(e) => warn!(log, "Unable to update the gossip scoring parameters"; "error" => e),
+                }
+            }
+        }
+
         Ok(Async::NotReady)
     })
 
@@ -384,6 +471,27 @@ fn next_fork_delay<T: BeaconChainTypes>(
     })
 }
 
+/// Returns the gossip scoring parameters for the current number of active validators at the head.
+fn gossip_score_params<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+) -> Result<PeerScoreParams, String> {
+    let head = beacon_chain
+        .head()
+        .map_err(|e| format!("Unable to read the head: {:?}", e))?;
+    let state = &head.beacon_state;
+    let epoch = state.current_epoch();
+    let active_validator_count = state
+        .validators
+        .iter()
+        .filter(|validator| validator.is_active_at(epoch))
+        .count();
+
+    Ok(PeerScoreParams::new::<T::EthSpec>(
+        &beacon_chain.spec,
+        active_validator_count,
+    ))
+}
+
 /// Types of messages that the network service can receive.
 #[derive(Debug)]
 pub enum NetworkMessage<T: EthSpec> {
@@ -400,6 +508,12 @@ pub enum NetworkMessage<T: EthSpec> {
         propagation_source: PeerId,
         message_id: MessageId,
     },
+    /// Scores the peer that propagated a gossipsub message by the result of validating it.
+    GossipValidation {
+        propagation_source: PeerId,
+        kind: GossipKind,
+        acceptance: MessageAcceptance,
+    },
     /// Disconnect and bans a peer id.
     Disconnect { peer_id: PeerId },
 }
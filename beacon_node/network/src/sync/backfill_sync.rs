@@ -0,0 +1,306 @@
+//! Downloads the blocks prior to the anchor of a node that was started from a weak subjectivity
+//! checkpoint.
+//!
+//! Blocks are requested with `BlocksByRange` in batches of `BACKFILL_BATCH_SLOTS`, working
+//! backwards from the oldest block in the database towards genesis. Each batch is verified by the
+//! `BeaconChain` against the parent root of the oldest known block, so no state is replayed.
+//!
+//! Backfilling runs alongside forward sync. Only one batch is downloaded or processed at a time
+//! and requests are spaced by at least `BACKFILL_REQUEST_INTERVAL`, so that backfill never competes
+//! with range sync or gossip for bandwidth. The current progress is published in the
+//! `NetworkGlobals` as a `BackFillState`.
+
+use super::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
+use super::manager::SyncMessage;
+use super::network_context::SyncNetworkContext;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2_libp2p::rpc::methods::BlocksByRangeRequest;
+use eth2_libp2p::rpc::RequestId;
+use eth2_libp2p::types::BackFillState;
+use eth2_libp2p::{NetworkGlobals, PeerId};
+use futures::prelude::*;
+use rand::seq::IteratorRandom;
+use slog::{debug, info, warn};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use store::Store;
+use tokio::sync::mpsc;
+use tokio::timer::Delay;
+use types::{EthSpec, SignedBeaconBlock, Slot};
+
+/// The number of slots requested in each backfill batch.
+const BACKFILL_BATCH_SLOTS: u64 = 64;
+/// The minimum time between two backfill requests.
+const BACKFILL_REQUEST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A `BlocksByRange` request which is currently being downloaded.
+struct BackFillRequest<T: EthSpec> {
+    request_id: RequestId,
+    peer_id: PeerId,
+    start_slot: Slot,
+    downloaded_blocks: Vec<SignedBeaconBlock<T>>,
+}
+
+/// A downloaded batch which has been sent to the block processor.
+struct ProcessingBatch {
+    peer_id: PeerId,
+    start_slot: Slot,
+}
+
+pub struct BackFillSync<T: BeaconChainTypes> {
+    /// The beacon chain for processing.
+    chain: Arc<BeaconChain<T>>,
+    /// A reference to the network globals and peer-db.
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    /// The exclusive upper bound of the next batch to request. Batches that contain only skip
+    /// slots do not move the anchor, so the position of the download is tracked separately.
+    next_end_slot: Option<Slot>,
+    /// The batch that is currently being downloaded, if any.
+    current_request: Option<BackFillRequest<T::EthSpec>>,
+    /// The batch that is currently being processed, if any.
+    processing_batch: Option<ProcessingBatch>,
+    /// Fires when the next batch may be requested.
+    next_request_delay: Delay,
+    /// The sync manager channel, allowing the block processor thread to callback the sync task
+    /// once complete.
+    sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// The syncing logger.
+    log: slog::Logger,
+}
+
+impl<T: BeaconChainTypes> BackFillSync<T> {
+    pub fn new(
+        chain: Arc<BeaconChain<T>>,
+        network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+        sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+        log: slog::Logger,
+    ) -> Self {
+        Self {
+            chain,
+            network_globals,
+            next_end_slot: None,
+            current_request: None,
+            processing_batch: None,
+            next_request_delay: Delay::new(Instant::now()),
+            sync_send,
+            log,
+        }
+    }
+
+    /// Returns `true` if `request_id` belongs to a backfill request.
+    pub fn is_backfill_request(&self, request_id: RequestId) -> bool {
+        self.current_request
+            .as_ref()
+            .map_or(false, |request| request.request_id == request_id)
+    }
+
+    /// A block (or stream termination) has been received for the current backfill request.
+    ///
+    /// Once the stream terminates, the downloaded blocks are sent to the block processor.
+    pub fn blocks_by_range_response(
+        &mut self,
+        request_id: RequestId,
+        beacon_block: Option<SignedBeaconBlock<T::EthSpec>>,
+    ) {
+        if !self.is_backfill_request(request_id) {
+            return;
+        }
+
+        if let Some(block) = beacon_block {
+            if let Some(request) = self.current_request.as_mut() {
+                request.downloaded_blocks.push(block);
+            }
+            return;
+        }
+
+        if let Some(request) = self.current_request.take() {
+            debug!(
+                self.log,
+                "Processing backfill batch";
+                "start_slot" => request.start_slot,
+                "blocks" => request.downloaded_blocks.len(),
+            );
+
+            self.processing_batch = Some(ProcessingBatch {
+                peer_id: request.peer_id,
+                start_slot: request.start_slot,
+            });
+            spawn_block_processor(
+                Arc::downgrade(&self.chain),
+                ProcessId::BackFillBatch(request.start_slot),
+                request.downloaded_blocks,
+                self.sync_send.clone(),
+                self.log.clone(),
+            );
+        }
+    }
+
+    /// The block processor has finished with the batch starting at `start_slot`.
+    ///
+    /// If the batch was invalid, the peer that sent it is downvoted and the download restarts from
+    /// the oldest block in the database.
+    pub fn handle_block_process_result(
+        &mut self,
+        network: &mut SyncNetworkContext<T::EthSpec>,
+        start_slot: Slot,
+        result: BatchProcessResult,
+    ) {
+        let batch = match self.processing_batch.take() {
+            Some(batch) if batch.start_slot == start_slot => batch,
+            other => {
+                self.processing_batch = other;
+                return;
+            }
+        };
+
+        match result {
+            BatchProcessResult::Success => {
+                self.next_end_slot = Some(batch.start_slot);
+            }
+            BatchProcessResult::Failed | BatchProcessResult::Partial => {
+                warn!(
+                    self.log,
+                    "Invalid backfill batch";
+                    "start_slot" => batch.start_slot,
+                    "peer" => format!("{}", batch.peer_id),
+                );
+                self.next_end_slot = None;
+                network.downvote_peer(batch.peer_id);
+            }
+        }
+
+        self.delay_next_request();
+    }
+
+    /// A backfill request has failed, it will be retried once the rate limit allows it.
+    pub fn inject_error(&mut self, peer_id: PeerId, request_id: RequestId) {
+        if self.is_backfill_request(request_id) {
+            debug!(
+                self.log,
+                "Backfill request failed";
+                "peer" => format!("{}", peer_id),
+            );
+            self.current_request = None;
+            self.delay_next_request();
+        }
+    }
+
+    /// A peer has disconnected. If it was serving the current request, the request is dropped.
+    pub fn peer_disconnect(&mut self, peer_id: &PeerId) {
+        if self
+            .current_request
+            .as_ref()
+            .map_or(false, |request| request.peer_id == *peer_id)
+        {
+            self.current_request = None;
+            self.delay_next_request();
+        }
+    }
+
+    /// Requests the next batch of historical blocks, if one is required and the rate limit allows
+    /// it.
+    ///
+    /// This must be called each time the sync manager is polled, so that the rate limit timer
+    /// can wake the task.
+    pub fn poll(&mut self, network: &mut SyncNetworkContext<T::EthSpec>) {
+        let anchor_info = match self.chain.store.get_anchor_info() {
+            Some(anchor_info) => anchor_info,
+            None => return,
+        };
+
+        if anchor_info.backfill_complete() {
+            self.set_state(BackFillState::Completed);
+            return;
+        }
+
+        if self.current_request.is_some() || self.processing_batch.is_some() {
+            return;
+        }
+
+        match self.next_request_delay.poll() {
+            Ok(Async::NotReady) => return,
+            Ok(Async::Ready(())) => {}
+            Err(e) => warn!(
+                self.log,
+                "Backfill rate limit timer failed";
+                "error" => format!("{:?}", e),
+            ),
+        }
+
+        let peer_id = match self
+            .network_globals
+            .peers
+            .read()
+            .synced_peers()
+            .choose(&mut rand::thread_rng())
+        {
+            Some(peer_id) => peer_id.clone(),
+            None => {
+                self.set_state(BackFillState::Stalled {
+                    oldest_block_slot: anchor_info.oldest_block_slot,
+                    anchor_slot: anchor_info.anchor_slot,
+                });
+                self.delay_next_request();
+                return;
+            }
+        };
+
+        self.set_state(BackFillState::Syncing {
+            oldest_block_slot: anchor_info.oldest_block_slot,
+            anchor_slot: anchor_info.anchor_slot,
+        });
+
+        // If genesis has been reached without completing the chain, then a peer withheld blocks.
+        // Restart from the oldest block that has been verified.
+        let end_slot = match self.next_end_slot {
+            Some(end_slot) if end_slot > 0 && end_slot <= anchor_info.oldest_block_slot => end_slot,
+            _ => anchor_info.oldest_block_slot,
+        };
+        let start_slot = end_slot.saturating_sub(BACKFILL_BATCH_SLOTS);
+
+        let request = BlocksByRangeRequest {
+            start_slot: start_slot.as_u64(),
+            count: (end_slot - start_slot).as_u64(),
+            step: 1,
+        };
+
+        match network.blocks_by_range_request(peer_id.clone(), request) {
+            Ok(request_id) => {
+                self.current_request = Some(BackFillRequest {
+                    request_id,
+                    peer_id,
+                    start_slot,
+                    downloaded_blocks: vec![],
+                });
+            }
+            Err(e) => {
+                debug!(self.log, "Unable to send backfill request"; "error" => e);
+                self.delay_next_request();
+            }
+        }
+    }
+
+    /// Prevents another batch from being requested until `BACKFILL_REQUEST_INTERVAL` has elapsed.
+    fn delay_next_request(&mut self) {
+        self.next_request_delay
+            .reset(Instant::now() + BACKFILL_REQUEST_INTERVAL);
+        // Poll the timer so that the task is woken once it fires.
+        let _ = self.next_request_delay.poll();
+    }
+
+    /// Updates the `BackFillState` in the network globals, logging any change.
+    fn set_state(&self, new_state: BackFillState) {
+        let mut state = self.network_globals.backfill_state.write();
+        if *state != new_state {
+            if std::mem::discriminant(&*state) != std::mem::discriminant(&new_state) {
+                info!(
+                    self.log,
+                    "Backfill state updated";
+                    "old_state" => format!("{}", *state),
+                    "new_state" => format!("{}", new_state),
+                );
+            }
+            *state = new_state;
+        }
+    }
+}
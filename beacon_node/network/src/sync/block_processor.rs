@@ -6,7 +6,7 @@ use eth2_libp2p::PeerId;
 use slog::{debug, error, trace, warn};
 use std::sync::{Arc, Weak};
 use tokio::sync::mpsc;
-use types::SignedBeaconBlock;
+use types::{SignedBeaconBlock, Slot};
 
 /// Id associated to a block processing request, either a batch or a single block.
 #[derive(Clone, Debug, PartialEq)]
@@ -15,6 +15,8 @@ pub enum ProcessId {
     RangeBatchId(ChainId, BatchId),
     /// Processing Id of the parent lookup of a block
     ParentLookup(PeerId),
+    /// Processing Id of a batch of historical blocks, identified by its start slot.
+    BackFillBatch(Slot),
 }
 
 /// The result of a block processing request.
@@ -29,7 +31,8 @@ pub enum BatchProcessResult {
     Partial,
 }
 
-/// Spawns a thread handling the block processing of a request: range syncing or parent lookup.
+/// Spawns a thread handling the block processing of a request: range syncing, parent lookup or
+/// backfill.
 pub fn spawn_block_processor<T: BeaconChainTypes>(
     chain: Weak<BeaconChain<T>>,
     process_id: ProcessId,
@@ -98,6 +101,33 @@ pub fn spawn_block_processor<T: BeaconChainTypes>(
                     }
                 }
             }
+            // this a batch of historical blocks from the backfill sync
+            ProcessId::BackFillBatch(start_slot) => {
+                let chain = match chain.upgrade() {
+                    Some(chain) => chain,
+                    None => return,
+                };
+                let result = match chain.import_historical_block_batch(downloaded_blocks) {
+                    Ok(imported_blocks) => {
+                        debug!(log, "Backfill batch processed";
+                            "start_slot" => start_slot, "imported_blocks" => imported_blocks);
+                        BatchProcessResult::Success
+                    }
+                    Err(e) => {
+                        debug!(log, "Backfill batch processing failed";
+                            "start_slot" => start_slot, "error" => format!("{:?}", e));
+                        BatchProcessResult::Failed
+                    }
+                };
+
+                let msg = SyncMessage::BackFillBatchProcessed { start_slot, result };
+                sync_send.try_send(msg).unwrap_or_else(|_| {
+                    debug!(
+                        log,
+                        "Block processor could not inform backfill sync result. Likely shutting down."
+                    );
+                });
+            }
         }
     });
 }
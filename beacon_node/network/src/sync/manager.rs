@@ -32,7 +32,13 @@
 //! To keep the logic maintained to the syncing thread (and manage the request_ids), when a block needs to be searched for (i.e
 //! if an attestation references an unknown block) this manager can search for the block and
 //! subsequently search for parents if needed.
+//!
+//! ## Backfill
+//!
+//! A node started from a weak subjectivity checkpoint downloads the blocks prior to its anchor in
+//! the background. See `BackFillSync` for further details.
 
+use super::backfill_sync::BackFillSync;
 use super::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
 use super::network_context::SyncNetworkContext;
 use super::peer_sync_info::{PeerSyncInfo, PeerSyncType};
@@ -106,6 +112,12 @@ pub enum SyncMessage<T: EthSpec> {
 
     /// A parent lookup has failed for a block given by this `peer_id`.
     ParentLookupFailed(PeerId),
+
+    /// A batch of historical blocks has been processed by the block processor thread.
+    BackFillBatchProcessed {
+        start_slot: Slot,
+        result: BatchProcessResult,
+    },
 }
 
 /// Maintains a sequential list of parents to lookup and the lookup's current state.
@@ -145,6 +157,9 @@ pub struct SyncManager<T: BeaconChainTypes> {
     /// The object handling long-range batch load-balanced syncing.
     range_sync: RangeSync<T>,
 
+    /// The object handling the download of blocks prior to a weak subjectivity anchor.
+    backfill_sync: BackFillSync<T>,
+
     /// A collection of parent block lookups.
     parent_queue: SmallVec<[ParentRequests<T::EthSpec>; 3]>,
 
@@ -204,6 +219,12 @@ pub fn spawn<T: BeaconChainTypes>(
             sync_send.clone(),
             log.clone(),
         ),
+        backfill_sync: BackFillSync::new(
+            beacon_chain.clone(),
+            network_globals.clone(),
+            sync_send.clone(),
+            log.clone(),
+        ),
         network: SyncNetworkContext::new(network_send, log.clone()),
         chain: beacon_chain,
         network_globals,
@@ -529,6 +550,11 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             return;
         }
 
+        if self.backfill_sync.is_backfill_request(request_id) {
+            self.backfill_sync.inject_error(peer_id, request_id);
+            return;
+        }
+
         // otherwise, this is a range sync issue, notify the range sync
         self.range_sync
             .inject_error(&mut self.network, peer_id.clone(), request_id);
@@ -536,6 +562,7 @@ impl<T: BeaconChainTypes> SyncManager<T> {
 
     fn peer_disconnect(&mut self, peer_id: &PeerId) {
         self.range_sync.peer_disconnect(&mut self.network, peer_id);
+        self.backfill_sync.peer_disconnect(peer_id);
         self.update_sync_state();
     }
 
@@ -749,12 +776,17 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                         request_id,
                         beacon_block,
                     } => {
-                        self.range_sync.blocks_by_range_response(
-                            &mut self.network,
-                            peer_id,
-                            request_id,
-                            beacon_block.map(|b| *b),
-                        );
+                        if self.backfill_sync.is_backfill_request(request_id) {
+                            self.backfill_sync
+                                .blocks_by_range_response(request_id, beacon_block.map(|b| *b));
+                        } else {
+                            self.range_sync.blocks_by_range_response(
+                                &mut self.network,
+                                peer_id,
+                                request_id,
+                                beacon_block.map(|b| *b),
+                            );
+                        }
                     }
                     SyncMessage::BlocksByRootResponse {
                         peer_id,
@@ -792,6 +824,13 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
                     SyncMessage::ParentLookupFailed(peer_id) => {
                         self.network.downvote_peer(peer_id);
                     }
+                    SyncMessage::BackFillBatchProcessed { start_slot, result } => {
+                        self.backfill_sync.handle_block_process_result(
+                            &mut self.network,
+                            start_slot,
+                            result,
+                        );
+                    }
                 },
                 Ok(Async::NotReady) => break,
                 Ok(Async::Ready(None)) => {
@@ -803,6 +842,9 @@ impl<T: BeaconChainTypes> Future for SyncManager<T> {
             }
         }
 
+        // request historical blocks, if required
+        self.backfill_sync.poll(&mut self.network);
+
         Ok(Async::NotReady)
     }
 }
@@ -1,8 +1,10 @@
 use crate::helpers::*;
 use crate::response_builder::ResponseBuilder;
 use crate::validator::get_state_for_epoch;
-use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
-use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use crate::{ApiError, ApiResult, BoxFut, NetworkChannel, UrlQuery};
+use beacon_chain::{
+    BeaconChain, BeaconChainError, BeaconChainTypes, ObservationOutcome, StateSkipConfig,
+};
 use futures::{Future, Stream};
 use hyper::{Body, Request};
 use rest_types::{
@@ -13,7 +15,7 @@ use std::sync::Arc;
 use store::Store;
 use types::{
     AttesterSlashing, BeaconState, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
-    RelativeEpoch, Slot,
+    RelativeEpoch, SignedVoluntaryExit, Slot,
 };
 
 /// HTTP handler to return a `BeaconBlock` at a given `root` or `slot`.
@@ -532,3 +534,68 @@ pub fn attester_slashing<T: BeaconChainTypes>(
 
     Box::new(future)
 }
+
+/// HTTP handler to verify a `SignedVoluntaryExit`, add it to the operation pool and publish it on
+/// gossip.
+///
+/// If an exit for the validator is already known, the pooled exit is published again, so that a
+/// resubmission can recover from an earlier failure to publish.
+pub fn voluntary_exit<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+) -> BoxFut {
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<SignedVoluntaryExit>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into SignedVoluntaryExit: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(move |exit| {
+            let validator_index = exit.message.validator_index;
+
+            match beacon_chain.verify_voluntary_exit_for_gossip(exit) {
+                Ok(ObservationOutcome::New(exit)) => {
+                    let signed_exit = exit.as_inner().clone();
+
+                    // Add the exit to the op pool first, so that it is included in our own blocks
+                    // even if it cannot be published.
+                    beacon_chain.import_voluntary_exit(exit);
+
+                    publish_voluntary_exit_to_network::<T>(network_chan, signed_exit)
+                }
+                // The known exit may never have been published (e.g., the network channel was
+                // full), so publish the pooled exit again.
+                Ok(ObservationOutcome::AlreadyKnown) => {
+                    match beacon_chain.op_pool.get_voluntary_exit(validator_index) {
+                        Some(pooled_exit) => {
+                            publish_voluntary_exit_to_network::<T>(network_chan, pooled_exit)
+                        }
+                        None => Err(ApiError::BadRequest(format!(
+                            "An exit for validator {} is already known but is no longer pooled, \
+                             it was not published",
+                            validator_index
+                        ))),
+                    }
+                }
+                Err(BeaconChainError::ExitValidationError(e)) => Err(ApiError::BadRequest(
+                    format!("Invalid voluntary exit: {:?}", e),
+                )),
+                Err(e) => Err(ApiError::ServerError(format!(
+                    "Unable to verify voluntary exit: {:?}",
+                    e
+                ))),
+            }
+        })
+        .and_then(|_| response_builder?.body(&true));
+
+    Box::new(future)
+}
@@ -0,0 +1,694 @@
+//! Handlers for the standard Eth2 beacon node API, served under `/eth/v1`.
+//!
+//! Unlike the Lighthouse-specific handlers, states and blocks are identified by a `StateId` or
+//! `BlockId` in the path and each response body is wrapped in a `GenericResponse`.
+
+use crate::helpers::*;
+use crate::response_builder::ResponseBuilder;
+use crate::validator::get_state_for_epoch;
+use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
+use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use eth2_config::Eth2Config;
+use eth2_libp2p::{NetworkGlobals, PeerId};
+use futures::{Future, Stream};
+use hyper::{Body, Request, Response, StatusCode};
+use rest_types::{
+    AttesterData, BlockHeaderData, BlockId, CommitteeData, DepositContractData,
+    FinalityCheckpointsData, GenericResponse, GenesisData, IdentityData, PeerData, PeerState,
+    ProposerData, RootData, StateId, SyncingData, ValidatorData, ValidatorStatus, VersionData,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use types::{
+    BeaconState, Epoch, EthSpec, Hash256, RelativeEpoch, SignedBeaconBlock,
+    SignedBeaconBlockHeader, Slot, YamlConfig,
+};
+
+/// Parse a `StateId`.
+///
+/// E.g., `"head"`, `"finalized"`, `"1234"` or `"0x0000...0000"`
+pub fn parse_state_id(string: &str) -> Result<StateId, ApiError> {
+    string.parse().map_err(ApiError::BadRequest)
+}
+
+/// Parse a `BlockId`.
+///
+/// E.g., `"head"`, `"genesis"`, `"1234"` or `"0x0000...0000"`
+pub fn parse_block_id(string: &str) -> Result<BlockId, ApiError> {
+    string.parse().map_err(ApiError::BadRequest)
+}
+
+/// Returns a response with `data` wrapped in a `GenericResponse`.
+fn respond<S: Serialize>(req: &Request<Body>, data: S) -> ApiResult {
+    ResponseBuilder::new(req)?.body_no_ssz(&GenericResponse::from(data))
+}
+
+/// Returns the slot of the state identified by `state_id`, which must not be a root.
+fn state_id_slot<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    state_id: StateId,
+) -> Result<Slot, ApiError> {
+    let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+    match state_id {
+        StateId::Head => Ok(beacon_chain.head_info()?.slot),
+        StateId::Genesis => Ok(Slot::new(0)),
+        StateId::Finalized => Ok(beacon_chain
+            .head_info()?
+            .finalized_checkpoint
+            .epoch
+            .start_slot(slots_per_epoch)),
+        StateId::Justified => Ok(beacon_chain
+            .head_info()?
+            .current_justified_checkpoint
+            .epoch
+            .start_slot(slots_per_epoch)),
+        StateId::Slot(slot) => Ok(slot),
+        StateId::Root(root) => Err(ApiError::ServerError(format!(
+            "State root {:?} does not identify a slot",
+            root
+        ))),
+    }
+}
+
+/// Returns the `BeaconState` identified by `state_id`, and its root.
+fn state_by_id<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    state_id: StateId,
+) -> Result<(Hash256, BeaconState<T::EthSpec>), ApiError> {
+    match state_id {
+        StateId::Head => {
+            let head = beacon_chain.head()?;
+            Ok((head.beacon_state_root, head.beacon_state))
+        }
+        StateId::Root(root) => beacon_chain
+            .get_state(&root, None)?
+            .map(|state| (root, state))
+            .ok_or_else(|| ApiError::NotFound(format!("No state exists with root: {:?}", root))),
+        other => state_at_slot(beacon_chain, state_id_slot(beacon_chain, other)?),
+    }
+}
+
+/// Returns the root of the `BeaconState` identified by `state_id`, without loading the state
+/// unless it is identified by its root.
+fn state_root_by_id<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    state_id: StateId,
+) -> Result<Hash256, ApiError> {
+    match state_id {
+        StateId::Head => Ok(beacon_chain.head_info()?.state_root),
+        StateId::Root(_) => state_by_id(beacon_chain, state_id).map(|(root, _state)| root),
+        other => state_root_at_slot(
+            beacon_chain,
+            state_id_slot(beacon_chain, other)?,
+            StateSkipConfig::WithStateRoots,
+        ),
+    }
+}
+
+/// Returns the `SignedBeaconBlock` identified by `block_id`, and its root.
+///
+/// A `BlockId::Slot` only identifies a block if the slot was not skipped.
+fn block_by_id<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    block_id: BlockId,
+) -> Result<(Hash256, SignedBeaconBlock<T::EthSpec>), ApiError> {
+    let root = match block_id {
+        BlockId::Head => {
+            let head = beacon_chain.head()?;
+            return Ok((head.beacon_block_root, head.beacon_block));
+        }
+        BlockId::Genesis => beacon_chain.genesis_block_root,
+        BlockId::Finalized => {
+            let root = beacon_chain.head_info()?.finalized_checkpoint.root;
+
+            // The finalized checkpoint has a zero root until the first epoch is finalized.
+            if root == Hash256::zero() {
+                beacon_chain.genesis_block_root
+            } else {
+                root
+            }
+        }
+        BlockId::Slot(slot) => block_root_at_slot(beacon_chain, slot)?.ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Unable to find SignedBeaconBlock for slot {}",
+                slot
+            ))
+        })?,
+        BlockId::Root(root) => root,
+    };
+
+    let block = beacon_chain.store.get_block(&root)?.ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Unable to find SignedBeaconBlock for root {:?}",
+            root
+        ))
+    })?;
+
+    match block_id {
+        BlockId::Slot(slot) if block.slot() != slot => Err(ApiError::NotFound(format!(
+            "No SignedBeaconBlock at skipped slot {}",
+            slot
+        ))),
+        _ => Ok((root, block)),
+    }
+}
+
+/// HTTP handler for `/eth/v1/beacon/genesis`.
+pub fn get_genesis<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let head_info = beacon_chain.head_info()?;
+
+    respond(
+        &req,
+        GenesisData {
+            genesis_time: head_info.genesis_time,
+            genesis_validators_root: head_info.genesis_validators_root,
+            genesis_fork_version: beacon_chain.spec.genesis_fork_version,
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/root`.
+pub fn get_state_root<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let root = state_root_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    respond(&req, RootData { root })
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/fork`.
+pub fn get_state_fork<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, state) = state_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    respond(&req, state.fork)
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/finality_checkpoints`.
+pub fn get_state_finality_checkpoints<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, state) = state_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    respond(
+        &req,
+        FinalityCheckpointsData {
+            previous_justified: state.previous_justified_checkpoint,
+            current_justified: state.current_justified_checkpoint,
+            finalized: state.finalized_checkpoint,
+        },
+    )
+}
+
+/// Returns the `ValidatorData` of the validator at `validator_index` in `state`.
+fn validator_data<E: EthSpec>(
+    state: &BeaconState<E>,
+    validator_index: usize,
+    far_future_epoch: Epoch,
+) -> Result<ValidatorData, ApiError> {
+    let validator = state.validators.get(validator_index).ok_or_else(|| {
+        ApiError::NotFound(format!("Unknown validator index: {}", validator_index))
+    })?;
+    let balance = state.balances.get(validator_index).ok_or_else(|| {
+        ApiError::ServerError(format!("Invalid balances index: {}", validator_index))
+    })?;
+
+    Ok(ValidatorData {
+        index: validator_index as u64,
+        balance: *balance,
+        status: ValidatorStatus::from_validator(validator, state.current_epoch(), far_future_epoch),
+        validator: validator.clone(),
+    })
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/validators`.
+pub fn get_state_validators<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, state) = state_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    let validators = (0..state.validators.len())
+        .map(|i| validator_data(&state, i, beacon_chain.spec.far_future_epoch))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    respond(&req, validators)
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/validators/{validator_id}`.
+///
+/// The `validator_id` is either the index of the validator or its `0x` prefixed public key.
+pub fn get_state_validator<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+    validator_id: &str,
+) -> ApiResult {
+    let (_root, mut state) = state_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    let validator_index = if validator_id.starts_with("0x") {
+        let pubkey = parse_pubkey_bytes(validator_id)?;
+        state.update_pubkey_cache()?;
+        state
+            .get_validator_index(&pubkey)?
+            .ok_or_else(|| ApiError::NotFound(format!("Unknown validator: {}", validator_id)))?
+    } else {
+        validator_id.parse::<usize>().map_err(|e| {
+            ApiError::BadRequest(format!("Unable to parse validator index: {:?}", e))
+        })?
+    };
+
+    respond(
+        &req,
+        validator_data(&state, validator_index, beacon_chain.spec.far_future_epoch)?,
+    )
+}
+
+/// HTTP handler for `/eth/v1/beacon/states/{state_id}/committees`.
+///
+/// Returns the committees of the `epoch` query parameter (or the epoch of the state), optionally
+/// filtered by the `index` and `slot` query parameters.
+pub fn get_state_committees<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+    let (_root, mut state) = state_by_id(&beacon_chain, parse_state_id(state_id)?)?;
+
+    let epoch = match query.first_of_opt(&["epoch"]) {
+        Some((_key, value)) => parse_epoch(&value)?,
+        None => state.current_epoch(),
+    };
+    let index = match query.first_of_opt(&["index"]) {
+        Some((_key, value)) => Some(parse_committee_index(&value)?),
+        None => None,
+    };
+    let slot = match query.first_of_opt(&["slot"]) {
+        Some((_key, value)) => Some(parse_slot(&value)?),
+        None => None,
+    };
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch).map_err(|e| {
+        ApiError::BadRequest(format!(
+            "Epoch {} is not within one epoch of the state: {:?}",
+            epoch, e
+        ))
+    })?;
+
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    let committees = state
+        .get_beacon_committees_at_epoch(relative_epoch)
+        .map_err(|e| ApiError::ServerError(format!("Unable to get all committees: {:?}", e)))?
+        .into_iter()
+        .filter(|c| index.map_or(true, |index| c.index == index))
+        .filter(|c| slot.map_or(true, |slot| c.slot == slot))
+        .map(|c| CommitteeData {
+            index: c.index,
+            slot: c.slot,
+            validators: c.committee.iter().map(|i| *i as u64).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    respond(&req, committees)
+}
+
+/// HTTP handler for `/eth/v1/beacon/headers/{block_id}`.
+pub fn get_block_header<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (root, block) = block_by_id(&beacon_chain, parse_block_id(block_id)?)?;
+    let canonical = block_root_at_slot(&beacon_chain, block.slot())? == Some(root);
+
+    respond(
+        &req,
+        BlockHeaderData {
+            root,
+            canonical,
+            header: SignedBeaconBlockHeader {
+                message: block.message.block_header(),
+                signature: block.signature,
+            },
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/beacon/blocks/{block_id}`.
+pub fn get_block<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (_root, block) = block_by_id(&beacon_chain, parse_block_id(block_id)?)?;
+
+    respond(&req, block)
+}
+
+/// HTTP handler for `/eth/v1/beacon/blocks/{block_id}/root`.
+pub fn get_block_root<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (root, _block) = block_by_id(&beacon_chain, parse_block_id(block_id)?)?;
+
+    respond(&req, RootData { root })
+}
+
+/// HTTP handler for `/eth/v1/beacon/blocks/{block_id}/attestations`.
+pub fn get_block_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (_root, block) = block_by_id(&beacon_chain, parse_block_id(block_id)?)?;
+
+    respond(&req, block.message.body.attestations)
+}
+
+/// HTTP handler for `/eth/v1/node/version`.
+pub fn get_node_version(req: Request<Body>) -> ApiResult {
+    respond(
+        &req,
+        VersionData {
+            version: version::version(),
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/node/syncing`.
+pub fn get_node_syncing<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    let head_slot = beacon_chain.head_info()?.slot;
+    let current_slot = beacon_chain
+        .slot()
+        .map_err(|_| ApiError::ServerError("Unable to read slot clock".to_string()))?;
+
+    respond(
+        &req,
+        SyncingData {
+            is_syncing: network_globals.is_syncing(),
+            head_slot,
+            sync_distance: current_slot.saturating_sub(head_slot),
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/node/identity`.
+pub fn get_node_identity<T: BeaconChainTypes>(
+    req: Request<Body>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    respond(
+        &req,
+        IdentityData {
+            peer_id: network_globals.local_peer_id().to_base58(),
+            enr: network_globals.local_enr().to_base64(),
+            p2p_addresses: network_globals
+                .listen_multiaddrs()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/node/peers`.
+pub fn get_node_peers<T: BeaconChainTypes>(
+    req: Request<Body>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    let peers = network_globals
+        .peers
+        .read()
+        .peers()
+        .map(|(peer_id, peer_info)| {
+            let state = if peer_info.connection_status.is_connected() {
+                PeerState::Connected
+            } else if peer_info.connection_status.is_dialing() {
+                PeerState::Connecting
+            } else {
+                PeerState::Disconnected
+            };
+
+            PeerData {
+                peer_id: PeerId::to_string(peer_id),
+                state,
+                last_seen_p2p_address: peer_info
+                    .listening_addresses
+                    .first()
+                    .map(ToString::to_string),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    respond(&req, peers)
+}
+
+/// HTTP handler for `/eth/v1/node/health`.
+///
+/// Returns an empty 200 response if the node is synced and a 206 response if it is syncing.
+pub fn get_node_health<T: BeaconChainTypes>(
+    _req: Request<Body>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    let status = if network_globals.is_syncing() {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
+
+/// HTTP handler for `/eth/v1/config/spec`.
+pub fn get_config_spec<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    respond(
+        &req,
+        YamlConfig::from_spec::<T::EthSpec>(&beacon_chain.spec),
+    )
+}
+
+/// HTTP handler for `/eth/v1/config/fork_schedule`.
+///
+/// Only the fork of the head state is known to the beacon node.
+pub fn get_config_fork_schedule<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    respond(&req, vec![beacon_chain.head_info()?.fork])
+}
+
+/// HTTP handler for `/eth/v1/config/deposit_contract`.
+pub fn get_config_deposit_contract(req: Request<Body>, eth2_config: Arc<Eth2Config>) -> ApiResult {
+    respond(
+        &req,
+        DepositContractData {
+            chain_id: eth2_config.deposit_chain_id,
+            address: eth2_config.deposit_contract_address,
+        },
+    )
+}
+
+/// HTTP handler for `/eth/v1/validator/duties/proposer/{epoch}`.
+pub fn get_proposer_duties<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    epoch: &str,
+) -> ApiResult {
+    let epoch = parse_epoch(epoch)?;
+    let mut state = get_state_for_epoch(&beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
+        .map_err(|_| ApiError::ServerError(String::from("Loaded state is in the wrong epoch")))?;
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    let duties = epoch
+        .slot_iter(T::EthSpec::slots_per_epoch())
+        .map(|slot| {
+            let validator_index = state
+                .get_beacon_proposer_index(slot, &beacon_chain.spec)
+                .map_err(|e| {
+                    ApiError::ServerError(format!("Unable to get proposer index: {:?}", e))
+                })?;
+            let validator = state.validators.get(validator_index).ok_or_else(|| {
+                ApiError::ServerError(format!("Invalid validator index: {}", validator_index))
+            })?;
+
+            Ok(ProposerData {
+                pubkey: validator.pubkey.clone(),
+                validator_index: validator_index as u64,
+                slot,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    respond(&req, duties)
+}
+
+/// HTTP handler for `/eth/v1/validator/duties/attester/{epoch}`.
+///
+/// Accepts a JSON list of validator indices. Validators without a duty in `epoch` are omitted
+/// from the response.
+pub fn post_attester_duties<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    epoch: &str,
+) -> BoxFut {
+    try_future!(check_content_type_for_json(&req));
+    let epoch = try_future!(parse_epoch(epoch));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<Vec<u64>>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into validator indices: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(move |indices| attester_duties(&beacon_chain, epoch, indices))
+        .and_then(|duties| response_builder?.body_no_ssz(&GenericResponse::from(duties)));
+
+    Box::new(future)
+}
+
+/// Returns the attestation duties of the validators at `indices` in `epoch`.
+fn attester_duties<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    epoch: Epoch,
+    indices: Vec<u64>,
+) -> Result<Vec<AttesterData>, ApiError> {
+    let mut state = get_state_for_epoch(beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
+        .map_err(|_| ApiError::ServerError(String::from("Loaded state is in the wrong epoch")))?;
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    indices
+        .into_iter()
+        .filter_map(|validator_index| {
+            let validator = match state.validators.get(validator_index as usize) {
+                Some(validator) => validator,
+                None => {
+                    return Some(Err(ApiError::BadRequest(format!(
+                        "Unknown validator index: {}",
+                        validator_index
+                    ))))
+                }
+            };
+
+            state
+                .get_attestation_duties(validator_index as usize, relative_epoch)
+                .map_err(|e| {
+                    ApiError::ServerError(format!("Unable to obtain attestation duties: {:?}", e))
+                })
+                .and_then(|duty_opt| {
+                    duty_opt
+                        .map(|duty| {
+                            Ok(AttesterData {
+                                pubkey: validator.pubkey.clone(),
+                                validator_index,
+                                committee_index: duty.index,
+                                committee_length: duty.committee_len as u64,
+                                committees_at_slot: state.get_committee_count_at_slot(duty.slot)?,
+                                validator_committee_index: duty.committee_position as u64,
+                                slot: duty.slot,
+                            })
+                        })
+                        .transpose()
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// HTTP handler for `/eth/v1/validator/blocks/{slot}`.
+pub fn get_validator_block<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    slot: &str,
+) -> ApiResult {
+    let slot = parse_slot(slot)?;
+    let randao_reveal = UrlQuery::from_request(&req)?.randao_reveal()?;
+
+    let (block, _state) = beacon_chain
+        .produce_block(randao_reveal, slot)
+        .map_err(|e| {
+            ApiError::ServerError(format!(
+                "Beacon node is not able to produce a block: {:?}",
+                e
+            ))
+        })?;
+
+    respond(&req, block)
+}
+
+/// HTTP handler for `/eth/v1/validator/attestation_data`.
+pub fn get_attestation_data<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let index = query.committee_index()?;
+
+    let attestation = beacon_chain
+        .produce_unaggregated_attestation(slot, index)
+        .map_err(|e| ApiError::BadRequest(format!("Unable to produce attestation: {:?}", e)))?;
+
+    respond(&req, attestation.data)
+}
+
+/// HTTP handler for `/eth/v1/validator/aggregate_attestation`.
+pub fn get_aggregate_attestation<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let (_key, value) = query.first_of(&["attestation_data_root"])?;
+    let attestation_data_root = parse_root(&value)?;
+
+    let attestation = beacon_chain
+        .get_aggregated_attestation_by_slot_and_root(slot, attestation_data_root)
+        .ok_or_else(|| ApiError::NotFound("No matching aggregate attestation is known".into()))?;
+
+    respond(&req, attestation)
+}
@@ -0,0 +1,115 @@
+use crate::{ApiError, ApiResult, UrlQuery};
+use beacon_chain::events::{EventKind, ServerSentEventHandler};
+use futures::Stream;
+use hyper::{Body, Request, Response};
+use rest_types::{
+    EventTopic, ServerSentEvent, SseBlock, SseChainReorg, SseFinalizedCheckpoint, SseHead,
+};
+use std::sync::Arc;
+use types::EthSpec;
+
+/// The number of unread events buffered for each subscriber. Events are dropped for a subscriber
+/// whose buffer is full.
+const EVENT_BUFFER_SIZE: usize = 256;
+
+/// HTTP handler to stream chain events as server-sent events.
+///
+/// The `topics` query parameter is a comma-separated list of topics and may be repeated.
+pub fn get_events<E: EthSpec>(
+    req: Request<Body>,
+    sse_handler: Option<Arc<ServerSentEventHandler<E>>>,
+) -> ApiResult {
+    let sse_handler = sse_handler.ok_or_else(|| {
+        ApiError::NotImplemented("The event stream is not enabled on this node".to_string())
+    })?;
+
+    let topics = UrlQuery::from_request(&req)?
+        .all_of("topics")?
+        .iter()
+        .flat_map(|topics| topics.split(','))
+        .map(|topic| topic.parse::<EventTopic>().map_err(ApiError::BadRequest))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if topics.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one topic must be supplied with the topics query parameter".to_string(),
+        ));
+    }
+
+    let stream = sse_handler
+        .subscribe(EVENT_BUFFER_SIZE)
+        .filter_map(move |kind| {
+            server_sent_event(&kind)
+                .filter(|event| topics.contains(&event.topic()))
+                .and_then(|event| {
+                    // Serialization of these types cannot fail, skip the event if it somehow does.
+                    serde_json::to_string(&event)
+                        .map(|data| format!("event: {}\ndata: {}\n\n", event.topic(), data))
+                        .ok()
+                })
+        })
+        // The receiver never returns an error.
+        .map_err(|()| "Event stream failed");
+
+    Response::builder()
+        .header("content-type", "text/event-stream")
+        .header("cache-control", "no-cache")
+        .body(Body::wrap_stream(stream))
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
+
+/// Returns the event to send to subscribers for `kind`, if any.
+fn server_sent_event<E: EthSpec>(kind: &EventKind<E>) -> Option<ServerSentEvent<E>> {
+    match kind {
+        EventKind::BeaconHeadChanged {
+            current_head_beacon_block_root,
+            slot,
+            current_head_state_root,
+            epoch_transition,
+            ..
+        } => Some(ServerSentEvent::Head(SseHead {
+            slot: *slot,
+            block: *current_head_beacon_block_root,
+            state: *current_head_state_root,
+            epoch_transition: *epoch_transition,
+        })),
+        EventKind::BeaconChainReorg {
+            slot,
+            depth,
+            old_head_beacon_block_root,
+            new_head_beacon_block_root,
+            old_head_state_root,
+            new_head_state_root,
+            epoch,
+        } => Some(ServerSentEvent::ChainReorg(SseChainReorg {
+            slot: *slot,
+            depth: *depth,
+            old_head_block: *old_head_beacon_block_root,
+            new_head_block: *new_head_beacon_block_root,
+            old_head_state: *old_head_state_root,
+            new_head_state: *new_head_state_root,
+            epoch: *epoch,
+        })),
+        EventKind::BeaconFinalization {
+            epoch,
+            root,
+            state_root,
+        } => Some(ServerSentEvent::FinalizedCheckpoint(
+            SseFinalizedCheckpoint {
+                block: *root,
+                state: *state_root,
+                epoch: *epoch,
+            },
+        )),
+        EventKind::BeaconBlockImported { block_root, block } => {
+            Some(ServerSentEvent::Block(SseBlock {
+                slot: block.slot(),
+                block: *block_root,
+            }))
+        }
+        EventKind::BeaconAttestationImported { attestation } => {
+            Some(ServerSentEvent::Attestation(attestation.clone()))
+        }
+        EventKind::BeaconBlockRejected { .. } | EventKind::BeaconAttestationRejected { .. } => None,
+    }
+}
@@ -9,6 +9,8 @@ mod beacon;
 pub mod config;
 mod consensus;
 mod error;
+mod eth_v1;
+mod events;
 mod helpers;
 mod lighthouse;
 mod metrics;
@@ -20,6 +22,7 @@ mod spec;
 mod url_query;
 mod validator;
 
+use beacon_chain::events::ServerSentEventHandler;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use client_network::NetworkMessage;
 pub use config::ApiEncodingFormat;
@@ -57,6 +60,7 @@ pub fn start_server<T: BeaconChainTypes>(
     executor: &TaskExecutor,
     beacon_chain: Arc<BeaconChain<T>>,
     network_info: NetworkInfo<T>,
+    sse_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     db_path: PathBuf,
     freezer_db_path: PathBuf,
     eth2_config: Eth2Config,
@@ -72,6 +76,7 @@ pub fn start_server<T: BeaconChainTypes>(
         let eth2_config = eth2_config.clone();
         let network_globals = network_info.network_globals.clone();
         let network_channel = network_info.network_chan.clone();
+        let sse_handler = sse_handler.clone();
         let db_path = db_path.clone();
         let freezer_db_path = freezer_db_path.clone();
 
@@ -81,6 +86,7 @@ pub fn start_server<T: BeaconChainTypes>(
                 beacon_chain.clone(),
                 network_globals.clone(),
                 network_channel.clone(),
+                sse_handler.clone(),
                 eth2_config.clone(),
                 log.clone(),
                 db_path.clone(),
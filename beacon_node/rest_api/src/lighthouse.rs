@@ -2,18 +2,22 @@
 
 use crate::response_builder::ResponseBuilder;
 use crate::ApiResult;
+use eth2_libp2p::types::{BackFillState, SyncState};
 use eth2_libp2p::{NetworkGlobals, PeerInfo};
 use hyper::{Body, Request};
 use serde::Serialize;
 use std::sync::Arc;
 use types::EthSpec;
 
-/// The syncing state of the beacon node.
+/// The syncing state of the beacon node, including the download of historical blocks.
 pub fn syncing<T: EthSpec>(
     req: Request<Body>,
     network_globals: Arc<NetworkGlobals<T>>,
 ) -> ApiResult {
-    ResponseBuilder::new(&req)?.body_no_ssz(&network_globals.sync_state())
+    ResponseBuilder::new(&req)?.body_no_ssz(&Syncing {
+        sync_state: network_globals.sync_state(),
+        backfill_state: network_globals.backfill_state(),
+    })
 }
 
 /// Returns all known peers and corresponding information
@@ -56,3 +60,12 @@ struct Peer<T: EthSpec> {
     /// The PeerInfo associated with the peer.
     peer_info: PeerInfo<T>,
 }
+
+/// Information returned by `syncing`.
+#[derive(Clone, Debug, Serialize)]
+struct Syncing {
+    /// The state of the forward (range) sync.
+    sync_state: SyncState,
+    /// The state of the download of blocks prior to the state the node was started from.
+    backfill_state: BackFillState,
+}
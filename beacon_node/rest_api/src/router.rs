@@ -1,7 +1,8 @@
 use crate::{
-    advanced, beacon, consensus, error::ApiError, helpers, lighthouse, metrics, network, node,
-    spec, validator, BoxFut, NetworkChannel,
+    advanced, beacon, consensus, error::ApiError, eth_v1, events, helpers, lighthouse, metrics,
+    network, node, spec, validator, BoxFut, NetworkChannel,
 };
+use beacon_chain::events::ServerSentEventHandler;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
@@ -13,6 +14,9 @@ use std::sync::Arc;
 use std::time::Instant;
 use types::Slot;
 
+/// The path prefix of the standard Eth2 API.
+const ETH_V1_PREFIX: &str = "/eth/v1/";
+
 fn into_boxfut<F: IntoFuture + 'static>(item: F) -> BoxFut
 where
     F: IntoFuture<Item = Response<Body>, Error = ApiError>,
@@ -28,6 +32,7 @@ pub fn route<T: BeaconChainTypes>(
     beacon_chain: Arc<BeaconChain<T>>,
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     network_channel: NetworkChannel<T::EthSpec>,
+    sse_handler: Option<Arc<ServerSentEventHandler<T::EthSpec>>>,
     eth2_config: Arc<Eth2Config>,
     local_log: slog::Logger,
     db_path: PathBuf,
@@ -42,6 +47,21 @@ pub fn route<T: BeaconChainTypes>(
     let log = local_log.clone();
     let request_result: Box<dyn Future<Item = Response<_>, Error = _> + Send> =
         match (req.method(), path.as_ref()) {
+            // Methods for the standard Eth2 API
+            (_, path) if path.starts_with(ETH_V1_PREFIX) => route_eth_v1::<T>(
+                req,
+                beacon_chain,
+                network_globals,
+                network_channel,
+                eth2_config,
+                log,
+            ),
+
+            // Server-sent events
+            (&Method::GET, "/events") => {
+                into_boxfut(events::get_events::<T::EthSpec>(req, sse_handler))
+            }
+
             // Methods for Client
             (&Method::GET, "/node/version") => into_boxfut(node::get_version(req)),
             (&Method::GET, "/node/syncing") => {
@@ -126,6 +146,11 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/beacon/attester_slashing") => {
                 into_boxfut(beacon::attester_slashing::<T>(req, beacon_chain))
             }
+            (&Method::POST, "/beacon/voluntary_exit") => into_boxfut(beacon::voluntary_exit::<T>(
+                req,
+                beacon_chain,
+                network_channel,
+            )),
 
             // Methods for Validator
             (&Method::POST, "/validator/duties") => {
@@ -138,6 +163,9 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/validator/subscribe") => {
                 validator::post_validator_subscriptions::<T>(req, network_channel)
             }
+            (&Method::POST, "/validator/liveness") => {
+                validator::post_validator_liveness::<T>(req, beacon_chain)
+            }
             (&Method::GET, "/validator/duties/all") => {
                 into_boxfut(validator::get_all_validator_duties::<T>(req, beacon_chain))
             }
@@ -259,3 +287,125 @@ pub fn route<T: BeaconChainTypes>(
         }
     })
 }
+
+/// Routes a request for the standard Eth2 API, where objects are identified by segments of the
+/// path (e.g., `/eth/v1/beacon/states/{state_id}/root`).
+fn route_eth_v1<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    network_channel: NetworkChannel<T::EthSpec>,
+    eth2_config: Arc<Eth2Config>,
+    log: slog::Logger,
+) -> BoxFut {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let segments = path
+        .trim_start_matches(ETH_V1_PREFIX)
+        .split('/')
+        .collect::<Vec<_>>();
+
+    match (&method, segments.as_slice()) {
+        // Methods for Beacon Node
+        (&Method::GET, ["beacon", "genesis"]) => {
+            into_boxfut(eth_v1::get_genesis::<T>(req, beacon_chain))
+        }
+        (&Method::GET, ["beacon", "states", state_id, "root"]) => {
+            into_boxfut(eth_v1::get_state_root::<T>(req, beacon_chain, state_id))
+        }
+        (&Method::GET, ["beacon", "states", state_id, "fork"]) => {
+            into_boxfut(eth_v1::get_state_fork::<T>(req, beacon_chain, state_id))
+        }
+        (&Method::GET, ["beacon", "states", state_id, "finality_checkpoints"]) => into_boxfut(
+            eth_v1::get_state_finality_checkpoints::<T>(req, beacon_chain, state_id),
+        ),
+        (&Method::GET, ["beacon", "states", state_id, "validators"]) => into_boxfut(
+            eth_v1::get_state_validators::<T>(req, beacon_chain, state_id),
+        ),
+        (&Method::GET, ["beacon", "states", state_id, "validators", validator_id]) => into_boxfut(
+            eth_v1::get_state_validator::<T>(req, beacon_chain, state_id, validator_id),
+        ),
+        (&Method::GET, ["beacon", "states", state_id, "committees"]) => into_boxfut(
+            eth_v1::get_state_committees::<T>(req, beacon_chain, state_id),
+        ),
+        (&Method::GET, ["beacon", "headers", block_id]) => {
+            into_boxfut(eth_v1::get_block_header::<T>(req, beacon_chain, block_id))
+        }
+        (&Method::POST, ["beacon", "blocks"]) => {
+            validator::publish_beacon_block::<T>(req, beacon_chain, network_channel, log)
+        }
+        (&Method::GET, ["beacon", "blocks", block_id]) => {
+            into_boxfut(eth_v1::get_block::<T>(req, beacon_chain, block_id))
+        }
+        (&Method::GET, ["beacon", "blocks", block_id, "root"]) => {
+            into_boxfut(eth_v1::get_block_root::<T>(req, beacon_chain, block_id))
+        }
+        (&Method::GET, ["beacon", "blocks", block_id, "attestations"]) => into_boxfut(
+            eth_v1::get_block_attestations::<T>(req, beacon_chain, block_id),
+        ),
+        (&Method::POST, ["beacon", "pool", "attestations"]) => {
+            validator::publish_attestations::<T>(req, beacon_chain, network_channel, log)
+        }
+        (&Method::POST, ["beacon", "pool", "attester_slashings"]) => {
+            beacon::attester_slashing::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "proposer_slashings"]) => {
+            beacon::proposer_slashing::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "voluntary_exits"]) => {
+            beacon::voluntary_exit::<T>(req, beacon_chain, network_channel)
+        }
+
+        // Methods for Client
+        (&Method::GET, ["node", "version"]) => into_boxfut(eth_v1::get_node_version(req)),
+        (&Method::GET, ["node", "syncing"]) => into_boxfut(eth_v1::get_node_syncing::<T>(
+            req,
+            beacon_chain,
+            network_globals,
+        )),
+        (&Method::GET, ["node", "identity"]) => {
+            into_boxfut(eth_v1::get_node_identity::<T>(req, network_globals))
+        }
+        (&Method::GET, ["node", "peers"]) => {
+            into_boxfut(eth_v1::get_node_peers::<T>(req, network_globals))
+        }
+        (&Method::GET, ["node", "health"]) => {
+            into_boxfut(eth_v1::get_node_health::<T>(req, network_globals))
+        }
+
+        // Methods for bootstrap and checking configuration
+        (&Method::GET, ["config", "spec"]) => {
+            into_boxfut(eth_v1::get_config_spec::<T>(req, beacon_chain))
+        }
+        (&Method::GET, ["config", "fork_schedule"]) => {
+            into_boxfut(eth_v1::get_config_fork_schedule::<T>(req, beacon_chain))
+        }
+        (&Method::GET, ["config", "deposit_contract"]) => {
+            into_boxfut(eth_v1::get_config_deposit_contract(req, eth2_config))
+        }
+
+        // Methods for Validator
+        (&Method::GET, ["validator", "duties", "proposer", epoch]) => {
+            into_boxfut(eth_v1::get_proposer_duties::<T>(req, beacon_chain, epoch))
+        }
+        (&Method::POST, ["validator", "duties", "attester", epoch]) => {
+            eth_v1::post_attester_duties::<T>(req, beacon_chain, epoch)
+        }
+        (&Method::GET, ["validator", "blocks", slot]) => {
+            into_boxfut(eth_v1::get_validator_block::<T>(req, beacon_chain, slot))
+        }
+        (&Method::GET, ["validator", "attestation_data"]) => {
+            into_boxfut(eth_v1::get_attestation_data::<T>(req, beacon_chain))
+        }
+        (&Method::GET, ["validator", "aggregate_attestation"]) => {
+            into_boxfut(eth_v1::get_aggregate_attestation::<T>(req, beacon_chain))
+        }
+        (&Method::POST, ["validator", "aggregate_and_proofs"]) => {
+            validator::publish_aggregate_and_proofs::<T>(req, beacon_chain, network_channel, log)
+        }
+
+        _ => Box::new(futures::future::err(ApiError::NotFound(
+            "Request path and/or method not found.".to_owned(),
+        ))),
+    }
+}
@@ -11,8 +11,12 @@ use futures::{Future, Stream};
 use hyper::{Body, Request};
 use network::NetworkMessage;
 use rayon::prelude::*;
-use rest_types::{ValidatorDutiesRequest, ValidatorDutyBytes, ValidatorSubscription};
+use rest_types::{
+    LivenessRequest, LivenessResponse, ValidatorDutiesRequest, ValidatorDutyBytes,
+    ValidatorSubscription,
+};
 use slog::{error, info, trace, warn, Logger};
+use state_processing::per_epoch_processing::ValidatorStatuses;
 use std::sync::Arc;
 use types::beacon_state::EthSpec;
 use types::{
@@ -134,6 +138,95 @@ pub fn get_active_validator_duties<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body_no_ssz(&duties)
 }
 
+/// HTTP Handler to determine whether a set of validators have been seen signing messages during
+/// the current or previous epoch.
+///
+/// A validator is considered live if the beacon node has seen an attestation, aggregate or block
+/// from it on gossip, or if one of its attestations is included in the head state.
+pub fn post_validator_liveness<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> BoxFut {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<LivenessRequest>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into LivenessRequest: {:?}",
+                    e
+                ))
+            })
+        })
+        .and_then(move |request| {
+            let epoch = request.epoch;
+            let current_epoch = beacon_chain
+                .epoch()
+                .map_err(|e| ApiError::ServerError(format!("Unable to read slot: {:?}", e)))?;
+
+            if epoch > current_epoch || epoch + 1 < current_epoch {
+                return Err(ApiError::BadRequest(format!(
+                    "Liveness is only known for the current epoch ({}) and the previous epoch",
+                    current_epoch
+                )));
+            }
+
+            let mut state = beacon_chain.head()?.beacon_state;
+            let spec = &beacon_chain.spec;
+
+            let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
+            validator_statuses.process_attestations(&state, spec)?;
+
+            state.update_pubkey_cache().map_err(|e| {
+                ApiError::ServerError(format!("Unable to build pubkey cache: {:?}", e))
+            })?;
+
+            let state_epoch = state.current_epoch();
+
+            request
+                .pubkeys
+                .into_iter()
+                .map(|pubkey| {
+                    let validator_index = state.get_validator_index(&pubkey).map_err(|e| {
+                        ApiError::ServerError(format!("Unable to read pubkey cache: {:?}", e))
+                    })?;
+
+                    let is_live = validator_index.map_or(false, |validator_index| {
+                        let included_in_state = validator_statuses
+                            .statuses
+                            .get(validator_index)
+                            .map_or(false, |status| {
+                                if state_epoch == epoch {
+                                    status.is_current_epoch_attester
+                                } else if state_epoch == epoch + 1 {
+                                    status.is_previous_epoch_attester
+                                } else {
+                                    false
+                                }
+                            });
+
+                        included_in_state
+                            || beacon_chain.validator_seen_at_epoch(validator_index, epoch)
+                    });
+
+                    Ok(LivenessResponse {
+                        epoch,
+                        pubkey,
+                        validator_index: validator_index.map(|i| i as u64),
+                        is_live,
+                    })
+                })
+                .collect::<Result<Vec<_>, ApiError>>()
+        })
+        .and_then(|liveness| response_builder?.body_no_ssz(&liveness));
+
+    Box::new(future)
+}
+
 /// Helper function to return the state that can be used to determine the duties for some `epoch`.
 pub fn get_state_for_epoch<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
@@ -1,14 +1,17 @@
 #![cfg(test)]
 
 use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use futures::Stream;
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorResponse,
+    BlockId, Committee, CommitteeData, Error as RemoteError, EventTopic, HeadBeaconBlock,
+    PersistedOperationPool, PublishStatus, ServerSentEvent, SseBlock, SseHead, StateId,
+    ValidatorResponse,
 };
-use rest_types::ValidatorDutyBytes;
+use rest_types::{ValidatorDutyBytes, ValidatorStatus};
 use std::convert::TryInto;
 use std::sync::Arc;
 use types::{
@@ -16,9 +19,9 @@ use types::{
         build_double_vote_attester_slashing, build_proposer_slashing,
         generate_deterministic_keypair, AttesterSlashingTestTask, ProposerSlashingTestTask,
     },
-    BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, MinimalEthSpec, PublicKey,
-    RelativeEpoch, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
-    Validator,
+    BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, Hash256, MinimalEthSpec,
+    PublicKey, RelativeEpoch, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot,
+    Slot, Validator, VoluntaryExit,
 };
 use version;
 
@@ -311,6 +314,64 @@ fn validator_duties() {
     // chain, yet.
 }
 
+#[test]
+fn validator_liveness() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    let epoch = beacon_chain.epoch().expect("should get epoch");
+
+    let mut validators = beacon_chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .validators
+        .iter()
+        .map(|v| (&v.pubkey).try_into().expect("pubkey should be valid"))
+        .collect::<Vec<_>>();
+    let num_validators = validators.len();
+    validators.push(generate_deterministic_keypair(num_validators).pk);
+
+    let liveness = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .get_liveness(epoch, &validators),
+        )
+        .expect("should fetch liveness from http api");
+
+    assert_eq!(liveness.len(), validators.len());
+    for (i, response) in liveness.iter().enumerate() {
+        assert_eq!(response.epoch, epoch);
+        assert!(!response.is_live, "no validator has signed a message");
+        if i < num_validators {
+            assert_eq!(response.validator_index, Some(i as u64));
+        } else {
+            assert_eq!(
+                response.validator_index, None,
+                "validator should be unknown"
+            );
+        }
+    }
+
+    let result = env.runtime().block_on(
+        remote_node
+            .http
+            .validator()
+            .get_liveness(epoch + 2, &validators),
+    );
+    assert!(result.is_err(), "should reject a future epoch");
+}
+
 fn check_duties<T: BeaconChainTypes>(
     duties: Vec<ValidatorDutyBytes>,
     epoch: Epoch,
@@ -1135,3 +1196,516 @@ fn attester_slashing() {
     assert_eq!(attester_slashings.len(), 1);
     assert_eq!(attester_slashing, attester_slashings[0]);
 }
+
+#[test]
+fn voluntary_exit() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+    let spec = &chain.spec;
+
+    let validator_index = 0;
+    let keypair = generate_deterministic_keypair(validator_index);
+
+    // The validator has not been active for `SHARD_COMMITTEE_PERIOD`, so it cannot exit yet.
+    let exit = VoluntaryExit {
+        epoch: state.current_epoch(),
+        validator_index: validator_index as u64,
+    }
+    .sign(
+        &keypair.sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit));
+    assert!(result.is_err(), "should reject exit for new validator");
+
+    // An exit signed by the wrong key is rejected.
+    let exit = VoluntaryExit {
+        epoch: state.current_epoch(),
+        validator_index: validator_index as u64,
+    }
+    .sign(
+        &generate_deterministic_keypair(validator_index + 1).sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit));
+    assert!(result.is_err(), "should reject exit with invalid signature");
+
+    assert_eq!(
+        chain.op_pool.num_voluntary_exits(),
+        0,
+        "no exits should be pooled"
+    );
+}
+
+#[test]
+fn eth_v1_beacon_states() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let head = chain.head().expect("should get head");
+    let state = head.beacon_state;
+    let state_root = head.beacon_state_root;
+
+    let genesis = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_genesis())
+        .expect("should fetch genesis from http api");
+    assert_eq!(genesis.genesis_time, state.genesis_time);
+    assert_eq!(
+        genesis.genesis_validators_root,
+        state.genesis_validators_root
+    );
+    assert_eq!(
+        genesis.genesis_fork_version,
+        chain.spec.genesis_fork_version
+    );
+
+    // The chain has not progressed past genesis, so every state id refers to the genesis state.
+    let state_ids = vec![
+        StateId::Head,
+        StateId::Genesis,
+        StateId::Finalized,
+        StateId::Justified,
+        StateId::Slot(Slot::new(0)),
+        StateId::Root(state_root),
+    ];
+
+    for state_id in state_ids {
+        let root = env
+            .runtime()
+            .block_on(remote_node.http.eth_v1().get_state_root(state_id))
+            .expect("should fetch state root from http api");
+        assert_eq!(root, state_root, "state root for {} should match", state_id);
+
+        let fork = env
+            .runtime()
+            .block_on(remote_node.http.eth_v1().get_state_fork(state_id))
+            .expect("should fetch fork from http api");
+        assert_eq!(fork, state.fork, "fork for {} should match", state_id);
+
+        let checkpoints = env
+            .runtime()
+            .block_on(
+                remote_node
+                    .http
+                    .eth_v1()
+                    .get_state_finality_checkpoints(state_id),
+            )
+            .expect("should fetch finality checkpoints from http api");
+        assert_eq!(
+            checkpoints.previous_justified,
+            state.previous_justified_checkpoint
+        );
+        assert_eq!(
+            checkpoints.current_justified,
+            state.current_justified_checkpoint
+        );
+        assert_eq!(checkpoints.finalized, state.finalized_checkpoint);
+    }
+
+    let unknown_root = env.runtime().block_on(
+        remote_node
+            .http
+            .eth_v1()
+            .get_state_fork(StateId::Root(Hash256::from_low_u64_be(42))),
+    );
+    assert!(unknown_root.is_err(), "should not find an unknown state");
+}
+
+#[test]
+fn eth_v1_beacon_validators() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain.head().expect("should get head").beacon_state;
+
+    let validators = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .eth_v1()
+                .get_state_validators(StateId::Head),
+        )
+        .expect("should fetch validators from http api");
+
+    assert_eq!(validators.len(), state.validators.len());
+    for (i, data) in validators.iter().enumerate() {
+        assert_eq!(data.index, i as u64);
+        assert_eq!(data.balance, state.balances[i]);
+        assert_eq!(data.validator, state.validators[i]);
+        assert_eq!(data.status, ValidatorStatus::ActiveOngoing);
+    }
+
+    let validator = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .eth_v1()
+                .get_state_validator(StateId::Genesis, 1),
+        )
+        .expect("should fetch validator from http api");
+    assert_eq!(validator, validators[1]);
+
+    let unknown_validator = env.runtime().block_on(
+        remote_node
+            .http
+            .eth_v1()
+            .get_state_validator(StateId::Head, validators.len() as u64),
+    );
+    assert!(
+        unknown_validator.is_err(),
+        "should not find an unknown validator"
+    );
+
+    let committees = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .eth_v1()
+                .get_state_committees(StateId::Head, Some(Epoch::new(0))),
+        )
+        .expect("should fetch committees from http api");
+
+    let expected = state
+        .get_beacon_committees_at_epoch(RelativeEpoch::Current)
+        .expect("should get committees")
+        .iter()
+        .map(|c| CommitteeData {
+            index: c.index,
+            slot: c.slot,
+            validators: c.committee.iter().map(|i| *i as u64).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(committees, expected, "committees should be as expected");
+}
+
+#[test]
+fn eth_v1_beacon_blocks() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let head = chain.head().expect("should get head");
+
+    // The chain has not progressed past genesis, so every block id refers to the genesis block.
+    let block_ids = vec![
+        BlockId::Head,
+        BlockId::Genesis,
+        BlockId::Finalized,
+        BlockId::Slot(Slot::new(0)),
+        BlockId::Root(head.beacon_block_root),
+    ];
+
+    for block_id in block_ids {
+        let block = env
+            .runtime()
+            .block_on(remote_node.http.eth_v1().get_block(block_id))
+            .expect("should fetch block from http api");
+        assert_eq!(block, head.beacon_block, "block {} should match", block_id);
+
+        let root = env
+            .runtime()
+            .block_on(remote_node.http.eth_v1().get_block_root(block_id))
+            .expect("should fetch block root from http api");
+        assert_eq!(root, head.beacon_block_root);
+
+        let header = env
+            .runtime()
+            .block_on(remote_node.http.eth_v1().get_block_header(block_id))
+            .expect("should fetch block header from http api");
+        assert_eq!(header.root, head.beacon_block_root);
+        assert!(header.canonical, "the genesis block should be canonical");
+        assert_eq!(
+            header.header.message,
+            head.beacon_block.message.block_header()
+        );
+    }
+
+    let unknown_block = env.runtime().block_on(
+        remote_node
+            .http
+            .eth_v1()
+            .get_block(BlockId::Slot(Slot::new(1))),
+    );
+    assert!(unknown_block.is_err(), "should not find an unknown block");
+}
+
+#[test]
+fn eth_v1_node() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let version = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_node_version())
+        .expect("should fetch version from http api");
+    assert_eq!(version, version::version());
+
+    let syncing = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_node_syncing())
+        .expect("should fetch syncing status from http api");
+    assert_eq!(syncing.head_slot, Slot::new(0));
+
+    let identity = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_node_identity())
+        .expect("should fetch identity from http api");
+    assert!(!identity.peer_id.is_empty(), "should have a peer id");
+    assert!(!identity.enr.is_empty(), "should have an enr");
+
+    let peers = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_node_peers())
+        .expect("should fetch peers from http api");
+    assert!(peers.is_empty(), "should not have any peers");
+
+    let deposit_contract = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_config_deposit_contract())
+        .expect("should fetch deposit contract from http api");
+    assert_eq!(
+        deposit_contract.chain_id, env.eth2_config.deposit_chain_id,
+        "should serve the deposit chain id of the eth2 config"
+    );
+    assert_eq!(
+        deposit_contract.address, env.eth2_config.deposit_contract_address,
+        "should serve the deposit contract address of the eth2 config"
+    );
+}
+
+#[test]
+fn eth_v1_validator_duties() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let epoch = Epoch::new(0);
+    let state = chain.head().expect("should get head").beacon_state;
+
+    let proposers = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().get_proposer_duties(epoch))
+        .expect("should fetch proposer duties from http api");
+
+    assert_eq!(proposers.len() as u64, E::slots_per_epoch());
+    for (slot, proposer) in epoch.slot_iter(E::slots_per_epoch()).zip(proposers) {
+        let index = chain
+            .block_proposer(slot)
+            .expect("should get proposer index");
+        assert_eq!(proposer.slot, slot);
+        assert_eq!(proposer.validator_index, index as u64);
+        assert_eq!(proposer.pubkey, state.validators[index].pubkey);
+    }
+
+    let indices = (0..state.validators.len() as u64).collect::<Vec<_>>();
+    let attesters = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .eth_v1()
+                .get_attester_duties(epoch, indices.clone()),
+        )
+        .expect("should fetch attester duties from http api");
+
+    assert_eq!(attesters.len(), indices.len());
+    for attester in attesters {
+        let duty = state
+            .get_attestation_duties(attester.validator_index as usize, RelativeEpoch::Current)
+            .expect("should have attestation duties cache")
+            .expect("should have attestation duties");
+
+        assert_eq!(attester.slot, duty.slot);
+        assert_eq!(attester.committee_index, duty.index);
+        assert_eq!(attester.committee_length, duty.committee_len as u64);
+        assert_eq!(
+            attester.validator_committee_index,
+            duty.committee_position as u64
+        );
+    }
+
+    let unknown_validator = env.runtime().block_on(
+        remote_node
+            .http
+            .eth_v1()
+            .get_attester_duties(epoch, vec![indices.len() as u64]),
+    );
+    assert!(
+        unknown_validator.is_err(),
+        "should reject an unknown validator"
+    );
+}
+
+#[test]
+fn eth_v1_validator_production() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let slot = Slot::new(1);
+    let randao_reveal = get_randao_reveal(chain.clone(), slot, spec);
+
+    let block = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .eth_v1()
+                .produce_block(slot, randao_reveal.clone()),
+        )
+        .expect("should fetch block from http api");
+
+    let (expected_block, _state) = chain
+        .produce_block(randao_reveal, slot)
+        .expect("should produce block");
+    assert_eq!(block, expected_block, "block should be as expected");
+
+    let slot = chain.slot().expect("should read slot");
+    let attestation_data = env
+        .runtime()
+        .block_on(remote_node.http.eth_v1().produce_attestation_data(slot, 0))
+        .expect("should fetch attestation data from http api");
+
+    let expected_attestation = chain
+        .produce_unaggregated_attestation(slot, 0)
+        .expect("should produce attestation");
+    assert_eq!(
+        attestation_data, expected_attestation.data,
+        "attestation data should be as expected"
+    );
+}
+
+#[test]
+fn events_stream() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: 13_371_337,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    match env.runtime().block_on(remote_node.http.events(&[])) {
+        Err(RemoteError::DidNotSucceed { status, .. }) => {
+            assert_eq!(status.as_u16(), 400, "should reject an empty topic list")
+        }
+        _ => panic!("should not subscribe without topics"),
+    }
+
+    let events = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .events(&[EventTopic::Head, EventTopic::Block]),
+        )
+        .expect("should subscribe to events");
+
+    let slot = Slot::new(1);
+    let randao_reveal = get_randao_reveal(beacon_chain.clone(), slot, spec);
+    let block = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .produce_block(slot, randao_reveal),
+        )
+        .expect("should fetch block from http api");
+    let signed_block = sign_block(beacon_chain.clone(), block, spec);
+    let block_root = signed_block.canonical_root();
+    let state_root = signed_block.message.state_root;
+
+    env.runtime()
+        .block_on(remote_node.http.validator().publish_block(signed_block))
+        .expect("should publish block");
+
+    let received = env
+        .runtime()
+        .block_on(events.take(2).collect())
+        .expect("should receive events");
+
+    assert_eq!(
+        received,
+        vec![
+            ServerSentEvent::Block(SseBlock {
+                slot,
+                block: block_root,
+            }),
+            ServerSentEvent::Head(SseHead {
+                slot,
+                block: block_root,
+                state: state_root,
+                epoch_transition: false,
+            }),
+        ],
+        "should receive the imported block and the new head"
+    );
+}
@@ -41,8 +41,12 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("listen-address")
                 .long("listen-address")
                 .value_name("ADDRESS")
-                .help("The address lighthouse will listen for UDP and TCP connections.")
+                .help("The address lighthouse will listen for UDP and TCP connections. To listen \
+                       over both IPv4 and IPv6, provide this flag twice, once with an IPv4 and once \
+                       with an IPv6 address.")
                 .default_value("0.0.0.0")
+                .multiple(true)
+                .number_of_values(1)
                 .takes_value(true)
         )
         .arg(
@@ -53,6 +57,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("9000")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("port6")
+                .long("port6")
+                .value_name("PORT")
+                .help("The TCP/UDP port to listen on over IPv6 when listening over both IPv4 and \
+                       IPv6. The UDP port can be modified by the --discovery-port6 flag.")
+                .default_value("9090")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("discovery-port")
                 .long("discovery-port")
@@ -60,6 +73,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The UDP port that discovery will listen on. Defaults to `port`")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("discovery-port6")
+                .long("discovery-port6")
+                .value_name("PORT")
+                .help("The UDP port that discovery will listen on over IPv6 when listening over both \
+                       IPv4 and IPv6. Defaults to `port6`")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("maxpeers")
                 .long("maxpeers")
@@ -90,6 +111,22 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     The --port flag is used if this is not set.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("enr-udp6-port")
+                .long("enr-udp6-port")
+                .value_name("PORT")
+                .help("The IPv6 UDP port of the local ENR when listening over both IPv4 and IPv6. \
+                    The --discovery-port6 flag is used if this is not set.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("enr-tcp6-port")
+                .long("enr-tcp6-port")
+                .value_name("PORT")
+                .help("The IPv6 TCP port of the local ENR when listening over both IPv4 and IPv6. \
+                    The --port6 flag is used if this is not set.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("enr-address")
                 .long("enr-address")
@@ -98,8 +135,11 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 If a DNS address is provided, the enr-address is set to the IP address it resolves to and \
                 does not auto-update based on PONG responses in discovery. \
                 Set this only if you are sure other nodes can connect to your local node on this address. \
-                Discovery will automatically find your external address,if possible.")
+                Discovery will automatically find your external address,if possible. \
+                When listening over both IPv4 and IPv6, this flag may be provided once for each.")
                 .requires("enr-udp-port")
+                .multiple(true)
+                .number_of_values(1)
                 .takes_value(true),
         )
         .arg(
@@ -107,7 +147,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .short("e")
                 .long("enr-match")
                 .help("Sets the local ENR IP address and port to match those set for lighthouse. \
-                Specifically, the IP address will be the value of --listen-address and the UDP port will be --discovery-port.")
+                Specifically, the IP addresses will be the values of --listen-address and the UDP port will be --discovery-port.")
         )
         .arg(
             Arg::with_name("disable-enr-auto-update")
@@ -115,7 +155,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .long("disable-enr-auto-update")
                 .help("Discovery automatically updates the nodes local ENR with an external IP address and port as seen by other peers on the network. \
                 This disables this feature, fixing the ENR's IP/PORT to those specified on boot.")
-                .takes_value(true),
+                .takes_value(false),
         )
         .arg(
             Arg::with_name("libp2p-addresses")
@@ -125,6 +165,48 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        without an ENR.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("trusted-peers")
+                .long("trusted-peers")
+                .allow_hyphen_values(true)
+                .value_name("MULTIADDR/ENR-LIST")
+                .help("One or more comma-delimited multiaddrs or base64-encoded ENRs of peers to \
+                       always stay connected to. Multiaddrs must end with the /p2p/ id of the peer. \
+                       Trusted peers are never banned, pruned or counted toward --maxpeers, and are \
+                       redialled when they disconnect.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("gossip-graylist-threshold")
+                .long("gossip-graylist-threshold")
+                .allow_hyphen_values(true)
+                .value_name("SCORE")
+                .help("Gossip messages from peers with a gossip score below this (non-positive) \
+                       threshold are ignored.")
+                .default_value("-400")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("gossip-disconnect-threshold")
+                .long("gossip-disconnect-threshold")
+                .allow_hyphen_values(true)
+                .value_name("SCORE")
+                .help("Peers with a gossip score below this threshold are disconnected and banned. \
+                       Must not be greater than --gossip-graylist-threshold.")
+                .default_value("-900")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("rpc-quotas")
+                .long("rpc-quotas")
+                .value_name("PROTOCOL:TOKENS/SECONDS-LIST")
+                .help("One or more comma-delimited quotas of the form protocol:tokens/seconds \
+                       (e.g., beacon_blocks_by_range:512/10) which replace the default quota of \
+                       inbound RPC requests of each peer. Blocks requests consume a token per \
+                       block, other requests a single token. The protocols are status, \
+                       beacon_blocks_by_range, beacon_blocks_by_root, ping and metadata.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("p2p-priv-key")
                 .long("p2p-priv-key")
@@ -229,6 +311,68 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many blocks the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("historic-state-cache-size")
+                .long("historic-state-cache-size")
+                .value_name("SIZE")
+                .help("Specifies how many states reconstructed from the freezer database should \
+                       be cached in memory. Later states are replayed from the closest cached \
+                       state [default: 1]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("db-backend")
+                .long("db-backend")
+                .value_name("BACKEND")
+                .help("Specifies the key-value database used to store the chain. Can only be \
+                       chosen when the database is created. [default: leveldb]")
+                .possible_values(&["leveldb", "sled"])
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("check-db")
+                .long("check-db")
+                .help("If present, check that the blocks and states referenced by the head \
+                       tracker, fork choice and freezer split are present in the database before \
+                       starting. The beacon node refuses to start if any are missing.")
+        )
+        .arg(
+            Arg::with_name("prune-bad-heads")
+                .long("prune-bad-heads")
+                .help("If present, heads with missing blocks or states are pruned from the \
+                       database instead of refusing to start. Implies --check-db.")
+        )
+
+        /*
+         * Weak subjectivity checkpoint sync.
+         */
+        .arg(
+            Arg::with_name("checkpoint-state")
+                .long("checkpoint-state")
+                .value_name("STATE_SSZ")
+                .help("Start from the SSZ-encoded BeaconState in this file instead of genesis. \
+                       The state must lie on an epoch boundary and be trusted, ideally a recent \
+                       finalized state. Requires --checkpoint-block.")
+                .takes_value(true)
+                .requires("checkpoint-block")
+                .conflicts_with("checkpoint-sync-url")
+        )
+        .arg(
+            Arg::with_name("checkpoint-block")
+                .long("checkpoint-block")
+                .value_name("BLOCK_SSZ")
+                .help("The SSZ-encoded SignedBeaconBlock which produced the --checkpoint-state.")
+                .takes_value(true)
+                .requires("checkpoint-state")
+        )
+        .arg(
+            Arg::with_name("checkpoint-sync-url")
+                .long("checkpoint-sync-url")
+                .value_name("HTTP_ADDRESS")
+                .help("Start from the latest finalized state of the trusted beacon node at this \
+                       HTTP API address, instead of genesis.")
+                .takes_value(true)
+        )
 
         /*
          * Purge.
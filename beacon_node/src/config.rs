@@ -1,15 +1,18 @@
 use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
 use clap::ArgMatches;
 use clap_utils::BAD_TESTNET_DIR_MESSAGE;
-use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis};
-use eth2_libp2p::{Enr, Multiaddr};
+use client::{
+    config::{WeakSubjectivityAnchor, DEFAULT_DATADIR},
+    ClientConfig, ClientGenesis,
+};
+use eth2_libp2p::{multiaddr::Protocol, Enr, Multiaddr};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{crit, info, Logger};
 use ssz::Encode;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
-use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
 use std::net::{TcpListener, UdpSocket};
 use std::path::PathBuf;
 use types::{ChainSpec, EthSpec};
@@ -95,11 +98,10 @@ pub fn get_config<E: EthSpec>(
         client_config.network.network_dir = client_config.data_dir.join(NETWORK_DIR);
     };
 
-    if let Some(listen_address_str) = cli_args.value_of("listen-address") {
-        let listen_address = listen_address_str
-            .parse()
-            .map_err(|_| format!("Invalid listen address: {:?}", listen_address_str))?;
+    if let Some(listen_address_strs) = cli_args.values_of("listen-address") {
+        let (listen_address, listen_address6) = parse_listen_addresses(listen_address_strs)?;
         client_config.network.listen_address = listen_address;
+        client_config.network.listen_address6 = listen_address6;
     }
 
     if let Some(max_peers_str) = cli_args.value_of("maxpeers") {
@@ -116,6 +118,14 @@ pub fn get_config<E: EthSpec>(
         client_config.network.discovery_port = port;
     }
 
+    if let Some(port_str) = cli_args.value_of("port6") {
+        let port = port_str
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid port: {}", port_str))?;
+        client_config.network.libp2p_port6 = port;
+        client_config.network.discovery_port6 = port;
+    }
+
     if let Some(port_str) = cli_args.value_of("discovery-port") {
         let port = port_str
             .parse::<u16>()
@@ -123,6 +133,12 @@ pub fn get_config<E: EthSpec>(
         client_config.network.discovery_port = port;
     }
 
+    if let Some(port_str) = cli_args.value_of("discovery-port6") {
+        client_config.network.discovery_port6 = port_str
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid port: {}", port_str))?;
+    }
+
     if let Some(boot_enr_str) = cli_args.value_of("boot-nodes") {
         client_config.network.boot_nodes = boot_enr_str
             .split(',')
@@ -141,6 +157,49 @@ pub fn get_config<E: EthSpec>(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
+    if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
+        client_config.network.trusted_peers = trusted_peers_str
+            .split(',')
+            .map(parse_trusted_peer)
+            .collect::<Result<Vec<Vec<Multiaddr>>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+
+    if let Some(threshold_str) = cli_args.value_of("gossip-graylist-threshold") {
+        client_config.network.gossip_graylist_threshold = threshold_str
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid gossip graylist threshold: {}", threshold_str))?;
+    }
+
+    if let Some(threshold_str) = cli_args.value_of("gossip-disconnect-threshold") {
+        client_config.network.gossip_disconnect_threshold = threshold_str
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid gossip disconnect threshold: {}", threshold_str))?;
+    }
+
+    if let Some(quotas_str) = cli_args.value_of("rpc-quotas") {
+        client_config
+            .network
+            .rpc_quotas
+            .apply_quotas(quotas_str)
+            .map_err(|e| format!("Invalid RPC quotas: {}", e))?;
+    }
+
+    let graylist_threshold = client_config.network.gossip_graylist_threshold;
+    let disconnect_threshold = client_config.network.gossip_disconnect_threshold;
+    if !(disconnect_threshold < 0.0
+        && disconnect_threshold <= graylist_threshold
+        && graylist_threshold <= 0.0)
+    {
+        return Err(format!(
+            "Gossip thresholds must satisfy disconnect ({}) <= graylist ({}) <= 0, with a \
+             negative disconnect threshold",
+            disconnect_threshold, graylist_threshold
+        ));
+    }
+
     if let Some(enr_udp_port_str) = cli_args.value_of("enr-udp-port") {
         client_config.network.enr_udp_port = Some(
             enr_udp_port_str
@@ -157,20 +216,40 @@ pub fn get_config<E: EthSpec>(
         );
     }
 
+    if let Some(enr_udp6_port_str) = cli_args.value_of("enr-udp6-port") {
+        client_config.network.enr_udp6_port = Some(
+            enr_udp6_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR UDP6 port: {}", enr_udp6_port_str))?,
+        );
+    }
+
+    if let Some(enr_tcp6_port_str) = cli_args.value_of("enr-tcp6-port") {
+        client_config.network.enr_tcp6_port = Some(
+            enr_tcp6_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR TCP6 port: {}", enr_tcp6_port_str))?,
+        );
+    }
+
     if cli_args.is_present("enr-match") {
-        // set the enr address to localhost if the address is 0.0.0.0
-        if client_config.network.listen_address
-            == "0.0.0.0".parse::<IpAddr>().expect("valid ip addr")
-        {
-            client_config.network.enr_address =
-                Some("127.0.0.1".parse::<IpAddr>().expect("valid ip addr"));
-        } else {
-            client_config.network.enr_address = Some(client_config.network.listen_address);
-        }
+        // set the enr addresses to localhost if the addresses are unspecified
+        client_config.network.enr_address =
+            Some(local_enr_address(client_config.network.listen_address));
+        client_config.network.enr_address6 = client_config.network.listen_address6.map(|addr| {
+            if addr.is_unspecified() {
+                Ipv6Addr::LOCALHOST
+            } else {
+                addr
+            }
+        });
         client_config.network.enr_udp_port = Some(client_config.network.discovery_port);
+        client_config.network.enr_udp6_port = Some(client_config.network.discovery_port6);
     }
 
-    if let Some(enr_address) = cli_args.value_of("enr-address") {
+    let mut enr_address_set = false;
+    let mut enr_address6_set = false;
+    for enr_address in cli_args.values_of("enr-address").into_iter().flatten() {
         let resolved_addr = match enr_address.parse::<IpAddr>() {
             Ok(addr) => addr, // // Input is an IpAddr
             Err(_) => {
@@ -201,10 +280,30 @@ pub fn get_config<E: EthSpec>(
                 resolved_addr
             }
         };
-        client_config.network.enr_address = Some(resolved_addr);
+        // the IPv6 address of a dual-stack node is advertised alongside its IPv4 address
+        match resolved_addr {
+            IpAddr::V6(addr) if client_config.network.listen_address6.is_some() => {
+                if enr_address6_set {
+                    return Err("Only one IPv6 enr-address may be provided".into());
+                }
+                client_config.network.enr_address6 = Some(addr);
+                enr_address6_set = true;
+            }
+            addr => {
+                if enr_address_set {
+                    return Err(
+                        "Only one enr-address may be provided, or one for each address \
+                         family when listening over both IPv4 and IPv6"
+                            .into(),
+                    );
+                }
+                client_config.network.enr_address = Some(addr);
+                enr_address_set = true;
+            }
+        }
     }
 
-    if cli_args.is_present("disable_enr_auto_update") {
+    if cli_args.is_present("disable-enr-auto-update") {
         client_config.network.discv5_config.enr_update = false;
     }
 
@@ -311,6 +410,19 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(historic_state_cache_size) = cli_args.value_of("historic-state-cache-size") {
+        client_config.store.historic_state_cache_size = historic_state_cache_size
+            .parse()
+            .map_err(|_| "historic-state-cache-size is not a valid integer".to_string())?;
+    }
+
+    if let Some(backend) = cli_args.value_of("db-backend") {
+        client_config.store.backend = backend.parse()?;
+    }
+
+    client_config.prune_bad_heads = cli_args.is_present("prune-bad-heads");
+    client_config.check_db = cli_args.is_present("check-db") || client_config.prune_bad_heads;
+
     if spec_constants != client_config.spec_constants {
         crit!(log, "Specification constants do not match.";
               "client_config" => client_config.spec_constants.to_string(),
@@ -334,8 +446,12 @@ pub fn get_config<E: EthSpec>(
         }
         client_config.network.libp2p_port =
             unused_port("tcp").map_err(|e| format!("Failed to get port for libp2p: {}", e))?;
+        client_config.network.libp2p_port6 =
+            unused_port("tcp").map_err(|e| format!("Failed to get IPv6 port for libp2p: {}", e))?;
         client_config.network.discovery_port =
             unused_port("udp").map_err(|e| format!("Failed to get port for discovery: {}", e))?;
+        client_config.network.discovery_port6 = unused_port("udp")
+            .map_err(|e| format!("Failed to get IPv6 port for discovery: {}", e))?;
         client_config.rest_api.port = 0;
         client_config.websocket_server.port = 0;
     }
@@ -369,6 +485,46 @@ pub fn get_config<E: EthSpec>(
         client_config.genesis = ClientGenesis::DepositContract;
     }
 
+    /*
+     * Weak subjectivity checkpoint sync.
+     */
+    let anchor = if let Some(url) = cli_args.value_of("checkpoint-sync-url") {
+        Some(WeakSubjectivityAnchor::RemoteBeaconNode {
+            url: url.to_string(),
+        })
+    } else if let (Some(state_path), Some(block_path)) = (
+        cli_args.value_of("checkpoint-state"),
+        cli_args.value_of("checkpoint-block"),
+    ) {
+        Some(WeakSubjectivityAnchor::SszBytes {
+            anchor_state_bytes: fs::read(state_path)
+                .map_err(|e| format!("Unable to read checkpoint state {}: {:?}", state_path, e))?,
+            anchor_block_bytes: fs::read(block_path)
+                .map_err(|e| format!("Unable to read checkpoint block {}: {:?}", block_path, e))?,
+        })
+    } else {
+        None
+    };
+
+    if let Some(anchor) = anchor {
+        let genesis_state_bytes = match client_config.genesis {
+            ClientGenesis::SszBytes {
+                genesis_state_bytes,
+            } => genesis_state_bytes,
+            _ => {
+                return Err(
+                    "Checkpoint sync requires a testnet directory with a known genesis state"
+                        .to_string(),
+                )
+            }
+        };
+
+        client_config.genesis = ClientGenesis::WeakSubjectivityState {
+            genesis_state_bytes,
+            anchor,
+        };
+    }
+
     if !config_file_existed {
         write_to_file(config_file_path, &client_config)?;
     }
@@ -413,6 +569,89 @@ pub fn get_eth2_testnet_config<E: EthSpec>(
     })
 }
 
+/// Parses a trusted peer given as a multiaddr or an ENR, returning its TCP multiaddrs ending with
+/// the `/p2p/` id of the peer.
+fn parse_trusted_peer(trusted_peer: &str) -> Result<Vec<Multiaddr>, String> {
+    if trusted_peer.starts_with("enr:") {
+        let enr: Enr = trusted_peer
+            .parse()
+            .map_err(|_| format!("Invalid ENR: {}", trusted_peer))?;
+        let peer_id = enr.peer_id();
+        let multiaddrs = enr
+            .multiaddr()
+            .into_iter()
+            .filter(|multiaddr| {
+                multiaddr.iter().any(|protocol| match protocol {
+                    Protocol::Tcp(_) => true,
+                    _ => false,
+                })
+            })
+            .map(|mut multiaddr| {
+                multiaddr.push(Protocol::P2p(peer_id.clone().into()));
+                multiaddr
+            })
+            .collect::<Vec<_>>();
+
+        if multiaddrs.is_empty() {
+            return Err(format!(
+                "Trusted peer ENR has no TCP address: {}",
+                trusted_peer
+            ));
+        }
+        Ok(multiaddrs)
+    } else {
+        let multiaddr: Multiaddr = trusted_peer
+            .parse()
+            .map_err(|_| format!("Invalid Multiaddr: {}", trusted_peer))?;
+
+        match multiaddr.iter().last() {
+            Some(Protocol::P2p(_)) => Ok(vec![multiaddr]),
+            _ => Err(format!(
+                "Trusted peer multiaddr must end with /p2p/<peer-id>: {}",
+                trusted_peer
+            )),
+        }
+    }
+}
+
+/// Parses the values of `--listen-address`: either a single address of either family, or one IPv4
+/// and one IPv6 address for a dual-stack node.
+///
+/// Returns the `listen_address` and `listen_address6` of the network config. The IPv4 address is
+/// the `listen_address` of a dual-stack node.
+fn parse_listen_addresses<'a>(
+    listen_address_strs: impl IntoIterator<Item = &'a str>,
+) -> Result<(IpAddr, Option<Ipv6Addr>), String> {
+    let mut listen_address4 = None;
+    let mut listen_address6 = None;
+    for listen_address_str in listen_address_strs {
+        let listen_address = listen_address_str
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid listen address: {:?}", listen_address_str))?;
+        match listen_address {
+            IpAddr::V4(addr) if listen_address4.is_none() => listen_address4 = Some(addr),
+            IpAddr::V6(addr) if listen_address6.is_none() => listen_address6 = Some(addr),
+            _ => return Err("Only one IPv4 and one IPv6 listen address may be provided".into()),
+        }
+    }
+
+    match (listen_address4, listen_address6) {
+        (Some(addr), listen_address6) => Ok((IpAddr::V4(addr), listen_address6)),
+        (None, Some(addr)) => Ok((IpAddr::V6(addr), None)),
+        (None, None) => Err("No listen address provided".into()),
+    }
+}
+
+/// Returns the loopback address of the address family of `address` if it is unspecified (e.g.,
+/// `0.0.0.0`), otherwise `address` itself.
+fn local_enr_address(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V4(addr) if addr.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(addr) if addr.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        addr => addr,
+    }
+}
+
 /// A bit of hack to find an unused port.
 ///
 /// Does not guarantee that the given port is unused after the function exists, just that it was
@@ -492,3 +731,82 @@ where
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth2_libp2p::discovery::{build_enr, CombinedKey, Keypair};
+    use eth2_libp2p::{NetworkConfig, PeerId};
+    use std::convert::TryInto;
+    use types::{EnrForkId, MinimalEthSpec};
+
+    /// An ENR with an `ip` and `udp` field, but no `tcp` field.
+    const ENR_WITHOUT_TCP: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+    #[test]
+    fn trusted_peer_multiaddr() {
+        let address = format!("/ip4/127.0.0.1/tcp/9000/p2p/{}", PeerId::random());
+
+        assert_eq!(
+            parse_trusted_peer(&address),
+            Ok(vec![address.parse().unwrap()])
+        );
+        assert!(
+            parse_trusted_peer("/ip4/127.0.0.1/tcp/9000").is_err(),
+            "should require the /p2p/ id of the peer"
+        );
+        assert!(parse_trusted_peer("127.0.0.1:9000").is_err());
+    }
+
+    #[test]
+    fn trusted_peer_enr() {
+        let enr_key: CombinedKey = Keypair::generate_secp256k1().try_into().unwrap();
+        let mut config = NetworkConfig::default();
+        config.enr_address = Some("127.0.0.1".parse().unwrap());
+        config.enr_udp_port = Some(9001);
+        config.enr_tcp_port = Some(9000);
+        let enr = build_enr::<MinimalEthSpec>(&enr_key, &config, EnrForkId::default()).unwrap();
+
+        let expected = format!("/ip4/127.0.0.1/tcp/9000/p2p/{}", enr.peer_id());
+        assert_eq!(
+            parse_trusted_peer(&enr.to_base64()),
+            Ok(vec![expected.parse().unwrap()]),
+            "should only use the TCP address"
+        );
+
+        assert!(
+            parse_trusted_peer(ENR_WITHOUT_TCP).is_err(),
+            "should require a TCP address"
+        );
+        assert!(parse_trusted_peer("enr:-IS4QHCYrYZbAKWC").is_err());
+    }
+
+    #[test]
+    fn listen_addresses() {
+        assert_eq!(
+            parse_listen_addresses(vec!["0.0.0.0"]),
+            Ok(("0.0.0.0".parse().unwrap(), None))
+        );
+        assert_eq!(
+            parse_listen_addresses(vec!["::"]),
+            Ok(("::".parse().unwrap(), None)),
+            "should listen on IPv6 only"
+        );
+        assert_eq!(
+            parse_listen_addresses(vec!["::1", "127.0.0.1"]),
+            Ok(("127.0.0.1".parse().unwrap(), Some(Ipv6Addr::LOCALHOST))),
+            "should be dual-stack, whatever the order of the addresses"
+        );
+
+        assert!(
+            parse_listen_addresses(vec!["0.0.0.0", "127.0.0.1"]).is_err(),
+            "should reject two IPv4 addresses"
+        );
+        assert!(
+            parse_listen_addresses(vec!["::", "::1"]).is_err(),
+            "should reject two IPv6 addresses"
+        );
+        assert!(parse_listen_addresses(vec!["0.0.0.0", "::", "::1"]).is_err());
+        assert!(parse_listen_addresses(vec!["localhost"]).is_err());
+    }
+}
@@ -12,7 +12,7 @@ pub use eth2_config::Eth2Config;
 
 use beacon_chain::migrate::{BackgroundMigrator, DiskStore};
 use beacon_chain::{
-    builder::Witness, eth1_chain::CachingEth1Backend, events::WebSocketSender,
+    builder::Witness, eth1_chain::CachingEth1Backend, events::TeeEventHandler,
     slot_clock::SystemTimeSlotClock,
 };
 use clap::ArgMatches;
@@ -31,7 +31,7 @@ pub type ProductionClient<E> = Client<
         SystemTimeSlotClock,
         CachingEth1Backend<E, DiskStore<E>>,
         E,
-        WebSocketSender<E>,
+        TeeEventHandler<E>,
     >,
 >;
 
@@ -77,6 +77,8 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
         let client_config_1 = client_config.clone();
         let client_genesis = client_config.genesis.clone();
         let store_config = client_config.store.clone();
+        let check_db = client_config.check_db;
+        let prune_bad_heads = client_config.prune_bad_heads;
         let log = context.log.clone();
 
         let db_path_res = client_config.create_db_path();
@@ -85,11 +87,16 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
         db_path_res
             .into_future()
             .and_then(move |db_path| {
-                Ok(ClientBuilder::new(context.eth_spec_instance.clone())
+                let builder = ClientBuilder::new(context.eth_spec_instance.clone())
                     .runtime_context(context)
                     .chain_spec(spec)
-                    .disk_store(&db_path, &freezer_db_path_res?, store_config)?
-                    .background_migrator()?)
+                    .disk_store(&db_path, &freezer_db_path_res?, store_config)?;
+                let builder = if check_db {
+                    builder.check_database(prune_bad_heads)?
+                } else {
+                    builder
+                };
+                Ok(builder.background_migrator()?)
             })
             .and_then(move |builder| builder.beacon_chain_builder(client_genesis, client_config_1))
             .and_then(move |builder| {
@@ -120,7 +127,7 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
 
                 let builder = builder
                     .system_time_slot_clock()?
-                    .websocket_event_handler(client_config.websocket_server.clone())?
+                    .tee_event_handler(client_config.websocket_server.clone())?
                     .build_beacon_chain()?
                     .network(&mut client_config.network)?
                     .notifier()?;
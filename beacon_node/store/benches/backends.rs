@@ -0,0 +1,148 @@
+//! Compares the throughput of the `DatabaseBackend`s when importing blocks and states, and when
+//! loading states.
+
+use criterion::Criterion;
+use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use rayon::prelude::*;
+use sloggers::{null::NullLoggerBuilder, Build};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use store::{DatabaseBackend, DiskStore, Store, StoreConfig};
+use tempfile::{tempdir, TempDir};
+use types::{
+    test_utils::generate_deterministic_keypair, BeaconBlock, BeaconState, Epoch, Eth1Data, EthSpec,
+    Hash256, MainnetEthSpec, Signature, SignedBeaconBlock, Slot, Validator,
+};
+
+type E = MainnetEthSpec;
+
+const BACKENDS: &[DatabaseBackend] = &[DatabaseBackend::LevelDb, DatabaseBackend::Sled];
+
+fn get_state(validator_count: usize) -> BeaconState<E> {
+    let spec = &E::default_spec();
+    let eth1_data = Eth1Data {
+        deposit_root: Hash256::zero(),
+        deposit_count: 0,
+        block_hash: Hash256::zero(),
+    };
+
+    let mut state = BeaconState::new(0, eth1_data, spec);
+
+    for i in 0..validator_count {
+        state.balances.push(i as u64).expect("should add balance");
+    }
+
+    state.validators = (0..validator_count)
+        .collect::<Vec<_>>()
+        .par_iter()
+        .map(|&i| Validator {
+            pubkey: generate_deterministic_keypair(i).pk.into(),
+            withdrawal_credentials: Hash256::from_low_u64_le(i as u64),
+            effective_balance: spec.max_effective_balance,
+            slashed: false,
+            activation_eligibility_epoch: Epoch::new(0),
+            activation_epoch: Epoch::new(0),
+            exit_epoch: Epoch::from(u64::max_value()),
+            withdrawable_epoch: Epoch::from(u64::max_value()),
+        })
+        .collect::<Vec<_>>()
+        .into();
+
+    state.build_all_caches(spec).expect("should build caches");
+
+    state
+}
+
+/// Opens a `DiskStore` using `backend` in a new temporary directory.
+///
+/// The directory is deleted when the returned `TempDir` is dropped.
+fn get_store(backend: DatabaseBackend) -> (TempDir, Arc<DiskStore<E>>) {
+    let db_path = tempdir().expect("should create temporary directory");
+    let config = StoreConfig {
+        backend,
+        ..StoreConfig::default()
+    };
+    let log = NullLoggerBuilder.build().expect("logger should build");
+    let store = DiskStore::open(
+        &db_path.path().join("hot_db"),
+        &db_path.path().join("cold_db"),
+        config,
+        E::default_spec(),
+        log,
+    )
+    .expect("disk store should initialize");
+    (db_path, Arc::new(store))
+}
+
+fn all_benches(c: &mut Criterion) {
+    let validator_count = 16_384;
+    let spec = E::default_spec();
+    let state = get_state(validator_count);
+    let block = SignedBeaconBlock {
+        message: BeaconBlock::<E>::full(&spec),
+        signature: Signature::empty_signature(),
+    };
+
+    for &backend in BACKENDS {
+        // Give each block a unique root, so that every iteration writes a new key.
+        let (block_dir, store) = get_store(backend);
+        let inner_block = block.clone();
+        let counter = AtomicU64::new(0);
+        c.bench(
+            &format!("{}", backend),
+            Benchmark::new("import/beacon_block", move |b| {
+                let _ = &block_dir;
+                b.iter(|| {
+                    let block_root =
+                        Hash256::from_low_u64_be(counter.fetch_add(1, Ordering::Relaxed));
+                    store
+                        .put_block(&block_root, inner_block.clone())
+                        .expect("should store block")
+                })
+            })
+            .sample_size(10),
+        );
+
+        let (state_dir, store) = get_store(backend);
+        let inner_state = state.clone();
+        let counter = AtomicU64::new(0);
+        c.bench(
+            &format!("{}_{}_validators", backend, validator_count),
+            Benchmark::new("import/beacon_state", move |b| {
+                let _ = &state_dir;
+                b.iter(|| {
+                    let state_root =
+                        Hash256::from_low_u64_be(counter.fetch_add(1, Ordering::Relaxed));
+                    store
+                        .put_state(&state_root, &inner_state)
+                        .expect("should store state")
+                })
+            })
+            .sample_size(10),
+        );
+
+        let (load_dir, store) = get_store(backend);
+        let state_root = state.canonical_root();
+        store
+            .put_state(&state_root, &state)
+            .expect("should store state");
+        c.bench(
+            &format!("{}_{}_validators", backend, validator_count),
+            Benchmark::new("load/beacon_state", move |b| {
+                let _ = &load_dir;
+                b.iter(|| {
+                    black_box(
+                        store
+                            .get_state(&state_root, Some(Slot::new(0)))
+                            .expect("should load state")
+                            .expect("state should exist"),
+                    )
+                })
+            })
+            .sample_size(10),
+        );
+    }
+}
+
+criterion_group!(benches, all_benches,);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 use crate::chunked_vector::{chunk_key, Chunk, Field};
-use crate::DiskStore;
+use crate::{DiskStore, KeyValueStore};
 use slog::error;
 use std::sync::Arc;
 use types::{ChainSpec, EthSpec, Slot};
@@ -7,22 +7,26 @@ use types::{ChainSpec, EthSpec, Slot};
 /// Iterator over the values of a `BeaconState` vector field (like `block_roots`).
 ///
 /// Uses the freezer DB's separate table to load the values.
-pub struct ChunkedVectorIter<F, E>
+pub struct ChunkedVectorIter<F, E, Hot, Cold>
 where
     F: Field<E>,
     E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
 {
-    pub(crate) store: Arc<DiskStore<E>>,
+    pub(crate) store: Arc<DiskStore<E, Hot, Cold>>,
     current_vindex: usize,
     pub(crate) end_vindex: usize,
     next_cindex: usize,
     current_chunk: Chunk<F::Value>,
 }
 
-impl<F, E> ChunkedVectorIter<F, E>
+impl<F, E, Hot, Cold> ChunkedVectorIter<F, E, Hot, Cold>
 where
     F: Field<E>,
     E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
 {
     /// Create a new iterator which can yield elements from `start_vindex` up to the last
     /// index stored by the restore point at `last_restore_point_slot`.
@@ -31,7 +35,7 @@ where
     /// `DiskStore::get_latest_restore_point_slot`. We pass it as a parameter so that the caller can
     /// maintain a stable view of the database (see `HybridForwardsBlockRootsIterator`).
     pub fn new(
-        store: Arc<DiskStore<E>>,
+        store: Arc<DiskStore<E, Hot, Cold>>,
         start_vindex: usize,
         last_restore_point_slot: Slot,
         spec: &ChainSpec,
@@ -53,10 +57,12 @@ where
     }
 }
 
-impl<F, E> Iterator for ChunkedVectorIter<F, E>
+impl<F, E, Hot, Cold> Iterator for ChunkedVectorIter<F, E, Hot, Cold>
 where
     F: Field<E>,
     E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
 {
     type Item = (usize, F::Value);
 
@@ -513,6 +513,19 @@ pub fn load_vector_from_db<F: FixedLengthField<E>, E: EthSpec, S: Store<E>>(
     Ok(result.into())
 }
 
+/// Load the value at `vindex` of a fixed length field from the database.
+///
+/// Returns `None` if the chunk containing `vindex` has not been stored.
+pub fn load_value_from_db<F: FixedLengthField<E>, E: EthSpec, S: Store<E>>(
+    store: &S,
+    vindex: usize,
+) -> Result<Option<F::Value>, Error> {
+    let chunk_size = F::chunk_size();
+    let chunk =
+        Chunk::<F::Value>::load(store, F::column(), &chunk_key((vindex / chunk_size) as u64))?;
+    Ok(chunk.and_then(|chunk| chunk.values.get(vindex % chunk_size).cloned()))
+}
+
 /// The historical roots are stored in vector chunks, despite not actually being a vector.
 pub fn load_variable_list_from_db<F: VariableLengthField<E>, E: EthSpec, S: Store<E>>(
     store: &S,
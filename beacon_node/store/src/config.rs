@@ -1,8 +1,19 @@
+use crate::{DBColumn, Error, SimpleStoreItem};
 use serde_derive::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
 use types::{EthSpec, MinimalEthSpec};
 
 pub const DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 2048;
 pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 5;
+pub const DEFAULT_HISTORIC_STATE_CACHE_SIZE: usize = 1;
+/// The file in each database directory which records the backend that created the database.
+pub const DATABASE_BACKEND_FILENAME: &str = "BACKEND";
 
 /// Database configuration parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,6 +22,10 @@ pub struct StoreConfig {
     pub slots_per_restore_point: u64,
     /// Maximum number of blocks to store in the in-memory block cache.
     pub block_cache_size: usize,
+    /// Maximum number of states reconstructed from the freezer database to store in memory.
+    pub historic_state_cache_size: usize,
+    /// The key-value database used for the hot and cold databases.
+    pub backend: DatabaseBackend,
 }
 
 impl Default for StoreConfig {
@@ -19,6 +34,107 @@ impl Default for StoreConfig {
             // Safe default for tests, shouldn't ever be read by a CLI node.
             slots_per_restore_point: MinimalEthSpec::slots_per_historical_root() as u64,
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
+            historic_state_cache_size: DEFAULT_HISTORIC_STATE_CACHE_SIZE,
+            backend: DatabaseBackend::default(),
         }
     }
 }
+
+/// The key-value databases which can back a `DiskStore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    LevelDb,
+    Sled,
+}
+
+impl DatabaseBackend {
+    /// Reads the backend of the existing database at `path`, as recorded by `Self::record`.
+    ///
+    /// Databases created before the backend was recorded can only be LevelDB databases. Returns
+    /// `None` if there is no database at `path`.
+    pub fn detect(path: &Path) -> Result<Option<Self>, Error> {
+        match fs::read_to_string(path.join(DATABASE_BACKEND_FILENAME)) {
+            Ok(backend) => backend
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|message| Error::DBError { message }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if path.join("CURRENT").exists() {
+                    Ok(Some(DatabaseBackend::LevelDb))
+                } else {
+                    Ok(None)
+                }
+            }
+            Err(e) => Err(Error::DBError {
+                message: format!("Unable to read database backend in {:?}: {:?}", path, e),
+            }),
+        }
+    }
+
+    /// Records `self` as the backend of the database at `path`.
+    pub fn record(self, path: &Path) -> Result<(), Error> {
+        fs::write(path.join(DATABASE_BACKEND_FILENAME), self.to_string()).map_err(|e| {
+            Error::DBError {
+                message: format!("Unable to record database backend in {:?}: {:?}", path, e),
+            }
+        })
+    }
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self {
+        DatabaseBackend::LevelDb
+    }
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leveldb" => Ok(DatabaseBackend::LevelDb),
+            "sled" => Ok(DatabaseBackend::Sled),
+            other => Err(format!("Unknown database backend: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseBackend::LevelDb => write!(f, "leveldb"),
+            DatabaseBackend::Sled => write!(f, "sled"),
+        }
+    }
+}
+
+/// The parts of the `StoreConfig` which determine the layout of the database, and so must not
+/// change once it has been created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct OnDiskStoreConfig {
+    pub slots_per_restore_point: u64,
+}
+
+impl StoreConfig {
+    pub fn as_disk_config(&self) -> OnDiskStoreConfig {
+        OnDiskStoreConfig {
+            slots_per_restore_point: self.slots_per_restore_point,
+        }
+    }
+}
+
+impl SimpleStoreItem for OnDiskStoreConfig {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+use crate::config::{DatabaseBackend, DATABASE_BACKEND_FILENAME};
+use crate::forwards_iter::SimpleForwardsBlockRootsIterator;
+use crate::impls::beacon_state::{get_full_state, store_full_state};
+use crate::leveldb_store::LevelDB;
+use std::path::Path;
+
+/// A `KeyValueStore` which is chosen at runtime by the `DatabaseBackend` in the `StoreConfig`.
+pub enum DiskBackend<E: EthSpec> {
+    LevelDb(LevelDB<E>),
+    Sled(SledDB<E>),
+}
+
+impl<E: EthSpec> DiskBackend<E> {
+    /// Open a database at `path`, creating a new database if one does not already exist.
+    ///
+    /// The backend is recorded alongside the database. Returns an error if an existing database
+    /// at `path` uses a different backend.
+    pub fn open(backend: DatabaseBackend, path: &Path) -> Result<Self, Error> {
+        if let Some(existing_backend) = DatabaseBackend::detect(path)? {
+            if existing_backend != backend {
+                return Err(Error::DatabaseBackendMismatch {
+                    database: existing_backend,
+                    config: backend,
+                });
+            }
+        }
+
+        let db = match backend {
+            DatabaseBackend::LevelDb => LevelDB::open(path).map(DiskBackend::LevelDb),
+            DatabaseBackend::Sled => SledDB::open(path).map(DiskBackend::Sled),
+        }?;
+
+        if !path.join(DATABASE_BACKEND_FILENAME).exists() {
+            backend.record(path)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Open the existing database at `path` with whichever backend created it.
+    ///
+    /// Returns an error rather than creating a new database if one does not exist.
+    pub fn open_existing(path: &Path) -> Result<Self, Error> {
+        match DatabaseBackend::detect(path)? {
+            Some(DatabaseBackend::LevelDb) => {
+                LevelDB::open_existing(path).map(DiskBackend::LevelDb)
+            }
+            Some(DatabaseBackend::Sled) => SledDB::open(path).map(DiskBackend::Sled),
+            None => Err(Error::DBError {
+                message: format!("No database found at {:?}", path),
+            }),
+        }
+    }
+}
+
+impl<E: EthSpec> Store<E> for DiskBackend<E> {
+    type ForwardsBlockRootsIterator = SimpleForwardsBlockRootsIterator;
+
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.get_bytes(col, key),
+            DiskBackend::Sled(db) => db.get_bytes(col, key),
+        }
+    }
+
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.put_bytes(col, key, val),
+            DiskBackend::Sled(db) => db.put_bytes(col, key, val),
+        }
+    }
+
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.key_exists(col, key),
+            DiskBackend::Sled(db) => db.key_exists(col, key),
+        }
+    }
+
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.key_delete(col, key),
+            DiskBackend::Sled(db) => db.key_delete(col, key),
+        }
+    }
+
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.do_atomically(batch),
+            DiskBackend::Sled(db) => db.do_atomically(batch),
+        }
+    }
+
+    fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        store_full_state(self, state_root, &state)
+    }
+
+    fn get_state(
+        &self,
+        state_root: &Hash256,
+        _: Option<Slot>,
+    ) -> Result<Option<BeaconState<E>>, Error> {
+        get_full_state(self, state_root)
+    }
+
+    fn forwards_block_roots_iterator(
+        store: Arc<Self>,
+        start_slot: Slot,
+        end_state: BeaconState<E>,
+        end_block_root: Hash256,
+        _: &ChainSpec,
+    ) -> Self::ForwardsBlockRootsIterator {
+        SimpleForwardsBlockRootsIterator::new(store, start_slot, end_state, end_block_root)
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for DiskBackend<E> {
+    fn is_empty(&self) -> Result<bool, Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.is_empty(),
+            DiskBackend::Sled(db) => db.is_empty(),
+        }
+    }
+
+    fn iter_raw_entries<'a>(&'a self) -> Box<dyn Iterator<Item = RawEntry> + 'a> {
+        match self {
+            DiskBackend::LevelDb(db) => db.iter_raw_entries(),
+            DiskBackend::Sled(db) => db.iter_raw_entries(),
+        }
+    }
+
+    fn compact(&self) -> Result<(), Error> {
+        match self {
+            DiskBackend::LevelDb(db) => db.compact(),
+            DiskBackend::Sled(db) => db.compact(),
+        }
+    }
+}
@@ -0,0 +1,537 @@
+//! A streaming archive format for moving finalized blocks and restore point states between
+//! databases, without syncing them from peers.
+//!
+//! The layout follows the `e2store` format used by era files. An archive is a sequence of
+//! records, each with an 8-byte header (a 2-byte type, a 4-byte little-endian length and 2 zero
+//! bytes) followed by `length` bytes of data. It begins with a `Version` record, followed by any
+//! number of chunks:
+//!
+//! ```text
+//! chunk := Block* | State | BlockIndex | StateIndex
+//! ```
+//!
+//! A chunk contains the blocks in the slots `start_slot..state.slot` and the restore point state
+//! at `state.slot`, whose `block_roots` authenticate the blocks. Blocks and states are SSZ encoded
+//! and compressed with the snappy framing format. The indices give the offset of each block (or
+//! the state) from the start of the index record, with zero for a skipped slot, so that an
+//! archive can be searched without decoding every record.
+
+use crate::Error;
+use snap::read::FrameDecoder;
+use snap::write::FrameEncoder;
+use ssz::{Decode, Encode};
+use std::io::{self, Read, Write};
+use types::{BeaconState, BeaconStateError, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The length of the header which precedes every record.
+pub const RECORD_HEADER_LEN: u64 = 8;
+
+/// The record types used in an archive.
+pub const VERSION: [u8; 2] = [0x65, 0x32];
+pub const COMPRESSED_SIGNED_BEACON_BLOCK: [u8; 2] = [0x01, 0x00];
+pub const COMPRESSED_BEACON_STATE: [u8; 2] = [0x02, 0x00];
+pub const SLOT_INDEX: [u8; 2] = [0x69, 0x32];
+
+#[derive(Debug, PartialEq)]
+pub enum EraError {
+    Io(String),
+    /// The archive does not begin with a `Version` record.
+    MissingVersion,
+    /// A record of type `record_type` was found where a different record was expected.
+    UnexpectedRecord {
+        record_type: [u8; 2],
+        position: u64,
+    },
+    /// The archive ended part-way through a record or chunk.
+    Truncated {
+        position: u64,
+    },
+    RecordTooLarge {
+        length: usize,
+    },
+    InvalidSlotIndex {
+        position: u64,
+    },
+    /// The index of the chunk starting at `start_slot` does not match its records.
+    IndexMismatch {
+        start_slot: Slot,
+    },
+    /// The chunk is empty, or longer than the `block_roots` of its state.
+    InvalidChunkLength {
+        start_slot: Slot,
+        state_slot: Slot,
+    },
+    /// The blocks of a chunk are not in strictly ascending slot order within the chunk.
+    BlockOutOfRange {
+        slot: Slot,
+        start_slot: Slot,
+        state_slot: Slot,
+    },
+    /// The root of a block is not the root the state has for its slot.
+    BlockRootMismatch {
+        slot: Slot,
+        block_root: Hash256,
+        expected_block_root: Hash256,
+    },
+    /// The parent of a block is not the previous block in the state's `block_roots`.
+    ParentRootMismatch {
+        slot: Slot,
+        parent_root: Hash256,
+        expected_parent_root: Hash256,
+    },
+    /// The state has a new block root at `slot`, but the chunk has no block for it.
+    MissingBlock {
+        slot: Slot,
+    },
+    /// The block root prior to the chunk is unknown, so a block at `start_slot` can't be checked.
+    UnknownPreviousBlockRoot {
+        start_slot: Slot,
+    },
+    /// The state root of a chunk is not the state root of its slot in the database, or in the
+    /// `state_roots` of the next chunk.
+    StateRootMismatch {
+        slot: Slot,
+        state_root: Hash256,
+        expected_state_root: Hash256,
+    },
+    /// The state root of a chunk is not the restore point already stored for its slot.
+    RestorePointConflict {
+        slot: Slot,
+        state_root: Hash256,
+        restore_point_state_root: Hash256,
+    },
+    /// The state of a chunk is not known to the database, nor authenticated by the next chunk.
+    UnknownStateRoot {
+        slot: Slot,
+    },
+    /// The archive changed while it was being imported.
+    ArchiveChanged,
+    /// An exported range must start and end on restore points in the freezer database.
+    InvalidExportRange {
+        from_slot: Slot,
+        to_slot: Slot,
+        slots_per_restore_point: u64,
+        latest_restore_point_slot: Slot,
+    },
+    /// An imported chunk must cover exactly one restore point interval of the database.
+    ChunkNotRestorePointInterval {
+        start_slot: Slot,
+        state_slot: Slot,
+        slots_per_restore_point: u64,
+    },
+    /// An imported chunk must precede the split slot, as newer states are in the hot database.
+    ChunkAfterSplit {
+        state_slot: Slot,
+        split_slot: Slot,
+    },
+    BeaconStateError(BeaconStateError),
+}
+
+impl From<io::Error> for EraError {
+    fn from(e: io::Error) -> EraError {
+        EraError::Io(format!("{:?}", e))
+    }
+}
+
+impl From<BeaconStateError> for EraError {
+    fn from(e: BeaconStateError) -> EraError {
+        EraError::BeaconStateError(e)
+    }
+}
+
+/// The number of chunks and blocks written to the database by `HotColdDB::import_era`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EraImport {
+    pub chunks: usize,
+    pub blocks: usize,
+}
+
+/// The blocks of one restore point interval, and the restore point state which ends it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EraChunk<E: EthSpec> {
+    pub start_slot: Slot,
+    pub blocks: Vec<SignedBeaconBlock<E>>,
+    pub state: BeaconState<E>,
+}
+
+impl<E: EthSpec> EraChunk<E> {
+    /// Check that the blocks are exactly the blocks the state's `block_roots` has for the slots of
+    /// the chunk, returning their roots.
+    ///
+    /// Every slot where the block root changes must have a block with that root, whose parent is
+    /// the block root of the previous slot. Skipped slots must repeat the previous block root.
+    ///
+    /// The block root at `start_slot - 1` is read from the state if it reaches back that far,
+    /// otherwise `previous_block_root` must provide it. A chunk starting at genesis must begin
+    /// with the genesis block.
+    pub fn verify(&self, previous_block_root: Option<Hash256>) -> Result<Vec<Hash256>, EraError> {
+        let state_slot = self.state.slot;
+        if self.start_slot >= state_slot
+            || state_slot - self.start_slot > E::slots_per_historical_root() as u64
+        {
+            return Err(EraError::InvalidChunkLength {
+                start_slot: self.start_slot,
+                state_slot,
+            });
+        }
+
+        let mut previous_root = if self.start_slot == 0 {
+            // The genesis block has a zero parent root.
+            Hash256::zero()
+        } else {
+            self.state
+                .get_block_root(self.start_slot - 1)
+                .ok()
+                .copied()
+                .or(previous_block_root)
+                .ok_or_else(|| EraError::UnknownPreviousBlockRoot {
+                    start_slot: self.start_slot,
+                })?
+        };
+
+        let mut blocks = self.blocks.iter().peekable();
+        let mut block_roots = Vec::with_capacity(self.blocks.len());
+
+        for slot in self.start_slot.as_u64()..state_slot.as_u64() {
+            let slot = Slot::new(slot);
+            let expected_block_root = *self.state.get_block_root(slot)?;
+
+            let block = match blocks.peek() {
+                Some(block) if block.slot() < slot || block.slot() >= state_slot => {
+                    return Err(EraError::BlockOutOfRange {
+                        slot: block.slot(),
+                        start_slot: self.start_slot,
+                        state_slot,
+                    });
+                }
+                Some(block) if block.slot() == slot => blocks.next(),
+                _ => None,
+            };
+
+            match block {
+                Some(block) => {
+                    let block_root = block.canonical_root();
+                    if block_root != expected_block_root {
+                        return Err(EraError::BlockRootMismatch {
+                            slot,
+                            block_root,
+                            expected_block_root,
+                        });
+                    }
+                    if block.parent_root() != previous_root {
+                        return Err(EraError::ParentRootMismatch {
+                            slot,
+                            parent_root: block.parent_root(),
+                            expected_parent_root: previous_root,
+                        });
+                    }
+                    block_roots.push(block_root);
+                }
+                None => {
+                    if previous_root != expected_block_root {
+                        return Err(EraError::MissingBlock { slot });
+                    }
+                }
+            }
+
+            previous_root = expected_block_root;
+        }
+
+        // Any remaining block lies outside the chunk, or shares a slot with an earlier block.
+        if let Some(block) = blocks.next() {
+            return Err(EraError::BlockOutOfRange {
+                slot: block.slot(),
+                start_slot: self.start_slot,
+                state_slot,
+            });
+        }
+
+        Ok(block_roots)
+    }
+}
+
+/// The offsets of the records for `offsets.len()` consecutive slots from `start_slot`.
+///
+/// Encoded as the starting slot, an `i64` offset per slot and the number of slots, all
+/// little-endian.
+#[derive(Debug, PartialEq)]
+struct SlotIndex {
+    start_slot: Slot,
+    offsets: Vec<i64>,
+}
+
+impl SlotIndex {
+    /// Index the `records` (slot and position pairs) in the slots `start_slot..start_slot + count`,
+    /// for an index record written at `index_position`.
+    fn new(start_slot: Slot, count: u64, index_position: u64, records: &[(Slot, u64)]) -> Self {
+        let mut offsets = vec![0; count as usize];
+        for &(slot, position) in records {
+            if slot >= start_slot && slot < start_slot + count {
+                offsets[(slot - start_slot).as_usize()] = position as i64 - index_position as i64;
+            }
+        }
+        Self {
+            start_slot,
+            offsets,
+        }
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + 8 * self.offsets.len());
+        bytes.extend_from_slice(&self.start_slot.as_u64().to_le_bytes());
+        for offset in &self.offsets {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.offsets.len() as u64).to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8], position: u64) -> Result<Self, EraError> {
+        let invalid = || EraError::InvalidSlotIndex { position };
+        if bytes.len() < 16 || bytes.len() % 8 != 0 {
+            return Err(invalid());
+        }
+
+        let mut words = bytes.chunks(8).map(|word| {
+            let mut array = [0; 8];
+            array.copy_from_slice(word);
+            array
+        });
+        let start_slot = Slot::new(u64::from_le_bytes(words.next().ok_or_else(invalid)?));
+        let count = u64::from_le_bytes(words.next_back().ok_or_else(invalid)?);
+        let offsets = words.map(i64::from_le_bytes).collect::<Vec<_>>();
+
+        if count != offsets.len() as u64 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            start_slot,
+            offsets,
+        })
+    }
+}
+
+fn compress(bytes: &[u8]) -> Result<Vec<u8>, EraError> {
+    let mut encoder = FrameEncoder::new(vec![]);
+    encoder.write_all(bytes)?;
+    encoder
+        .into_inner()
+        .map_err(|e| EraError::Io(format!("{:?}", e)))
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, EraError> {
+    let mut decompressed = vec![];
+    FrameDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Writes an archive to `writer`, one chunk at a time.
+pub struct EraWriter<W: Write> {
+    writer: W,
+    position: u64,
+}
+
+impl<W: Write> EraWriter<W> {
+    /// Write the `Version` record which begins the archive.
+    pub fn new(writer: W) -> Result<Self, Error> {
+        let mut era_writer = Self {
+            writer,
+            position: 0,
+        };
+        era_writer.write_record(VERSION, &[])?;
+        Ok(era_writer)
+    }
+
+    /// Append a chunk to the archive. The chunk is not verified.
+    pub fn write_chunk<E: EthSpec>(&mut self, chunk: &EraChunk<E>) -> Result<(), Error> {
+        let mut block_positions = Vec::with_capacity(chunk.blocks.len());
+        for block in &chunk.blocks {
+            block_positions.push((block.slot(), self.position));
+            self.write_record(
+                COMPRESSED_SIGNED_BEACON_BLOCK,
+                &compress(&block.as_ssz_bytes())?,
+            )?;
+        }
+
+        let state_slot = chunk.state.slot;
+        let state_position = self.position;
+        self.write_record(
+            COMPRESSED_BEACON_STATE,
+            &compress(&chunk.state.as_ssz_bytes())?,
+        )?;
+
+        let block_index = SlotIndex::new(
+            chunk.start_slot,
+            (state_slot - chunk.start_slot).as_u64(),
+            self.position,
+            &block_positions,
+        );
+        self.write_record(SLOT_INDEX, &block_index.as_bytes())?;
+
+        let state_index = SlotIndex::new(
+            state_slot,
+            1,
+            self.position,
+            &[(state_slot, state_position)],
+        );
+        self.write_record(SLOT_INDEX, &state_index.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Flush the archive, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.writer.flush().map_err(EraError::from)?;
+        Ok(self.writer)
+    }
+
+    fn write_record(&mut self, record_type: [u8; 2], data: &[u8]) -> Result<(), EraError> {
+        if data.len() > u32::max_value() as usize {
+            return Err(EraError::RecordTooLarge { length: data.len() });
+        }
+
+        let mut header = [0; RECORD_HEADER_LEN as usize];
+        header[0..2].copy_from_slice(&record_type);
+        header[2..6].copy_from_slice(&(data.len() as u32).to_le_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)?;
+        self.position += RECORD_HEADER_LEN + data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Reads an archive from `reader`, one chunk at a time.
+pub struct EraReader<R: Read> {
+    reader: R,
+    position: u64,
+}
+
+impl<R: Read> EraReader<R> {
+    /// Read the `Version` record which begins the archive.
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut era_reader = Self {
+            reader,
+            position: 0,
+        };
+        match era_reader.read_record()? {
+            Some((VERSION, _)) => Ok(era_reader),
+            _ => Err(EraError::MissingVersion.into()),
+        }
+    }
+
+    /// Read the next chunk, returning `None` at the end of the archive.
+    ///
+    /// The indices of the chunk are checked against the positions of its records, but the blocks
+    /// are not verified against the state, see `EraChunk::verify`.
+    pub fn next_chunk<E: EthSpec>(&mut self) -> Result<Option<EraChunk<E>>, Error> {
+        let mut blocks = vec![];
+        let mut block_positions = vec![];
+
+        let (state, state_position) = loop {
+            let position = self.position;
+            let (record_type, data) = match self.read_record()? {
+                Some(record) => record,
+                None if blocks.is_empty() => return Ok(None),
+                None => return Err(EraError::Truncated { position }.into()),
+            };
+
+            match record_type {
+                COMPRESSED_SIGNED_BEACON_BLOCK => {
+                    let block = SignedBeaconBlock::from_ssz_bytes(&decompress(&data)?)?;
+                    block_positions.push((block.slot(), position));
+                    blocks.push(block);
+                }
+                COMPRESSED_BEACON_STATE => {
+                    let state = BeaconState::from_ssz_bytes(&decompress(&data)?)?;
+                    break (state, position);
+                }
+                record_type => {
+                    return Err(EraError::UnexpectedRecord {
+                        record_type,
+                        position,
+                    }
+                    .into())
+                }
+            }
+        };
+
+        let (block_index_position, block_index) = self.read_slot_index()?;
+        let start_slot = block_index.start_slot;
+        let expected_block_index = SlotIndex::new(
+            start_slot,
+            block_index.offsets.len() as u64,
+            block_index_position,
+            &block_positions,
+        );
+
+        let (state_index_position, state_index) = self.read_slot_index()?;
+        let expected_state_index = SlotIndex::new(
+            state.slot,
+            1,
+            state_index_position,
+            &[(state.slot, state_position)],
+        );
+
+        if block_index != expected_block_index
+            || start_slot + block_index.offsets.len() as u64 != state.slot
+            || state_index != expected_state_index
+        {
+            return Err(EraError::IndexMismatch { start_slot }.into());
+        }
+
+        Ok(Some(EraChunk {
+            start_slot,
+            blocks,
+            state,
+        }))
+    }
+
+    fn read_slot_index(&mut self) -> Result<(u64, SlotIndex), EraError> {
+        let position = self.position;
+        match self.read_record()? {
+            Some((SLOT_INDEX, data)) => Ok((position, SlotIndex::from_bytes(&data, position)?)),
+            Some((record_type, _)) => Err(EraError::UnexpectedRecord {
+                record_type,
+                position,
+            }),
+            None => Err(EraError::Truncated { position }),
+        }
+    }
+
+    /// Read the next record, returning `None` if the archive ends before it.
+    fn read_record(&mut self) -> Result<Option<([u8; 2], Vec<u8>)>, EraError> {
+        let position = self.position;
+        let mut header = [0; RECORD_HEADER_LEN as usize];
+        let mut header_len = 0;
+        while header_len < header.len() {
+            match self.reader.read(&mut header[header_len..]) {
+                Ok(0) => break,
+                Ok(n) => header_len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if header_len == 0 {
+            return Ok(None);
+        } else if header_len < header.len() {
+            return Err(EraError::Truncated { position });
+        }
+
+        let record_type = [header[0], header[1]];
+        let mut length = [0; 4];
+        length.copy_from_slice(&header[2..6]);
+        let length = u64::from(u32::from_le_bytes(length));
+
+        let mut data = vec![];
+        (&mut self.reader).take(length).read_to_end(&mut data)?;
+        if data.len() as u64 != length {
+            return Err(EraError::Truncated { position });
+        }
+
+        self.position += RECORD_HEADER_LEN + length;
+        Ok(Some((record_type, data)))
+    }
+}
@@ -1,4 +1,6 @@
 use crate::chunked_vector::ChunkError;
+use crate::config::DatabaseBackend;
+use crate::era::EraError;
 use crate::hot_cold_store::HotColdDBError;
 use ssz::DecodeError;
 use types::BeaconStateError;
@@ -10,8 +12,16 @@ pub enum Error {
     BeaconStateError(BeaconStateError),
     PartialBeaconStateError,
     HotColdDBError(HotColdDBError),
-    DBError { message: String },
+    DBError {
+        message: String,
+    },
+    /// The existing database was created with a different backend.
+    DatabaseBackendMismatch {
+        database: DatabaseBackend,
+        config: DatabaseBackend,
+    },
     RlpError(String),
+    EraError(EraError),
 }
 
 impl From<DecodeError> for Error {
@@ -38,6 +48,12 @@ impl From<BeaconStateError> for Error {
     }
 }
 
+impl From<EraError> for Error {
+    fn from(e: EraError) -> Error {
+        Error::EraError(e)
+    }
+}
+
 impl From<DBError> for Error {
     fn from(e: DBError) -> Error {
         Error::DBError { message: e.message }
@@ -1,14 +1,19 @@
 use crate::chunked_iter::ChunkedVectorIter;
 use crate::chunked_vector::BlockRoots;
 use crate::iter::{BlockRootsIterator, ReverseBlockRootIterator};
-use crate::{DiskStore, Store};
+use crate::{DiskStore, KeyValueStore, Store};
 use slog::error;
 use std::sync::Arc;
 use types::{BeaconState, ChainSpec, EthSpec, Hash256, Slot};
 
 /// Forwards block roots iterator that makes use of the `block_roots` table in the freezer DB.
-pub struct FrozenForwardsBlockRootsIterator<E: EthSpec> {
-    inner: ChunkedVectorIter<BlockRoots, E>,
+pub struct FrozenForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
+    inner: ChunkedVectorIter<BlockRoots, E, Hot, Cold>,
 }
 
 /// Forwards block roots iterator that reverses a backwards iterator (only good for short ranges).
@@ -18,9 +23,14 @@ pub struct SimpleForwardsBlockRootsIterator {
 }
 
 /// Fusion of the above two approaches to forwards iteration. Fast and efficient.
-pub enum HybridForwardsBlockRootsIterator<E: EthSpec> {
+pub enum HybridForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     PreFinalization {
-        iter: Box<FrozenForwardsBlockRootsIterator<E>>,
+        iter: Box<FrozenForwardsBlockRootsIterator<E, Hot, Cold>>,
         /// Data required by the `PostFinalization` iterator when we get to it.
         continuation_data: Box<Option<(BeaconState<E>, Hash256)>>,
     },
@@ -29,9 +39,14 @@ pub enum HybridForwardsBlockRootsIterator<E: EthSpec> {
     },
 }
 
-impl<E: EthSpec> FrozenForwardsBlockRootsIterator<E> {
+impl<E, Hot, Cold> FrozenForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     pub fn new(
-        store: Arc<DiskStore<E>>,
+        store: Arc<DiskStore<E, Hot, Cold>>,
         start_slot: Slot,
         last_restore_point_slot: Slot,
         spec: &ChainSpec,
@@ -47,7 +62,12 @@ impl<E: EthSpec> FrozenForwardsBlockRootsIterator<E> {
     }
 }
 
-impl<E: EthSpec> Iterator for FrozenForwardsBlockRootsIterator<E> {
+impl<E, Hot, Cold> Iterator for FrozenForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     type Item = (Hash256, Slot);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -85,9 +105,14 @@ impl Iterator for SimpleForwardsBlockRootsIterator {
     }
 }
 
-impl<E: EthSpec> HybridForwardsBlockRootsIterator<E> {
+impl<E, Hot, Cold> HybridForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     pub fn new(
-        store: Arc<DiskStore<E>>,
+        store: Arc<DiskStore<E, Hot, Cold>>,
         start_slot: Slot,
         end_state: BeaconState<E>,
         end_block_root: Hash256,
@@ -120,7 +145,12 @@ impl<E: EthSpec> HybridForwardsBlockRootsIterator<E> {
     }
 }
 
-impl<E: EthSpec> Iterator for HybridForwardsBlockRootsIterator<E> {
+impl<E, Hot, Cold> Iterator for HybridForwardsBlockRootsIterator<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     type Item = (Hash256, Slot);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -1,13 +1,20 @@
 use crate::chunked_vector::{
-    store_updated_vector, BlockRoots, HistoricalRoots, RandaoMixes, StateRoots,
+    load_value_from_db, store_updated_vector, BlockRoots, FixedLengthField, HistoricalRoots,
+    RandaoMixes, StateRoots,
 };
-use crate::config::StoreConfig;
+use crate::config::{OnDiskStoreConfig, StoreConfig};
+use crate::era::{EraChunk, EraError, EraImport, EraReader, EraWriter};
 use crate::forwards_iter::HybridForwardsBlockRootsIterator;
-use crate::impls::beacon_state::store_full_state;
+use crate::impls::beacon_state::full_state_as_kv_store_op;
 use crate::iter::{ParentRootBlockIterator, StateRootsIterator};
+use crate::metadata::{
+    SchemaVersion, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY, UNVERSIONED_SCHEMA_VERSION,
+};
 use crate::metrics;
+use crate::schema_change::migrate_schema;
 use crate::{
-    leveldb_store::LevelDB, DBColumn, Error, PartialBeaconState, SimpleStoreItem, Store, StoreItem,
+    get_key_for_col, DBColumn, DiskBackend, Error, KeyValueStore, KeyValueStoreOp,
+    PartialBeaconState, SimpleStoreItem, Store, StoreItem,
 };
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
@@ -18,7 +25,9 @@ use state_processing::{
     per_block_processing, per_slot_processing, BlockProcessingError, BlockSignatureStrategy,
     SlotProcessingError,
 };
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::sync::Arc;
@@ -26,26 +35,42 @@ use types::*;
 
 /// 32-byte key for accessing the `split` of the freezer DB.
 pub const SPLIT_DB_KEY: &str = "FREEZERDBSPLITFREEZERDBSPLITFREE";
+/// 32-byte key for accessing the `AnchorInfo` of a database that was not started from genesis.
+pub const ANCHOR_INFO_DB_KEY: &str = "ANCHORINFOANCHORINFOANCHORINFOAN";
+/// 32-byte key for accessing the `OnDiskStoreConfig` the database was created with.
+pub const CONFIG_DB_KEY: &str = "STORECONFIGSTORECONFIGSTORECONFI";
 
 /// On-disk database that stores finalized states efficiently.
 ///
 /// Stores vector fields like the `block_roots` and `state_roots` separately, and only stores
 /// intermittent "restore point" states pre-finalization.
-pub struct HotColdDB<E: EthSpec> {
+///
+/// The hot and cold databases may be any `KeyValueStore`. By default they are a `DiskBackend`,
+/// whose backend is chosen by the `StoreConfig`.
+pub struct HotColdDB<E, Hot = DiskBackend<E>, Cold = DiskBackend<E>>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     /// The slot and state root at the point where the database is split between hot and cold.
     ///
     /// States with slots less than `split.slot` are in the cold DB, while states with slots
     /// greater than or equal are in the hot DB.
     split: RwLock<Split>,
+    /// Information about the state the database was initialized from, if it wasn't genesis.
+    anchor_info: RwLock<Option<AnchorInfo>>,
     config: StoreConfig,
     /// Cold database containing compact historical data.
-    pub(crate) cold_db: LevelDB<E>,
+    pub(crate) cold_db: Cold,
     /// Hot database containing duplicated but quick-to-access recent data.
     ///
     /// The hot database also contains all blocks.
-    pub(crate) hot_db: LevelDB<E>,
+    pub(crate) hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// LRU cache of freezer states reconstructed by replaying blocks, keyed by slot.
+    historic_state_cache: Mutex<LruCache<Slot, BeaconState<E>>>,
     /// Chain spec.
     spec: ChainSpec,
     /// Logger.
@@ -70,6 +95,8 @@ pub enum HotColdDBError {
     MissingHotStateSummary(Hash256),
     MissingEpochBoundaryState(Hash256),
     MissingSplitState(Hash256, Slot),
+    MissingBlockToReplay(Hash256),
+    MissingBlockToExport(Hash256),
     HotStateSummaryError(BeaconStateError),
     RestorePointDecodeError(ssz::DecodeError),
     BlockReplayBeaconError(BeaconStateError),
@@ -81,10 +108,41 @@ pub enum HotColdDBError {
         slots_per_epoch: u64,
     },
     RestorePointBlockHashError(BeaconStateError),
+    /// The database already contains a chain, so it can't be anchored on a new state.
+    AnchorOnNonEmptyDatabase {
+        split_slot: Slot,
+    },
+    /// The state at `slot` can't be reconstructed because it precedes the first restore point
+    /// after the anchor.
+    StateBeforeAnchor {
+        slot: Slot,
+        anchor_slot: Slot,
+    },
+    /// The database was written by a newer version of Lighthouse.
+    SchemaDowngrade {
+        database_version: SchemaVersion,
+        software_version: SchemaVersion,
+    },
+    /// There is no migration from the `from` schema version to the `to` version.
+    MissingSchemaMigration {
+        from: SchemaVersion,
+        to: SchemaVersion,
+    },
+    /// The database was created with a different `slots_per_restore_point`, its restore points
+    /// must be re-indexed before it can be opened with the new value.
+    SlotsPerRestorePointMismatch {
+        database: u64,
+        config: u64,
+    },
 }
 
-impl<E: EthSpec> Store<E> for HotColdDB<E> {
-    type ForwardsBlockRootsIterator = HybridForwardsBlockRootsIterator<E>;
+impl<E, Hot, Cold> Store<E> for HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
+    type ForwardsBlockRootsIterator = HybridForwardsBlockRootsIterator<E, Hot, Cold>;
 
     // Defer to the hot database for basic operations (including blocks for now)
     fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
@@ -103,6 +161,30 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         self.hot_db.key_delete(column, key)
     }
 
+    /// Write `batch` to the hot database, then evict any deleted blocks from the block cache.
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let block_column: &str = DBColumn::BeaconBlock.into();
+        let block_column = block_column.as_bytes();
+        let deleted_blocks = batch
+            .iter()
+            .filter_map(|op| match op {
+                KeyValueStoreOp::DeleteKey(key) if key.starts_with(block_column) => {
+                    Some(Hash256::from_slice(&key[block_column.len()..]))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        self.hot_db.do_atomically(batch)?;
+
+        let mut block_cache = self.block_cache.lock();
+        for block_root in &deleted_blocks {
+            block_cache.pop(block_root);
+        }
+
+        Ok(())
+    }
+
     /// Store a block and update the LRU cache.
     fn put_block(&self, block_root: &Hash256, block: SignedBeaconBlock<E>) -> Result<(), Error> {
         // Store on disk.
@@ -114,6 +196,23 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         Ok(())
     }
 
+    /// Store a block along with `batch`, and update the LRU cache.
+    fn put_block_with_ops(
+        &self,
+        block_root: &Hash256,
+        block: SignedBeaconBlock<E>,
+        mut batch: Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        // Store on disk.
+        batch.push(self.block_as_kv_store_op(block_root, &block));
+        self.do_atomically(batch)?;
+
+        // Update cache.
+        self.block_cache.lock().put(*block_root, block);
+
+        Ok(())
+    }
+
     /// Fetch a block from the store.
     fn get_block(&self, block_root: &Hash256) -> Result<Option<SignedBeaconBlock<E>>, Error> {
         metrics::inc_counter(&metrics::BEACON_BLOCK_GET_COUNT);
@@ -183,6 +282,23 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         }
     }
 
+    /// Append the operations which store a hot state to `ops`.
+    ///
+    /// As with `put_state`, a state prior to the split is stored in the freezer database. It is
+    /// written immediately, since it can't be part of a batch for the hot database.
+    fn state_as_kv_store_ops(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        if state.slot < self.get_split_slot() {
+            self.store_cold_state(state_root, state)
+        } else {
+            self.hot_state_as_kv_store_ops(state_root, state, ops)
+        }
+    }
+
     /// Delete a state, ensuring it is removed from the LRU cache, as well as from on-disk.
     ///
     /// It is assumed that all states being deleted reside in the hot DB, even if their slot is less
@@ -190,17 +306,31 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
     /// (which are frozen, and won't be deleted), or valid descendents of the finalized checkpoint
     /// (which will be deleted by this function but shouldn't be).
     fn delete_state(&self, state_root: &Hash256, slot: Slot) -> Result<(), Error> {
-        // Delete the state summary.
-        self.hot_db
-            .key_delete(DBColumn::BeaconStateSummary.into(), state_root.as_bytes())?;
+        let mut ops = vec![];
+        self.delete_state_as_kv_store_ops(state_root, slot, &mut ops);
+        self.hot_db.do_atomically(ops)
+    }
+
+    /// Delete the state summary, and the full state if it lies on an epoch boundary.
+    ///
+    /// The same assumptions as for `delete_state` apply.
+    fn delete_state_as_kv_store_ops(
+        &self,
+        state_root: &Hash256,
+        slot: Slot,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) {
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconStateSummary.into(),
+            state_root.as_bytes(),
+        )));
 
-        // Delete the full state if it lies on an epoch boundary.
         if slot % E::slots_per_epoch() == 0 {
-            self.hot_db
-                .key_delete(DBColumn::BeaconState.into(), state_root.as_bytes())?;
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconState.into(),
+                state_root.as_bytes(),
+            )));
         }
-
-        Ok(())
     }
 
     /// Advance the split point of the store, moving new finalized states to the freezer.
@@ -234,6 +364,9 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
 
         // 1. Copy all of the states between the head and the split slot, from the hot DB
         // to the cold DB.
+        //
+        // These writes are not atomic, but they are harmless if we crash before step 2, as the
+        // split is unchanged and the migration will be repeated.
         let state_root_iter = StateRootsIterator::new(store.clone(), frozen_head);
 
         let mut to_delete = vec![];
@@ -257,18 +390,25 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
             to_delete.push((state_root, slot));
         }
 
-        // 2. Update the split slot
-        *store.split.write() = Split {
+        // 2. Update the split slot and delete the frozen states from the hot DB, in a single
+        // batch so that the hot DB never refers to states which have been deleted.
+        let new_split = Split {
             slot: frozen_head.slot,
             state_root: frozen_head_root,
         };
-        store.store_split()?;
-
-        // 3. Delete from the hot DB
+        let mut hot_db_ops =
+            vec![new_split.as_kv_store_op(&Hash256::from_slice(SPLIT_DB_KEY.as_bytes()))];
         for (state_root, slot) in to_delete {
-            store.delete_state(&state_root, slot)?;
+            store.delete_state_as_kv_store_ops(&state_root, slot, &mut hot_db_ops);
         }
 
+        // Hold the split lock whilst writing, so that no state is loaded from the hot DB using
+        // the old split once it has been deleted.
+        let mut split_guard = store.split.write();
+        store.hot_db.do_atomically(hot_db_ops)?;
+        *split_guard = new_split;
+        drop(split_guard);
+
         debug!(
             store.log,
             "Freezer migration complete";
@@ -278,6 +418,79 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         Ok(())
     }
 
+    /// Initialize the database from a non-genesis state.
+    ///
+    /// The split is set to the anchor, so that the freezer migration never searches for states
+    /// prior to it.
+    ///
+    /// If `state` was advanced through skipped slots after its latest block, the split is set to
+    /// that block instead, and `state` is also stored under the block's state root. The children
+    /// of the block load their parent state by that root and the block's slot.
+    ///
+    /// The anchor info, split and state are written in a single batch, so that an interrupted
+    /// initialization leaves the database empty.
+    fn init_anchor_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        let split_slot = self.get_split_slot();
+        if split_slot != 0 || self.anchor_info.read().is_some() {
+            return Err(HotColdDBError::AnchorOnNonEmptyDatabase { split_slot }.into());
+        }
+
+        let anchor_info = AnchorInfo {
+            anchor_slot: state.slot,
+            anchor_block_root: state.get_latest_block_root(*state_root),
+            oldest_block_slot: state.latest_block_header.slot,
+            oldest_block_parent: state.latest_block_header.parent_root,
+        };
+
+        let mut ops = vec![];
+        self.hot_state_as_kv_store_ops(state_root, state, &mut ops)?;
+
+        let split = if state.latest_block_header.slot < state.slot {
+            let block_state_root = state.latest_block_header.state_root;
+            ops.push(
+                HotStateSummary {
+                    slot: state.slot,
+                    latest_block_root: anchor_info.anchor_block_root,
+                    epoch_boundary_state_root: *state_root,
+                }
+                .as_kv_store_op(&block_state_root),
+            );
+            Split {
+                slot: state.latest_block_header.slot,
+                state_root: block_state_root,
+            }
+        } else {
+            Split {
+                slot: state.slot,
+                state_root: *state_root,
+            }
+        };
+        ops.push(anchor_info.as_kv_store_op(&Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes())));
+        ops.push(split.as_kv_store_op(&Hash256::from_slice(SPLIT_DB_KEY.as_bytes())));
+
+        // Hold the split lock whilst writing, as in the freezer migration.
+        let mut split_guard = self.split.write();
+        self.hot_db.do_atomically(ops)?;
+        *split_guard = split;
+        drop(split_guard);
+        *self.anchor_info.write() = Some(anchor_info);
+
+        Ok(())
+    }
+
+    fn get_anchor_info(&self) -> Option<AnchorInfo> {
+        *self.anchor_info.read()
+    }
+
+    fn set_anchor_info(&self, anchor_info: AnchorInfo) -> Result<(), Error> {
+        self.hot_db.put(
+            &Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes()),
+            &anchor_info,
+        )?;
+        *self.anchor_info.write() = Some(anchor_info);
+        Ok(())
+    }
+
     fn forwards_block_roots_iterator(
         store: Arc<Self>,
         start_slot: Slot,
@@ -321,7 +534,7 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
     }
 }
 
-impl<E: EthSpec> HotColdDB<E> {
+impl<E: EthSpec> HotColdDB<E, DiskBackend<E>, DiskBackend<E>> {
     /// Open a new or existing database, with the given paths to the hot and cold DBs.
     ///
     /// The `slots_per_restore_point` parameter must be a divisor of `SLOTS_PER_HISTORICAL_ROOT`.
@@ -336,23 +549,75 @@ impl<E: EthSpec> HotColdDB<E> {
 
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            anchor_info: RwLock::new(None),
+            cold_db: DiskBackend::open(config.backend, cold_path)?,
+            hot_db: DiskBackend::open(config.backend, hot_path)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            historic_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
             _phantom: PhantomData,
         };
 
+        // Ensure the database is in the format expected by this version of Lighthouse before
+        // reading anything else from it.
+        let stored_schema_version = db.load_schema_version()?;
+        let schema_version = match stored_schema_version {
+            Some(schema_version) => schema_version,
+            None if db.hot_db.is_empty()? => CURRENT_SCHEMA_VERSION,
+            None => UNVERSIONED_SCHEMA_VERSION,
+        };
+        migrate_schema(&db, schema_version, CURRENT_SCHEMA_VERSION)?;
+        // Avoid writing to an up-to-date database, so that offline tools don't modify it.
+        if stored_schema_version != Some(CURRENT_SCHEMA_VERSION) {
+            db.store_schema_version(CURRENT_SCHEMA_VERSION)?;
+        }
+
+        // `reindex_restore_points` records the new config in the freezer database along with the
+        // new restore points, and then in the hot database. If it was interrupted between the two
+        // writes, the copy in the freezer database is correct.
+        let stored_config = match (db.load_disk_config()?, db.load_freezer_disk_config()?) {
+            (hot_config, Some(freezer_config)) if hot_config != Some(freezer_config) => {
+                db.store_disk_config(&freezer_config)?;
+                Some(freezer_config)
+            }
+            (hot_config, _) => hot_config,
+        };
+
+        // Databases created before the config was stored are assumed to have been created with
+        // the current config.
+        let disk_config = db.config.as_disk_config();
+        match stored_config {
+            Some(stored_config) if stored_config != disk_config => {
+                return Err(HotColdDBError::SlotsPerRestorePointMismatch {
+                    database: stored_config.slots_per_restore_point,
+                    config: disk_config.slots_per_restore_point,
+                }
+                .into());
+            }
+            Some(_) => (),
+            None => db.store_disk_config(&disk_config)?,
+        }
+
         // Load the previous split slot from the database (if any). This ensures we can
         // stop and restart correctly.
         if let Some(split) = db.load_split()? {
             *db.split.write() = split;
         }
+        *db.anchor_info.write() = db
+            .hot_db
+            .get(&Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes()))?;
         Ok(db)
     }
+}
 
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: KeyValueStore<E>,
+    Cold: KeyValueStore<E>,
+{
     /// Store a post-finalization state efficiently in the hot database.
     ///
     /// On an epoch boundary, store a full state. On an intermediate slot, store
@@ -361,6 +626,18 @@ impl<E: EthSpec> HotColdDB<E> {
         &self,
         state_root: &Hash256,
         state: &BeaconState<E>,
+    ) -> Result<(), Error> {
+        let mut ops = vec![];
+        self.hot_state_as_kv_store_ops(state_root, state, &mut ops)?;
+        self.hot_db.do_atomically(ops)
+    }
+
+    /// Append the operations for `store_hot_state` to `ops`.
+    fn hot_state_as_kv_store_ops(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
     ) -> Result<(), Error> {
         // On the epoch boundary, store the full state.
         if state.slot % E::slots_per_epoch() == 0 {
@@ -370,13 +647,13 @@ impl<E: EthSpec> HotColdDB<E> {
                 "slot" => state.slot.as_u64(),
                 "state_root" => format!("{:?}", state_root)
             );
-            store_full_state(&self.hot_db, state_root, &state)?;
+            ops.push(full_state_as_kv_store_op(state_root, state));
         }
 
         // Store a summary of the state.
         // We store one even for the epoch boundary states, as we may need their slots
         // when doing a look up by state root.
-        self.put_state_summary(state_root, HotStateSummary::new(state_root, state)?)?;
+        ops.push(HotStateSummary::new(state_root, state)?.as_kv_store_op(state_root));
 
         Ok(())
     }
@@ -442,6 +719,23 @@ impl<E: EthSpec> HotColdDB<E> {
             "state_root" => format!("{:?}", state_root)
         );
 
+        // 1. Store the state and its vector entries.
+        self.store_restore_point_state(state_root, state)?;
+
+        // 2. Store restore point.
+        let restore_point_index = state.slot.as_u64() / self.config.slots_per_restore_point;
+        self.store_restore_point_hash(restore_point_index, *state_root)?;
+
+        Ok(())
+    }
+
+    /// Store a `PartialBeaconState` and the vector entries it refers to in the freezer database,
+    /// without indexing it as a restore point.
+    fn store_restore_point_state(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+    ) -> Result<(), Error> {
         // 1. Convert to PartialBeaconState and store that in the DB.
         let partial_state = PartialBeaconState::from_state_forgetful(state);
         partial_state.db_put(&self.cold_db, state_root)?;
@@ -453,10 +747,6 @@ impl<E: EthSpec> HotColdDB<E> {
         store_updated_vector(HistoricalRoots, db, state, &self.spec)?;
         store_updated_vector(RandaoMixes, db, state, &self.spec)?;
 
-        // 3. Store restore point.
-        let restore_point_index = state.slot.as_u64() / self.config.slots_per_restore_point;
-        self.store_restore_point_hash(restore_point_index, *state_root)?;
-
         Ok(())
     }
 
@@ -474,6 +764,8 @@ impl<E: EthSpec> HotColdDB<E> {
     ///
     /// Will reconstruct the state if it lies between restore points.
     pub fn load_cold_state_by_slot(&self, slot: Slot) -> Result<BeaconState<E>, Error> {
+        self.check_state_after_anchor(slot)?;
+
         if slot % self.config.slots_per_restore_point == 0 {
             let restore_point_idx = slot.as_u64() / self.config.slots_per_restore_point;
             self.load_restore_point_by_index(restore_point_idx)
@@ -482,6 +774,25 @@ impl<E: EthSpec> HotColdDB<E> {
         }
     }
 
+    /// Check that the state at `slot` can be reconstructed from the freezer.
+    ///
+    /// If the database was started from an anchor state, then only genesis and the states at or
+    /// after the first restore point following the anchor are available.
+    fn check_state_after_anchor(&self, slot: Slot) -> Result<(), HotColdDBError> {
+        if let Some(anchor_info) = *self.anchor_info.read() {
+            let low_restore_point_slot =
+                slot / self.config.slots_per_restore_point * self.config.slots_per_restore_point;
+
+            if slot != 0 && low_restore_point_slot < anchor_info.anchor_slot {
+                return Err(HotColdDBError::StateBeforeAnchor {
+                    slot,
+                    anchor_slot: anchor_info.anchor_slot,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Load a restore point state by its `state_root`.
     fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
         let mut partial_state = PartialBeaconState::db_get(&self.cold_db, state_root)?
@@ -506,7 +817,17 @@ impl<E: EthSpec> HotColdDB<E> {
     }
 
     /// Load a frozen state that lies between restore points.
+    ///
+    /// Reconstructed states are cached by slot. On a cache miss, blocks are replayed from the
+    /// latest cached state prior to `slot`, or from the restore point prior to `slot` if there is
+    /// no cached state after it.
     fn load_cold_intermediate_state(&self, slot: Slot) -> Result<BeaconState<E>, Error> {
+        if let Some(state) = self.historic_state_cache.lock().get(&slot) {
+            metrics::inc_counter(&metrics::BEACON_HISTORIC_STATE_CACHE_HIT_COUNT);
+            return Ok(state.clone());
+        }
+        metrics::inc_counter(&metrics::BEACON_HISTORIC_STATE_CACHE_MISS_COUNT);
+
         // 1. Load the restore points either side of the intermediate state.
         let low_restore_point_idx = slot.as_u64() / self.config.slots_per_restore_point;
         let high_restore_point_idx = low_restore_point_idx + 1;
@@ -514,7 +835,13 @@ impl<E: EthSpec> HotColdDB<E> {
         // Acquire the read lock, so that the split can't change while this is happening.
         let split = self.split.read();
 
-        let low_restore_point = self.load_restore_point_by_index(low_restore_point_idx)?;
+        let low_restore_point_slot =
+            Slot::new(low_restore_point_idx * self.config.slots_per_restore_point);
+        // Start from a cached state after the low restore point, if there is one.
+        let start_state = match self.get_closest_historic_state(low_restore_point_slot, slot) {
+            Some(state) => state,
+            None => self.load_restore_point_by_index(low_restore_point_idx)?,
+        };
         // If the slot of the high point lies outside the freezer, use the split state
         // as the upper restore point.
         let high_restore_point = if high_restore_point_idx * self.config.slots_per_restore_point
@@ -526,15 +853,33 @@ impl<E: EthSpec> HotColdDB<E> {
             self.load_restore_point_by_index(high_restore_point_idx)?
         };
 
-        // 2. Load the blocks from the high restore point back to the low restore point.
+        // 2. Load the blocks from the high restore point back to the start state.
         let blocks = self.load_blocks_to_replay(
-            low_restore_point.slot,
+            start_state.slot,
             slot,
             self.get_high_restore_point_block_root(&high_restore_point, slot)?,
         )?;
 
-        // 3. Replay the blocks on top of the low restore point.
-        self.replay_blocks(low_restore_point, blocks, slot)
+        // 3. Replay the blocks on top of the start state.
+        let state = self.replay_blocks(start_state, blocks, slot)?;
+
+        // 4. Cache the reconstructed state.
+        if self.config.historic_state_cache_size > 0 {
+            self.historic_state_cache.lock().put(slot, state.clone());
+        }
+
+        Ok(state)
+    }
+
+    /// Return the cached historic state with the highest slot in `min_slot..max_slot`, if any.
+    fn get_closest_historic_state(&self, min_slot: Slot, max_slot: Slot) -> Option<BeaconState<E>> {
+        let mut cache = self.historic_state_cache.lock();
+        let closest_slot = cache
+            .iter()
+            .map(|(slot, _)| *slot)
+            .filter(|slot| *slot >= min_slot && *slot < max_slot)
+            .max()?;
+        cache.get(&closest_slot).cloned()
     }
 
     /// Get a suitable block root for backtracking from `high_restore_point` to the state at `slot`.
@@ -632,12 +977,627 @@ impl<E: EthSpec> HotColdDB<E> {
         self.split.read().slot
     }
 
+    /// Fetch a copy of the state root at the split slot from memory.
+    ///
+    /// This is the zero hash if the database has never been migrated or anchored.
+    pub fn get_split_state_root(&self) -> Hash256 {
+        self.split.read().state_root
+    }
+
+    /// Return `true` if the state with `state_root` could be loaded, without loading it.
+    ///
+    /// For a hot state this checks its summary and epoch boundary state, for a frozen state it
+    /// checks the pointer to its slot.
+    pub fn state_exists(&self, state_root: &Hash256) -> Result<bool, Error> {
+        if let Some(summary) = self.load_hot_state_summary(state_root)? {
+            self.hot_db.key_exists(
+                DBColumn::BeaconState.into(),
+                summary.epoch_boundary_state_root.as_bytes(),
+            )
+        } else {
+            Ok(self.load_cold_state_slot(state_root)?.is_some())
+        }
+    }
+
     /// Fetch the slot of the most recently stored restore point.
     pub fn get_latest_restore_point_slot(&self) -> Slot {
         (self.get_split_slot() - 1) / self.config.slots_per_restore_point
             * self.config.slots_per_restore_point
     }
 
+    /// Delete the restore points in the freezer database, returning the number deleted.
+    ///
+    /// The genesis restore point is always kept. If the database was initialized from an anchor
+    /// state, the first restore point after the anchor is also kept. The remaining restore points
+    /// can be rebuilt from these by replaying blocks. Until they are rebuilt, other states in the
+    /// freezer database cannot be loaded.
+    pub fn prune_historic_states(&self) -> Result<usize, Error> {
+        let split_slot = self.get_split_slot();
+        if split_slot == 0 {
+            return Ok(0);
+        }
+
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let anchor_restore_point_index = self.anchor_info.read().map(|anchor_info| {
+            (anchor_info.anchor_slot.as_u64() + slots_per_restore_point - 1)
+                / slots_per_restore_point
+        });
+        let latest_restore_point_index =
+            self.get_latest_restore_point_slot().as_u64() / slots_per_restore_point;
+
+        let mut num_pruned = 0;
+        for restore_point_index in 1..=latest_restore_point_index {
+            if Some(restore_point_index) == anchor_restore_point_index {
+                continue;
+            }
+
+            let key = Self::restore_point_key(restore_point_index);
+            if let Some(RestorePointHash { state_root }) =
+                RestorePointHash::db_get(&self.cold_db, &key)?
+            {
+                self.cold_db.delete::<PartialBeaconState<E>>(&state_root)?;
+                self.cold_db.delete::<RestorePointHash>(&key)?;
+                num_pruned += 1;
+            }
+        }
+
+        debug!(
+            self.log,
+            "Pruned historic states";
+            "restore_points" => num_pruned,
+        );
+
+        Ok(num_pruned)
+    }
+
+    /// Rewrite the restore points in the freezer database so that they are
+    /// `new_slots_per_restore_point` slots apart, returning the number of restore points stored.
+    ///
+    /// The new restore points are built by replaying blocks forwards from the genesis restore
+    /// point, or the first restore point after the anchor, so this also rebuilds the restore points
+    /// deleted by `prune_historic_states`. The `ColdStateSummary` of each new restore point is
+    /// stored, the other summaries map state roots to slots and remain valid.
+    ///
+    /// Once the new restore points are stored, they replace the old ones in the index and the new
+    /// config is recorded, then the states of the old restore points are deleted. The database
+    /// must then be re-opened with the new `slots_per_restore_point`. The index is replaced in a
+    /// single write, so an interruption never leaves the freezer database without usable restore
+    /// points.
+    pub fn reindex_restore_points(
+        store: Arc<Self>,
+        new_slots_per_restore_point: u64,
+    ) -> Result<usize, Error> {
+        Self::verify_slots_per_restore_point(new_slots_per_restore_point)?;
+
+        let old_slots_per_restore_point = store.config.slots_per_restore_point;
+        let split = *store.split.read();
+
+        // 1. Find the restore point to start replaying from.
+        let start_restore_point_index = match *store.anchor_info.read() {
+            Some(anchor_info) => {
+                (anchor_info.anchor_slot.as_u64() + old_slots_per_restore_point - 1)
+                    / old_slots_per_restore_point
+            }
+            None => 0,
+        };
+        let start_slot = Slot::new(start_restore_point_index * old_slots_per_restore_point);
+        let first_new_restore_point_slot = Slot::new(
+            (start_slot.as_u64() + new_slots_per_restore_point - 1) / new_slots_per_restore_point
+                * new_slots_per_restore_point,
+        );
+
+        let mut new_restore_points = vec![];
+        if let Ok(genesis_state_root) = store.load_restore_point_hash(0) {
+            new_restore_points.push((0, genesis_state_root));
+        }
+
+        // 2. Replay blocks from the start restore point, storing a state every
+        // `new_slots_per_restore_point` slots.
+        if start_slot < split.slot {
+            let mut state = store.load_restore_point_by_index(start_restore_point_index)?;
+            let split_state = store
+                .get_state(&split.state_root, Some(split.slot))?
+                .ok_or_else(|| HotColdDBError::MissingSplitState(split.state_root, split.slot))?;
+            let split_block_root = split_state.get_latest_block_root(split.state_root);
+
+            let mut block_roots = Self::forwards_block_roots_iterator(
+                store.clone(),
+                start_slot,
+                split_state,
+                split_block_root,
+                &store.spec,
+            )
+            .peekable();
+
+            let mut slot = first_new_restore_point_slot.as_u64();
+            // The genesis restore point has already been kept.
+            if slot == 0 {
+                slot = new_slots_per_restore_point;
+            }
+
+            while slot < split.slot {
+                let target_slot = Slot::new(slot);
+
+                // Load the blocks up to and including the target slot, ignoring skip slots.
+                let mut blocks = vec![];
+                let mut prev_block_root = None;
+                while let Some(&(block_root, block_slot)) = block_roots.peek() {
+                    if block_slot > target_slot {
+                        break;
+                    }
+                    if prev_block_root != Some(block_root) {
+                        let block = store
+                            .get_block(&block_root)?
+                            .ok_or_else(|| HotColdDBError::MissingBlockToReplay(block_root))?;
+                        blocks.push(block);
+                    }
+                    prev_block_root = Some(block_root);
+                    block_roots.next();
+                }
+
+                state = store.replay_blocks(state, blocks, target_slot)?;
+                let state_root = state.canonical_root();
+
+                store.store_restore_point_state(&state_root, &state)?;
+                store.store_cold_state_slot(&state_root, target_slot)?;
+                new_restore_points.push((slot / new_slots_per_restore_point, state_root));
+
+                slot += new_slots_per_restore_point;
+            }
+        }
+
+        // 3. Replace the old restore point index with the new one, recording the new config in
+        // the freezer database in the same write. An interruption leaves either the old or the
+        // new restore points usable.
+        let latest_restore_point_index =
+            store.get_latest_restore_point_slot().as_u64() / old_slots_per_restore_point;
+        let mut old_restore_points = vec![];
+        for restore_point_index in 1..=latest_restore_point_index {
+            if let Ok(state_root) = store.load_restore_point_hash(restore_point_index) {
+                old_restore_points.push((restore_point_index, state_root));
+            }
+        }
+
+        let new_disk_config = OnDiskStoreConfig {
+            slots_per_restore_point: new_slots_per_restore_point,
+        };
+        let new_restore_point_indices = new_restore_points
+            .iter()
+            .map(|(restore_point_index, _)| *restore_point_index)
+            .collect::<HashSet<_>>();
+
+        let mut cold_db_ops = new_restore_points
+            .iter()
+            .map(|(restore_point_index, state_root)| {
+                RestorePointHash {
+                    state_root: *state_root,
+                }
+                .as_kv_store_op(&Self::restore_point_key(*restore_point_index))
+            })
+            .collect::<Vec<_>>();
+        for (restore_point_index, _) in &old_restore_points {
+            if !new_restore_point_indices.contains(restore_point_index) {
+                cold_db_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    RestorePointHash::db_column().into(),
+                    Self::restore_point_key(*restore_point_index).as_bytes(),
+                )));
+            }
+        }
+        cold_db_ops
+            .push(new_disk_config.as_kv_store_op(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes())));
+        store.cold_db.do_atomically(cold_db_ops)?;
+
+        // 4. Record the new config in the hot database. If this is interrupted, `open` copies it
+        // from the freezer database.
+        //
+        // If the restore point after the anchor is not a multiple of the new interval, it is no
+        // longer indexed and the states before the next restore point cannot be reconstructed,
+        // so the anchor is moved forwards to that restore point.
+        let mut hot_db_ops =
+            vec![new_disk_config.as_kv_store_op(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes()))];
+        let new_anchor_info = match *store.anchor_info.read() {
+            Some(anchor_info) if first_new_restore_point_slot != start_slot => Some(AnchorInfo {
+                anchor_slot: first_new_restore_point_slot,
+                ..anchor_info
+            }),
+            _ => None,
+        };
+        if let Some(anchor_info) = &new_anchor_info {
+            hot_db_ops.push(
+                anchor_info.as_kv_store_op(&Hash256::from_slice(ANCHOR_INFO_DB_KEY.as_bytes())),
+            );
+        }
+        store.hot_db.do_atomically(hot_db_ops)?;
+        if new_anchor_info.is_some() {
+            *store.anchor_info.write() = new_anchor_info;
+        }
+
+        // 5. Delete the states of the old restore points that are not also new restore points.
+        let new_state_roots = new_restore_points
+            .iter()
+            .map(|(_, state_root)| *state_root)
+            .collect::<HashSet<_>>();
+        for (_, state_root) in old_restore_points {
+            if !new_state_roots.contains(&state_root) {
+                store.cold_db.delete::<PartialBeaconState<E>>(&state_root)?;
+            }
+        }
+
+        debug!(
+            store.log,
+            "Re-indexed restore points";
+            "old_slots_per_restore_point" => old_slots_per_restore_point,
+            "new_slots_per_restore_point" => new_slots_per_restore_point,
+            "restore_points" => new_restore_points.len(),
+        );
+
+        Ok(new_restore_points.len())
+    }
+
+    /// Write the blocks and restore point states from `from_slot` to `to_slot` to `writer` as an
+    /// archive (see the `era` module), returning the number of chunks written.
+    ///
+    /// Both slots must be restore point slots, and the restore point at `to_slot` must be in the
+    /// freezer database. Each chunk contains the blocks of one restore point interval and the
+    /// restore point state at the end of it.
+    pub fn export_era<W: Write>(
+        &self,
+        from_slot: Slot,
+        to_slot: Slot,
+        writer: W,
+    ) -> Result<usize, Error> {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let latest_restore_point_slot = self.get_latest_restore_point_slot();
+
+        if from_slot >= to_slot
+            || from_slot % slots_per_restore_point != 0
+            || to_slot % slots_per_restore_point != 0
+            || to_slot > latest_restore_point_slot
+        {
+            return Err(EraError::InvalidExportRange {
+                from_slot,
+                to_slot,
+                slots_per_restore_point,
+                latest_restore_point_slot,
+            }
+            .into());
+        }
+
+        let mut writer = EraWriter::new(writer)?;
+        let mut chunks = 0;
+        let mut start_slot = from_slot;
+
+        while start_slot < to_slot {
+            let state = self.load_cold_state_by_slot(start_slot + slots_per_restore_point)?;
+
+            // Load each distinct block root once, ignoring the blocks of skip slots at the start of
+            // the chunk, which belong to an earlier chunk.
+            let mut blocks = vec![];
+            let mut prev_block_root = None;
+            for slot in start_slot.as_u64()..state.slot.as_u64() {
+                let slot = Slot::new(slot);
+                let block_root = *state.get_block_root(slot)?;
+                if prev_block_root == Some(block_root) {
+                    continue;
+                }
+                prev_block_root = Some(block_root);
+
+                let block = self
+                    .get_block(&block_root)?
+                    .ok_or_else(|| HotColdDBError::MissingBlockToExport(block_root))?;
+                if block.slot() == slot {
+                    blocks.push(block);
+                }
+            }
+
+            let chunk = EraChunk {
+                start_slot,
+                blocks,
+                state,
+            };
+            writer.write_chunk(&chunk)?;
+            chunks += 1;
+            start_slot = chunk.state.slot;
+        }
+
+        writer.finish()?;
+
+        debug!(
+            self.log,
+            "Exported archive";
+            "from_slot" => from_slot,
+            "to_slot" => to_slot,
+            "chunks" => chunks,
+        );
+
+        Ok(chunks)
+    }
+
+    /// Store the blocks and restore point states of the archive read from `reader`.
+    ///
+    /// The archive is read twice. The first pass checks every chunk with `check_era`, so that
+    /// nothing is stored from an archive of another chain, or one which has been tampered with.
+    /// The second pass stores the chunks. The state at the split slot is already in the hot
+    /// database, so only the blocks of a chunk ending there are stored.
+    ///
+    /// If the database was started from an anchor and the imported blocks lead back from its
+    /// oldest block, the `AnchorInfo` is updated as if they had been backfilled. States prior to
+    /// the anchor remain unavailable.
+    pub fn import_era<R: Read + Seek>(&self, mut reader: R) -> Result<EraImport, Error> {
+        let checked_chunks = self.check_era(&mut reader)?;
+        reader.seek(SeekFrom::Start(0)).map_err(EraError::from)?;
+
+        let split_slot = self.get_split_slot();
+        let mut reader = EraReader::new(reader)?;
+        let mut imported = EraImport::default();
+        // The slot and parent root of each imported block, by block root.
+        let mut imported_blocks = HashMap::new();
+
+        while let Some(chunk) = reader.next_chunk::<E>()? {
+            let state_slot = chunk.state.slot;
+            let state_root = chunk.state.canonical_root();
+            let checked_chunk = checked_chunks
+                .get(imported.chunks)
+                .filter(|checked_chunk| checked_chunk.state_root == state_root)
+                .ok_or(EraError::ArchiveChanged)?;
+
+            let block_roots = chunk.verify(checked_chunk.previous_block_root)?;
+
+            let mut ops = Vec::with_capacity(chunk.blocks.len());
+            for (block_root, block) in block_roots.iter().zip(&chunk.blocks) {
+                ops.push(self.block_as_kv_store_op(block_root, block));
+                imported_blocks.insert(*block_root, (block.slot(), block.parent_root()));
+            }
+            self.do_atomically(ops)?;
+
+            if state_slot < split_slot {
+                self.store_cold_state(&state_root, &chunk.state)?;
+                self.store_cold_state_slot(&state_root, state_slot)?;
+            }
+
+            imported.chunks += 1;
+            imported.blocks += chunk.blocks.len();
+        }
+
+        if imported.chunks != checked_chunks.len() {
+            return Err(EraError::ArchiveChanged.into());
+        }
+
+        if let Some(anchor_info) = self.get_anchor_info() {
+            let mut new_anchor_info = anchor_info;
+            while let Some(&(slot, parent_root)) =
+                imported_blocks.get(&new_anchor_info.oldest_block_parent)
+            {
+                if slot >= new_anchor_info.oldest_block_slot {
+                    break;
+                }
+                new_anchor_info.oldest_block_slot = slot;
+                new_anchor_info.oldest_block_parent = parent_root;
+            }
+
+            if new_anchor_info != anchor_info {
+                self.set_anchor_info(new_anchor_info)?;
+            }
+        }
+
+        debug!(
+            self.log,
+            "Imported archive";
+            "chunks" => imported.chunks,
+            "blocks" => imported.blocks,
+        );
+
+        Ok(imported)
+    }
+
+    /// Check every chunk of the archive read from `reader` against this database, without storing
+    /// any of it.
+    ///
+    /// Each chunk must cover one restore point interval of this database and end at or before
+    /// the split slot, and its blocks are verified against the `block_roots` of its state (see
+    /// `EraChunk::verify`). Its state root must match:
+    ///
+    /// - The state root this database has for its slot, from the split state or the freezer
+    ///   `state_roots`, if any.
+    /// - The `state_roots` of the next chunk, if that chunk follows on from it.
+    /// - The restore point stored for its slot, if any.
+    ///
+    /// Every chunk must be authenticated by this database, or by a chunk after it.
+    fn check_era<R: Read>(&self, reader: R) -> Result<Vec<CheckedEraChunk>, Error> {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let split = *self.split.read();
+        let split_state = if split.state_root.is_zero() {
+            None
+        } else {
+            self.get_state(&split.state_root, None)?
+        };
+
+        let mut reader = EraReader::new(reader)?;
+        let mut checked_chunks: Vec<CheckedEraChunk> = vec![];
+        // The state slot, state root and latest block root of the previous chunk.
+        let mut previous_chunk: Option<(Slot, Hash256, Hash256)> = None;
+
+        while let Some(chunk) = reader.next_chunk::<E>()? {
+            let start_slot = chunk.start_slot;
+            let state_slot = chunk.state.slot;
+            if start_slot % slots_per_restore_point != 0
+                || state_slot != start_slot + slots_per_restore_point
+            {
+                return Err(EraError::ChunkNotRestorePointInterval {
+                    start_slot,
+                    state_slot,
+                    slots_per_restore_point,
+                }
+                .into());
+            }
+            if state_slot > split.slot {
+                return Err(EraError::ChunkAfterSplit {
+                    state_slot,
+                    split_slot: split.slot,
+                }
+                .into());
+            }
+
+            // The previous chunk, if this chunk follows on from it.
+            let preceding_chunk = previous_chunk
+                .take()
+                .filter(|(previous_state_slot, _, _)| *previous_state_slot == start_slot);
+
+            let previous_block_root = match preceding_chunk {
+                Some((_, _, latest_block_root)) => Some(latest_block_root),
+                None if start_slot == 0 => None,
+                None => match split_state
+                    .as_ref()
+                    .and_then(|state| state.get_block_root(start_slot - 1).ok())
+                {
+                    Some(block_root) => Some(*block_root),
+                    None => self.load_frozen_root::<BlockRoots>(start_slot - 1)?,
+                },
+            };
+            chunk.verify(previous_block_root)?;
+
+            let state_root = chunk.state.canonical_root();
+
+            if let Some((previous_state_slot, previous_state_root, _)) = preceding_chunk {
+                let expected_state_root = *chunk
+                    .state
+                    .get_state_root(previous_state_slot)
+                    .map_err(EraError::from)?;
+                if previous_state_root != expected_state_root {
+                    return Err(EraError::StateRootMismatch {
+                        slot: previous_state_slot,
+                        state_root: previous_state_root,
+                        expected_state_root,
+                    }
+                    .into());
+                }
+            }
+
+            let known_state_root = if state_slot == split.slot {
+                Some(split.state_root)
+            } else {
+                match split_state
+                    .as_ref()
+                    .and_then(|state| state.get_state_root(state_slot).ok())
+                {
+                    Some(state_root) => Some(*state_root),
+                    None => self.load_frozen_root::<StateRoots>(state_slot)?,
+                }
+            };
+            if let Some(expected_state_root) = known_state_root {
+                if state_root != expected_state_root {
+                    return Err(EraError::StateRootMismatch {
+                        slot: state_slot,
+                        state_root,
+                        expected_state_root,
+                    }
+                    .into());
+                }
+            }
+
+            let restore_point_key =
+                Self::restore_point_key(state_slot.as_u64() / slots_per_restore_point);
+            if let Some(restore_point) =
+                RestorePointHash::db_get(&self.cold_db, &restore_point_key)?
+            {
+                if state_root != restore_point.state_root {
+                    return Err(EraError::RestorePointConflict {
+                        slot: state_slot,
+                        state_root,
+                        restore_point_state_root: restore_point.state_root,
+                    }
+                    .into());
+                }
+            }
+
+            checked_chunks.push(CheckedEraChunk {
+                state_slot,
+                state_root,
+                previous_block_root,
+                state_root_known: known_state_root.is_some(),
+                follows_previous_chunk: preceding_chunk.is_some(),
+            });
+            previous_chunk = Some((
+                state_slot,
+                state_root,
+                *chunk
+                    .state
+                    .get_block_root(state_slot - 1)
+                    .map_err(EraError::from)?,
+            ));
+        }
+
+        // Chunks are authenticated from the latest, as each state commits to the state roots
+        // before it.
+        let mut authenticated_by_next_chunk = false;
+        for checked_chunk in checked_chunks.iter().rev() {
+            if !checked_chunk.state_root_known && !authenticated_by_next_chunk {
+                return Err(EraError::UnknownStateRoot {
+                    slot: checked_chunk.state_slot,
+                }
+                .into());
+            }
+            authenticated_by_next_chunk = checked_chunk.follows_previous_chunk;
+        }
+
+        Ok(checked_chunks)
+    }
+
+    /// Load the root at `slot` of the `block_roots` or `state_roots` of the freezer database.
+    ///
+    /// Returns `None` if the freezer database doesn't have a root for `slot`.
+    fn load_frozen_root<F: FixedLengthField<E, Value = Hash256>>(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<Hash256>, Error> {
+        Ok(
+            load_value_from_db::<F, E, _>(&self.cold_db, slot.as_usize())?
+                .filter(|root| !root.is_zero()),
+        )
+    }
+
+    /// Compact the hot and freezer databases, reclaiming the space used by deleted items.
+    pub fn compact(&self) -> Result<(), Error> {
+        self.hot_db.compact()?;
+        self.cold_db.compact()
+    }
+
+    /// Load the schema version of the database from disk.
+    ///
+    /// Returns `None` if the database is new, or was created before schema versioning.
+    pub fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
+        self.hot_db
+            .get(&Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()))
+    }
+
+    /// Store the schema version of the database to disk.
+    pub fn store_schema_version(&self, schema_version: SchemaVersion) -> Result<(), Error> {
+        self.hot_db.put(
+            &Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()),
+            &schema_version,
+        )
+    }
+
+    /// Load the layout-determining parts of the config the database was created with.
+    ///
+    /// Returns `None` if the database is new, or was created before the config was stored.
+    pub fn load_disk_config(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
+        self.hot_db
+            .get(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes()))
+    }
+
+    /// Load the config recorded in the freezer database by `reindex_restore_points`, if any.
+    fn load_freezer_disk_config(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
+        self.cold_db
+            .get(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes()))
+    }
+
+    /// Store the layout-determining parts of the config to disk.
+    fn store_disk_config(&self, disk_config: &OnDiskStoreConfig) -> Result<(), Error> {
+        self.hot_db
+            .put(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes()), disk_config)
+    }
+
     /// Load the split point from disk.
     fn load_split(&self) -> Result<Option<Split>, Error> {
         let key = Hash256::from_slice(SPLIT_DB_KEY.as_bytes());
@@ -728,6 +1688,18 @@ impl<E: EthSpec> HotColdDB<E> {
     }
 }
 
+/// A chunk of an archive which has been checked against the database by `HotColdDB::check_era`.
+struct CheckedEraChunk {
+    state_slot: Slot,
+    state_root: Hash256,
+    /// The block root prior to the chunk, if its state doesn't reach back that far.
+    previous_block_root: Option<Hash256>,
+    /// Whether the database has a state root for `state_slot`.
+    state_root_known: bool,
+    /// Whether the chunk starts at the state slot of the previous chunk.
+    follows_previous_chunk: bool,
+}
+
 /// Struct for storing the split slot and state root in the database.
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
 struct Split {
@@ -749,6 +1721,43 @@ impl SimpleStoreItem for Split {
     }
 }
 
+/// Struct for storing information about the state a database was initialized from, when that
+/// state was not genesis.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub struct AnchorInfo {
+    /// The slot of the state the database was initialized from.
+    ///
+    /// Re-indexing the restore points may move it forwards to the first restore point after it.
+    pub anchor_slot: Slot,
+    /// The root of the block that was applied to the anchor state.
+    pub anchor_block_root: Hash256,
+    /// The slot of the oldest block in the database.
+    pub oldest_block_slot: Slot,
+    /// The parent of the oldest block in the database, which is the next block to backfill.
+    pub oldest_block_parent: Hash256,
+}
+
+impl AnchorInfo {
+    /// Returns `true` once every block back to genesis has been stored.
+    pub fn backfill_complete(&self) -> bool {
+        self.oldest_block_slot == 0
+    }
+}
+
+impl SimpleStoreItem for AnchorInfo {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
 /// Struct for summarising a state in the hot database.
 ///
 /// Allows full reconstruction by replaying blocks.
@@ -10,18 +10,31 @@ pub fn store_full_state<S: Store<E>, E: EthSpec>(
     state: &BeaconState<E>,
 ) -> Result<(), Error> {
     let total_timer = metrics::start_timer(&metrics::BEACON_STATE_WRITE_TIMES);
+
+    let result = store.do_atomically(vec![full_state_as_kv_store_op(state_root, state)]);
+
+    metrics::stop_timer(total_timer);
+
+    result
+}
+
+/// Return the operation which stores the full `state`, for use with `Store::do_atomically`.
+pub fn full_state_as_kv_store_op<E: EthSpec>(
+    state_root: &Hash256,
+    state: &BeaconState<E>,
+) -> KeyValueStoreOp {
     let overhead_timer = metrics::start_timer(&metrics::BEACON_STATE_WRITE_OVERHEAD_TIMES);
 
     let bytes = StorageContainer::new(state).as_ssz_bytes();
     metrics::stop_timer(overhead_timer);
 
-    let result = store.put_bytes(DBColumn::BeaconState.into(), state_root.as_bytes(), &bytes);
-
-    metrics::stop_timer(total_timer);
     metrics::inc_counter(&metrics::BEACON_STATE_WRITE_COUNT);
     metrics::inc_counter_by(&metrics::BEACON_STATE_WRITE_BYTES, bytes.len() as i64);
 
-    result
+    KeyValueStoreOp::PutKeyValue(
+        get_key_for_col(DBColumn::BeaconState.into(), state_root.as_bytes()),
+        bytes,
+    )
 }
 
 pub fn get_full_state<S: Store<E>, E: EthSpec>(
@@ -3,9 +3,12 @@ use crate::forwards_iter::SimpleForwardsBlockRootsIterator;
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use crate::metrics;
 use db_key::Key;
+use leveldb::compaction::Compaction;
+use leveldb::database::batch::{Batch, Writebatch};
 use leveldb::database::kv::KV;
 use leveldb::database::Database;
 use leveldb::error::Error as LevelDBError;
+use leveldb::iterator::Iterable;
 use leveldb::options::{Options, ReadOptions, WriteOptions};
 use std::marker::PhantomData;
 use std::path::Path;
@@ -19,9 +22,19 @@ pub struct LevelDB<E: EthSpec> {
 impl<E: EthSpec> LevelDB<E> {
     /// Open a database at `path`, creating a new database if one does not already exist.
     pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with_options(path, true)
+    }
+
+    /// Open the existing database at `path`, returning an error rather than creating a new
+    /// database if one does not exist.
+    pub fn open_existing(path: &Path) -> Result<Self, Error> {
+        Self::open_with_options(path, false)
+    }
+
+    fn open_with_options(path: &Path, create_if_missing: bool) -> Result<Self, Error> {
         let mut options = Options::new();
 
-        options.create_if_missing = true;
+        options.create_if_missing = create_if_missing;
 
         let db = Database::open(path, options)?;
 
@@ -40,9 +53,9 @@ impl<E: EthSpec> LevelDB<E> {
     }
 
     fn get_key_for_col(col: &str, key: &[u8]) -> BytesKey {
-        let mut col = col.as_bytes().to_vec();
-        col.append(&mut key.to_vec());
-        BytesKey { key: col }
+        BytesKey {
+            key: get_key_for_col(col, key),
+        }
     }
 }
 
@@ -122,6 +135,33 @@ impl<E: EthSpec> Store<E> for LevelDB<E> {
             .map_err(Into::into)
     }
 
+    /// Write all of the operations in `batch` using a single LevelDB `WriteBatch`.
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let mut leveldb_batch = Writebatch::new();
+        for op in batch {
+            match op {
+                KeyValueStoreOp::PutKeyValue(key, value) => {
+                    metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+                    metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, value.len() as i64);
+                    leveldb_batch.put(BytesKey { key }, &value);
+                }
+                KeyValueStoreOp::DeleteKey(key) => {
+                    metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+                    leveldb_batch.delete(BytesKey { key });
+                }
+            }
+        }
+
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        self.db
+            .write(self.write_options(), &leveldb_batch)
+            .map_err(Into::into)
+            .map(|()| {
+                metrics::stop_timer(timer);
+            })
+    }
+
     /// Store a state in the store.
     fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
         store_full_state(self, state_root, &state)
@@ -147,6 +187,31 @@ impl<E: EthSpec> Store<E> for LevelDB<E> {
     }
 }
 
+impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.db.keys_iter(self.read_options()).next().is_none())
+    }
+
+    fn iter_raw_entries<'a>(&'a self) -> Box<dyn Iterator<Item = RawEntry> + 'a> {
+        Box::new(
+            self.db
+                .iter(self.read_options())
+                .map(|(key, value)| Ok((key.key, value))),
+        )
+    }
+
+    /// Compact every column of the database.
+    fn compact(&self) -> Result<(), Error> {
+        for column in DBColumn::all() {
+            let column: &str = (*column).into();
+            let start_key = Self::get_key_for_col(column, &[]);
+            let end_key = Self::get_key_for_col(column, &[0xff; 33]);
+            self.db.compact(&start_key, &end_key);
+        }
+        Ok(())
+    }
+}
+
 impl From<LevelDBError> for Error {
     fn from(e: LevelDBError) -> Error {
         Error::DBError {
@@ -2,7 +2,8 @@
 //!
 //! Provides the following stores:
 //!
-//! - `DiskStore`: an on-disk store backed by leveldb. Used in production.
+//! - `DiskStore`: an on-disk store backed by a `KeyValueStore`, LevelDB or sled by default. Used in
+//!   production.
 //! - `MemoryStore`: an in-memory store backed by a hash-map. Used for testing.
 //!
 //! Provides a simple API for storing/retrieving all types that sometimes needs type-hints. See
@@ -13,31 +14,58 @@ extern crate lazy_static;
 pub mod chunked_iter;
 pub mod chunked_vector;
 pub mod config;
+mod disk_backend;
+pub mod era;
 mod errors;
 mod forwards_iter;
 pub mod hot_cold_store;
 mod impls;
 mod leveldb_store;
 mod memory_store;
+pub mod metadata;
 mod metrics;
 mod partial_beacon_state;
+mod schema_change;
+mod sled_store;
 mod state_batch;
 
 pub mod iter;
 
+use crate::impls::beacon_state::full_state_as_kv_store_op;
 use std::sync::Arc;
 
-pub use self::config::StoreConfig;
-pub use self::hot_cold_store::{HotColdDB as DiskStore, HotStateSummary};
+pub use self::config::{DatabaseBackend, StoreConfig};
+pub use self::disk_backend::DiskBackend;
+pub use self::hot_cold_store::{AnchorInfo, HotColdDB as DiskStore, HotStateSummary};
 pub use self::leveldb_store::LevelDB as SimpleDiskStore;
 pub use self::memory_store::MemoryStore;
 pub use self::partial_beacon_state::PartialBeaconState;
+pub use self::sled_store::SledDB;
 pub use errors::Error;
 pub use impls::beacon_state::StorageContainer as BeaconStateStorageContainer;
 pub use metrics::scrape_for_metrics;
 pub use state_batch::StateBatch;
 pub use types::*;
 
+/// Returns the key used for `key` in `column` of a key-value database.
+pub fn get_key_for_col(column: &str, key: &[u8]) -> Vec<u8> {
+    let mut result = column.as_bytes().to_vec();
+    result.extend_from_slice(key);
+    result
+}
+
+/// A single write to a key-value database, for use in a batch passed to `Store::do_atomically`.
+///
+/// Keys are prefixed by their column, see `get_key_for_col`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyValueStoreOp {
+    PutKeyValue(Vec<u8>, Vec<u8>),
+    DeleteKey(Vec<u8>),
+}
+
+/// The raw (column-prefixed) key and value of an item read from a `KeyValueStore`.
+pub type RawEntry = Result<(Vec<u8>, Vec<u8>), Error>;
+
 /// An object capable of storing and retrieving objects implementing `StoreItem`.
 ///
 /// A `Store` is fundamentally backed by a key-value database, however it provides support for
@@ -58,6 +86,10 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
     /// Removes `key` from `column`.
     fn key_delete(&self, column: &str, key: &[u8]) -> Result<(), Error>;
 
+    /// Execute all of the operations in `batch`, such that either all of them are written to
+    /// disk or none of them are.
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error>;
+
     /// Store an item in `Self`.
     fn put<I: StoreItem>(&self, key: &Hash256, item: &I) -> Result<(), Error> {
         item.db_put(self, key)
@@ -93,6 +125,37 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         self.delete::<SignedBeaconBlock<E>>(block_root)
     }
 
+    /// Return the operation which stores a block, for use with `do_atomically`.
+    fn block_as_kv_store_op(
+        &self,
+        block_root: &Hash256,
+        block: &SignedBeaconBlock<E>,
+    ) -> KeyValueStoreOp {
+        block.as_kv_store_op(block_root)
+    }
+
+    /// Store a block, writing it in a single batch with the operations in `batch`.
+    ///
+    /// Unlike `block_as_kv_store_op`, this updates any cache of blocks in the same way as
+    /// `put_block`.
+    fn put_block_with_ops(
+        &self,
+        block_root: &Hash256,
+        block: SignedBeaconBlock<E>,
+        mut batch: Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        batch.push(self.block_as_kv_store_op(block_root, &block));
+        self.do_atomically(batch)
+    }
+
+    /// Return the operation which deletes a block, for use with `do_atomically`.
+    fn delete_block_as_kv_store_op(&self, block_root: &Hash256) -> KeyValueStoreOp {
+        KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconBlock.into(),
+            block_root.as_bytes(),
+        ))
+    }
+
     /// Store a state in the store.
     fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error>;
 
@@ -124,11 +187,44 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         self.get_state(state_root, slot)
     }
 
+    /// Append the operations which store a state to `ops`, for use with `do_atomically`.
+    fn state_as_kv_store_ops(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        ops.push(full_state_as_kv_store_op(state_root, state));
+        Ok(())
+    }
+
+    /// Return the operation which stores a state summary, for use with `do_atomically`.
+    fn state_summary_as_kv_store_op(
+        &self,
+        state_root: &Hash256,
+        summary: &HotStateSummary,
+    ) -> KeyValueStoreOp {
+        summary.as_kv_store_op(state_root)
+    }
+
     /// Delete a state from the store.
     fn delete_state(&self, state_root: &Hash256, _slot: Slot) -> Result<(), Error> {
         self.key_delete(DBColumn::BeaconState.into(), state_root.as_bytes())
     }
 
+    /// Append the operations which delete a state to `ops`, for use with `do_atomically`.
+    fn delete_state_as_kv_store_ops(
+        &self,
+        state_root: &Hash256,
+        _slot: Slot,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) {
+        ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+            DBColumn::BeaconState.into(),
+            state_root.as_bytes(),
+        )));
+    }
+
     /// (Optionally) Move all data before the frozen slot to the freezer database.
     fn process_finalization(
         _store: Arc<Self>,
@@ -138,6 +234,29 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         Ok(())
     }
 
+    /// Initialize an empty store from a non-genesis `state`, e.g. a weak subjectivity checkpoint.
+    ///
+    /// Blocks and states prior to `state` are not required to be present in the store.
+    ///
+    /// If `state` was advanced through skipped slots after its latest block, it is also stored
+    /// under the state root of that block.
+    fn init_anchor_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        if state.latest_block_header.slot < state.slot {
+            self.put_state(&state.latest_block_header.state_root, state)?;
+        }
+        self.put_state(state_root, state)
+    }
+
+    /// Returns information about the anchor state, if the store was not initialized from genesis.
+    fn get_anchor_info(&self) -> Option<AnchorInfo> {
+        None
+    }
+
+    /// Replace the anchor information, e.g. after backfilling historical blocks.
+    fn set_anchor_info(&self, _anchor_info: AnchorInfo) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Get a forwards (slot-ascending) iterator over the beacon block roots since `start_slot`.
     ///
     /// Will be efficient for frozen portions of the database if using `DiskStore`.
@@ -180,6 +299,23 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
     }
 }
 
+/// A key-value database which can back the hot or cold part of a `DiskStore`.
+///
+/// Keys are prefixed by their column, as described on `Store`.
+pub trait KeyValueStore<E: EthSpec>: Store<E> {
+    /// Returns `true` if the database does not contain any keys.
+    fn is_empty(&self) -> Result<bool, Error>;
+
+    /// Returns an iterator over the raw (column-prefixed) key and value of every item, in key
+    /// order.
+    ///
+    /// Errors reading items are returned rather than skipped.
+    fn iter_raw_entries<'a>(&'a self) -> Box<dyn Iterator<Item = RawEntry> + 'a>;
+
+    /// Compact the database, reclaiming the space used by deleted items.
+    fn compact(&self) -> Result<(), Error>;
+}
+
 /// A unique column identifier.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DBColumn {
@@ -201,6 +337,31 @@ pub enum DBColumn {
     BeaconHistoricalRoots,
     BeaconRandaoMixes,
     DhtEnrs,
+    /// For persisting the reputations of peers across restarts.
+    PeerDB,
+}
+
+impl DBColumn {
+    /// Returns every column.
+    pub fn all() -> &'static [DBColumn] {
+        &[
+            DBColumn::BeaconMeta,
+            DBColumn::BeaconBlock,
+            DBColumn::BeaconState,
+            DBColumn::BeaconChain,
+            DBColumn::OpPool,
+            DBColumn::Eth1Cache,
+            DBColumn::ForkChoice,
+            DBColumn::BeaconRestorePoint,
+            DBColumn::BeaconStateSummary,
+            DBColumn::BeaconBlockRoots,
+            DBColumn::BeaconStateRoots,
+            DBColumn::BeaconHistoricalRoots,
+            DBColumn::BeaconRandaoMixes,
+            DBColumn::DhtEnrs,
+            DBColumn::PeerDB,
+        ]
+    }
 }
 
 impl Into<&'static str> for DBColumn {
@@ -221,6 +382,7 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconHistoricalRoots => "bhr",
             DBColumn::BeaconRandaoMixes => "brm",
             DBColumn::DhtEnrs => "dht",
+            DBColumn::PeerDB => "pdb",
         }
     }
 }
@@ -237,6 +399,12 @@ pub trait SimpleStoreItem: Sized {
     ///
     /// Return an instance of the type and the number of bytes that were read.
     fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error>;
+
+    /// Return the operation which stores `self` under `key`, for use with `do_atomically`.
+    fn as_kv_store_op(&self, key: &Hash256) -> KeyValueStoreOp {
+        let db_key = get_key_for_col(Self::db_column().into(), key.as_bytes());
+        KeyValueStoreOp::PutKeyValue(db_key, self.as_store_bytes())
+    }
 }
 
 /// An item that may be stored in a `Store`.
@@ -341,6 +509,24 @@ mod tests {
         assert_eq!(store.exists::<StorableThing>(&key), Ok(false));
 
         assert_eq!(store.get::<StorableThing>(&key), Ok(None));
+
+        // Write two items and delete a third in a single batch.
+        let (key1, key2) = (Hash256::random(), Hash256::random());
+        store.put(&key2, &item).unwrap();
+        store
+            .do_atomically(vec![
+                item.as_kv_store_op(&key),
+                item.as_kv_store_op(&key1),
+                KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    StorableThing::db_column().into(),
+                    key2.as_bytes(),
+                )),
+            ])
+            .unwrap();
+
+        assert_eq!(store.get(&key), Ok(Some(StorableThing { a: 1, b: 42 })));
+        assert_eq!(store.get(&key1), Ok(Some(StorableThing { a: 1, b: 42 })));
+        assert_eq!(store.exists::<StorableThing>(&key2), Ok(false));
     }
 
     #[test]
@@ -363,6 +549,64 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    fn diskdb_schema_version() {
+        use crate::hot_cold_store::HotColdDBError;
+        use crate::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+        use sloggers::{null::NullLoggerBuilder, Build};
+
+        let hot_dir = tempdir().unwrap();
+        let cold_dir = tempdir().unwrap();
+        let open = || {
+            DiskStore::<MinimalEthSpec>::open(
+                &hot_dir.path(),
+                &cold_dir.path(),
+                StoreConfig::default(),
+                MinimalEthSpec::default_spec(),
+                NullLoggerBuilder.build().unwrap(),
+            )
+        };
+
+        let store = open().unwrap();
+        assert_eq!(
+            store.load_schema_version(),
+            Ok(Some(CURRENT_SCHEMA_VERSION)),
+            "a new database should have the current schema version"
+        );
+
+        let newer_version = CURRENT_SCHEMA_VERSION.next();
+        store.store_schema_version(newer_version).unwrap();
+        drop(store);
+        assert_eq!(
+            open().err(),
+            Some(Error::HotColdDBError(HotColdDBError::SchemaDowngrade {
+                database_version: newer_version,
+                software_version: CURRENT_SCHEMA_VERSION,
+            })),
+            "should refuse to open a database from a newer version"
+        );
+
+        // Write an unsupported version directly to the hot database.
+        let hot_db = SimpleDiskStore::<MinimalEthSpec>::open(&hot_dir.path()).unwrap();
+        hot_db
+            .put(
+                &Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()),
+                &SchemaVersion(0),
+            )
+            .unwrap();
+        drop(hot_db);
+        assert_eq!(
+            open().err(),
+            Some(Error::HotColdDBError(
+                HotColdDBError::MissingSchemaMigration {
+                    from: SchemaVersion(0),
+                    to: SchemaVersion(1),
+                }
+            )),
+            "should refuse to open a database without a migration path"
+        );
+    }
+
     #[test]
     fn simplediskdb() {
         let dir = tempdir().unwrap();
@@ -372,6 +616,99 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    fn simplediskdb_open_existing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        assert!(
+            SimpleDiskStore::<MinimalEthSpec>::open_existing(&path).is_err(),
+            "should not open a database that does not exist"
+        );
+        assert!(!path.exists(), "should not create a database");
+
+        let store = SimpleDiskStore::<MinimalEthSpec>::open(&path).unwrap();
+        let key = Hash256::random();
+        store.put_bytes("exists", key.as_bytes(), &[42]).unwrap();
+        drop(store);
+
+        let store = SimpleDiskStore::<MinimalEthSpec>::open_existing(&path)
+            .expect("should open an existing database");
+        assert_eq!(
+            store.get_bytes("exists", key.as_bytes()).unwrap(),
+            Some(vec![42])
+        );
+    }
+
+    #[test]
+    fn sleddb() {
+        let dir = tempdir().unwrap();
+        let store = SledDB::open(&dir.path()).unwrap();
+
+        test_impl(store);
+    }
+
+    #[test]
+    fn database_backend_is_recorded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        assert_eq!(DatabaseBackend::detect(&path), Ok(None));
+        drop(DiskBackend::<MinimalEthSpec>::open(DatabaseBackend::Sled, &path).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(path.join(config::DATABASE_BACKEND_FILENAME)).unwrap(),
+            "sled"
+        );
+        assert_eq!(
+            DatabaseBackend::detect(&path),
+            Ok(Some(DatabaseBackend::Sled))
+        );
+
+        // LevelDB databases created before the backend was recorded are recognised, and the
+        // backend is recorded once they are opened.
+        let legacy_path = dir.path().join("legacy");
+        drop(SimpleDiskStore::<MinimalEthSpec>::open(&legacy_path).unwrap());
+        assert_eq!(
+            DatabaseBackend::detect(&legacy_path),
+            Ok(Some(DatabaseBackend::LevelDb))
+        );
+        drop(DiskBackend::<MinimalEthSpec>::open(DatabaseBackend::LevelDb, &legacy_path).unwrap());
+        assert!(legacy_path.join(config::DATABASE_BACKEND_FILENAME).exists());
+    }
+
+    #[test]
+    fn diskdb_backend_mismatch() {
+        use sloggers::{null::NullLoggerBuilder, Build};
+
+        let hot_dir = tempdir().unwrap();
+        let cold_dir = tempdir().unwrap();
+        let open = |backend| {
+            DiskStore::<MinimalEthSpec>::open(
+                &hot_dir.path(),
+                &cold_dir.path(),
+                StoreConfig {
+                    backend,
+                    ..StoreConfig::default()
+                },
+                MinimalEthSpec::default_spec(),
+                NullLoggerBuilder.build().unwrap(),
+            )
+        };
+
+        let store = open(DatabaseBackend::Sled).unwrap();
+        test_impl(store);
+
+        assert_eq!(
+            open(DatabaseBackend::LevelDb).err(),
+            Some(Error::DatabaseBackendMismatch {
+                database: DatabaseBackend::Sled,
+                config: DatabaseBackend::LevelDb,
+            }),
+            "should refuse to open a database with a different backend"
+        );
+        assert!(open(DatabaseBackend::Sled).is_ok());
+    }
+
     #[test]
     fn memorydb() {
         let store = MemoryStore::open();
@@ -1,4 +1,4 @@
-use super::{Error, Store};
+use super::{get_key_for_col, Error, KeyValueStore, KeyValueStoreOp, RawEntry, Store};
 use crate::forwards_iter::SimpleForwardsBlockRootsIterator;
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use parking_lot::RwLock;
@@ -32,12 +32,6 @@ impl<E: EthSpec> MemoryStore<E> {
             _phantom: PhantomData,
         }
     }
-
-    fn get_key_for_col(col: &str, key: &[u8]) -> Vec<u8> {
-        let mut col = col.as_bytes().to_vec();
-        col.append(&mut key.to_vec());
-        col
-    }
 }
 
 impl<E: EthSpec> Store<E> for MemoryStore<E> {
@@ -45,14 +39,14 @@ impl<E: EthSpec> Store<E> for MemoryStore<E> {
 
     /// Get the value of some key from the database. Returns `None` if the key does not exist.
     fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
-        let column_key = Self::get_key_for_col(col, key);
+        let column_key = get_key_for_col(col, key);
 
         Ok(self.db.read().get(&column_key).cloned())
     }
 
     /// Puts a key in the database.
     fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
-        let column_key = Self::get_key_for_col(col, key);
+        let column_key = get_key_for_col(col, key);
 
         self.db.write().insert(column_key, val.to_vec());
 
@@ -61,20 +55,36 @@ impl<E: EthSpec> Store<E> for MemoryStore<E> {
 
     /// Return true if some key exists in some column.
     fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
-        let column_key = Self::get_key_for_col(col, key);
+        let column_key = get_key_for_col(col, key);
 
         Ok(self.db.read().contains_key(&column_key))
     }
 
     /// Delete some key from the database.
     fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
-        let column_key = Self::get_key_for_col(col, key);
+        let column_key = get_key_for_col(col, key);
 
         self.db.write().remove(&column_key);
 
         Ok(())
     }
 
+    /// Apply all of the operations in `batch` while holding the write lock.
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let mut db = self.db.write();
+        for op in batch {
+            match op {
+                KeyValueStoreOp::PutKeyValue(key, value) => {
+                    db.insert(key, value);
+                }
+                KeyValueStoreOp::DeleteKey(key) => {
+                    db.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Store a state in the store.
     fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
         store_full_state(self, state_root, &state)
@@ -99,3 +109,25 @@ impl<E: EthSpec> Store<E> for MemoryStore<E> {
         SimpleForwardsBlockRootsIterator::new(store, start_slot, end_state, end_block_root)
     }
 }
+
+impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.db.read().is_empty())
+    }
+
+    fn iter_raw_entries<'a>(&'a self) -> Box<dyn Iterator<Item = RawEntry> + 'a> {
+        let mut entries = self
+            .db
+            .read()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        Box::new(entries.into_iter().map(Ok))
+    }
+
+    /// Nothing to compact, deleted items are freed immediately.
+    fn compact(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
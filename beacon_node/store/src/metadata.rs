@@ -0,0 +1,53 @@
+//! Metadata describing the on-disk format of the database.
+
+use crate::{DBColumn, Error, SimpleStoreItem};
+use ssz::{Decode, Encode};
+use std::fmt;
+
+/// The schema version written by this version of Lighthouse.
+///
+/// This must be incremented (and a migration added to `crate::schema_change`) whenever the
+/// encoding of a stored item changes, e.g. `PartialBeaconState`, `HotStateSummary` or
+/// `PersistedBeaconChain`.
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(1);
+
+/// The schema version of databases created before schema versioning was introduced.
+pub const UNVERSIONED_SCHEMA_VERSION: SchemaVersion = SchemaVersion(1);
+
+/// 32-byte key for accessing the `SchemaVersion` of the database.
+pub const SCHEMA_VERSION_KEY: &str = "SCHEMAVERSIONSCHEMAVERSIONSCHEMA";
+
+/// The version of the on-disk format of the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion(pub u64);
+
+impl SchemaVersion {
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the version that follows `self`.
+    pub fn next(self) -> Self {
+        SchemaVersion(self.0 + 1)
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SimpleStoreItem for SchemaVersion {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(SchemaVersion(u64::from_ssz_bytes(bytes)?))
+    }
+}
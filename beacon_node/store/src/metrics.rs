@@ -62,6 +62,14 @@ lazy_static! {
         "store_beacon_state_cache_clone_time",
         "Time to load a beacon block from the block cache"
     );
+    pub static ref BEACON_HISTORIC_STATE_CACHE_HIT_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_historic_state_cache_hit_total",
+        "Number of hits to the store's cache of reconstructed freezer states"
+    );
+    pub static ref BEACON_HISTORIC_STATE_CACHE_MISS_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_historic_state_cache_miss_total",
+        "Number of misses to the store's cache of reconstructed freezer states"
+    );
     pub static ref BEACON_STATE_READ_TIMES: Result<Histogram> = try_create_histogram(
         "store_beacon_state_read_seconds",
         "Total time required to read a BeaconState from the database"
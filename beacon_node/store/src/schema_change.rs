@@ -0,0 +1,65 @@
+//! Forward migrations of the on-disk database between schema versions.
+//!
+//! Each migration upgrades a database from one `SchemaVersion` to the next. Migrations are run
+//! automatically by `HotColdDB::open`, one version at a time, and the new version is stored after
+//! each step so that an interrupted upgrade resumes from where it left off.
+use crate::hot_cold_store::{HotColdDB, HotColdDBError};
+use crate::metadata::SchemaVersion;
+use crate::Error;
+use slog::info;
+use types::EthSpec;
+
+/// A function that upgrades a database from some version to the next.
+type Migration<E> = fn(&HotColdDB<E>) -> Result<(), Error>;
+
+/// Returns the migrations for each schema version, keyed by the version that they upgrade from.
+///
+/// A migration must be added here whenever `CURRENT_SCHEMA_VERSION` is incremented.
+fn migrations<E: EthSpec>() -> Vec<(SchemaVersion, Migration<E>)> {
+    vec![]
+}
+
+/// Migrates the database from schema version `from` to `to`.
+///
+/// Returns an error if `from` is newer than `to`, since downgrades are not supported.
+pub fn migrate_schema<E: EthSpec>(
+    db: &HotColdDB<E>,
+    from: SchemaVersion,
+    to: SchemaVersion,
+) -> Result<(), Error> {
+    if from > to {
+        return Err(HotColdDBError::SchemaDowngrade {
+            database_version: from,
+            software_version: to,
+        }
+        .into());
+    }
+
+    let migrations = migrations::<E>();
+    let mut version = from;
+
+    while version < to {
+        let migration = migrations
+            .iter()
+            .find(|(migration_from, _)| *migration_from == version)
+            .map(|(_, migration)| migration)
+            .ok_or_else(|| HotColdDBError::MissingSchemaMigration {
+                from: version,
+                to: version.next(),
+            })?;
+
+        migration(db)?;
+
+        version = version.next();
+        db.store_schema_version(version)?;
+
+        info!(
+            db.log,
+            "Database schema migrated";
+            "from" => from.as_u64(),
+            "to" => version.as_u64(),
+        );
+    }
+
+    Ok(())
+}
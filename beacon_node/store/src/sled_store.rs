@@ -0,0 +1,157 @@
+use super::*;
+use crate::forwards_iter::SimpleForwardsBlockRootsIterator;
+use crate::impls::beacon_state::{get_full_state, store_full_state};
+use crate::metrics;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// A wrapped sled database, a pure-Rust embedded alternative to LevelDB.
+pub struct SledDB<E: EthSpec> {
+    db: sled::Db,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: EthSpec> SledDB<E> {
+    /// Open a database at `path`, creating a new database if one does not already exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let db = sled::open(path)?;
+
+        Ok(Self {
+            db,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<E: EthSpec> Store<E> for SledDB<E> {
+    type ForwardsBlockRootsIterator = SimpleForwardsBlockRootsIterator;
+
+    /// Retrieve some bytes in `column` with `key`.
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_READ_COUNT);
+        let timer = metrics::start_timer(&metrics::DISK_DB_READ_TIMES);
+
+        self.db.get(column_key).map_err(Into::into).map(|opt| {
+            opt.map(|bytes| {
+                metrics::inc_counter_by(&metrics::DISK_DB_READ_BYTES, bytes.len() as i64);
+                metrics::stop_timer(timer);
+                bytes.to_vec()
+            })
+        })
+    }
+
+    /// Store some `value` in `column`, indexed with `key`.
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+        metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, val.len() as i64);
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        self.db
+            .insert(column_key, val)
+            .map_err(Into::into)
+            .map(|_| {
+                metrics::stop_timer(timer);
+            })
+    }
+
+    /// Return `true` if `key` exists in `column`.
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_EXISTS_COUNT);
+
+        self.db.contains_key(column_key).map_err(Into::into)
+    }
+
+    /// Removes `key` from `column`.
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+
+        self.db.remove(column_key).map(|_| ()).map_err(Into::into)
+    }
+
+    /// Write all of the operations in `batch` using a single sled `Batch`.
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let mut sled_batch = sled::Batch::default();
+        for op in batch {
+            match op {
+                KeyValueStoreOp::PutKeyValue(key, value) => {
+                    metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+                    metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, value.len() as i64);
+                    sled_batch.insert(key, value);
+                }
+                KeyValueStoreOp::DeleteKey(key) => {
+                    metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+                    sled_batch.remove(key);
+                }
+            }
+        }
+
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        self.db
+            .apply_batch(sled_batch)
+            .map_err(Into::into)
+            .map(|()| {
+                metrics::stop_timer(timer);
+            })
+    }
+
+    /// Store a state in the store.
+    fn put_state(&self, state_root: &Hash256, state: &BeaconState<E>) -> Result<(), Error> {
+        store_full_state(self, state_root, &state)
+    }
+
+    /// Fetch a state from the store.
+    fn get_state(
+        &self,
+        state_root: &Hash256,
+        _: Option<Slot>,
+    ) -> Result<Option<BeaconState<E>>, Error> {
+        get_full_state(self, state_root)
+    }
+
+    fn forwards_block_roots_iterator(
+        store: Arc<Self>,
+        start_slot: Slot,
+        end_state: BeaconState<E>,
+        end_block_root: Hash256,
+        _: &ChainSpec,
+    ) -> Self::ForwardsBlockRootsIterator {
+        SimpleForwardsBlockRootsIterator::new(store, start_slot, end_state, end_block_root)
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for SledDB<E> {
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.db.is_empty())
+    }
+
+    /// Entries which sled fails to read are returned as errors.
+    fn iter_raw_entries<'a>(&'a self) -> Box<dyn Iterator<Item = RawEntry> + 'a> {
+        Box::new(self.db.iter().map(|entry| {
+            entry
+                .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                .map_err(Into::into)
+        }))
+    }
+
+    /// Sled reclaims space in the background, so this only flushes pending writes to disk.
+    fn compact(&self) -> Result<(), Error> {
+        self.db.flush().map(|_| ()).map_err(Into::into)
+    }
+}
+
+impl From<sled::Error> for Error {
+    fn from(e: sled::Error) -> Error {
+        Error::DBError {
+            message: format!("{:?}", e),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Error, HotStateSummary, Store};
+use crate::{Error, HotStateSummary, KeyValueStoreOp, Store};
 use types::{BeaconState, EthSpec, Hash256};
 
 /// A collection of states to be stored in the database.
@@ -33,14 +33,27 @@ impl<E: EthSpec> StateBatch<E> {
         Ok(())
     }
 
-    /// Write the batch to the database.
-    ///
-    /// May fail to write the full batch if any of the items error (i.e. not atomic!)
+    /// Write the batch to the database atomically.
     pub fn commit<S: Store<E>>(self, store: &S) -> Result<(), Error> {
+        let mut ops = vec![];
+        self.into_kv_store_ops(store, &mut ops)?;
+        store.do_atomically(ops)
+    }
+
+    /// Append the operations which store the batch to `ops`, so that they may be written
+    /// atomically alongside other items.
+    pub fn into_kv_store_ops<S: Store<E>>(
+        self,
+        store: &S,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
         self.items.into_iter().try_for_each(|item| match item {
-            BatchItem::Full(state_root, state) => store.put_state(&state_root, &state),
+            BatchItem::Full(state_root, state) => {
+                store.state_as_kv_store_ops(&state_root, &state, ops)
+            }
             BatchItem::Summary(state_root, summary) => {
-                store.put_state_summary(&state_root, summary)
+                ops.push(store.state_summary_as_kv_store_op(&state_root, &summary));
+                Ok(())
             }
         })
     }
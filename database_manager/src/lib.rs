@@ -0,0 +1,545 @@
+//! Provides the `lighthouse db` command, which inspects and maintains the beacon node database
+//! whilst the beacon node is stopped.
+//!
+//! The hot and freezer databases are opened directly, no network or beacon chain is started.
+//! Commands that modify the database (`compact`, `prune-states`, `reindex`, `import` and
+//! `check --prune-bad-heads`) say so in their help text.
+
+use beacon_chain::consistency_check::{check_consistency, prune_bad_heads};
+use beacon_node::{get_data_dir, ClientConfig};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use environment::Environment;
+use slog::{info, warn, Logger};
+use ssz::Encode;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use store::config::OnDiskStoreConfig;
+use store::hot_cold_store::{HotColdDB, CONFIG_DB_KEY};
+use store::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+use store::{DBColumn, DatabaseBackend, DiskBackend, Hash256, KeyValueStore, Slot, Store};
+use types::{ChainSpec, EthSpec};
+
+pub const CMD: &str = "database_manager";
+
+/// The length of the prefix prepended to every key by `DBColumn`.
+const COLUMN_PREFIX_LEN: usize = 3;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .visible_aliases(&["db"])
+        .about(
+            "Utilities for inspecting and maintaining the beacon node database. The beacon node \
+             must not be running.",
+        )
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("freezer-dir")
+                .long("freezer-dir")
+                .value_name("DIR")
+                .help("Data directory for the freezer database.")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("slots-per-restore-point")
+                .long("slots-per-restore-point")
+                .value_name("SLOT_COUNT")
+                .help(
+                    "The slots-per-restore-point used by the beacon node which created the \
+                     database. Defaults to the value stored in the database, or the beacon \
+                     node default for databases which don't store it.",
+                )
+                .global(true)
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("List the number of keys and bytes stored in each database column."),
+        )
+        .subcommand(
+            SubCommand::with_name("version").about("Display the schema version of the database."),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about(
+                    "Check that the blocks and states referenced by the head tracker, fork \
+                     choice and freezer split are present in the database.",
+                )
+                .arg(
+                    Arg::with_name("prune-bad-heads")
+                        .long("prune-bad-heads")
+                        .help(
+                            "Prune the heads with missing blocks or states, so that the beacon \
+                             node can start. Modifies the database.",
+                        ),
+                ),
+        )
+        .subcommand(SubCommand::with_name("compact").about(
+            "Compact the hot and freezer databases, reclaiming the space used by deleted items. \
+             Modifies the database.",
+        ))
+        .subcommand(SubCommand::with_name("prune-states").about(
+            "Delete the restore points in the freezer database, except for the genesis (or \
+             anchor) restore point. Historic states cannot be loaded until the restore points \
+             are rebuilt. Modifies the database.",
+        ))
+        .subcommand(
+            SubCommand::with_name("reindex")
+                .about(
+                    "Rewrite the restore points in the freezer database at a new \
+                     slots-per-restore-point, by replaying blocks from the genesis (or anchor) \
+                     restore point. Modifies the database.",
+                )
+                .arg(
+                    Arg::with_name("new-slots-per-restore-point")
+                        .long("new-slots-per-restore-point")
+                        .value_name("SLOT_COUNT")
+                        .help(
+                            "The new number of slots between restore points. Start the beacon \
+                             node with the same --slots-per-restore-point afterwards.",
+                        )
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about(
+                    "Write the blocks and restore point states between two restore points in the \
+                     freezer database to an archive file, which can be imported by another node.",
+                )
+                .arg(
+                    Arg::with_name("from-slot")
+                        .long("from-slot")
+                        .value_name("SLOT")
+                        .help("The first slot to export, which must be a restore point slot.")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("to-slot")
+                        .long("to-slot")
+                        .value_name("SLOT")
+                        .help(
+                            "The slot of the last restore point state to export. The blocks \
+                             prior to it are exported. Defaults to the latest restore point.",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("The file to write the archive to.")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about(
+                    "Verify and store the blocks and restore point states from an archive file \
+                     written by `export`. The archive must use the same slots-per-restore-point \
+                     and end at or before the split slot. Modifies the database.",
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("The archive file to import.")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dump-state")
+                .about("Write the SSZ bytes of a state from the freezer database to a file.")
+                .arg(
+                    Arg::with_name("slot")
+                        .long("slot")
+                        .value_name("SLOT")
+                        .help("The slot of the state, which must be prior to the split slot.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("The file to write the state to. Defaults to state_<slot>.ssz")
+                        .takes_value(true),
+                ),
+        )
+}
+
+/// Run the database manager, returning an error if the operation did not succeed.
+pub fn run<E: EthSpec>(matches: &ArgMatches, mut env: Environment<E>) -> Result<(), String> {
+    let log = env.core_context().log;
+    let spec = env.eth2_config.spec.clone();
+    let client_config = parse_client_config::<E>(matches)?;
+
+    let hot_path = client_config
+        .get_db_path()
+        .ok_or_else(|| "Unable to locate the hot database".to_string())?;
+    let cold_path = client_config
+        .get_freezer_db_path()
+        .ok_or_else(|| "Unable to locate the freezer database".to_string())?;
+
+    // Opening the databases as part of a `HotColdDB` creates them if they do not exist, so check
+    // that they exist first.
+    for path in &[&hot_path, &cold_path] {
+        DiskBackend::<E>::open_existing(path)
+            .map_err(|e| format!("Unable to open database at {:?}: {:?}", path, e))?;
+    }
+
+    info!(
+        log,
+        "Located databases";
+        "hot_path" => format!("{:?}", hot_path),
+        "freezer_path" => format!("{:?}", cold_path),
+        "backend" => format!("{}", client_config.store.backend),
+    );
+
+    match matches.subcommand() {
+        ("inspect", Some(_)) => inspect::<E>(&hot_path, &cold_path),
+        ("version", Some(_)) => version::<E>(&hot_path),
+        ("check", Some(matches)) => {
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let report = check_consistency(&db)?
+                .ok_or_else(|| "The database does not contain a beacon chain".to_string())?;
+
+            if report.is_consistent() {
+                info!(
+                    log,
+                    "Database is consistent";
+                    "heads" => report.heads_checked,
+                    "fork_choice_nodes" => report.fork_choice_nodes_checked,
+                );
+                return Ok(());
+            }
+
+            for inconsistency in &report.inconsistencies {
+                warn!(
+                    log,
+                    "Database inconsistency";
+                    "error" => inconsistency.to_string()
+                );
+            }
+
+            if matches.is_present("prune-bad-heads") {
+                prune_bad_heads(&db, &report, &log)?;
+                Ok(())
+            } else {
+                Err(format!(
+                    "The database refers to {} missing blocks or states. Run with \
+                     --prune-bad-heads to prune the heads with missing data",
+                    report.inconsistencies.len()
+                ))
+            }
+        }
+        ("compact", Some(_)) => {
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            db.compact()
+                .map_err(|e| format!("Unable to compact database: {:?}", e))?;
+            info!(log, "Compacted database");
+            Ok(())
+        }
+        ("prune-states", Some(_)) => {
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let pruned = db
+                .prune_historic_states()
+                .map_err(|e| format!("Unable to prune states: {:?}", e))?;
+            info!(
+                log,
+                "Pruned historic states";
+                "restore_points" => pruned,
+                "msg" => "run `lighthouse db compact` to reclaim disk space",
+            );
+            Ok(())
+        }
+        ("reindex", Some(matches)) => {
+            let new_slots_per_restore_point = matches
+                .value_of("new-slots-per-restore-point")
+                .ok_or_else(|| "Expected --new-slots-per-restore-point flag".to_string())?
+                .parse::<u64>()
+                .map_err(|e| format!("Unable to parse --new-slots-per-restore-point: {:?}", e))?;
+            let old_slots_per_restore_point = client_config.store.slots_per_restore_point;
+
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let restore_points =
+                HotColdDB::reindex_restore_points(Arc::new(db), new_slots_per_restore_point)
+                    .map_err(|e| format!("Unable to re-index restore points: {:?}", e))?;
+            info!(
+                log,
+                "Re-indexed restore points";
+                "old_slots_per_restore_point" => old_slots_per_restore_point,
+                "new_slots_per_restore_point" => new_slots_per_restore_point,
+                "restore_points" => restore_points,
+                "msg" => "run `lighthouse db compact` to reclaim disk space",
+            );
+            Ok(())
+        }
+        ("export", Some(matches)) => {
+            let from_slot = parse_slot(matches, "from-slot")?
+                .ok_or_else(|| "Expected --from-slot flag".to_string())?;
+            let to_slot = parse_slot(matches, "to-slot")?;
+            let output = matches
+                .value_of("output")
+                .map(PathBuf::from)
+                .ok_or_else(|| "Expected --output flag".to_string())?;
+
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let to_slot = to_slot.unwrap_or_else(|| db.get_latest_restore_point_slot());
+            let file = File::create(&output)
+                .map_err(|e| format!("Unable to create {:?}: {:?}", output, e))?;
+            let chunks = db
+                .export_era(from_slot, to_slot, BufWriter::new(file))
+                .map_err(|e| format!("Unable to export archive: {:?}", e))?;
+
+            info!(
+                log,
+                "Exported archive";
+                "from_slot" => from_slot,
+                "to_slot" => to_slot,
+                "chunks" => chunks,
+                "path" => format!("{:?}", output),
+            );
+            Ok(())
+        }
+        ("import", Some(matches)) => {
+            let input = matches
+                .value_of("input")
+                .map(PathBuf::from)
+                .ok_or_else(|| "Expected --input flag".to_string())?;
+
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let file =
+                File::open(&input).map_err(|e| format!("Unable to open {:?}: {:?}", input, e))?;
+            let imported = db
+                .import_era(BufReader::new(file))
+                .map_err(|e| format!("Unable to import archive: {:?}", e))?;
+
+            info!(
+                log,
+                "Imported archive";
+                "chunks" => imported.chunks,
+                "blocks" => imported.blocks,
+            );
+            if let Some(anchor_info) = db.get_anchor_info() {
+                info!(
+                    log,
+                    "Database anchor";
+                    "oldest_block_slot" => anchor_info.oldest_block_slot,
+                    "backfill_complete" => anchor_info.backfill_complete(),
+                );
+            }
+            Ok(())
+        }
+        ("dump-state", Some(matches)) => {
+            let slot = matches
+                .value_of("slot")
+                .ok_or_else(|| "Expected --slot flag".to_string())?
+                .parse::<u64>()
+                .map(Slot::new)
+                .map_err(|e| format!("Unable to parse --slot: {:?}", e))?;
+            let output = matches
+                .value_of("output")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(format!("state_{}.ssz", slot)));
+
+            let db = open_store::<E>(&hot_path, &cold_path, client_config, spec, &log)?;
+            let split_slot = db.get_split_slot();
+            if slot >= split_slot {
+                return Err(format!(
+                    "Slot {} is not in the freezer database, which ends at slot {}",
+                    slot, split_slot
+                ));
+            }
+
+            let state = db
+                .load_cold_state_by_slot(slot)
+                .map_err(|e| format!("Unable to load state at slot {}: {:?}", slot, e))?;
+            fs::write(&output, state.as_ssz_bytes())
+                .map_err(|e| format!("Unable to write {:?}: {:?}", output, e))?;
+
+            info!(
+                log,
+                "Wrote state";
+                "slot" => slot,
+                "state_root" => format!("{:?}", state.canonical_root()),
+                "path" => format!("{:?}", output),
+            );
+            Ok(())
+        }
+        _ => Err("Invalid 'db' command. See --help.".to_string()),
+    }
+}
+
+/// Parses the slot given by the `name` flag, if present.
+fn parse_slot(matches: &ArgMatches, name: &str) -> Result<Option<Slot>, String> {
+    matches
+        .value_of(name)
+        .map(|slot| {
+            slot.parse::<u64>()
+                .map(Slot::new)
+                .map_err(|e| format!("Unable to parse --{}: {:?}", name, e))
+        })
+        .transpose()
+}
+
+/// Builds the subset of the beacon node `ClientConfig` that describes the database.
+fn parse_client_config<E: EthSpec>(matches: &ArgMatches) -> Result<ClientConfig, String> {
+    let mut client_config = ClientConfig::default();
+    client_config.data_dir = get_data_dir(matches);
+
+    if let Some(freezer_dir) = matches.value_of("freezer-dir") {
+        client_config.freezer_db_path = Some(PathBuf::from(freezer_dir));
+    }
+
+    // Use the backend which created the database.
+    if let Some(hot_path) = client_config.get_db_path() {
+        if let Some(backend) = DatabaseBackend::detect(&hot_path)
+            .map_err(|e| format!("Unable to read database backend: {:?}", e))?
+        {
+            client_config.store.backend = backend;
+        }
+    }
+
+    if let Some(slots_per_restore_point) = matches.value_of("slots-per-restore-point") {
+        client_config.store.slots_per_restore_point = slots_per_restore_point
+            .parse()
+            .map_err(|_| "slots-per-restore-point is not a valid integer".to_string())?;
+    } else if let Some(disk_config) = load_disk_config::<E>(&client_config)? {
+        client_config.store.slots_per_restore_point = disk_config.slots_per_restore_point;
+    } else {
+        client_config.store.slots_per_restore_point = std::cmp::min(
+            E::slots_per_historical_root() as u64,
+            store::config::DEFAULT_SLOTS_PER_RESTORE_POINT,
+        );
+    }
+
+    Ok(client_config)
+}
+
+/// Opens the hot and freezer databases.
+///
+/// The database must already have the current schema version, so that opening it never runs a
+/// migration.
+fn open_store<E: EthSpec>(
+    hot_path: &Path,
+    cold_path: &Path,
+    client_config: ClientConfig,
+    spec: ChainSpec,
+    log: &Logger,
+) -> Result<HotColdDB<E>, String> {
+    let schema_version = load_schema_version::<E>(hot_path)?;
+    if schema_version != Some(CURRENT_SCHEMA_VERSION) {
+        return Err(format!(
+            "The database has schema version {}, expected version {}. Start the beacon node \
+             with this version of Lighthouse to migrate it",
+            display_version(schema_version),
+            CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    HotColdDB::open(hot_path, cold_path, client_config.store, spec, log.clone())
+        .map_err(|e| format!("Unable to open database: {:?}", e))
+}
+
+/// Reads the schema version from the hot database without migrating it.
+fn load_schema_version<E: EthSpec>(hot_path: &Path) -> Result<Option<SchemaVersion>, String> {
+    let hot_db = DiskBackend::<E>::open_existing(hot_path)
+        .map_err(|e| format!("Unable to open hot database: {:?}", e))?;
+    hot_db
+        .get(&Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()))
+        .map_err(|e| format!("Unable to read schema version: {:?}", e))
+}
+
+/// Reads the config the database was created with, if the hot database exists and stores it.
+fn load_disk_config<E: EthSpec>(
+    client_config: &ClientConfig,
+) -> Result<Option<OnDiskStoreConfig>, String> {
+    let hot_path = match client_config.get_db_path() {
+        Some(hot_path) if hot_path.exists() => hot_path,
+        _ => return Ok(None),
+    };
+    let hot_db = DiskBackend::<E>::open_existing(&hot_path)
+        .map_err(|e| format!("Unable to open hot database: {:?}", e))?;
+    hot_db
+        .get(&Hash256::from_slice(CONFIG_DB_KEY.as_bytes()))
+        .map_err(|e| format!("Unable to read database config: {:?}", e))
+}
+
+fn display_version(schema_version: Option<SchemaVersion>) -> String {
+    schema_version
+        .map(|version| version.to_string())
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+fn version<E: EthSpec>(hot_path: &Path) -> Result<(), String> {
+    let schema_version = load_schema_version::<E>(hot_path)?;
+    println!(
+        "Database schema version: {}",
+        display_version(schema_version)
+    );
+    println!("Supported schema version: {}", CURRENT_SCHEMA_VERSION);
+    Ok(())
+}
+
+/// The number of keys and the total size of the keys and values in a column.
+#[derive(Default)]
+struct ColumnStats {
+    keys: usize,
+    bytes: usize,
+}
+
+fn inspect<E: EthSpec>(hot_path: &Path, cold_path: &Path) -> Result<(), String> {
+    for (name, path) in &[("Hot database", hot_path), ("Freezer database", cold_path)] {
+        let db = DiskBackend::<E>::open_existing(path)
+            .map_err(|e| format!("Unable to open {:?}: {:?}", path, e))?;
+
+        let mut stats: BTreeMap<Vec<u8>, ColumnStats> = BTreeMap::new();
+        for entry in db.iter_raw_entries() {
+            let (key, value) = entry.map_err(|e| format!("Unable to read {:?}: {:?}", path, e))?;
+            let prefix_len = std::cmp::min(key.len(), COLUMN_PREFIX_LEN);
+            let column = stats.entry(key[..prefix_len].to_vec()).or_default();
+            column.keys += 1;
+            column.bytes += key.len() + value.len();
+        }
+
+        println!("{} ({:?}):", name, path);
+        println!("{:<24} {:>12} {:>16}", "column", "keys", "bytes");
+        for (prefix, column) in &stats {
+            println!(
+                "{:<24} {:>12} {:>16}",
+                column_name(prefix),
+                column.keys,
+                column.bytes
+            );
+        }
+        println!(
+            "{:<24} {:>12} {:>16}",
+            "total",
+            stats.values().map(|column| column.keys).sum::<usize>(),
+            stats.values().map(|column| column.bytes).sum::<usize>()
+        );
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Returns the name of the `DBColumn` with the given key prefix.
+fn column_name(prefix: &[u8]) -> String {
+    DBColumn::all()
+        .iter()
+        .find(|column| {
+            let column_prefix: &str = (**column).into();
+            column_prefix.as_bytes() == prefix
+        })
+        .map(|column| format!("{:?}", column))
+        .unwrap_or_else(|| format!("unknown (0x{})", hex::encode(prefix)))
+}
@@ -181,10 +181,17 @@ impl<T: EthSpec> OperationPool<T> {
         // TODO: should maybe insert anyway if the proposer is unknown in the validator index,
         // because they could *become* known later
         verify_proposer_slashing(&slashing, state, VerifySignatures::True, spec)?;
+        self.insert_verified_proposer_slashing(slashing);
+        Ok(())
+    }
+
+    /// Insert a proposer slashing into the pool, without verifying it.
+    ///
+    /// The caller must have already verified `slashing`, including its signatures.
+    pub fn insert_verified_proposer_slashing(&self, slashing: ProposerSlashing) {
         self.proposer_slashings
             .write()
             .insert(slashing.signed_header_1.message.proposer_index, slashing);
-        Ok(())
     }
 
     /// Compute the tuple ID that is used to identify an attester slashing.
@@ -219,9 +226,21 @@ impl<T: EthSpec> OperationPool<T> {
         spec: &ChainSpec,
     ) -> Result<(), AttesterSlashingValidationError> {
         verify_attester_slashing(state, &slashing, VerifySignatures::True, spec)?;
+        self.insert_verified_attester_slashing(slashing, state, spec);
+        Ok(())
+    }
+
+    /// Insert an attester slashing into the pool, without verifying it.
+    ///
+    /// The caller must have already verified `slashing`, including its signatures.
+    pub fn insert_verified_attester_slashing(
+        &self,
+        slashing: AttesterSlashing<T>,
+        state: &BeaconState<T>,
+        spec: &ChainSpec,
+    ) {
         let id = Self::attester_slashing_id(&slashing, state, spec);
         self.attester_slashings.write().insert(id, slashing);
-        Ok(())
     }
 
     /// Get proposer and attester slashings for inclusion in a block.
@@ -328,10 +347,22 @@ impl<T: EthSpec> OperationPool<T> {
         spec: &ChainSpec,
     ) -> Result<(), ExitValidationError> {
         verify_exit_time_independent_only(state, &exit, VerifySignatures::True, spec)?;
+        self.insert_verified_voluntary_exit(exit);
+        Ok(())
+    }
+
+    /// Insert a voluntary exit, without verifying it.
+    ///
+    /// The caller must have already verified `exit`, including its signature.
+    pub fn insert_verified_voluntary_exit(&self, exit: SignedVoluntaryExit) {
         self.voluntary_exits
             .write()
             .insert(exit.message.validator_index, exit);
-        Ok(())
+    }
+
+    /// Get the pooled voluntary exit for the validator with `validator_index`, if any.
+    pub fn get_voluntary_exit(&self, validator_index: u64) -> Option<SignedVoluntaryExit> {
+        self.voluntary_exits.read().get(&validator_index).cloned()
     }
 
     /// Get a list of voluntary exits for inclusion in a block.
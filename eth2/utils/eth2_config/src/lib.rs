@@ -1,5 +1,11 @@
 use serde_derive::{Deserialize, Serialize};
-use types::ChainSpec;
+use types::{Address, ChainSpec};
+
+/// The Eth1 chain id of Goerli, which hosts the deposit contracts of the testnets.
+pub const GOERLI_CHAIN_ID: u64 = 5;
+
+/// The Eth1 chain id of the Ethereum mainnet.
+pub const MAINNET_CHAIN_ID: u64 = 1;
 
 /// The core configuration of a Lighthouse beacon node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,14 +13,15 @@ use types::ChainSpec;
 pub struct Eth2Config {
     pub spec_constants: String,
     pub spec: ChainSpec,
+    /// The Eth1 chain id of the deposit contract.
+    pub deposit_chain_id: u64,
+    /// The address of the deposit contract, set from the testnet config.
+    pub deposit_contract_address: Address,
 }
 
 impl Default for Eth2Config {
     fn default() -> Self {
-        Self {
-            spec_constants: "minimal".to_string(),
-            spec: ChainSpec::minimal(),
-        }
+        Self::minimal()
     }
 }
 
@@ -23,6 +30,8 @@ impl Eth2Config {
         Self {
             spec_constants: "mainnet".to_string(),
             spec: ChainSpec::mainnet(),
+            deposit_chain_id: MAINNET_CHAIN_ID,
+            deposit_contract_address: Address::zero(),
         }
     }
 
@@ -30,6 +39,8 @@ impl Eth2Config {
         Self {
             spec_constants: "minimal".to_string(),
             spec: ChainSpec::minimal(),
+            deposit_chain_id: GOERLI_CHAIN_ID,
+            deposit_contract_address: Address::zero(),
         }
     }
 
@@ -37,6 +48,8 @@ impl Eth2Config {
         Self {
             spec_constants: "interop".to_string(),
             spec: ChainSpec::interop(),
+            deposit_chain_id: GOERLI_CHAIN_ID,
+            deposit_contract_address: Address::zero(),
         }
     }
 }
@@ -97,6 +97,12 @@ impl<'a> KeystoreBuilder<'a> {
         }
     }
 
+    /// Build the keystore using the supplied `kdf` instead of `crate::default_kdf`.
+    pub fn kdf(mut self, kdf: Kdf) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
     /// Consumes `self`, returning a `Keystore`.
     pub fn build(self) -> Result<Keystore, Error> {
         Keystore::encrypt(
@@ -196,6 +202,13 @@ impl Keystore {
         &self.json.uuid
     }
 
+    /// Returns the pubkey for the keystore, as a hex string without a `0x` prefix.
+    ///
+    /// Note: the pubkey is not validated, it is simply whatever string the keystore provided.
+    pub fn pubkey(&self) -> &str {
+        &self.json.pubkey
+    }
+
     /// Returns the path for the keystore.
     ///
     /// Note: the path is not validated, it is simply whatever string the keystore provided.
@@ -2,7 +2,10 @@
 #![cfg(not(debug_assertions))]
 
 use bls::Keypair;
-use eth2_keystore::{Error, Keystore, KeystoreBuilder};
+use eth2_keystore::{
+    json_keystore::{Kdf, Pbkdf2, Prf},
+    Error, Keystore, KeystoreBuilder, DKLEN, SALT_SIZE,
+};
 use std::fs::OpenOptions;
 use tempfile::tempdir;
 
@@ -107,3 +110,36 @@ fn scrypt_params() {
         "should decrypt with good password"
     );
 }
+
+#[test]
+fn custom_kdf() {
+    let keypair = Keypair::random();
+
+    let kdf = Kdf::Pbkdf2(Pbkdf2 {
+        dklen: DKLEN,
+        c: 2,
+        prf: Prf::HmacSha256,
+        salt: vec![42; SALT_SIZE].into(),
+    });
+
+    let keystore = KeystoreBuilder::new(&keypair, GOOD_PASSWORD, "".into())
+        .unwrap()
+        .kdf(kdf)
+        .build()
+        .unwrap();
+
+    let json = keystore.to_json_string().unwrap();
+    let decoded = Keystore::from_json_str(&json).unwrap();
+
+    assert_eq!(
+        decoded.pubkey(),
+        &keypair.pk.as_hex_string()[2..],
+        "should expose the pubkey"
+    );
+
+    assert_eq!(
+        decoded.decrypt_keypair(GOOD_PASSWORD).unwrap(),
+        keypair,
+        "should decrypt with good password"
+    );
+}
@@ -4,7 +4,7 @@
 //! Presently, this is only used for testing but it _could_ become a user-facing library.
 
 use eth2_config::Eth2Config;
-use futures::{future, Future, IntoFuture};
+use futures::{future, stream, Future, IntoFuture, Stream};
 use reqwest::{
     r#async::{Client, ClientBuilder, Response},
     StatusCode,
@@ -16,16 +16,20 @@ use std::time::Duration;
 use types::{
     Attestation, AttestationData, AttesterSlashing, BeaconBlock, BeaconState, CommitteeIndex,
     Epoch, EthSpec, Fork, Hash256, ProposerSlashing, PublicKey, PublicKeyBytes, Signature,
-    SignedAggregateAndProof, SignedBeaconBlock, Slot,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 use url::Url;
 
 pub use operation_pool::PersistedOperationPool;
 pub use proto_array_fork_choice::core::ProtoArray;
 pub use rest_types::{
-    CanonicalHeadResponse, Committee, HeadBeaconBlock, IndividualVotesRequest,
-    IndividualVotesResponse, SyncingResponse, ValidatorDutiesRequest, ValidatorDutyBytes,
-    ValidatorRequest, ValidatorResponse, ValidatorSubscription,
+    AttesterData, BlockHeaderData, BlockId, CanonicalHeadResponse, Committee, CommitteeData,
+    DepositContractData, EventTopic, FinalityCheckpointsData, GenericResponse, GenesisData,
+    HeadBeaconBlock, IdentityData, IndividualVotesRequest, IndividualVotesResponse,
+    LivenessRequest, LivenessResponse, PeerData, ProposerData, RootData, ServerSentEvent, SseBlock,
+    SseChainReorg, SseFinalizedCheckpoint, SseHead, StateId, SyncingData, SyncingResponse,
+    ValidatorData, ValidatorDutiesRequest, ValidatorDutyBytes, ValidatorRequest, ValidatorResponse,
+    ValidatorSubscription, VersionData,
 };
 
 // Setting a long timeout for debug ensures that crypto-heavy operations can still succeed.
@@ -67,6 +71,8 @@ pub enum Error {
     DidNotSucceed { status: StatusCode, body: String },
     /// The request input was invalid.
     InvalidInput,
+    /// The server sent an event on the `/events` stream which could not be parsed.
+    InvalidEvent(String),
 }
 
 #[derive(Clone)]
@@ -115,10 +121,58 @@ impl<E: EthSpec> HttpClient<E> {
         Consensus(self.clone())
     }
 
+    pub fn eth_v1(&self) -> EthV1<E> {
+        EthV1(self.clone())
+    }
+
     fn url(&self, path: &str) -> Result<Url, Error> {
         self.url.join(path).map_err(|e| e.into())
     }
 
+    /// Subscribes to the `/events` stream of the node.
+    ///
+    /// Resolves once the node has accepted the subscription, returning a stream of the events on
+    /// `topics` as they are received. The stream ends with an error once the timeout of the client
+    /// has elapsed.
+    pub fn events(
+        &self,
+        topics: &[EventTopic],
+    ) -> impl Future<Item = impl Stream<Item = ServerSentEvent<E>, Error = Error>, Error = Error>
+    {
+        let client = self.client.clone();
+        let topics = topics
+            .iter()
+            .map(|topic| topic.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        self.url("events")
+            .into_future()
+            .and_then(move |mut url| {
+                url.query_pairs_mut().append_pair("topics", &topics);
+                client.get(&url.to_string()).send().map_err(Error::from)
+            })
+            .and_then(error_for_status)
+            .map(|response| {
+                // Events may be split across chunks, so the incomplete end of each chunk is kept
+                // until the rest of the event arrives.
+                let mut buffer = vec![];
+                response
+                    .into_body()
+                    .map_err(Error::from)
+                    .map(move |chunk| {
+                        buffer.extend_from_slice(&chunk);
+                        let mut frames = vec![];
+                        while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                            frames.push(buffer.drain(..end + 2).collect::<Vec<_>>());
+                        }
+                        stream::iter_ok(frames)
+                    })
+                    .flatten()
+                    .and_then(|frame| parse_event(&frame))
+            })
+    }
+
     pub fn json_post<T: Serialize>(
         &self,
         url: Url,
@@ -168,6 +222,37 @@ fn error_for_status(
     }
 }
 
+/// Parses a single event (i.e., the `event` and `data` lines) from the `/events` stream.
+fn parse_event<E: EthSpec>(frame: &[u8]) -> Result<ServerSentEvent<E>, Error> {
+    let frame = std::str::from_utf8(frame)
+        .map_err(|e| Error::InvalidEvent(format!("Event is not UTF-8: {:?}", e)))?;
+
+    let mut topic = None;
+    let mut data = String::new();
+    for line in frame.lines() {
+        if line.starts_with("event:") {
+            topic = Some(line.trim_start_matches("event:").trim());
+        } else if line.starts_with("data:") {
+            data.push_str(line.trim_start_matches("data:").trim());
+        }
+    }
+
+    let topic = topic
+        .ok_or_else(|| Error::InvalidEvent(format!("Event has no topic: {}", frame)))?
+        .parse::<EventTopic>()
+        .map_err(Error::InvalidEvent)?;
+
+    Ok(match topic {
+        EventTopic::Head => ServerSentEvent::Head(serde_json::from_str(&data)?),
+        EventTopic::Block => ServerSentEvent::Block(serde_json::from_str(&data)?),
+        EventTopic::FinalizedCheckpoint => {
+            ServerSentEvent::FinalizedCheckpoint(serde_json::from_str(&data)?)
+        }
+        EventTopic::Attestation => ServerSentEvent::Attestation(serde_json::from_str(&data)?),
+        EventTopic::ChainReorg => ServerSentEvent::ChainReorg(serde_json::from_str(&data)?),
+    })
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PublishStatus {
     /// The object was valid and has been published to the network.
@@ -304,6 +389,29 @@ impl<E: EthSpec> Validator<E> {
             .and_then(|mut success| success.json().map_err(Error::from))
     }
 
+    /// Returns whether each of the given validator pubkeys was seen signing messages in `epoch`.
+    pub fn get_liveness(
+        &self,
+        epoch: Epoch,
+        validator_pubkeys: &[PublicKey],
+    ) -> impl Future<Item = Vec<LivenessResponse>, Error = Error> {
+        let client = self.0.clone();
+
+        let request = LivenessRequest {
+            epoch,
+            pubkeys: validator_pubkeys
+                .iter()
+                .map(|pubkey| pubkey.clone().into())
+                .collect(),
+        };
+
+        self.url("liveness")
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, request))
+            .and_then(|response| error_for_status(response).map_err(Error::from))
+            .and_then(|mut success| success.json().map_err(Error::from))
+    }
+
     /// Posts a block to the beacon node, expecting it to verify it and publish it to the network.
     pub fn publish_block(
         &self,
@@ -614,6 +722,24 @@ impl<E: EthSpec> Beacon<E> {
                     .and_then(|mut success| success.json().map_err(Error::from))
             })
     }
+
+    /// Submits a signed voluntary exit to the beacon node, which will pool it and publish it on
+    /// gossip.
+    pub fn voluntary_exit(
+        &self,
+        exit: SignedVoluntaryExit,
+    ) -> impl Future<Item = bool, Error = Error> {
+        let client = self.0.clone();
+
+        self.url("voluntary_exit")
+            .into_future()
+            .and_then(move |url| {
+                client
+                    .json_post::<_>(url, exit)
+                    .and_then(|response| error_for_status(response).map_err(Error::from))
+                    .and_then(|mut success| success.json().map_err(Error::from))
+            })
+    }
 }
 
 /// Provides the functions on the `/spec` endpoint of the node.
@@ -735,6 +861,205 @@ impl<E: EthSpec> Consensus<E> {
     }
 }
 
+/// Provides the functions on the standard `/eth/v1` endpoints of the node.
+#[derive(Clone)]
+pub struct EthV1<E>(HttpClient<E>);
+
+impl<E: EthSpec> EthV1<E> {
+    fn url(&self, path: &str) -> Result<Url, Error> {
+        self.0
+            .url("eth/v1/")
+            .and_then(move |url| url.join(path).map_err(Error::from))
+            .map_err(Into::into)
+    }
+
+    /// Performs a GET request on `path`, returning the `data` of the `GenericResponse`.
+    fn get_data<T: DeserializeOwned>(
+        &self,
+        path: String,
+        query_pairs: Vec<(String, String)>,
+    ) -> impl Future<Item = T, Error = Error> {
+        let client = self.0.clone();
+        self.url(&path)
+            .into_future()
+            .and_then(move |url| client.json_get::<GenericResponse<T>>(url, query_pairs))
+            .map(|response| response.data)
+    }
+
+    /// Returns the genesis time, genesis validators root and genesis fork version.
+    pub fn get_genesis(&self) -> impl Future<Item = GenesisData, Error = Error> {
+        self.get_data("beacon/genesis".into(), vec![])
+    }
+
+    /// Returns the root of the state identified by `state_id`.
+    pub fn get_state_root(&self, state_id: StateId) -> impl Future<Item = Hash256, Error = Error> {
+        self.get_data::<RootData>(format!("beacon/states/{}/root", state_id), vec![])
+            .map(|data| data.root)
+    }
+
+    /// Returns the fork of the state identified by `state_id`.
+    pub fn get_state_fork(&self, state_id: StateId) -> impl Future<Item = Fork, Error = Error> {
+        self.get_data(format!("beacon/states/{}/fork", state_id), vec![])
+    }
+
+    /// Returns the justified and finalized checkpoints of the state identified by `state_id`.
+    pub fn get_state_finality_checkpoints(
+        &self,
+        state_id: StateId,
+    ) -> impl Future<Item = FinalityCheckpointsData, Error = Error> {
+        self.get_data(
+            format!("beacon/states/{}/finality_checkpoints", state_id),
+            vec![],
+        )
+    }
+
+    /// Returns all validators of the state identified by `state_id`.
+    pub fn get_state_validators(
+        &self,
+        state_id: StateId,
+    ) -> impl Future<Item = Vec<ValidatorData>, Error = Error> {
+        self.get_data(format!("beacon/states/{}/validators", state_id), vec![])
+    }
+
+    /// Returns the validator at `validator_index` in the state identified by `state_id`.
+    pub fn get_state_validator(
+        &self,
+        state_id: StateId,
+        validator_index: u64,
+    ) -> impl Future<Item = ValidatorData, Error = Error> {
+        self.get_data(
+            format!("beacon/states/{}/validators/{}", state_id, validator_index),
+            vec![],
+        )
+    }
+
+    /// Returns the committees of the state identified by `state_id` at `epoch`, or at the epoch of
+    /// the state if `epoch` is `None`.
+    pub fn get_state_committees(
+        &self,
+        state_id: StateId,
+        epoch: Option<Epoch>,
+    ) -> impl Future<Item = Vec<CommitteeData>, Error = Error> {
+        let query_params = if let Some(epoch) = epoch {
+            vec![("epoch".into(), format!("{}", epoch.as_u64()))]
+        } else {
+            vec![]
+        };
+
+        self.get_data(
+            format!("beacon/states/{}/committees", state_id),
+            query_params,
+        )
+    }
+
+    /// Returns the header of the block identified by `block_id`.
+    pub fn get_block_header(
+        &self,
+        block_id: BlockId,
+    ) -> impl Future<Item = BlockHeaderData, Error = Error> {
+        self.get_data(format!("beacon/headers/{}", block_id), vec![])
+    }
+
+    /// Returns the block identified by `block_id`.
+    pub fn get_block(
+        &self,
+        block_id: BlockId,
+    ) -> impl Future<Item = SignedBeaconBlock<E>, Error = Error> {
+        self.get_data(format!("beacon/blocks/{}", block_id), vec![])
+    }
+
+    /// Returns the root of the block identified by `block_id`.
+    pub fn get_block_root(&self, block_id: BlockId) -> impl Future<Item = Hash256, Error = Error> {
+        self.get_data::<RootData>(format!("beacon/blocks/{}/root", block_id), vec![])
+            .map(|data| data.root)
+    }
+
+    /// Returns the version string of the node.
+    pub fn get_node_version(&self) -> impl Future<Item = String, Error = Error> {
+        self.get_data::<VersionData>("node/version".into(), vec![])
+            .map(|data| data.version)
+    }
+
+    /// Returns the sync status of the node.
+    pub fn get_node_syncing(&self) -> impl Future<Item = SyncingData, Error = Error> {
+        self.get_data("node/syncing".into(), vec![])
+    }
+
+    /// Returns the Eth1 chain id and address of the deposit contract.
+    pub fn get_config_deposit_contract(
+        &self,
+    ) -> impl Future<Item = DepositContractData, Error = Error> {
+        self.get_data("config/deposit_contract".into(), vec![])
+    }
+
+    /// Returns the network identity of the node.
+    pub fn get_node_identity(&self) -> impl Future<Item = IdentityData, Error = Error> {
+        self.get_data("node/identity".into(), vec![])
+    }
+
+    /// Returns the peers known to the node.
+    pub fn get_node_peers(&self) -> impl Future<Item = Vec<PeerData>, Error = Error> {
+        self.get_data("node/peers".into(), vec![])
+    }
+
+    /// Returns the block proposers of each slot in `epoch`.
+    pub fn get_proposer_duties(
+        &self,
+        epoch: Epoch,
+    ) -> impl Future<Item = Vec<ProposerData>, Error = Error> {
+        self.get_data(
+            format!("validator/duties/proposer/{}", epoch.as_u64()),
+            vec![],
+        )
+    }
+
+    /// Returns the attestation duties in `epoch` of the validators at `validator_indices`.
+    pub fn get_attester_duties(
+        &self,
+        epoch: Epoch,
+        validator_indices: Vec<u64>,
+    ) -> impl Future<Item = Vec<AttesterData>, Error = Error> {
+        let client = self.0.clone();
+        self.url(&format!("validator/duties/attester/{}", epoch.as_u64()))
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, validator_indices))
+            .and_then(|response| error_for_status(response).map_err(Error::from))
+            .and_then(|mut success| {
+                success
+                    .json::<GenericResponse<Vec<AttesterData>>>()
+                    .map_err(Error::from)
+            })
+            .map(|response| response.data)
+    }
+
+    /// Requests a new (unsigned) block from the beacon node.
+    pub fn produce_block(
+        &self,
+        slot: Slot,
+        randao_reveal: Signature,
+    ) -> impl Future<Item = BeaconBlock<E>, Error = Error> {
+        self.get_data(
+            format!("validator/blocks/{}", slot.as_u64()),
+            vec![("randao_reveal".into(), as_ssz_hex_string(&randao_reveal))],
+        )
+    }
+
+    /// Produces the `AttestationData` for a committee at `slot`.
+    pub fn produce_attestation_data(
+        &self,
+        slot: Slot,
+        committee_index: CommitteeIndex,
+    ) -> impl Future<Item = AttestationData, Error = Error> {
+        self.get_data(
+            "validator/attestation_data".into(),
+            vec![
+                ("slot".into(), format!("{}", slot)),
+                ("committee_index".into(), format!("{}", committee_index)),
+            ],
+        )
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(bound = "T: EthSpec")]
 pub struct BlockResponse<T: EthSpec> {
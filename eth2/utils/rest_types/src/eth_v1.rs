@@ -0,0 +1,526 @@
+//! A collection of types for the standard `/eth/v1` beacon node API.
+//!
+//! Unlike the Lighthouse-specific API, every response is wrapped in a `GenericResponse`, and
+//! integers (including slots and epochs) are quoted decimal strings.
+
+use bls::PublicKeyBytes;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use types::utils::{fork_from_hex_str, fork_to_hex_str};
+use types::{
+    Address, Checkpoint, CommitteeIndex, Epoch, Hash256, SignedBeaconBlockHeader, Slot, Validator,
+};
+
+/// The envelope of every `/eth/v1` response body, i.e., `{"data": ...}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenericResponse<T> {
+    pub data: T,
+}
+
+impl<T> From<T> for GenericResponse<T> {
+    fn from(data: T) -> Self {
+        Self { data }
+    }
+}
+
+/// Identifies a `BeaconState` in the `/eth/v1/beacon/states/{state_id}` endpoints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StateId {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl FromStr for StateId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(StateId::Head),
+            "genesis" => Ok(StateId::Genesis),
+            "finalized" => Ok(StateId::Finalized),
+            "justified" => Ok(StateId::Justified),
+            other => parse_slot_or_root(other)
+                .map(|slot_or_root| match slot_or_root {
+                    Ok(slot) => StateId::Slot(slot),
+                    Err(root) => StateId::Root(root),
+                })
+                .map_err(|e| format!("Invalid state id {}: {}", other, e)),
+        }
+    }
+}
+
+impl fmt::Display for StateId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateId::Head => write!(f, "head"),
+            StateId::Genesis => write!(f, "genesis"),
+            StateId::Finalized => write!(f, "finalized"),
+            StateId::Justified => write!(f, "justified"),
+            StateId::Slot(slot) => write!(f, "{}", slot),
+            StateId::Root(root) => write!(f, "{:?}", root),
+        }
+    }
+}
+
+/// Identifies a `SignedBeaconBlock` in the `/eth/v1/beacon/blocks/{block_id}` and
+/// `/eth/v1/beacon/headers/{block_id}` endpoints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockId {
+    Head,
+    Genesis,
+    Finalized,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl FromStr for BlockId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(BlockId::Head),
+            "genesis" => Ok(BlockId::Genesis),
+            "finalized" => Ok(BlockId::Finalized),
+            other => parse_slot_or_root(other)
+                .map(|slot_or_root| match slot_or_root {
+                    Ok(slot) => BlockId::Slot(slot),
+                    Err(root) => BlockId::Root(root),
+                })
+                .map_err(|e| format!("Invalid block id {}: {}", other, e)),
+        }
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockId::Head => write!(f, "head"),
+            BlockId::Genesis => write!(f, "genesis"),
+            BlockId::Finalized => write!(f, "finalized"),
+            BlockId::Slot(slot) => write!(f, "{}", slot),
+            BlockId::Root(root) => write!(f, "{:?}", root),
+        }
+    }
+}
+
+/// Parses a decimal slot or a `0x` prefixed root, returning `Ok` for a slot and `Err` for a root.
+fn parse_slot_or_root(s: &str) -> Result<Result<Slot, Hash256>, String> {
+    if s.starts_with("0x") {
+        s.trim_start_matches("0x")
+            .parse()
+            .map(Err)
+            .map_err(|e| format!("{:?}", e))
+    } else {
+        s.parse::<u64>()
+            .map(|slot| Ok(Slot::new(slot)))
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenesisData {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub genesis_time: u64,
+    pub genesis_validators_root: Hash256,
+    #[serde(
+        serialize_with = "fork_to_hex_str",
+        deserialize_with = "fork_from_hex_str"
+    )]
+    pub genesis_fork_version: [u8; 4],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RootData {
+    pub root: Hash256,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FinalityCheckpointsData {
+    #[serde(with = "quoted_checkpoint")]
+    pub previous_justified: Checkpoint,
+    #[serde(with = "quoted_checkpoint")]
+    pub current_justified: Checkpoint,
+    #[serde(with = "quoted_checkpoint")]
+    pub finalized: Checkpoint,
+}
+
+/// The status of a validator, as defined by the standard API.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ActiveSlashed,
+    ExitedUnslashed,
+    ExitedSlashed,
+    WithdrawalPossible,
+}
+
+impl ValidatorStatus {
+    /// Returns the status of `validator` during `epoch`.
+    pub fn from_validator(validator: &Validator, epoch: Epoch, far_future_epoch: Epoch) -> Self {
+        if validator.is_withdrawable_at(epoch) {
+            ValidatorStatus::WithdrawalPossible
+        } else if validator.is_exited_at(epoch) {
+            if validator.slashed {
+                ValidatorStatus::ExitedSlashed
+            } else {
+                ValidatorStatus::ExitedUnslashed
+            }
+        } else if validator.is_active_at(epoch) {
+            if validator.slashed {
+                ValidatorStatus::ActiveSlashed
+            } else if validator.exit_epoch != far_future_epoch {
+                ValidatorStatus::ActiveExiting
+            } else {
+                ValidatorStatus::ActiveOngoing
+            }
+        } else if validator.activation_eligibility_epoch == far_future_epoch {
+            ValidatorStatus::PendingInitialized
+        } else {
+            ValidatorStatus::PendingQueued
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorData {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub balance: u64,
+    pub status: ValidatorStatus,
+    #[serde(with = "quoted_validator")]
+    pub validator: Validator,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CommitteeData {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: CommitteeIndex,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+    #[serde(with = "serde_utils::quoted_u64_vec")]
+    pub validators: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderData {
+    pub root: Hash256,
+    /// True if the block is in the canonical chain of the head.
+    pub canonical: bool,
+    #[serde(with = "quoted_block_header")]
+    pub header: SignedBeaconBlockHeader,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VersionData {
+    pub version: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncingData {
+    pub is_syncing: bool,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub head_slot: Slot,
+    /// The number of slots between the head and the current slot.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub sync_distance: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IdentityData {
+    /// The base58 encoded libp2p peer id.
+    pub peer_id: String,
+    /// The base64 encoded ENR.
+    pub enr: String,
+    pub p2p_addresses: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerState {
+    Connected,
+    Connecting,
+    Disconnected,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerData {
+    pub peer_id: String,
+    pub state: PeerState,
+    pub last_seen_p2p_address: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DepositContractData {
+    /// The Eth1 chain id of the deposit contract.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub chain_id: u64,
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProposerData {
+    pub pubkey: PublicKeyBytes,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttesterData {
+    pub pubkey: PublicKeyBytes,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committee_index: CommitteeIndex,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committee_length: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committees_at_slot: u64,
+    /// The position of the validator in the committee.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_committee_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+}
+
+/// Formats a `Validator` with quoted integers.
+///
+/// Use with `#[serde(with = "quoted_validator")]`.
+mod quoted_validator {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct QuotedValidator {
+        pubkey: PublicKeyBytes,
+        withdrawal_credentials: Hash256,
+        #[serde(with = "serde_utils::quoted_u64")]
+        effective_balance: u64,
+        slashed: bool,
+        #[serde(with = "serde_utils::quoted_u64")]
+        activation_eligibility_epoch: Epoch,
+        #[serde(with = "serde_utils::quoted_u64")]
+        activation_epoch: Epoch,
+        #[serde(with = "serde_utils::quoted_u64")]
+        exit_epoch: Epoch,
+        #[serde(with = "serde_utils::quoted_u64")]
+        withdrawable_epoch: Epoch,
+    }
+
+    pub fn serialize<S: Serializer>(
+        validator: &Validator,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        QuotedValidator {
+            pubkey: validator.pubkey.clone(),
+            withdrawal_credentials: validator.withdrawal_credentials,
+            effective_balance: validator.effective_balance,
+            slashed: validator.slashed,
+            activation_eligibility_epoch: validator.activation_eligibility_epoch,
+            activation_epoch: validator.activation_epoch,
+            exit_epoch: validator.exit_epoch,
+            withdrawable_epoch: validator.withdrawable_epoch,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Validator, D::Error> {
+        let validator = QuotedValidator::deserialize(deserializer)?;
+        Ok(Validator {
+            pubkey: validator.pubkey,
+            withdrawal_credentials: validator.withdrawal_credentials,
+            effective_balance: validator.effective_balance,
+            slashed: validator.slashed,
+            activation_eligibility_epoch: validator.activation_eligibility_epoch,
+            activation_epoch: validator.activation_epoch,
+            exit_epoch: validator.exit_epoch,
+            withdrawable_epoch: validator.withdrawable_epoch,
+        })
+    }
+}
+
+/// Formats a `Checkpoint` with a quoted epoch.
+///
+/// Use with `#[serde(with = "quoted_checkpoint")]`.
+mod quoted_checkpoint {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct QuotedCheckpoint {
+        #[serde(with = "serde_utils::quoted_u64")]
+        epoch: Epoch,
+        root: Hash256,
+    }
+
+    pub fn serialize<S: Serializer>(
+        checkpoint: &Checkpoint,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        QuotedCheckpoint {
+            epoch: checkpoint.epoch,
+            root: checkpoint.root,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Checkpoint, D::Error> {
+        let checkpoint = QuotedCheckpoint::deserialize(deserializer)?;
+        Ok(Checkpoint {
+            epoch: checkpoint.epoch,
+            root: checkpoint.root,
+        })
+    }
+}
+
+/// Formats a `SignedBeaconBlockHeader` with a quoted slot and proposer index.
+///
+/// Use with `#[serde(with = "quoted_block_header")]`.
+mod quoted_block_header {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+    use types::{BeaconBlockHeader, Signature};
+
+    #[derive(Serialize, Deserialize)]
+    struct QuotedBeaconBlockHeader {
+        #[serde(with = "serde_utils::quoted_u64")]
+        slot: Slot,
+        #[serde(with = "serde_utils::quoted_u64")]
+        proposer_index: u64,
+        parent_root: Hash256,
+        state_root: Hash256,
+        body_root: Hash256,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct QuotedSignedBeaconBlockHeader {
+        message: QuotedBeaconBlockHeader,
+        signature: Signature,
+    }
+
+    pub fn serialize<S: Serializer>(
+        header: &SignedBeaconBlockHeader,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        QuotedSignedBeaconBlockHeader {
+            message: QuotedBeaconBlockHeader {
+                slot: header.message.slot,
+                proposer_index: header.message.proposer_index,
+                parent_root: header.message.parent_root,
+                state_root: header.message.state_root,
+                body_root: header.message.body_root,
+            },
+            signature: header.signature.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SignedBeaconBlockHeader, D::Error> {
+        let header = QuotedSignedBeaconBlockHeader::deserialize(deserializer)?;
+        Ok(SignedBeaconBlockHeader {
+            message: BeaconBlockHeader {
+                slot: header.message.slot,
+                proposer_index: header.message.proposer_index,
+                parent_root: header.message.parent_root,
+                state_root: header.message.state_root,
+                body_root: header.message.body_root,
+            },
+            signature: header.signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn state_id_round_trip() {
+        let ids = vec![
+            StateId::Head,
+            StateId::Genesis,
+            StateId::Finalized,
+            StateId::Justified,
+            StateId::Slot(Slot::new(42)),
+            StateId::Root(Hash256::from_low_u64_be(42)),
+        ];
+
+        for id in ids {
+            assert_eq!(id.to_string().parse::<StateId>(), Ok(id));
+        }
+
+        assert!("cats".parse::<StateId>().is_err());
+        assert!("0x42".parse::<StateId>().is_err());
+        assert!("-1".parse::<StateId>().is_err());
+    }
+
+    #[test]
+    fn block_id_round_trip() {
+        let ids = vec![
+            BlockId::Head,
+            BlockId::Genesis,
+            BlockId::Finalized,
+            BlockId::Slot(Slot::new(42)),
+            BlockId::Root(Hash256::from_low_u64_be(42)),
+        ];
+
+        for id in ids {
+            assert_eq!(id.to_string().parse::<BlockId>(), Ok(id));
+        }
+
+        assert!("justified".parse::<BlockId>().is_err());
+    }
+
+    #[test]
+    fn integers_are_quoted() {
+        let syncing = SyncingData {
+            is_syncing: true,
+            head_slot: Slot::new(1),
+            sync_distance: Slot::new(2),
+        };
+        assert_eq!(
+            serde_json::to_string(&syncing).unwrap(),
+            r#"{"is_syncing":true,"head_slot":"1","sync_distance":"2"}"#
+        );
+
+        let validator = ValidatorData {
+            index: 3,
+            balance: 32_000_000_000,
+            status: ValidatorStatus::ActiveOngoing,
+            validator: Validator {
+                pubkey: PublicKeyBytes::empty(),
+                withdrawal_credentials: Hash256::zero(),
+                effective_balance: 32_000_000_000,
+                slashed: false,
+                activation_eligibility_epoch: Epoch::new(0),
+                activation_epoch: Epoch::new(1),
+                exit_epoch: Epoch::new(u64::max_value()),
+                withdrawable_epoch: Epoch::new(u64::max_value()),
+            },
+        };
+        let json = serde_json::to_value(&validator).unwrap();
+        assert_eq!(json["index"], "3");
+        assert_eq!(json["balance"], "32000000000");
+        assert_eq!(json["validator"]["effective_balance"], "32000000000");
+        assert_eq!(json["validator"]["activation_epoch"], "1");
+        assert_eq!(json["validator"]["exit_epoch"], "18446744073709551615");
+        assert_eq!(
+            serde_json::from_value::<ValidatorData>(json).unwrap(),
+            validator,
+            "should round trip"
+        );
+    }
+}
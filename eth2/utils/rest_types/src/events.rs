@@ -0,0 +1,164 @@
+//! Types for the server-sent events stream of the `/events` endpoint.
+//!
+//! As in the `/eth/v1` types, integers are quoted decimal strings. Attestations keep the
+//! serialization of the consensus types.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use types::{Attestation, Epoch, EthSpec, Hash256, Slot};
+
+/// A topic which may be subscribed to with `/events?topics=...`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    Head,
+    Block,
+    FinalizedCheckpoint,
+    Attestation,
+    ChainReorg,
+}
+
+impl EventTopic {
+    pub fn all() -> Vec<EventTopic> {
+        vec![
+            EventTopic::Head,
+            EventTopic::Block,
+            EventTopic::FinalizedCheckpoint,
+            EventTopic::Attestation,
+            EventTopic::ChainReorg,
+        ]
+    }
+}
+
+impl FromStr for EventTopic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(EventTopic::Head),
+            "block" => Ok(EventTopic::Block),
+            "finalized_checkpoint" => Ok(EventTopic::FinalizedCheckpoint),
+            "attestation" => Ok(EventTopic::Attestation),
+            "chain_reorg" => Ok(EventTopic::ChainReorg),
+            other => Err(format!("Unknown event topic: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for EventTopic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventTopic::Head => write!(f, "head"),
+            EventTopic::Block => write!(f, "block"),
+            EventTopic::FinalizedCheckpoint => write!(f, "finalized_checkpoint"),
+            EventTopic::Attestation => write!(f, "attestation"),
+            EventTopic::ChainReorg => write!(f, "chain_reorg"),
+        }
+    }
+}
+
+/// The canonical head has changed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SseHead {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+    pub block: Hash256,
+    pub state: Hash256,
+    /// True if the new head is in a later epoch than the previous head.
+    pub epoch_transition: bool,
+}
+
+/// A block has been imported, whether or not it became the head.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SseBlock {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+    pub block: Hash256,
+}
+
+/// The finalized checkpoint has advanced.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SseFinalizedCheckpoint {
+    pub block: Hash256,
+    pub state: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: Epoch,
+}
+
+/// The new head is not a descendant of the previous head.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SseChainReorg {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+    /// The number of slots between the previous head and the common ancestor of both heads.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub depth: u64,
+    pub old_head_block: Hash256,
+    pub new_head_block: Hash256,
+    pub old_head_state: Hash256,
+    pub new_head_state: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: Epoch,
+}
+
+/// An event sent on the `/events` stream.
+///
+/// Serializes to the `data` of the event, its `topic` is sent as the `event` field.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(bound = "T: EthSpec", untagged)]
+pub enum ServerSentEvent<T: EthSpec> {
+    Head(SseHead),
+    Block(SseBlock),
+    FinalizedCheckpoint(SseFinalizedCheckpoint),
+    Attestation(Box<Attestation<T>>),
+    ChainReorg(SseChainReorg),
+}
+
+impl<T: EthSpec> ServerSentEvent<T> {
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            ServerSentEvent::Head(_) => EventTopic::Head,
+            ServerSentEvent::Block(_) => EventTopic::Block,
+            ServerSentEvent::FinalizedCheckpoint(_) => EventTopic::FinalizedCheckpoint,
+            ServerSentEvent::Attestation(_) => EventTopic::Attestation,
+            ServerSentEvent::ChainReorg(_) => EventTopic::ChainReorg,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn topic_round_trip() {
+        for topic in EventTopic::all() {
+            assert_eq!(topic.to_string().parse::<EventTopic>(), Ok(topic));
+        }
+
+        assert!("blocks".parse::<EventTopic>().is_err());
+    }
+
+    #[test]
+    fn integers_are_quoted() {
+        let reorg = SseChainReorg {
+            slot: Slot::new(65),
+            depth: 2,
+            old_head_block: Hash256::zero(),
+            new_head_block: Hash256::zero(),
+            old_head_state: Hash256::zero(),
+            new_head_state: Hash256::zero(),
+            epoch: Epoch::new(2),
+        };
+        let json = serde_json::to_value(&reorg).unwrap();
+        assert_eq!(json["slot"], "65");
+        assert_eq!(json["depth"], "2");
+        assert_eq!(json["epoch"], "2");
+        assert_eq!(
+            serde_json::from_value::<SseChainReorg>(json).unwrap(),
+            reorg,
+            "should round trip"
+        );
+    }
+}
@@ -4,6 +4,8 @@
 
 mod beacon;
 mod consensus;
+mod eth_v1;
+mod events;
 mod node;
 mod validator;
 
@@ -13,9 +15,20 @@ pub use beacon::{
 };
 
 pub use validator::{
-    ValidatorDutiesRequest, ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription,
+    LivenessRequest, LivenessResponse, ValidatorDutiesRequest, ValidatorDuty, ValidatorDutyBytes,
+    ValidatorSubscription,
 };
 
 pub use consensus::{IndividualVote, IndividualVotesRequest, IndividualVotesResponse};
 
 pub use node::{SyncingResponse, SyncingStatus};
+
+pub use events::{
+    EventTopic, ServerSentEvent, SseBlock, SseChainReorg, SseFinalizedCheckpoint, SseHead,
+};
+
+pub use eth_v1::{
+    AttesterData, BlockHeaderData, BlockId, CommitteeData, DepositContractData,
+    FinalityCheckpointsData, GenericResponse, GenesisData, IdentityData, PeerData, PeerState,
+    ProposerData, RootData, StateId, SyncingData, ValidatorData, ValidatorStatus, VersionData,
+};
@@ -70,3 +70,23 @@ pub struct ValidatorSubscription {
     /// for this slot.
     pub is_aggregator: bool,
 }
+
+/// A request for the liveness of a set of validators during an epoch.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct LivenessRequest {
+    pub epoch: Epoch,
+    pub pubkeys: Vec<PublicKeyBytes>,
+}
+
+/// Indicates whether a validator has been seen signing messages during an epoch.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct LivenessResponse {
+    /// The epoch to which the liveness applies.
+    pub epoch: Epoch,
+    /// The validator's BLS public key.
+    pub pubkey: PublicKeyBytes,
+    /// The index of the validator in `state.validators`, if it is known.
+    pub validator_index: Option<u64>,
+    /// True if an attestation or block from the validator was seen during `epoch`.
+    pub is_live: bool,
+}
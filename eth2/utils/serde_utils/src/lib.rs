@@ -0,0 +1,3 @@
+//! Serde helpers for formats which differ from the default serialization of our types.
+pub mod quoted_u64;
+pub mod quoted_u64_vec;
@@ -0,0 +1,88 @@
+//! Formats a `u64`, or a type convertible to and from one (e.g., `Slot` or `Epoch`), as a quoted
+//! decimal string (e.g., `"42"`), as required by the standard Eth2 APIs and the slashing
+//! protection interchange format.
+//!
+//! Unquoted numbers are also accepted when deserializing.
+//!
+//! Use with `#[serde(with = "serde_utils::quoted_u64")]`.
+use serde::de::{self, Deserializer, Visitor};
+use serde::Serializer;
+use std::fmt;
+
+pub struct QuotedU64Visitor;
+
+impl<'de> Visitor<'de> for QuotedU64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a quoted or unquoted u64")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        value
+            .parse()
+            .map_err(|e| de::Error::custom(format!("invalid u64 ({:?})", e)))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(value)
+    }
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<u64>,
+    S: Serializer,
+{
+    let value: u64 = (*value).into();
+    serializer.serialize_str(&value.to_string())
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<u64>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(QuotedU64Visitor).map(T::from)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Wrapper {
+        #[serde(with = "super")]
+        value: u64,
+    }
+
+    #[test]
+    fn serializes_as_string() {
+        let json = serde_json::to_string(&Wrapper {
+            value: u64::max_value(),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"value":"18446744073709551615"}"#);
+    }
+
+    #[test]
+    fn deserializes_quoted_and_unquoted() {
+        let expected = Wrapper { value: 42 };
+        assert_eq!(
+            serde_json::from_str::<Wrapper>(r#"{"value":"42"}"#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            serde_json::from_str::<Wrapper>(r#"{"value":42}"#).unwrap(),
+            expected
+        );
+        assert!(serde_json::from_str::<Wrapper>(r#"{"value":"-1"}"#).is_err());
+        assert!(serde_json::from_str::<Wrapper>(r#"{"value":"0x2a"}"#).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Formats a `Vec<u64>` as a list of quoted decimal strings (e.g., `["1", "2"]`).
+//!
+//! Unquoted numbers are also accepted when deserializing.
+//!
+//! Use with `#[serde(with = "serde_utils::quoted_u64_vec")]`.
+use crate::quoted_u64::QuotedU64Visitor;
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::Deserialize;
+use std::fmt;
+
+/// A single `u64` which deserializes from a quoted or unquoted number.
+struct QuotedU64(u64);
+
+impl<'de> Deserialize<'de> for QuotedU64 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_any(QuotedU64Visitor)
+            .map(QuotedU64)
+    }
+}
+
+struct QuotedU64VecVisitor;
+
+impl<'de> Visitor<'de> for QuotedU64VecVisitor {
+    type Value = Vec<u64>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of quoted or unquoted u64s")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(QuotedU64(value)) = seq.next_element()? {
+            vec.push(value);
+        }
+        Ok(vec)
+    }
+}
+
+pub fn serialize<S>(value: &[u64], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(value.len()))?;
+    for &int in value {
+        seq.serialize_element(&int.to_string())?;
+    }
+    seq.end()
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(QuotedU64VecVisitor)
+}
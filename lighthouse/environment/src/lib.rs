@@ -159,6 +159,9 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
                 )
             })?;
 
+        self.eth2_config.deposit_contract_address =
+            eth2_testnet_config.deposit_contract_address()?;
+
         self.testnet = Some(eth2_testnet_config);
 
         Ok(self)
@@ -91,6 +91,7 @@ fn main() {
         .subcommand(beacon_node::cli_app())
         .subcommand(validator_client::cli_app())
         .subcommand(account_manager::cli_app())
+        .subcommand(database_manager::cli_app())
         .get_matches();
 
     macro_rules! run_with_spec {
@@ -181,6 +182,13 @@ fn run<E: EthSpec>(
         return Ok(());
     };
 
+    if let Some(sub_matches) = matches.subcommand_matches(database_manager::CMD) {
+        database_manager::run(sub_matches, environment)?;
+
+        // Exit as soon as the database manager returns control.
+        return Ok(());
+    };
+
     let beacon_node = if let Some(sub_matches) = matches.subcommand_matches("beacon_node") {
         let runtime_context = environment.core_context();
 
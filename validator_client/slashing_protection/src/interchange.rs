@@ -0,0 +1,105 @@
+//! The slashing protection interchange format of EIP-3076, for moving slashing protection history
+//! between machines or clients.
+use serde_derive::{Deserialize, Serialize};
+use std::io;
+use types::{Epoch, Hash256, PublicKey, Slot};
+
+/// The only version of the interchange format that this crate can produce and consume.
+pub const SUPPORTED_INTERCHANGE_FORMAT_VERSION: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeMetadata {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub interchange_format_version: u64,
+    /// The chain on which the messages were signed.
+    ///
+    /// Signing roots include the `genesis_validators_root`, so history from one chain is
+    /// meaningless on another.
+    pub genesis_validators_root: Hash256,
+}
+
+/// The signing history of a single validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeData {
+    pub pubkey: PublicKey,
+    pub signed_blocks: Vec<InterchangeBlock>,
+    pub signed_attestations: Vec<InterchangeAttestation>,
+}
+
+/// A block signed by a validator.
+///
+/// The `signing_root` is optional in EIP-3076. Without it, a block can't be told apart from a
+/// different block at the same slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeBlock {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: Slot,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+/// An attestation signed by a validator.
+///
+/// The `signing_root` is optional in EIP-3076. Without it, an attestation can't be told apart
+/// from a different attestation with the same source and target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeAttestation {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub source_epoch: Epoch,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub target_epoch: Epoch,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+/// A slashing protection interchange document, as defined by EIP-3076.
+///
+/// ```json
+/// {
+///   "metadata": {
+///     "interchange_format_version": "5",
+///     "genesis_validators_root": "0x04700007fabc8282644aed6d1c7c9e21d38a03a0c4ba193f3afe428824b3a673"
+///   },
+///   "data": [
+///     {
+///       "pubkey": "0xb845089a1457f811bfc000588fbb4e713669be8ce060ea6be3c6ece09afc3794106c91ca73acda5e5457122d58723bed",
+///       "signed_blocks": [
+///         { "slot": "81952", "signing_root": "0x4ff6f743a43f3b4f95350831aeaf0a122a1a392922c45d804280284a69eb850b" },
+///         { "slot": "81951" }
+///       ],
+///       "signed_attestations": [
+///         {
+///           "source_epoch": "2290",
+///           "target_epoch": "3007",
+///           "signing_root": "0x587d6a4f59a58fe24f406e0502413e77fe1babddee641fda30034ed37ecc884d"
+///         },
+///         { "source_epoch": "2290", "target_epoch": "3008" }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeData>,
+}
+
+impl Interchange {
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn from_json_reader(reader: impl io::Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    pub fn write_to(&self, writer: impl io::Write) -> Result<(), serde_json::Error> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+}
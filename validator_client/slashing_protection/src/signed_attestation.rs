@@ -0,0 +1,66 @@
+use crate::hash256_from_row;
+use types::{AttestationData, Epoch, Hash256, SignedRoot};
+
+/// An attestation that has previously been signed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedAttestation {
+    pub source_epoch: Epoch,
+    pub target_epoch: Epoch,
+    pub signing_root: Hash256,
+}
+
+/// Reasons why an attestation may be slashable (or invalid).
+#[derive(PartialEq, Debug)]
+pub enum InvalidAttestation {
+    /// The attestation has the same target epoch as an attestation from the DB (enclosed).
+    DoubleVote(SignedAttestation),
+    /// The attestation surrounds an existing attestation from the database (`prev`).
+    NewSurroundsPrev { prev: SignedAttestation },
+    /// The attestation is surrounded by an existing attestation from the database (`prev`).
+    PrevSurroundsNew { prev: SignedAttestation },
+    /// The attestation is invalid because its source epoch is greater than its target epoch.
+    SourceExceedsTarget,
+    /// The attestation's source epoch is less than the greatest source epoch of an imported
+    /// interchange file.
+    SourceLessThanLowerBound {
+        source_epoch: Epoch,
+        bound_epoch: Epoch,
+    },
+    /// The attestation's target epoch is not greater than the greatest target epoch of an
+    /// imported interchange file.
+    TargetLessThanOrEqLowerBound {
+        target_epoch: Epoch,
+        bound_epoch: Epoch,
+    },
+}
+
+impl SignedAttestation {
+    pub fn new(source_epoch: Epoch, target_epoch: Epoch, signing_root: Hash256) -> Self {
+        Self {
+            source_epoch,
+            target_epoch,
+            signing_root,
+        }
+    }
+
+    /// Create a `SignedAttestation` from attestation data and a domain.
+    pub fn from_attestation(attestation: &AttestationData, domain: Hash256) -> Self {
+        Self {
+            source_epoch: attestation.source.epoch,
+            target_epoch: attestation.target.epoch,
+            signing_root: attestation.signing_root(domain),
+        }
+    }
+
+    /// Create a signed attestation from an SQLite row of `(source, target, signing_root)`.
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        let source = row.get::<_, i64>(0)? as u64;
+        let target = row.get::<_, i64>(1)? as u64;
+        let signing_root = hash256_from_row(2, row)?;
+        Ok(SignedAttestation::new(
+            Epoch::new(source),
+            Epoch::new(target),
+            signing_root,
+        ))
+    }
+}
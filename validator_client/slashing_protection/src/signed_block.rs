@@ -0,0 +1,36 @@
+use crate::hash256_from_row;
+use types::{Hash256, Slot};
+
+/// A block that has previously been signed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedBlock {
+    pub slot: Slot,
+    pub signing_root: Hash256,
+}
+
+/// Reasons why a block may be slashable.
+#[derive(PartialEq, Debug)]
+pub enum InvalidBlock {
+    DoubleBlockProposal(SignedBlock),
+    /// The block's slot is not greater than the greatest slot of an imported interchange file.
+    SlotViolatesLowerBound {
+        block_slot: Slot,
+        bound_slot: Slot,
+    },
+}
+
+impl SignedBlock {
+    pub fn new(slot: Slot, signing_root: Hash256) -> Self {
+        Self { slot, signing_root }
+    }
+
+    /// Parse an SQLite row of `(slot, signing_root)`.
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        let slot = row.get::<_, i64>(0)? as u64;
+        let signing_root = hash256_from_row(1, row)?;
+        Ok(SignedBlock {
+            slot: Slot::new(slot),
+            signing_root,
+        })
+    }
+}
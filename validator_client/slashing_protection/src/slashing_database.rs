@@ -0,0 +1,1363 @@
+use crate::interchange::{
+    Interchange, InterchangeAttestation, InterchangeBlock, InterchangeData, InterchangeMetadata,
+    SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+};
+use crate::signed_attestation::InvalidAttestation;
+use crate::signed_block::InvalidBlock;
+use crate::{NotSafe, Safe, SignedAttestation, SignedBlock};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension, Transaction, TransactionBehavior};
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::Duration;
+use types::{AttestationData, BeaconBlockHeader, Epoch, Hash256, PublicKey, SignedRoot, Slot};
+
+type Pool = r2d2::Pool<SqliteConnectionManager>;
+
+/// We set the pool size to 1 for compatibility with `locking_mode=EXCLUSIVE`.
+///
+/// This is perhaps overkill in the presence of exclusive transactions, but has
+/// the added bonus of preventing other processes from trying to use our slashing database.
+pub const POOL_SIZE: u32 = 1;
+#[cfg(not(test))]
+pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+#[cfg(test)]
+pub const CONNECTION_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct SlashingDatabase {
+    conn_pool: Pool,
+}
+
+impl SlashingDatabase {
+    /// Open an existing database at the given `path`, or create one if none exists.
+    pub fn open_or_create(path: &Path) -> Result<Self, NotSafe> {
+        if path.exists() {
+            Self::open(path)
+        } else {
+            Self::create(path)
+        }
+    }
+
+    /// Create a slashing database at the given path.
+    ///
+    /// Error if a database (or any file) already exists at `path`.
+    pub fn create(path: &Path) -> Result<Self, NotSafe> {
+        let file = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create_new(true)
+            .open(path)?;
+
+        Self::set_db_file_permissions(&file)?;
+        let conn_pool = Self::open_conn_pool(path)?;
+        Self::create_tables(&conn_pool)?;
+
+        Ok(Self { conn_pool })
+    }
+
+    /// Open an existing `SlashingDatabase` from disk.
+    ///
+    /// Any missing tables are created, in case the creation of the database was interrupted.
+    pub fn open(path: &Path) -> Result<Self, NotSafe> {
+        let conn_pool = Self::open_conn_pool(&path)?;
+        Self::create_tables(&conn_pool)?;
+        Ok(Self { conn_pool })
+    }
+
+    /// Create the tables of the database that do not exist yet, in a single transaction.
+    fn create_tables(conn_pool: &Pool) -> Result<(), NotSafe> {
+        let mut conn = conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        txn.execute(
+            "CREATE TABLE IF NOT EXISTS validators (
+                id INTEGER PRIMARY KEY,
+                public_key BLOB NOT NULL UNIQUE
+            )",
+            params![],
+        )?;
+
+        txn.execute(
+            "CREATE TABLE IF NOT EXISTS signed_blocks (
+                validator_id INTEGER NOT NULL,
+                slot INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+                UNIQUE (validator_id, slot)
+            )",
+            params![],
+        )?;
+
+        txn.execute(
+            "CREATE TABLE IF NOT EXISTS signed_attestations (
+                validator_id INTEGER NOT NULL,
+                source_epoch INTEGER NOT NULL,
+                target_epoch INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+                UNIQUE (validator_id, target_epoch)
+            )",
+            params![],
+        )?;
+
+        // The greatest slot and epochs of the messages imported from interchange files. Nothing
+        // may be signed at or below them, as the imported history may be incomplete.
+        txn.execute(
+            "CREATE TABLE IF NOT EXISTS lower_bounds (
+                validator_id INTEGER NOT NULL UNIQUE,
+                max_slot INTEGER,
+                max_source_epoch INTEGER,
+                max_target_epoch INTEGER,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+            )",
+            params![],
+        )?;
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Open a new connection pool with all of the necessary settings and tweaks.
+    fn open_conn_pool(path: &Path) -> Result<Pool, NotSafe> {
+        let manager = SqliteConnectionManager::file(path)
+            .with_flags(rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)
+            .with_init(Self::apply_pragmas);
+        let conn_pool = Pool::builder()
+            .max_size(POOL_SIZE)
+            .connection_timeout(CONNECTION_TIMEOUT)
+            .build(manager)
+            .map_err(|e| NotSafe::SQLError(format!("Unable to open database: {:?}", e)))?;
+        Ok(conn_pool)
+    }
+
+    /// Apply the necessary settings to an SQLite connection.
+    ///
+    /// Most importantly, put the database into exclusive locking mode, so that threads are forced
+    /// to serialise all DB access (to prevent slashable data being checked and signed in parallel).
+    /// The exclusive locking mode also has the benefit of applying to other processes, so multiple
+    /// Lighthouse processes trying to access the same database will also be blocked.
+    fn apply_pragmas(conn: &mut rusqlite::Connection) -> Result<(), rusqlite::Error> {
+        conn.pragma_update(None, "foreign_keys", &true)?;
+        conn.pragma_update(None, "locking_mode", &"EXCLUSIVE")?;
+        Ok(())
+    }
+
+    /// Set the database file to readable and writable only by its owner (0600).
+    fn set_db_file_permissions(file: &File) -> Result<(), NotSafe> {
+        let mut perm = file.metadata()?.permissions();
+        perm.set_mode(0o600);
+        file.set_permissions(perm)?;
+        Ok(())
+    }
+
+    /// Register a validator with the slashing protection database.
+    ///
+    /// This allows the validator to record their signatures in the database, and check
+    /// for slashings.
+    pub fn register_validator(&self, validator_pk: &PublicKey) -> Result<(), NotSafe> {
+        self.register_validators(std::iter::once(validator_pk))
+    }
+
+    /// Register multiple validators with the slashing protection database.
+    ///
+    /// Validators that are already registered are ignored.
+    pub fn register_validators<'a>(
+        &self,
+        public_keys: impl Iterator<Item = &'a PublicKey>,
+    ) -> Result<(), NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+        {
+            let mut stmt =
+                txn.prepare("INSERT OR IGNORE INTO validators (public_key) VALUES (?1)")?;
+
+            for pubkey in public_keys {
+                stmt.execute(&[pubkey.as_hex_string()])?;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the database-internal ID for a validator.
+    ///
+    /// This is NOT the same as a validator index, and depends on the ordering that validators
+    /// are registered with the slashing protection database (and may vary between machines).
+    fn get_validator_id(txn: &Transaction, public_key: &PublicKey) -> Result<i64, NotSafe> {
+        txn.query_row(
+            "SELECT id FROM validators WHERE public_key = ?1",
+            params![&public_key.as_hex_string()],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| NotSafe::UnregisteredValidator(public_key.clone()))
+    }
+
+    /// Check a block proposal from `validator_pubkey` for slash safety.
+    fn check_block_proposal(
+        &self,
+        txn: &Transaction,
+        validator_id: i64,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let existing_block = txn
+            .prepare(
+                "SELECT slot, signing_root
+                 FROM signed_blocks
+                 WHERE validator_id = ?1 AND slot = ?2",
+            )?
+            .query_row(
+                params![validator_id, slot.as_u64() as i64],
+                SignedBlock::from_row,
+            )
+            .optional()?;
+
+        if let Some(existing_block) = existing_block {
+            if existing_block.signing_root == signing_root {
+                // Same slot and same hash -> we're re-broadcasting a previously signed block
+                return Ok(Safe::SameData);
+            } else {
+                // Same epoch but not the same hash -> it's a DoubleBlockProposal
+                return Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+                    existing_block,
+                )));
+            }
+        }
+
+        // Nothing may be signed at or below the greatest imported slot.
+        let bound_slot = txn
+            .query_row(
+                "SELECT max_slot FROM lower_bounds WHERE validator_id = ?1",
+                params![validator_id],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .and_then(|max_slot| max_slot.map(|slot| Slot::new(slot as u64)));
+
+        match bound_slot {
+            Some(bound_slot) if slot <= bound_slot => Err(NotSafe::InvalidBlock(
+                InvalidBlock::SlotViolatesLowerBound {
+                    block_slot: slot,
+                    bound_slot,
+                },
+            )),
+            _ => Ok(Safe::Valid),
+        }
+    }
+
+    /// Check an attestation from `validator_pubkey` for slash safety.
+    fn check_attestation(
+        &self,
+        txn: &Transaction,
+        validator_id: i64,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        // Although it's not required to avoid slashing, we disallow attestations
+        // which are obviously invalid by virtue of their source epoch exceeding their target.
+        if att_source_epoch > att_target_epoch {
+            return Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceExceedsTarget,
+            ));
+        }
+
+        // 1. Check for a double vote. Namely, an existing attestation with the same target epoch,
+        //    and a different signing root.
+        let same_target_att = txn
+            .prepare(
+                "SELECT source_epoch, target_epoch, signing_root
+                 FROM signed_attestations
+                 WHERE validator_id = ?1 AND target_epoch = ?2",
+            )?
+            .query_row(
+                params![validator_id, att_target_epoch.as_u64() as i64],
+                SignedAttestation::from_row,
+            )
+            .optional()?;
+
+        if let Some(existing_attestation) = same_target_att {
+            // If the new attestation is identical to the existing attestation, then we already
+            // know that it is safe, and can return immediately.
+            if existing_attestation.signing_root == att_signing_root {
+                return Ok(Safe::SameData);
+            // Otherwise if the hashes are different, this is a double vote.
+            } else {
+                return Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                    existing_attestation,
+                )));
+            }
+        }
+
+        // 2. Check that no previous vote is surrounding `attestation`.
+        // If there is a surrounding attestation, we only return the most recent one.
+        let surrounding_attestation = txn
+            .prepare(
+                "SELECT source_epoch, target_epoch, signing_root
+                 FROM signed_attestations
+                 WHERE validator_id = ?1 AND source_epoch < ?2 AND target_epoch > ?3
+                 ORDER BY target_epoch DESC
+                 LIMIT 1",
+            )?
+            .query_row(
+                params![
+                    validator_id,
+                    att_source_epoch.as_u64() as i64,
+                    att_target_epoch.as_u64() as i64
+                ],
+                SignedAttestation::from_row,
+            )
+            .optional()?;
+
+        if let Some(prev) = surrounding_attestation {
+            return Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::PrevSurroundsNew { prev },
+            ));
+        }
+
+        // 3. Check that no previous vote is surrounded by `attestation`.
+        // If there is a surrounded attestation, we only return the most recent one.
+        let surrounded_attestation = txn
+            .prepare(
+                "SELECT source_epoch, target_epoch, signing_root
+                 FROM signed_attestations
+                 WHERE validator_id = ?1 AND source_epoch > ?2 AND target_epoch < ?3
+                 ORDER BY target_epoch DESC
+                 LIMIT 1",
+            )?
+            .query_row(
+                params![
+                    validator_id,
+                    att_source_epoch.as_u64() as i64,
+                    att_target_epoch.as_u64() as i64
+                ],
+                SignedAttestation::from_row,
+            )
+            .optional()?;
+
+        if let Some(prev) = surrounded_attestation {
+            return Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::NewSurroundsPrev { prev },
+            ));
+        }
+
+        // 4. Check that the attestation is not below the greatest imported source and target.
+        let bounds = txn
+            .query_row(
+                "SELECT max_source_epoch, max_target_epoch
+                 FROM lower_bounds
+                 WHERE validator_id = ?1",
+                params![validator_id],
+                |row| {
+                    let source = row.get::<_, Option<i64>>(0)?;
+                    let target = row.get::<_, Option<i64>>(1)?;
+                    Ok((source, target))
+                },
+            )
+            .optional()?;
+
+        if let Some((max_source, max_target)) = bounds {
+            if let Some(bound_epoch) = max_source.map(|epoch| Epoch::new(epoch as u64)) {
+                if att_source_epoch < bound_epoch {
+                    return Err(NotSafe::InvalidAttestation(
+                        InvalidAttestation::SourceLessThanLowerBound {
+                            source_epoch: att_source_epoch,
+                            bound_epoch,
+                        },
+                    ));
+                }
+            }
+            if let Some(bound_epoch) = max_target.map(|epoch| Epoch::new(epoch as u64)) {
+                if att_target_epoch <= bound_epoch {
+                    return Err(NotSafe::InvalidAttestation(
+                        InvalidAttestation::TargetLessThanOrEqLowerBound {
+                            target_epoch: att_target_epoch,
+                            bound_epoch,
+                        },
+                    ));
+                }
+            }
+        }
+
+        // Everything has been checked, return Valid
+        Ok(Safe::Valid)
+    }
+
+    /// Insert a block proposal into the slashing database.
+    ///
+    /// This should *only* be called in the same (exclusive) transaction as `check_block_proposal`
+    /// so that the check isn't invalidated by a concurrent mutation.
+    fn insert_block_proposal(
+        &self,
+        txn: &Transaction,
+        validator_id: i64,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        txn.execute(
+            "INSERT INTO signed_blocks (validator_id, slot, signing_root)
+             VALUES (?1, ?2, ?3)",
+            params![validator_id, slot.as_u64() as i64, signing_root.as_bytes()],
+        )?;
+        Ok(())
+    }
+
+    /// Insert an attestation into the slashing database.
+    ///
+    /// This should *only* be called in the same (exclusive) transaction as `check_attestation`
+    /// so that the check isn't invalidated by a concurrent mutation.
+    fn insert_attestation(
+        &self,
+        txn: &Transaction,
+        validator_id: i64,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        txn.execute(
+            "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                validator_id,
+                att_source_epoch.as_u64() as i64,
+                att_target_epoch.as_u64() as i64,
+                att_signing_root.as_bytes()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Check a block proposal for slash safety, and if it is safe, record it in the database.
+    ///
+    /// The checking and inserting happen atomically and exclusively. We enforce exclusivity
+    /// to prevent concurrent checks and inserts from resulting in slashable data being inserted.
+    ///
+    /// This is the safe, externally-callable interface for checking block proposals.
+    pub fn check_and_insert_block_proposal(
+        &self,
+        validator_pubkey: &PublicKey,
+        block_header: &BeaconBlockHeader,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_block_signing_root(
+            validator_pubkey,
+            block_header.slot,
+            block_header.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_block_proposal` but without requiring the whole `BeaconBlockHeader`.
+    pub fn check_and_insert_block_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        let safe = self.check_and_insert_block_signing_root_txn(
+            &txn,
+            validator_pubkey,
+            slot,
+            signing_root,
+        )?;
+        txn.commit()?;
+        Ok(safe)
+    }
+
+    /// Transactional variant of `check_and_insert_block_signing_root`.
+    fn check_and_insert_block_signing_root_txn(
+        &self,
+        txn: &Transaction,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+
+        let safe = self.check_block_proposal(txn, validator_id, slot, signing_root)?;
+
+        if safe != Safe::SameData {
+            self.insert_block_proposal(txn, validator_id, slot, signing_root)?;
+        }
+        Ok(safe)
+    }
+
+    /// Check an attestation for slash safety, and if it is safe, record it in the database.
+    ///
+    /// The checking and inserting happen atomically and exclusively. We enforce exclusivity
+    /// to prevent concurrent checks and inserts from resulting in slashable data being inserted.
+    ///
+    /// This is the safe, externally-callable interface for checking attestations.
+    pub fn check_and_insert_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &AttestationData,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_attestation_signing_root(
+            validator_pubkey,
+            attestation.source.epoch,
+            attestation.target.epoch,
+            attestation.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_attestation` but without requiring the whole `AttestationData`.
+    pub fn check_and_insert_attestation_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        let safe = self.check_and_insert_attestation_signing_root_txn(
+            &txn,
+            validator_pubkey,
+            att_source_epoch,
+            att_target_epoch,
+            att_signing_root,
+        )?;
+        txn.commit()?;
+        Ok(safe)
+    }
+
+    /// Transactional variant of `check_and_insert_attestation_signing_root`.
+    fn check_and_insert_attestation_signing_root_txn(
+        &self,
+        txn: &Transaction,
+        validator_pubkey: &PublicKey,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+
+        let safe = self.check_attestation(
+            txn,
+            validator_id,
+            att_source_epoch,
+            att_target_epoch,
+            att_signing_root,
+        )?;
+
+        if safe != Safe::SameData {
+            self.insert_attestation(
+                txn,
+                validator_id,
+                att_source_epoch,
+                att_target_epoch,
+                att_signing_root,
+            )?;
+        }
+        Ok(safe)
+    }
+
+    /// Import slashing protection data from an `Interchange` document.
+    ///
+    /// Every block and attestation in the document is checked against the existing contents of
+    /// the database, as if it were being signed. If any imported message conflicts with the
+    /// existing history the entire import is aborted, leaving the database untouched.
+    ///
+    /// Messages without a `signing_root` are recorded with a zero signing root, which never
+    /// matches the root of a message we sign, so signing the same slot or target again is refused.
+    /// Such a message is skipped if the database already records a message for the same slot, or
+    /// the same source and target.
+    ///
+    /// The greatest slot, source epoch and target epoch imported for each validator are recorded
+    /// as lower bounds, as the document may omit older messages (e.g., a minimal document with a
+    /// single block and attestation). No block with a slot at or below the bound may be signed,
+    /// nor any attestation with a source epoch below, or a target epoch at or below, the bounds.
+    /// Imported messages that are already below the bounds of a previous import are skipped.
+    ///
+    /// Validators in the document that are not yet registered with the database are registered.
+    pub fn import_interchange_info(
+        &self,
+        interchange: &Interchange,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), InterchangeError> {
+        if interchange.metadata.interchange_format_version != SUPPORTED_INTERCHANGE_FORMAT_VERSION {
+            return Err(InterchangeError::UnsupportedVersion(
+                interchange.metadata.interchange_format_version,
+            ));
+        }
+
+        if interchange.metadata.genesis_validators_root != genesis_validators_root {
+            return Err(InterchangeError::GenesisValidatorsMismatch {
+                client: genesis_validators_root,
+                interchange_file: interchange.metadata.genesis_validators_root,
+            });
+        }
+
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        for record in &interchange.data {
+            txn.execute(
+                "INSERT OR IGNORE INTO validators (public_key) VALUES (?1)",
+                params![record.pubkey.as_hex_string()],
+            )?;
+
+            for block in &record.signed_blocks {
+                self.import_block_txn(&txn, &record.pubkey, block)
+                    .map_err(|error| InterchangeError::InvalidBlock {
+                        pubkey: record.pubkey.clone(),
+                        slot: block.slot,
+                        error,
+                    })?;
+            }
+
+            for attestation in &record.signed_attestations {
+                self.import_attestation_txn(&txn, &record.pubkey, attestation)
+                    .map_err(|error| InterchangeError::InvalidAttestation {
+                        pubkey: record.pubkey.clone(),
+                        source_epoch: attestation.source_epoch,
+                        target_epoch: attestation.target_epoch,
+                        error,
+                    })?;
+            }
+
+            Self::update_lower_bounds_txn(&txn, record)?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Raise the lower bounds of a validator to the greatest slot and epochs of its imported
+    /// messages, within the import transaction.
+    fn update_lower_bounds_txn(
+        txn: &Transaction,
+        record: &InterchangeData,
+    ) -> Result<(), InterchangeError> {
+        let validator_id = Self::get_validator_id(txn, &record.pubkey)
+            .map_err(|e| InterchangeError::SQLError(format!("{:?}", e)))?;
+        let max_slot = record.signed_blocks.iter().map(|block| block.slot).max();
+        let max_source_epoch = record
+            .signed_attestations
+            .iter()
+            .map(|attestation| attestation.source_epoch)
+            .max();
+        let max_target_epoch = record
+            .signed_attestations
+            .iter()
+            .map(|attestation| attestation.target_epoch)
+            .max();
+
+        txn.execute(
+            "INSERT OR IGNORE INTO lower_bounds (validator_id) VALUES (?1)",
+            params![validator_id],
+        )?;
+        // `MAX` of SQLite is NULL if any argument is NULL, so a missing bound is replaced by the
+        // imported one, and a missing imported bound keeps the existing one.
+        txn.execute(
+            "UPDATE lower_bounds
+             SET max_slot = MAX(IFNULL(max_slot, ?2), IFNULL(?2, max_slot)),
+                 max_source_epoch = MAX(IFNULL(max_source_epoch, ?3), IFNULL(?3, max_source_epoch)),
+                 max_target_epoch = MAX(IFNULL(max_target_epoch, ?4), IFNULL(?4, max_target_epoch))
+             WHERE validator_id = ?1",
+            params![
+                validator_id,
+                max_slot.map(|slot| slot.as_u64() as i64),
+                max_source_epoch.map(|epoch| epoch.as_u64() as i64),
+                max_target_epoch.map(|epoch| epoch.as_u64() as i64)
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Import a single block of an interchange document, within the import transaction.
+    fn import_block_txn(
+        &self,
+        txn: &Transaction,
+        validator_pubkey: &PublicKey,
+        block: &InterchangeBlock,
+    ) -> Result<Safe, NotSafe> {
+        let signing_root = block.signing_root.unwrap_or_else(Hash256::zero);
+        match self.check_and_insert_block_signing_root_txn(
+            txn,
+            validator_pubkey,
+            block.slot,
+            signing_root,
+        ) {
+            Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(_)))
+                if block.signing_root.is_none() =>
+            {
+                Ok(Safe::SameData)
+            }
+            // Already protected by the bounds of a previous import.
+            Err(NotSafe::InvalidBlock(InvalidBlock::SlotViolatesLowerBound { .. })) => {
+                Ok(Safe::SameData)
+            }
+            result => result,
+        }
+    }
+
+    /// Import a single attestation of an interchange document, within the import transaction.
+    fn import_attestation_txn(
+        &self,
+        txn: &Transaction,
+        validator_pubkey: &PublicKey,
+        attestation: &InterchangeAttestation,
+    ) -> Result<Safe, NotSafe> {
+        let signing_root = attestation.signing_root.unwrap_or_else(Hash256::zero);
+        match self.check_and_insert_attestation_signing_root_txn(
+            txn,
+            validator_pubkey,
+            attestation.source_epoch,
+            attestation.target_epoch,
+            signing_root,
+        ) {
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(existing)))
+                if attestation.signing_root.is_none()
+                    && existing.source_epoch == attestation.source_epoch =>
+            {
+                Ok(Safe::SameData)
+            }
+            // Already protected by the bounds of a previous import.
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::SourceLessThanLowerBound {
+                ..
+            }))
+            | Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::TargetLessThanOrEqLowerBound { .. },
+            )) => Ok(Safe::SameData),
+            result => result,
+        }
+    }
+
+    /// Export the complete signing history of every registered validator as an `Interchange`
+    /// document.
+    ///
+    /// Messages imported without a signing root are exported without one.
+    ///
+    /// The slashing protection database does not know which chain it was used on, so the caller
+    /// must supply the `genesis_validators_root`.
+    pub fn export_interchange_info(
+        &self,
+        genesis_validators_root: Hash256,
+    ) -> Result<Interchange, InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        let validators = txn
+            .prepare("SELECT id, public_key FROM validators ORDER BY id ASC")?
+            .query_and_then(params![], |row| {
+                let id: i64 = row.get(0)?;
+                let pubkey_str: String = row.get(1)?;
+                Ok((id, pubkey_str))
+            })?
+            .collect::<Result<Vec<_>, InterchangeError>>()?;
+
+        let data = validators
+            .into_iter()
+            .map(|(id, pubkey_str)| {
+                let pubkey = pubkey_from_hex_str(&pubkey_str).map_err(|e| {
+                    InterchangeError::InvalidPubkey(format!("{}: {}", pubkey_str, e))
+                })?;
+
+                let signed_blocks = txn
+                    .prepare(
+                        "SELECT slot, signing_root
+                         FROM signed_blocks
+                         WHERE validator_id = ?1
+                         ORDER BY slot ASC",
+                    )?
+                    .query_map(params![id], SignedBlock::from_row)?
+                    .map(|block| {
+                        block.map(|block| InterchangeBlock {
+                            slot: block.slot,
+                            signing_root: interchange_signing_root(block.signing_root),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                let signed_attestations = txn
+                    .prepare(
+                        "SELECT source_epoch, target_epoch, signing_root
+                         FROM signed_attestations
+                         WHERE validator_id = ?1
+                         ORDER BY target_epoch ASC",
+                    )?
+                    .query_map(params![id], SignedAttestation::from_row)?
+                    .map(|attestation| {
+                        attestation.map(|attestation| InterchangeAttestation {
+                            source_epoch: attestation.source_epoch,
+                            target_epoch: attestation.target_epoch,
+                            signing_root: interchange_signing_root(attestation.signing_root),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(InterchangeData {
+                    pubkey,
+                    signed_blocks,
+                    signed_attestations,
+                })
+            })
+            .collect::<Result<_, InterchangeError>>()?;
+
+        Ok(Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root,
+            },
+            data,
+        })
+    }
+}
+
+/// The signing root of an exported message, or `None` for the zero root recorded for imported
+/// messages without a signing root.
+fn interchange_signing_root(signing_root: Hash256) -> Option<Hash256> {
+    if signing_root == Hash256::zero() {
+        None
+    } else {
+        Some(signing_root)
+    }
+}
+
+/// Parse a public key stored in the `validators` table (see `PublicKey::as_hex_string`).
+fn pubkey_from_hex_str(s: &str) -> Result<PublicKey, String> {
+    let bytes = if s.starts_with("0x") {
+        hex::decode(&s[2..]).map_err(|e| format!("Invalid hex: {:?}", e))?
+    } else {
+        return Err("Public key must be prefixed with 0x".to_string());
+    };
+
+    PublicKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {:?}", e))
+}
+
+#[derive(Debug)]
+pub enum InterchangeError {
+    UnsupportedVersion(u64),
+    GenesisValidatorsMismatch {
+        interchange_file: Hash256,
+        client: Hash256,
+    },
+    InvalidPubkey(String),
+    InvalidBlock {
+        pubkey: PublicKey,
+        slot: Slot,
+        error: NotSafe,
+    },
+    InvalidAttestation {
+        pubkey: PublicKey,
+        source_epoch: Epoch,
+        target_epoch: Epoch,
+        error: NotSafe,
+    },
+    SQLError(String),
+    SQLPoolError(r2d2::Error),
+}
+
+impl From<rusqlite::Error> for InterchangeError {
+    fn from(error: rusqlite::Error) -> Self {
+        InterchangeError::SQLError(error.to_string())
+    }
+}
+
+impl From<r2d2::Error> for InterchangeError {
+    fn from(error: r2d2::Error) -> Self {
+        InterchangeError::SQLPoolError(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SLASHING_PROTECTION_FILENAME;
+    use tempdir::TempDir;
+    use types::{test_utils::generate_deterministic_keypair, Checkpoint};
+
+    const DEFAULT_DOMAIN: Hash256 = Hash256::zero();
+
+    fn create_db() -> (TempDir, SlashingDatabase, PublicKey) {
+        let dir = TempDir::new("slashing_protection").expect("should create temp dir");
+        let db = SlashingDatabase::create(&dir.path().join(SLASHING_PROTECTION_FILENAME))
+            .expect("should create db");
+        let pubkey = generate_deterministic_keypair(0).pk;
+        db.register_validator(&pubkey)
+            .expect("should register validator");
+        (dir, db, pubkey)
+    }
+
+    fn block(slot: u64) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: Slot::new(slot),
+            proposer_index: 0,
+            parent_root: Hash256::zero(),
+            state_root: Hash256::zero(),
+            body_root: Hash256::zero(),
+        }
+    }
+
+    fn attestation(source: u64, target: u64) -> AttestationData {
+        AttestationData {
+            slot: Slot::new(0),
+            index: 0,
+            beacon_block_root: Hash256::zero(),
+            source: Checkpoint {
+                epoch: Epoch::new(source),
+                root: Hash256::zero(),
+            },
+            target: Checkpoint {
+                epoch: Epoch::new(target),
+                root: Hash256::zero(),
+            },
+        }
+    }
+
+    #[test]
+    fn unregistered_validator() {
+        let (_dir, db, _) = create_db();
+        let other = generate_deterministic_keypair(1).pk;
+
+        assert_eq!(
+            db.check_and_insert_block_proposal(&other, &block(1), DEFAULT_DOMAIN),
+            Err(NotSafe::UnregisteredValidator(other.clone()))
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&other, &attestation(0, 1), DEFAULT_DOMAIN),
+            Err(NotSafe::UnregisteredValidator(other))
+        );
+    }
+
+    #[test]
+    fn double_block_proposal() {
+        let (_dir, db, pubkey) = create_db();
+        let first = block(1);
+        let mut second = block(1);
+        second.state_root = Hash256::repeat_byte(1);
+
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &first, DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &first, DEFAULT_DOMAIN),
+            Ok(Safe::SameData)
+        );
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &second, DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+                SignedBlock::new(first.slot, first.signing_root(DEFAULT_DOMAIN))
+            )))
+        );
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &block(2), DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+    }
+
+    #[test]
+    fn double_vote() {
+        let (_dir, db, pubkey) = create_db();
+        let first = attestation(0, 1);
+        let mut second = attestation(0, 1);
+        second.beacon_block_root = Hash256::repeat_byte(1);
+
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &first, DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &first, DEFAULT_DOMAIN),
+            Ok(Safe::SameData)
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &second, DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                SignedAttestation::from_attestation(&first, DEFAULT_DOMAIN)
+            )))
+        );
+    }
+
+    #[test]
+    fn surround_votes() {
+        let (_dir, db, pubkey) = create_db();
+        let middle = attestation(2, 3);
+
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &middle, DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(1, 4), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::NewSurroundsPrev {
+                    prev: SignedAttestation::from_attestation(&middle, DEFAULT_DOMAIN)
+                }
+            ))
+        );
+
+        let outer = attestation(5, 10);
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &outer, DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(6, 7), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::PrevSurroundsNew {
+                    prev: SignedAttestation::from_attestation(&outer, DEFAULT_DOMAIN)
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn source_exceeds_target() {
+        let (_dir, db, pubkey) = create_db();
+
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(2, 1), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceExceedsTarget
+            ))
+        );
+    }
+
+    #[test]
+    fn persists_across_reopen() {
+        let (dir, db, pubkey) = create_db();
+        let first = attestation(0, 1);
+        let mut second = attestation(0, 1);
+        second.beacon_block_root = Hash256::repeat_byte(1);
+
+        db.check_and_insert_attestation(&pubkey, &first, DEFAULT_DOMAIN)
+            .expect("should insert attestation");
+        drop(db);
+
+        let db = SlashingDatabase::open_or_create(&dir.path().join(SLASHING_PROTECTION_FILENAME))
+            .expect("should re-open db");
+
+        assert!(db
+            .check_and_insert_attestation(&pubkey, &second, DEFAULT_DOMAIN)
+            .is_err());
+    }
+
+    #[test]
+    fn open_interrupted_creation() {
+        let dir = TempDir::new("slashing_protection").expect("should create temp dir");
+        let path = dir.path().join(SLASHING_PROTECTION_FILENAME);
+        // The file was created, but not the tables.
+        File::create(&path).expect("should create file");
+
+        let db = SlashingDatabase::open_or_create(&path).expect("should open db");
+        let pubkey = generate_deterministic_keypair(0).pk;
+        db.register_validator(&pubkey)
+            .expect("should create the tables on open");
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &block(1), DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+    }
+
+    #[test]
+    fn parallel_block_proposals() {
+        use rayon::prelude::*;
+
+        let (_dir, db, pubkey) = create_db();
+
+        let results = (0..8)
+            .into_par_iter()
+            .map(|i| {
+                let mut header = block(1);
+                header.state_root = Hash256::from_low_u64_be(i);
+                db.check_and_insert_block_proposal(&pubkey, &header, DEFAULT_DOMAIN)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results.iter().filter(|res| res.is_ok()).count(),
+            1,
+            "only one proposal for the slot should be accepted"
+        );
+    }
+
+    #[test]
+    fn interchange_round_trip() {
+        let (_dir, db, pubkey) = create_db();
+        let genesis_validators_root = Hash256::repeat_byte(0xaa);
+
+        db.check_and_insert_block_proposal(&pubkey, &block(1), DEFAULT_DOMAIN)
+            .expect("should insert block");
+        db.check_and_insert_attestation(&pubkey, &attestation(0, 1), DEFAULT_DOMAIN)
+            .expect("should insert attestation");
+        db.check_and_insert_attestation(&pubkey, &attestation(1, 2), DEFAULT_DOMAIN)
+            .expect("should insert attestation");
+
+        let exported = db
+            .export_interchange_info(genesis_validators_root)
+            .expect("should export");
+
+        let mut json = vec![];
+        exported.write_to(&mut json).expect("should write json");
+        let decoded = Interchange::from_json_reader(&json[..]).expect("should decode json");
+        assert_eq!(decoded, exported);
+
+        let (_dir_2, db_2, _) = create_db();
+        db_2.import_interchange_info(&decoded, genesis_validators_root)
+            .expect("should import");
+
+        assert_eq!(
+            db_2.export_interchange_info(genesis_validators_root)
+                .expect("should export"),
+            exported
+        );
+
+        // The imported history should protect the validator.
+        assert!(db_2
+            .check_and_insert_attestation(&pubkey, &attestation(0, 3), DEFAULT_DOMAIN)
+            .is_err());
+    }
+
+    #[test]
+    fn interchange_wrong_genesis_validators_root() {
+        let (_dir, db, _) = create_db();
+
+        let exported = db
+            .export_interchange_info(Hash256::repeat_byte(0xaa))
+            .expect("should export");
+
+        match db.import_interchange_info(&exported, Hash256::repeat_byte(0xbb)) {
+            Err(InterchangeError::GenesisValidatorsMismatch { .. }) => {}
+            other => panic!("import should fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interchange_conflicting_import() {
+        let (_dir, db, pubkey) = create_db();
+        let genesis_validators_root = Hash256::repeat_byte(0xaa);
+
+        db.check_and_insert_attestation(&pubkey, &attestation(0, 2), DEFAULT_DOMAIN)
+            .expect("should insert attestation");
+        let before = db
+            .export_interchange_info(genesis_validators_root)
+            .expect("should export");
+
+        // Valid blocks and attestations, followed by a double vote on the existing attestation.
+        let interchange_attestation = |source: u64, target: u64, root: u8| InterchangeAttestation {
+            source_epoch: Epoch::new(source),
+            target_epoch: Epoch::new(target),
+            signing_root: Some(Hash256::repeat_byte(root)),
+        };
+        let mut interchange = before.clone();
+        interchange.data[0].signed_blocks.push(InterchangeBlock {
+            slot: Slot::new(1),
+            signing_root: Some(Hash256::repeat_byte(1)),
+        });
+        interchange.data[0]
+            .signed_attestations
+            .push(interchange_attestation(0, 1, 1));
+        interchange.data[0]
+            .signed_attestations
+            .push(interchange_attestation(2, 3, 1));
+        interchange.data[0]
+            .signed_attestations
+            .push(interchange_attestation(1, 2, 1));
+
+        match db.import_interchange_info(&interchange, genesis_validators_root) {
+            Err(InterchangeError::InvalidAttestation { .. }) => {}
+            other => panic!("import should fail, got {:?}", other),
+        }
+
+        // A failed import must not leave partial data behind.
+        assert_eq!(
+            db.export_interchange_info(genesis_validators_root)
+                .expect("should export"),
+            before
+        );
+    }
+
+    /// An interchange document for `pubkey`, with blocks at `slots` and attestations with the
+    /// given source and target epochs, all with a signing root.
+    fn interchange(
+        pubkey: &PublicKey,
+        genesis_validators_root: Hash256,
+        slots: &[u64],
+        attestations: &[(u64, u64)],
+    ) -> Interchange {
+        Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root,
+            },
+            data: vec![InterchangeData {
+                pubkey: pubkey.clone(),
+                signed_blocks: slots
+                    .iter()
+                    .map(|slot| InterchangeBlock {
+                        slot: Slot::new(*slot),
+                        signing_root: Some(Hash256::repeat_byte(1)),
+                    })
+                    .collect(),
+                signed_attestations: attestations
+                    .iter()
+                    .map(|(source, target)| InterchangeAttestation {
+                        source_epoch: Epoch::new(*source),
+                        target_epoch: Epoch::new(*target),
+                        signing_root: Some(Hash256::repeat_byte(1)),
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    #[test]
+    fn interchange_minimal_lower_bounds() {
+        let (_dir, db, pubkey) = create_db();
+        let genesis_validators_root = Hash256::repeat_byte(0xaa);
+
+        // A minimal document, with only the latest block and attestation.
+        db.import_interchange_info(
+            &interchange(&pubkey, genesis_validators_root, &[10], &[(5, 6)]),
+            genesis_validators_root,
+        )
+        .expect("should import");
+
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &block(9), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidBlock(
+                InvalidBlock::SlotViolatesLowerBound {
+                    block_slot: Slot::new(9),
+                    bound_slot: Slot::new(10),
+                }
+            )),
+            "should refuse a block below the greatest imported slot"
+        );
+        assert!(
+            db.check_and_insert_block_proposal(&pubkey, &block(10), DEFAULT_DOMAIN)
+                .is_err(),
+            "should refuse a block at the greatest imported slot"
+        );
+        assert_eq!(
+            db.check_and_insert_block_proposal(&pubkey, &block(11), DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(4, 7), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::SourceLessThanLowerBound {
+                    source_epoch: Epoch::new(4),
+                    bound_epoch: Epoch::new(5),
+                }
+            )),
+            "should refuse a source below the greatest imported source"
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(5, 5), DEFAULT_DOMAIN),
+            Err(NotSafe::InvalidAttestation(
+                InvalidAttestation::TargetLessThanOrEqLowerBound {
+                    target_epoch: Epoch::new(5),
+                    bound_epoch: Epoch::new(6),
+                }
+            )),
+            "should refuse a target below the greatest imported target"
+        );
+        assert!(
+            db.check_and_insert_attestation(&pubkey, &attestation(5, 6), DEFAULT_DOMAIN)
+                .is_err(),
+            "should refuse a target at the greatest imported target"
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(5, 7), DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+    }
+
+    #[test]
+    fn interchange_lower_bounds_only_increase() {
+        let (_dir, db, pubkey) = create_db();
+        let genesis_validators_root = Hash256::repeat_byte(0xaa);
+
+        db.import_interchange_info(
+            &interchange(&pubkey, genesis_validators_root, &[10], &[(5, 6)]),
+            genesis_validators_root,
+        )
+        .expect("should import");
+        // Older history below the bounds is already protected by them.
+        db.import_interchange_info(
+            &interchange(&pubkey, genesis_validators_root, &[3], &[(1, 2)]),
+            genesis_validators_root,
+        )
+        .expect("should import older history");
+
+        assert!(db
+            .check_and_insert_block_proposal(&pubkey, &block(9), DEFAULT_DOMAIN)
+            .is_err());
+        assert!(db
+            .check_and_insert_attestation(&pubkey, &attestation(4, 7), DEFAULT_DOMAIN)
+            .is_err());
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, &attestation(5, 7), DEFAULT_DOMAIN),
+            Ok(Safe::Valid)
+        );
+    }
+
+    #[test]
+    fn interchange_eip_3076_document() {
+        let (_dir, db, pubkey) = create_db();
+        let genesis_validators_root = Hash256::repeat_byte(0xaa);
+
+        // Integers are quoted, and signing roots are optional.
+        let json = format!(
+            r#"{{
+                "metadata": {{
+                    "interchange_format_version": "5",
+                    "genesis_validators_root": "0x{}"
+                }},
+                "data": [
+                    {{
+                        "pubkey": "{}",
+                        "signed_blocks": [{{ "slot": "81952" }}],
+                        "signed_attestations": [{{ "source_epoch": "2290", "target_epoch": "3007" }}]
+                    }}
+                ]
+            }}"#,
+            "aa".repeat(32),
+            pubkey.as_hex_string()
+        );
+        let interchange = Interchange::from_json_str(&json).expect("should decode json");
+
+        db.import_interchange_info(&interchange, genesis_validators_root)
+            .expect("should import");
+        // The messages are already recorded, so importing them again changes nothing.
+        db.import_interchange_info(&interchange, genesis_validators_root)
+            .expect("should import again");
+
+        // Without signing roots, no message for the same slot or target may be signed.
+        assert!(db
+            .check_and_insert_block_signing_root(&pubkey, Slot::new(81952), Hash256::repeat_byte(1))
+            .is_err());
+        assert!(db
+            .check_and_insert_attestation_signing_root(
+                &pubkey,
+                Epoch::new(2290),
+                Epoch::new(3007),
+                Hash256::repeat_byte(1)
+            )
+            .is_err());
+
+        let mut exported = vec![];
+        db.export_interchange_info(genesis_validators_root)
+            .expect("should export")
+            .write_to(&mut exported)
+            .expect("should write json");
+        let exported = String::from_utf8(exported).expect("should be utf8");
+        assert!(exported.contains(r#""interchange_format_version": "5""#));
+        assert!(exported.contains(r#""slot": "81952""#));
+        assert!(exported.contains(r#""target_epoch": "3007""#));
+        assert!(
+            !exported.contains("signing_root"),
+            "should not export the zero signing roots of imported messages"
+        );
+    }
+}
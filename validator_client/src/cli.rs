@@ -14,12 +14,32 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value(&DEFAULT_HTTP_SERVER)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIRECTORY")
+                .help("The directory which contains the password to unlock the validator \
+                    voting keypairs. Each password should be contained in a file where the \
+                    name is the 0x-prefixed hex representation of the validators voting public \
+                    key. Defaults to ~/.lighthouse/secrets.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("allow-unsynced")
                 .long("allow-unsynced")
                 .help("If present, the validator client will still poll for duties if the beacon
                       node is not synced.")
         )
+        .arg(
+            Arg::with_name("doppelganger-detection-epochs")
+                .long("doppelganger-detection-epochs")
+                .value_name("EPOCHS")
+                .help("If present, validators will not sign any messages for this many epochs \
+                      after startup, whilst the beacon node is asked if any other instance of \
+                      each validator is active. A validator that is seen signing messages will \
+                      not be started.")
+                .takes_value(true)
+        )
         /*
          * The "testnet" sub-command.
          *
@@ -1,3 +1,4 @@
+use crate::validator_directory::DEFAULT_SECRETS_DIR;
 use clap::ArgMatches;
 use serde_derive::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -25,6 +26,8 @@ impl Default for KeySource {
 pub struct Config {
     /// The data directory, which stores all validator databases
     pub data_dir: PathBuf,
+    /// The directory containing the passwords for the validator keystores.
+    pub secrets_dir: PathBuf,
     /// Specifies how the validator client should load keypairs.
     #[serde(skip)]
     pub key_source: KeySource,
@@ -35,6 +38,9 @@ pub struct Config {
     /// If true, the validator client will still poll for duties and produce blocks even if the
     /// beacon node is not synced at startup.
     pub allow_unsynced_beacon_node: bool,
+    /// If set, validators will not sign any messages until the beacon node has observed no
+    /// messages from them for this many epochs after startup.
+    pub doppelganger_detection_epochs: Option<u64>,
 }
 
 impl Default for Config {
@@ -43,11 +49,16 @@ impl Default for Config {
         let mut data_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         data_dir.push(".lighthouse");
         data_dir.push("validators");
+        let secrets_dir = dirs::home_dir()
+            .map(|home| home.join(DEFAULT_SECRETS_DIR))
+            .unwrap_or_else(|| PathBuf::from("secrets"));
         Self {
             data_dir,
+            secrets_dir,
             key_source: <_>::default(),
             http_server: DEFAULT_HTTP_SERVER.to_string(),
             allow_unsynced_beacon_node: false,
+            doppelganger_detection_epochs: None,
         }
     }
 }
@@ -71,6 +82,10 @@ impl Config {
                     .unwrap_or_else(|| PathBuf::from("."))
             });
 
+        if let Some(secrets_dir) = cli_args.value_of("secrets-dir") {
+            config.secrets_dir = PathBuf::from(secrets_dir);
+        }
+
         if let Some(server) = cli_args.value_of("server") {
             config.http_server = server.to_string();
         }
@@ -94,6 +109,13 @@ impl Config {
 
         config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
 
+        if let Some(epochs) = cli_args.value_of("doppelganger-detection-epochs") {
+            config.doppelganger_detection_epochs =
+                Some(epochs.parse::<u64>().map_err(|e| {
+                    format!("Unable to parse doppelganger detection epochs: {:?}", e)
+                })?);
+        }
+
         Ok(config)
     }
 }
@@ -0,0 +1,554 @@
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{future, Future, Stream};
+use parking_lot::RwLock;
+use remote_beacon_node::{LivenessResponse, RemoteBeaconNode};
+use slog::{crit, debug, info, warn};
+use slot_clock::SlotClock;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, Epoch, EthSpec, PublicKey, PublicKeyBytes};
+
+/// Builds a `DoppelgangerService`.
+pub struct DoppelgangerServiceBuilder<T, E: EthSpec> {
+    validator_store: Option<ValidatorStore<T, E>>,
+    slot_clock: Option<T>,
+    beacon_node: Option<RemoteBeaconNode<E>>,
+    context: Option<RuntimeContext<E>>,
+    detection_epochs: Option<u64>,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> DoppelgangerServiceBuilder<T, E> {
+    pub fn new() -> Self {
+        Self {
+            validator_store: None,
+            slot_clock: None,
+            beacon_node: None,
+            context: None,
+            detection_epochs: None,
+        }
+    }
+
+    pub fn validator_store(mut self, store: ValidatorStore<T, E>) -> Self {
+        self.validator_store = Some(store);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: T) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<E>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn detection_epochs(mut self, detection_epochs: u64) -> Self {
+        self.detection_epochs = Some(detection_epochs);
+        self
+    }
+
+    /// Builds the service, silencing every validator in the `validator_store` until detection is
+    /// complete.
+    pub fn build(self) -> Result<DoppelgangerService<T, E>, String> {
+        let validator_store = self
+            .validator_store
+            .ok_or_else(|| "Cannot build DoppelgangerService without validator_store")?;
+        let slot_clock = self
+            .slot_clock
+            .ok_or_else(|| "Cannot build DoppelgangerService without slot_clock")?;
+        // If the slot clock cannot be read then genesis has not occurred and no other instance
+        // can have signed messages, so detection starts from the genesis epoch.
+        let start_epoch = slot_clock
+            .now()
+            .map(|slot| slot.epoch(E::slots_per_epoch()))
+            .unwrap_or_else(|| Epoch::new(0));
+
+        validator_store.silence_all();
+
+        Ok(DoppelgangerService {
+            inner: Arc::new(Inner {
+                validator_store,
+                slot_clock,
+                beacon_node: self
+                    .beacon_node
+                    .ok_or_else(|| "Cannot build DoppelgangerService without beacon_node")?,
+                context: self
+                    .context
+                    .ok_or_else(|| "Cannot build DoppelgangerService without runtime_context")?,
+                detection_epochs: self
+                    .detection_epochs
+                    .ok_or_else(|| "Cannot build DoppelgangerService without detection_epochs")?,
+                start_epoch,
+                state: RwLock::new(DetectionState::default()),
+            }),
+        })
+    }
+}
+
+/// The progress of doppelganger detection.
+#[derive(Default)]
+struct DetectionState {
+    /// The most recent epoch that was checked without error.
+    last_checked_epoch: Option<Epoch>,
+    /// The number of epochs that have been checked.
+    checked_epochs: u64,
+    /// Validators which were seen signing messages elsewhere. They are never started.
+    detected: HashSet<PublicKey>,
+    /// Set once the remaining validators have been started.
+    complete: bool,
+}
+
+impl DetectionState {
+    /// Returns the oldest epoch prior to `current_epoch` that is yet to be checked, if any.
+    ///
+    /// Epochs are checked in order, so an epoch that could not be checked is retried before any
+    /// later epoch.
+    fn next_unchecked_epoch(&self, start_epoch: Epoch, current_epoch: Epoch) -> Option<Epoch> {
+        if self.complete {
+            return None;
+        }
+
+        let epoch = self
+            .last_checked_epoch
+            .map_or(start_epoch, |epoch| epoch.max(start_epoch))
+            + 1;
+
+        if epoch < current_epoch {
+            Some(epoch)
+        } else {
+            None
+        }
+    }
+
+    /// Restarts detection from the epoch prior to `current_epoch` if an older epoch is yet to be
+    /// checked, returning that epoch.
+    ///
+    /// The beacon node only knows the liveness of validators during the current and previous
+    /// epochs, so an older epoch can never be checked. The epochs checked so far no longer count
+    /// towards detection, since the unchecked epoch between them and the restart is unverified.
+    fn restart_if_unverifiable(
+        &mut self,
+        start_epoch: Epoch,
+        current_epoch: Epoch,
+    ) -> Option<Epoch> {
+        let epoch = self.next_unchecked_epoch(start_epoch, current_epoch)?;
+
+        if epoch + 1 < current_epoch {
+            self.last_checked_epoch = Some(current_epoch - 2);
+            self.checked_epochs = 0;
+            Some(epoch)
+        } else {
+            None
+        }
+    }
+
+    /// Records that `epoch` was checked and that the `live` validators were seen signing during
+    /// it. Returns `true` if the epoch was not already checked.
+    fn record_checked_epoch<I>(&mut self, start_epoch: Epoch, epoch: Epoch, live: I) -> bool
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        if self.next_unchecked_epoch(start_epoch, epoch + 1) != Some(epoch) {
+            return false;
+        }
+
+        self.detected.extend(live);
+        self.last_checked_epoch = Some(epoch);
+        self.checked_epochs += 1;
+        true
+    }
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    validator_store: ValidatorStore<T, E>,
+    slot_clock: T,
+    beacon_node: RemoteBeaconNode<E>,
+    context: RuntimeContext<E>,
+    detection_epochs: u64,
+    /// The epoch in which the validator client started.
+    start_epoch: Epoch,
+    state: RwLock<DetectionState>,
+}
+
+/// Keeps each validator silent for `detection_epochs` after startup, whilst asking the beacon node
+/// if any other instance of the validator is signing messages.
+///
+/// The epoch in which the validator client starts is never checked, since the messages seen in it
+/// may have been signed by this validator client before it was restarted. Each subsequent epoch is
+/// checked once it has become the previous epoch, and an epoch that could not be checked is
+/// retried before any later epoch. A validator that is seen signing is never started, the others
+/// are started once `detection_epochs` epochs have been checked.
+///
+/// If an epoch is not checked before it is older than the previous epoch, the beacon node can no
+/// longer report on it, so detection restarts from the previous epoch.
+pub struct DoppelgangerService<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for DoppelgangerService<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for DoppelgangerService<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> DoppelgangerService<T, E> {
+    /// Starts the service that checks the liveness of the silenced validators once per epoch.
+    ///
+    /// Checks run three-quarters of the way through each epoch, giving the beacon node time to
+    /// include the attestations of the previous epoch in blocks.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+
+        info!(
+            log,
+            "Doppelganger detection enabled";
+            "msg" => "validators will not sign messages until detection is complete",
+            "detection_epochs" => self.detection_epochs,
+            "start_epoch" => self.start_epoch.as_u64(),
+        );
+
+        let duration_to_next_epoch = self
+            .slot_clock
+            .duration_to_next_epoch(E::slots_per_epoch())
+            .ok_or_else(|| "Unable to determine duration to next epoch".to_string())?;
+
+        let interval = {
+            let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+            let epoch_duration = slot_duration * E::slots_per_epoch() as u32;
+            Interval::new(
+                Instant::now() + duration_to_next_epoch + epoch_duration * 3 / 4,
+                epoch_duration,
+            )
+        };
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let service = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log.clone();
+
+        // Run an immediate update before starting the updater service.
+        self.context.executor.spawn(service.do_update());
+
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| service.do_update().then(|_| Ok(()))),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Checks the liveness of the silenced validators during each epoch that has not yet been
+    /// checked, oldest first.
+    ///
+    /// If an epoch cannot be checked then the later epochs are left for the next update, so that
+    /// the failed epoch is retried.
+    fn do_update(&self) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+        let log = self.context.log.clone();
+
+        let current_epoch = match self.slot_clock.now() {
+            Some(slot) => slot.epoch(E::slots_per_epoch()),
+            None => {
+                warn!(log, "Doppelganger service failed to read slot clock");
+                return Box::new(future::ok(()));
+            }
+        };
+
+        if let Some(epoch) = self
+            .state
+            .write()
+            .restart_if_unverifiable(self.start_epoch, current_epoch)
+        {
+            crit!(
+                log,
+                "Doppelganger detection restarted";
+                "msg" => "an epoch could not be checked before the beacon node stopped reporting \
+                    on it, validators will remain silent whilst detection is repeated",
+                "unchecked_epoch" => epoch.as_u64(),
+                "restart_epoch" => (current_epoch - 1).as_u64(),
+                "detection_epochs" => self.detection_epochs,
+            );
+        }
+
+        let service = self.clone();
+        Box::new(future::loop_fn((), move |()| {
+            let epoch = match service
+                .state
+                .read()
+                .next_unchecked_epoch(service.start_epoch, current_epoch)
+            {
+                Some(epoch) => epoch,
+                None => return future::Either::A(future::ok(future::Loop::Break(()))),
+            };
+
+            let pubkeys = {
+                let state = service.state.read();
+                service
+                    .validator_store
+                    .silenced_pubkeys()
+                    .into_iter()
+                    .filter(|pubkey| !state.detected.contains(pubkey))
+                    .collect::<Vec<_>>()
+            };
+
+            let service = service.clone();
+            let log = log.clone();
+            future::Either::B(
+                service
+                    .beacon_node
+                    .http
+                    .validator()
+                    .get_liveness(epoch, &pubkeys)
+                    .map(move |liveness| {
+                        service.process_liveness(epoch, pubkeys, liveness);
+                        future::Loop::Continue(())
+                    })
+                    .map_err(move |e| {
+                        warn!(
+                            log,
+                            "Unable to check for doppelgangers";
+                            "msg" => "validators will remain silent until the epoch can be checked",
+                            "epoch" => epoch.as_u64(),
+                            "error" => format!("{:?}", e),
+                        )
+                    }),
+            )
+        }))
+    }
+
+    /// Records the liveness of the `pubkeys` during `epoch`, starting the validators that have not
+    /// been seen signing once enough epochs have been checked.
+    fn process_liveness(
+        &self,
+        epoch: Epoch,
+        pubkeys: Vec<PublicKey>,
+        liveness: Vec<LivenessResponse>,
+    ) {
+        let log = &self.context.log;
+        let mut state = self.state.write();
+
+        let pubkeys = pubkeys
+            .into_iter()
+            .map(|pubkey| (PublicKeyBytes::from(pubkey.clone()), pubkey))
+            .collect::<HashMap<_, _>>();
+
+        let live = liveness
+            .into_iter()
+            .filter(|response| response.is_live)
+            .filter_map(|response| {
+                let pubkey = pubkeys.get(&response.pubkey)?;
+                crit!(
+                    log,
+                    "Doppelganger detected";
+                    "msg" => "another instance of this validator is signing messages, it will \
+                        not be started. Stop the other instance and restart this validator client",
+                    "epoch" => epoch.as_u64(),
+                    "validator_index" => format!("{:?}", response.validator_index),
+                    "validator" => format!("{:?}", pubkey),
+                );
+                Some(pubkey.clone())
+            })
+            .collect::<Vec<_>>();
+
+        if !state.record_checked_epoch(self.start_epoch, epoch, live) {
+            return;
+        }
+
+        debug!(
+            log,
+            "Checked for doppelgangers";
+            "epoch" => epoch.as_u64(),
+            "checked_epochs" => state.checked_epochs,
+            "detection_epochs" => self.detection_epochs,
+        );
+
+        if state.checked_epochs >= self.detection_epochs {
+            let started = pubkeys
+                .values()
+                .filter(|pubkey| !state.detected.contains(pubkey))
+                .map(|pubkey| self.validator_store.unsilence(pubkey))
+                .count();
+
+            info!(
+                log,
+                "Doppelganger detection complete";
+                "started_validators" => started,
+                "detected_doppelgangers" => state.detected.len(),
+            );
+            state.complete = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::test_utils::generate_deterministic_keypair;
+
+    const START_EPOCH: u64 = 10;
+
+    fn pubkey(i: usize) -> PublicKey {
+        generate_deterministic_keypair(i).pk
+    }
+
+    #[test]
+    fn start_epoch_is_not_checked() {
+        let state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, start_epoch),
+            None,
+            "should not check the start epoch whilst it is the current epoch"
+        );
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, start_epoch + 1),
+            None,
+            "should not check the start epoch once it is the previous epoch"
+        );
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, start_epoch + 2),
+            Some(start_epoch + 1),
+            "should check the epoch after the start epoch"
+        );
+    }
+
+    #[test]
+    fn unchecked_epochs_are_retried() {
+        let mut state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 1, vec![]));
+
+        // The checks of the next two epochs failed, they should be checked in order.
+        let current_epoch = start_epoch + 5;
+        for epoch in 2..5 {
+            assert_eq!(
+                state.next_unchecked_epoch(start_epoch, current_epoch),
+                Some(start_epoch + epoch),
+                "should check the oldest unchecked epoch"
+            );
+            assert!(state.record_checked_epoch(start_epoch, start_epoch + epoch, vec![]));
+        }
+
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, current_epoch),
+            None,
+            "should not check the current epoch"
+        );
+        assert_eq!(state.checked_epochs, 4);
+        assert_eq!(state.last_checked_epoch, Some(start_epoch + 4));
+    }
+
+    #[test]
+    fn unverifiable_epoch_restarts_detection() {
+        let mut state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 1, vec![]));
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 2, vec![]));
+
+        // The check of the previous epoch may still succeed.
+        assert_eq!(
+            state.restart_if_unverifiable(start_epoch, start_epoch + 4),
+            None,
+            "should not restart whilst the oldest unchecked epoch is the previous epoch"
+        );
+        assert_eq!(state.checked_epochs, 2);
+
+        // The oldest unchecked epoch is now too old for the beacon node to report on.
+        let current_epoch = start_epoch + 6;
+        assert_eq!(
+            state.restart_if_unverifiable(start_epoch, current_epoch),
+            Some(start_epoch + 3),
+            "should restart when the oldest unchecked epoch is older than the previous epoch"
+        );
+        assert_eq!(state.checked_epochs, 0, "should reset the checked epochs");
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, current_epoch),
+            Some(current_epoch - 1),
+            "should check the previous epoch"
+        );
+        assert!(state.record_checked_epoch(start_epoch, current_epoch - 1, vec![]));
+        assert_eq!(state.checked_epochs, 1);
+    }
+
+    #[test]
+    fn epochs_are_checked_once_and_in_order() {
+        let mut state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        assert!(
+            !state.record_checked_epoch(start_epoch, start_epoch, vec![]),
+            "should not record the start epoch"
+        );
+        assert!(
+            !state.record_checked_epoch(start_epoch, start_epoch + 2, vec![]),
+            "should not record an epoch whilst an earlier epoch is unchecked"
+        );
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 1, vec![]));
+        assert!(
+            !state.record_checked_epoch(start_epoch, start_epoch + 1, vec![]),
+            "should not record an epoch twice"
+        );
+        assert_eq!(state.checked_epochs, 1);
+    }
+
+    #[test]
+    fn live_validators_are_detected() {
+        let mut state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 1, vec![pubkey(0)]));
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 2, vec![pubkey(1)]));
+        assert!(state.record_checked_epoch(start_epoch, start_epoch + 3, vec![]));
+
+        assert!(state.detected.contains(&pubkey(0)));
+        assert!(state.detected.contains(&pubkey(1)));
+        assert!(!state.detected.contains(&pubkey(2)));
+    }
+
+    #[test]
+    fn complete_detection_checks_no_epochs() {
+        let mut state = DetectionState::default();
+        let start_epoch = Epoch::new(START_EPOCH);
+
+        state.complete = true;
+        assert_eq!(
+            state.next_unchecked_epoch(start_epoch, start_epoch + 5),
+            None
+        );
+    }
+}
@@ -2,11 +2,13 @@ mod attestation_service;
 mod block_service;
 mod cli;
 mod config;
+mod doppelganger_service;
 mod duties_service;
 mod fork_service;
 mod notifier;
 mod validator_store;
 
+pub mod signing_method;
 pub mod validator_directory;
 
 pub use cli::cli_app;
@@ -15,6 +17,7 @@ pub use config::{Config, KeySource};
 use attestation_service::{AttestationService, AttestationServiceBuilder};
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
+use doppelganger_service::{DoppelgangerService, DoppelgangerServiceBuilder};
 use duties_service::{DutiesService, DutiesServiceBuilder};
 use environment::RuntimeContext;
 use exit_future::Signal;
@@ -46,6 +49,7 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     fork_service: ForkService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    doppelganger_service: Option<DoppelgangerService<SystemTimeSlotClock, T>>,
     exit_signals: Vec<Signal>,
 }
 
@@ -78,6 +82,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             "Starting validator client";
             "beacon_node" => &config.http_server,
             "datadir" => format!("{:?}", config.data_dir),
+            "secrets_dir" => format!("{:?}", config.secrets_dir),
         );
 
         RemoteBeaconNode::new_with_timeout(config.http_server.clone(), HTTP_TIMEOUT)
@@ -199,6 +204,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                             // Use the `account_manager` to generate these files.
                             KeySource::Disk => ValidatorStore::load_from_disk(
                                 config.data_dir.clone(),
+                                config.secrets_dir.clone(),
                                 genesis_validators_root,
                                 context.eth2_config.spec.clone(),
                                 fork_service.clone(),
@@ -224,6 +230,19 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                         "voting_validators" => validator_store.num_voting_validators()
                     );
 
+                    let doppelganger_service = match config.doppelganger_detection_epochs {
+                        Some(epochs) if epochs > 0 => Some(
+                            DoppelgangerServiceBuilder::new()
+                                .slot_clock(slot_clock.clone())
+                                .validator_store(validator_store.clone())
+                                .beacon_node(beacon_node.clone())
+                                .runtime_context(context.service_context("doppelganger".into()))
+                                .detection_epochs(epochs)
+                                .build()?,
+                        ),
+                        _ => None,
+                    };
+
                     let duties_service = DutiesServiceBuilder::new()
                         .slot_clock(slot_clock.clone())
                         .validator_store(validator_store.clone())
@@ -254,6 +273,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                         fork_service,
                         block_service,
                         attestation_service,
+                        doppelganger_service,
                         exit_signals: vec![],
                     })
                 },
@@ -292,6 +312,13 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             notifier_exit,
         ];
 
+        if let Some(doppelganger_service) = &self.doppelganger_service {
+            let doppelganger_exit = doppelganger_service
+                .start_update_service(&self.context.eth2_config.spec)
+                .map_err(|e| format!("Unable to start doppelganger service: {}", e))?;
+            self.exit_signals.push(doppelganger_exit);
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,440 @@
+//! Provides the `SigningMethod`, which produces signatures for a single validator using either a
+//! keypair held in memory or a remote signer.
+//!
+//! ## Remote signer protocol
+//!
+//! To sign a message, the validator client sends a `POST` request with a `RemoteSignerRequest`
+//! JSON body to:
+//!
+//! ```ignore
+//! <url>/sign/<0x-prefixed hex voting public key>
+//! ```
+//!
+//! The request contains the `signing_root` that must be signed, along with the fork, the
+//! genesis validators root and the message itself so that the signer can apply its own policy
+//! (e.g., slashing protection) before signing. A successful response has status `200` and a
+//! `RemoteSignerResponse` JSON body. Any other status is treated as a refusal to sign.
+//!
+//! The validator client verifies each signature returned by the remote signer before using it.
+
+use crate::validator_directory::ValidatorDirectory;
+use futures::Async;
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+use types::{
+    AggregateAndProof, AttestationData, BeaconBlock, Epoch, EthSpec, Fork, Hash256, Keypair,
+    PublicKey, Signature, SignedRoot, Slot,
+};
+
+/// The timeout for requests to a remote signer.
+///
+/// Signing is on the critical path of block and attestation production, so a slow signer should
+/// fail quickly rather than cause the message to be published late.
+pub const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A message that may be signed by a validator.
+pub enum SignableMessage<'a, T: EthSpec> {
+    RandaoReveal(Epoch),
+    BeaconBlock(&'a BeaconBlock<T>),
+    AttestationData(&'a AttestationData),
+    SignedAggregateAndProof(&'a AggregateAndProof<T>),
+    SelectionProof(Slot),
+}
+
+impl<'a, T: EthSpec> SignableMessage<'a, T> {
+    /// Returns the root that must be signed for `self` in the given `domain`.
+    pub fn signing_root(&self, domain: Hash256) -> Hash256 {
+        match self {
+            SignableMessage::RandaoReveal(epoch) => epoch.signing_root(domain),
+            SignableMessage::BeaconBlock(block) => block.signing_root(domain),
+            SignableMessage::AttestationData(data) => data.signing_root(domain),
+            SignableMessage::SignedAggregateAndProof(message) => message.signing_root(domain),
+            SignableMessage::SelectionProof(slot) => slot.signing_root(domain),
+        }
+    }
+
+    fn to_remote_signer_message(&self) -> RemoteSignerMessage<T> {
+        match self {
+            SignableMessage::RandaoReveal(epoch) => {
+                RemoteSignerMessage::RandaoReveal { epoch: *epoch }
+            }
+            SignableMessage::BeaconBlock(block) => RemoteSignerMessage::Block {
+                block: (*block).clone(),
+            },
+            SignableMessage::AttestationData(data) => RemoteSignerMessage::Attestation {
+                attestation: (*data).clone(),
+            },
+            SignableMessage::SignedAggregateAndProof(message) => {
+                RemoteSignerMessage::AggregateAndProof {
+                    aggregate_and_proof: (*message).clone(),
+                }
+            }
+            SignableMessage::SelectionProof(slot) => {
+                RemoteSignerMessage::SelectionProof { slot: *slot }
+            }
+        }
+    }
+}
+
+/// The message that a remote signer is asked to sign, tagged by its `type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", bound = "T: EthSpec")]
+pub enum RemoteSignerMessage<T: EthSpec> {
+    RandaoReveal {
+        epoch: Epoch,
+    },
+    Block {
+        block: BeaconBlock<T>,
+    },
+    Attestation {
+        attestation: AttestationData,
+    },
+    AggregateAndProof {
+        aggregate_and_proof: AggregateAndProof<T>,
+    },
+    SelectionProof {
+        slot: Slot,
+    },
+}
+
+/// The body of a request to a remote signer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: EthSpec")]
+pub struct RemoteSignerRequest<T: EthSpec> {
+    pub signing_root: Hash256,
+    pub fork: Fork,
+    pub genesis_validators_root: Hash256,
+    pub message: RemoteSignerMessage<T>,
+}
+
+/// The body of a successful response from a remote signer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignerResponse {
+    pub signature: Signature,
+}
+
+/// The method used by a validator to sign messages.
+pub enum SigningMethod {
+    /// The voting keypair is held in memory, having been decrypted from a keystore.
+    LocalKeystore { voting_keypair: Keypair },
+    /// The voting keypair is held by a remote signer at `url`.
+    RemoteSigner {
+        url: String,
+        voting_public_key: PublicKey,
+        http_client: reqwest::Client,
+    },
+}
+
+impl SigningMethod {
+    /// Returns the signing method defined by the `validator_directory`.
+    pub fn from_validator_directory(
+        validator_directory: &ValidatorDirectory,
+    ) -> Result<Self, String> {
+        if let Some(remote_signer) = &validator_directory.remote_signer {
+            Self::remote_signer(
+                remote_signer.url.clone(),
+                remote_signer.voting_public_key.clone(),
+            )
+        } else if let Some(voting_keypair) = &validator_directory.voting_keypair {
+            Ok(SigningMethod::LocalKeystore {
+                voting_keypair: voting_keypair.clone(),
+            })
+        } else {
+            Err(format!(
+                "Validator directory {:?} has no voting keypair or remote signer",
+                validator_directory.directory
+            ))
+        }
+    }
+
+    /// Returns a signing method which sends each message to the remote signer at `url`.
+    pub fn remote_signer(url: String, voting_public_key: PublicKey) -> Result<Self, String> {
+        let http_client = reqwest::Client::builder()
+            .timeout(REMOTE_SIGNER_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Unable to build remote signer client: {:?}", e))?;
+
+        Ok(SigningMethod::RemoteSigner {
+            url,
+            voting_public_key,
+            http_client,
+        })
+    }
+
+    /// Returns the public key of the validator that signs with `self`.
+    pub fn voting_public_key(&self) -> &PublicKey {
+        match self {
+            SigningMethod::LocalKeystore { voting_keypair } => &voting_keypair.pk,
+            SigningMethod::RemoteSigner {
+                voting_public_key, ..
+            } => voting_public_key,
+        }
+    }
+
+    /// Signs `message` in the given `domain`.
+    ///
+    /// The `fork` and `genesis_validators_root` must be those used to compute the `domain`, they
+    /// are provided to remote signers as context.
+    ///
+    /// A request to a remote signer blocks the current thread until it completes or times out,
+    /// however it does not stall the other tasks of the tokio thread pool.
+    pub fn get_signature<T: EthSpec>(
+        &self,
+        message: SignableMessage<T>,
+        domain: Hash256,
+        fork: &Fork,
+        genesis_validators_root: Hash256,
+    ) -> Result<Signature, String> {
+        let signing_root = message.signing_root(domain);
+
+        match self {
+            SigningMethod::LocalKeystore { voting_keypair } => {
+                Ok(Signature::new(signing_root.as_bytes(), &voting_keypair.sk))
+            }
+            SigningMethod::RemoteSigner {
+                url,
+                voting_public_key,
+                http_client,
+            } => {
+                let request = RemoteSignerRequest {
+                    signing_root,
+                    fork: fork.clone(),
+                    genesis_validators_root,
+                    message: message.to_remote_signer_message(),
+                };
+                let url = format!(
+                    "{}/sign/{}",
+                    url.trim_end_matches('/'),
+                    voting_public_key.as_hex_string()
+                );
+
+                let response: RemoteSignerResponse = run_blocking(|| {
+                    http_client
+                        .post(&url)
+                        .json(&request)
+                        .send()
+                        .and_then(|response| response.error_for_status())
+                        .and_then(|mut response| response.json())
+                })
+                .map_err(|e| format!("Remote signer request to {} failed: {:?}", url, e))?;
+
+                // Never trust a remote signer to have signed the correct message with the correct
+                // key, a bad signature would cause the message to be rejected by the network.
+                if response
+                    .signature
+                    .verify(signing_root.as_bytes(), voting_public_key)
+                {
+                    Ok(response.signature)
+                } else {
+                    Err(format!(
+                        "Remote signer at {} returned an invalid signature",
+                        url
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Runs `f`, which blocks the current thread, without stalling the other tasks of the tokio
+/// thread pool.
+///
+/// When called from a thread pool worker, the pool first hands the other tasks of the worker to a
+/// new thread. Otherwise, or if the pool has reached its limit of blocking threads, `f` is run
+/// directly.
+fn run_blocking<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let mut f = Some(f);
+    match tokio_threadpool::blocking(|| f.take().expect("f is only taken once")()) {
+        Ok(Async::Ready(result)) => result,
+        Ok(Async::NotReady) | Err(_) => f.take().expect("f was not run by the pool")(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{future, Future, Stream};
+    use hyper::service::service_fn;
+    use hyper::{Body, Request, Response, Server, StatusCode};
+    use std::net::SocketAddr;
+    use std::thread;
+    use tokio::runtime::Runtime;
+    use types::{
+        test_utils::{generate_deterministic_keypair, SeedableRng, TestRandom, XorShiftRng},
+        Attestation, Domain, MinimalEthSpec,
+    };
+
+    type E = MinimalEthSpec;
+
+    /// Starts a mock remote signer which signs any request for the `keypair`, returning the
+    /// address that it listens on.
+    ///
+    /// If `corrupt` is `true`, the signer returns signatures over the wrong message.
+    fn spawn_mock_signer(keypair: Keypair, corrupt: bool) -> SocketAddr {
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(move || {
+            let keypair = keypair.clone();
+            service_fn(move |req: Request<Body>| {
+                let keypair = keypair.clone();
+                let path = req.uri().path().to_string();
+                req.into_body().concat2().map(move |body| {
+                    let expected_path = format!("/sign/{}", keypair.pk.as_hex_string());
+                    if path != expected_path {
+                        return Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .body(Body::empty())
+                            .expect("should build response");
+                    }
+
+                    let request: RemoteSignerRequest<E> =
+                        serde_json::from_slice(&body).expect("should decode request");
+                    let mut signing_root = request.signing_root;
+                    if corrupt {
+                        signing_root = Hash256::repeat_byte(42);
+                    }
+                    let response = RemoteSignerResponse {
+                        signature: Signature::new(signing_root.as_bytes(), &keypair.sk),
+                    };
+
+                    Response::new(Body::from(
+                        serde_json::to_vec(&response).expect("should encode response"),
+                    ))
+                })
+            })
+        });
+        let addr = server.local_addr();
+
+        thread::spawn(move || hyper::rt::run(server.map_err(|e| panic!("{:?}", e))));
+
+        addr
+    }
+
+    #[test]
+    fn remote_signer_matches_local_keystore() {
+        let keypair = generate_deterministic_keypair(0);
+        let addr = spawn_mock_signer(keypair.clone(), false);
+
+        let local = SigningMethod::LocalKeystore {
+            voting_keypair: keypair.clone(),
+        };
+        let remote = SigningMethod::remote_signer(format!("http://{}", addr), keypair.pk)
+            .expect("should build remote signer");
+
+        let rng = &mut XorShiftRng::from_seed([42; 16]);
+        let spec = E::default_spec();
+        let fork = Fork::random_for_test(rng);
+        let genesis_validators_root = Hash256::random_for_test(rng);
+        let block = BeaconBlock::<E>::random_for_test(rng);
+        let attestation = Attestation::<E>::random_for_test(rng);
+        let aggregate_and_proof = AggregateAndProof::<E>::random_for_test(rng);
+
+        let messages = || {
+            vec![
+                (SignableMessage::RandaoReveal(Epoch::new(3)), Domain::Randao),
+                (SignableMessage::BeaconBlock(&block), Domain::BeaconProposer),
+                (
+                    SignableMessage::AttestationData(&attestation.data),
+                    Domain::BeaconAttester,
+                ),
+                (
+                    SignableMessage::SignedAggregateAndProof(&aggregate_and_proof),
+                    Domain::AggregateAndProof,
+                ),
+                (
+                    SignableMessage::SelectionProof(Slot::new(7)),
+                    Domain::SelectionProof,
+                ),
+            ]
+        };
+
+        for ((local_message, domain), (remote_message, _)) in
+            messages().into_iter().zip(messages().into_iter())
+        {
+            let domain = spec.get_domain(Epoch::new(0), domain, &fork, genesis_validators_root);
+
+            assert_eq!(
+                remote
+                    .get_signature(remote_message, domain, &fork, genesis_validators_root)
+                    .expect("remote signer should sign"),
+                local
+                    .get_signature(local_message, domain, &fork, genesis_validators_root)
+                    .expect("local keystore should sign"),
+                "remote and local signatures should match"
+            );
+        }
+    }
+
+    #[test]
+    fn remote_signer_on_thread_pool() {
+        let keypair = generate_deterministic_keypair(0);
+        let addr = spawn_mock_signer(keypair.clone(), false);
+
+        let local = SigningMethod::LocalKeystore {
+            voting_keypair: keypair.clone(),
+        };
+        let remote = SigningMethod::remote_signer(format!("http://{}", addr), keypair.pk)
+            .expect("should build remote signer");
+
+        let fork = Fork::default();
+        let genesis_validators_root = Hash256::zero();
+        let domain = Hash256::zero();
+        let message = || SignableMessage::<E>::RandaoReveal(Epoch::new(1));
+
+        let mut runtime = Runtime::new().expect("should start runtime");
+        let signature = runtime
+            .block_on(future::lazy(move || {
+                remote.get_signature(message(), domain, &fork, genesis_validators_root)
+            }))
+            .expect("remote signer should sign on the thread pool");
+
+        assert_eq!(
+            signature,
+            local
+                .get_signature(message(), domain, &Fork::default(), genesis_validators_root)
+                .expect("local keystore should sign"),
+            "remote and local signatures should match"
+        );
+    }
+
+    #[test]
+    fn remote_signer_errors() {
+        let keypair = generate_deterministic_keypair(0);
+        let other_keypair = generate_deterministic_keypair(1);
+        let fork = Fork::default();
+        let genesis_validators_root = Hash256::zero();
+        let domain = Hash256::zero();
+
+        let addr = spawn_mock_signer(keypair.clone(), false);
+        let unknown_key =
+            SigningMethod::remote_signer(format!("http://{}", addr), other_keypair.pk)
+                .expect("should build remote signer");
+        assert!(
+            unknown_key
+                .get_signature::<E>(
+                    SignableMessage::RandaoReveal(Epoch::new(0)),
+                    domain,
+                    &fork,
+                    genesis_validators_root
+                )
+                .is_err(),
+            "should error if the signer does not hold the key"
+        );
+
+        let corrupt_addr = spawn_mock_signer(keypair.clone(), true);
+        let corrupt = SigningMethod::remote_signer(format!("http://{}", corrupt_addr), keypair.pk)
+            .expect("should build remote signer");
+        assert!(
+            corrupt
+                .get_signature::<E>(
+                    SignableMessage::RandaoReveal(Epoch::new(0)),
+                    domain,
+                    &fork,
+                    genesis_validators_root
+                )
+                .is_err(),
+            "should reject an invalid signature"
+        );
+    }
+}
@@ -1,14 +1,20 @@
 use bls::get_withdrawal_credentials;
 use deposit_contract::{encode_eth1_tx_data, DEPOSIT_GAS};
+use eth2_keystore::{
+    json_keystore::{Kdf, Pbkdf2, Prf},
+    Keystore, KeystoreBuilder, PlainText, DKLEN, SALT_SIZE,
+};
 use futures::{Future, IntoFuture};
 use hex;
+use rand::{distributions::Alphanumeric, Rng};
+use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use std::fs;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use types::{
     test_utils::generate_deterministic_keypair, ChainSpec, DepositData, Hash256, Keypair,
     PublicKey, SecretKey, Signature,
@@ -18,11 +24,23 @@ use web3::{
     Transport, Web3,
 };
 
+/// The default location of the keystore passwords, relative to the home directory.
+pub const DEFAULT_SECRETS_DIR: &str = ".lighthouse/secrets";
+
+const VOTING_KEYSTORE_FILE: &str = "voting-keystore.json";
+const WITHDRAWAL_KEYSTORE_FILE: &str = "withdrawal-keystore.json";
+const ETH1_DEPOSIT_DATA_FILE: &str = "eth1_deposit_data.rlp";
+/// If present, the voting key is held by a remote signer rather than in a keystore.
+pub const REMOTE_SIGNER_FILE: &str = "remote-signer.json";
+
+/// Prefixes of the legacy, unencrypted SSZ keypair files.
 const VOTING_KEY_PREFIX: &str = "voting";
 const WITHDRAWAL_KEY_PREFIX: &str = "withdrawal";
-const ETH1_DEPOSIT_DATA_FILE: &str = "eth1_deposit_data.rlp";
 
-/// Returns the filename of a keypair file.
+/// The number of characters in a randomly-generated keystore password.
+const PASSWORD_LEN: usize = 48;
+
+/// Returns the filename of a legacy, unencrypted keypair file.
 fn keypair_file(prefix: &str) -> String {
     format!("{}_keypair", prefix)
 }
@@ -32,13 +50,26 @@ fn dir_name(voting_pubkey: &PublicKey) -> String {
     format!("0x{}", hex::encode(voting_pubkey.as_ssz_bytes()))
 }
 
+/// Returns the name of the file in the secrets directory that holds the password for the keystore
+/// of `pubkey`.
+///
+/// `pubkey` is the hex-encoded public key _without_ a `0x` prefix, as stored in the keystore.
+fn password_file_name(pubkey: &str) -> String {
+    format!("0x{}", pubkey)
+}
+
 /// Represents the files/objects for each dedicated lighthouse validator directory.
 ///
 /// Generally lives in `~/.lighthouse/validators/`.
+///
+/// The voting and withdrawal keys are stored as [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335)
+/// keystores. The password for each keystore is stored separately, in a file named after the
+/// keystore's public key in the _secrets_ directory (generally `~/.lighthouse/secrets/`).
 #[derive(Debug, Clone, PartialEq)]
 pub struct ValidatorDirectory {
     pub directory: PathBuf,
     pub voting_keypair: Option<Keypair>,
+    pub remote_signer: Option<RemoteSignerDefinition>,
     pub withdrawal_keypair: Option<Keypair>,
     pub deposit_data: Option<Vec<u8>>,
 }
@@ -46,7 +77,13 @@ pub struct ValidatorDirectory {
 impl ValidatorDirectory {
     /// Attempts to load a validator from the given directory, requiring only components necessary
     /// for signing messages.
-    pub fn load_for_signing(directory: PathBuf) -> Result<Self, String> {
+    ///
+    /// If the directory contains a `REMOTE_SIGNER_FILE`, the voting key is held by the remote
+    /// signer it describes. Otherwise, the voting keystore is decrypted using the password from
+    /// `secrets_dir`.
+    ///
+    /// The withdrawal keystore is never decrypted, so its password need not be present.
+    pub fn load_for_signing(directory: PathBuf, secrets_dir: &Path) -> Result<Self, String> {
         if !directory.exists() {
             return Err(format!(
                 "Validator directory does not exist: {:?}",
@@ -54,19 +91,295 @@ impl ValidatorDirectory {
             ));
         }
 
-        Ok(Self {
-            voting_keypair: Some(
-                load_keypair(directory.clone(), VOTING_KEY_PREFIX)
+        let remote_signer = load_remote_signer_definition(&directory)?;
+
+        let voting_keypair = if remote_signer.is_some() {
+            None
+        } else {
+            if !directory.join(VOTING_KEYSTORE_FILE).exists()
+                && directory.join(keypair_file(VOTING_KEY_PREFIX)).exists()
+            {
+                return Err(format!(
+                    "Validator directory {:?} contains an unencrypted keypair. Run \
+                     `lighthouse account validator migrate` to convert it to a keystore",
+                    directory
+                ));
+            }
+
+            Some(
+                load_keystore(&directory, VOTING_KEYSTORE_FILE, secrets_dir)
                     .map_err(|e| format!("Unable to get voting keypair: {}", e))?,
-            ),
-            withdrawal_keypair: load_keypair(directory.clone(), WITHDRAWAL_KEY_PREFIX).ok(),
+            )
+        };
+
+        Ok(Self {
+            voting_keypair,
+            remote_signer,
+            withdrawal_keypair: None,
             deposit_data: load_eth1_deposit_data(directory.clone()).ok(),
             directory,
         })
     }
+
+    /// Converts a validator directory containing legacy, unencrypted SSZ keypair files into one
+    /// containing EIP-2335 keystores.
+    ///
+    /// Each keypair is encrypted with a new random password, which is stored in `secrets_dir`. The
+    /// unencrypted files are only deleted once the keystores have been written and successfully
+    /// decrypted.
+    ///
+    /// It is safe to run this again after a partial failure: a keystore left behind by an earlier
+    /// attempt is not re-written, but it must decrypt to the unencrypted keypair.
+    ///
+    /// Returns `Ok(false)` if `directory` does not contain any unencrypted keypairs (e.g., it has
+    /// already been migrated).
+    pub fn migrate_to_keystores(directory: &Path, secrets_dir: &Path) -> Result<bool, String> {
+        let legacy_keys = [
+            (VOTING_KEY_PREFIX, VOTING_KEYSTORE_FILE),
+            (WITHDRAWAL_KEY_PREFIX, WITHDRAWAL_KEYSTORE_FILE),
+        ]
+        .iter()
+        .filter(|(prefix, _)| directory.join(keypair_file(prefix)).exists())
+        .collect::<Vec<_>>();
+
+        if legacy_keys.is_empty() {
+            return Ok(false);
+        }
+
+        for (prefix, keystore_file) in &legacy_keys {
+            let keypair = load_keypair(directory.to_path_buf(), prefix)?;
+
+            if !directory.join(keystore_file).exists() {
+                let (keystore, password) = encrypt_keypair(&keypair, secrets_dir, None)?;
+                save_keystore(&keystore, &password, directory, keystore_file, secrets_dir)?;
+            }
+
+            // Check that the keystore can be decrypted before deleting the only other copy of the
+            // key.
+            if load_keystore(directory, keystore_file, secrets_dir)? != keypair {
+                return Err(format!(
+                    "Keystore {:?} does not match the unencrypted keypair",
+                    directory.join(keystore_file)
+                ));
+            }
+        }
+
+        for (prefix, _) in &legacy_keys {
+            let path = directory.join(keypair_file(prefix));
+            fs::remove_file(&path).map_err(|e| format!("Unable to remove {:?}: {}", path, e))?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Describes a voting key that is held by a remote signer, see `crate::signing_method`.
+///
+/// Stored as JSON in the `REMOTE_SIGNER_FILE` of a validator directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignerDefinition {
+    /// The public key of the voting keypair held by the remote signer.
+    pub voting_public_key: PublicKey,
+    /// The base URL of the remote signer (e.g., `http://localhost:9000`).
+    pub url: String,
+}
+
+impl RemoteSignerDefinition {
+    /// Writes `self` to the `REMOTE_SIGNER_FILE` in `directory`.
+    pub fn write_to_directory(&self, directory: &Path) -> Result<(), String> {
+        let path = directory.join(REMOTE_SIGNER_FILE);
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("Unable to create remote signer file {:?}: {}", path, e))?;
+
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|e| format!("Unable to write remote signer file {:?}: {}", path, e))
+    }
+}
+
+/// Loads the `RemoteSignerDefinition` from `directory`, returning `None` if there is no
+/// `REMOTE_SIGNER_FILE`.
+fn load_remote_signer_definition(
+    directory: &Path,
+) -> Result<Option<RemoteSignerDefinition>, String> {
+    let path = directory.join(REMOTE_SIGNER_FILE);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    File::open(&path)
+        .map_err(|e| format!("Unable to open remote signer file {:?}: {}", path, e))
+        .and_then(|file| {
+            serde_json::from_reader(file)
+                .map_err(|e| format!("Unable to parse remote signer file {:?}: {}", path, e))
+        })
+        .map(Some)
+}
+
+/// Load and decrypt the keystore at `file_name` in `directory`, reading the password from
+/// `secrets_dir`.
+fn load_keystore(directory: &Path, file_name: &str, secrets_dir: &Path) -> Result<Keypair, String> {
+    let path = directory.join(file_name);
+
+    if !path.exists() {
+        return Err(format!("Keystore file does not exist: {:?}", path));
+    }
+
+    let keystore = File::open(&path)
+        .map_err(|e| format!("Unable to open keystore file: {}", e))
+        .and_then(|file| {
+            Keystore::from_json_reader(file)
+                .map_err(|e| format!("Unable to parse keystore file: {:?}", e))
+        })?;
+
+    let password_path = secrets_dir.join(password_file_name(keystore.pubkey()));
+    let password = read_password(&password_path)?;
+
+    keystore
+        .decrypt_keypair(password.as_bytes())
+        .map_err(|e| format!("Unable to decrypt keystore {:?}: {:?}", path, e))
+}
+
+/// Reads a password from the file at `path`, removing any trailing newline.
+pub fn read_password(path: &Path) -> Result<PlainText, String> {
+    if !path.exists() {
+        return Err(format!("Password file does not exist: {:?}", path));
+    }
+
+    let mut bytes = vec![];
+
+    File::open(path)
+        .map_err(|e| format!("Unable to open password file: {}", e))?
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Unable to read password file: {}", e))?;
+
+    while bytes.last() == Some(&b'\n') || bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+
+    Ok(bytes.into())
+}
+
+/// Returns a new random password, suitable for encrypting a keystore.
+pub fn random_password() -> PlainText {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PASSWORD_LEN)
+        .collect::<String>()
+        .into_bytes()
+        .into()
+}
+
+/// Encrypts `keypair` into a keystore, returning the keystore and its password.
+///
+/// If `secrets_dir` already holds a password for `keypair` (e.g., when the voting and withdrawal
+/// keys are the same) then that password is reused, otherwise a random password is generated.
+///
+/// Uses `kdf` to encrypt the keystore, if supplied. Otherwise, uses the default KDF of
+/// `KeystoreBuilder`.
+fn encrypt_keypair(
+    keypair: &Keypair,
+    secrets_dir: &Path,
+    kdf: Option<Kdf>,
+) -> Result<(Keystore, PlainText), String> {
+    let password_path = secrets_dir.join(password_file_name(&keypair.pk.as_hex_string()[2..]));
+
+    let password = if password_path.exists() {
+        read_password(&password_path)?
+    } else {
+        random_password()
+    };
+
+    let mut builder = KeystoreBuilder::new(keypair, password.as_bytes(), "".into())
+        .map_err(|e| format!("Unable to create keystore builder: {:?}", e))?;
+    if let Some(kdf) = kdf {
+        builder = builder.kdf(kdf);
+    }
+    let keystore = builder
+        .build()
+        .map_err(|e| format!("Unable to build keystore: {:?}", e))?;
+
+    Ok((keystore, password))
+}
+
+/// Writes `keystore` to `file_name` in `directory` and its `password` to `secrets_dir`.
+///
+/// If `secrets_dir` already holds a password for the keystore, it must be equal to `password`.
+fn save_keystore(
+    keystore: &Keystore,
+    password: &PlainText,
+    directory: &Path,
+    file_name: &str,
+    secrets_dir: &Path,
+) -> Result<(), String> {
+    let path = directory.join(file_name);
+
+    if path.exists() {
+        return Err(format!("Keystore file already exists at: {:?}", path));
+    }
+
+    // Write the password before the keystore, so we never have a keystore on disk that cannot be
+    // decrypted.
+    let password_path = secrets_dir.join(password_file_name(keystore.pubkey()));
+    if password_path.exists() {
+        if read_password(&password_path)? != *password {
+            return Err(format!(
+                "A different password already exists at: {:?}",
+                password_path
+            ));
+        }
+    } else {
+        fs::create_dir_all(secrets_dir)
+            .map_err(|e| format!("Unable to create secrets directory: {}", e))?;
+        create_private_file(&password_path)?
+            .write_all(password.as_bytes())
+            .map_err(|e| format!("Unable to write password file: {}", e))?;
+    }
+
+    keystore
+        .to_json_writer(create_private_file(&path)?)
+        .map_err(|e| format!("Unable to write keystore to file: {:?}", e))
+}
+
+/// Creates a new file at `path` that is only readable and writable by the current user.
+///
+/// Returns an error if the file already exists.
+pub fn create_private_file(path: &Path) -> Result<File, String> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| format!("Unable to create file {:?}: {}", path, e))?;
+
+    // Ensure file has correct permissions.
+    let mut perm = file
+        .metadata()
+        .map_err(|e| format!("Unable to get file metadata: {}", e))?
+        .permissions();
+    perm.set_mode((libc::S_IWUSR | libc::S_IRUSR) as u32);
+    file.set_permissions(perm)
+        .map_err(|e| format!("Unable to set file permissions: {}", e))?;
+
+    Ok(file)
+}
+
+/// Returns a KDF that is very fast to compute, but which provides almost no protection.
+///
+/// Only suitable for keys that are already public knowledge (e.g., the insecure interop keys).
+fn insecure_kdf() -> Kdf {
+    Kdf::Pbkdf2(Pbkdf2 {
+        dklen: DKLEN,
+        c: 2,
+        prf: Prf::HmacSha256,
+        salt: rand::thread_rng().gen::<[u8; SALT_SIZE]>().to_vec().into(),
+    })
 }
 
-/// Load a `Keypair` from a file.
+/// Load a legacy, unencrypted `Keypair` from a file.
 fn load_keypair(base_path: PathBuf, file_prefix: &str) -> Result<Keypair, String> {
     let path = base_path.join(keypair_file(file_prefix));
 
@@ -111,6 +424,8 @@ fn load_eth1_deposit_data(base_path: PathBuf) -> Result<Vec<u8>, String> {
 }
 
 /// A helper struct to allow SSZ enc/dec for a `Keypair`.
+///
+/// Only used to read the legacy, unencrypted keypair files.
 #[derive(Encode, Decode)]
 struct SszEncodableKeypair {
     pk: PublicKey,
@@ -144,6 +459,9 @@ pub struct ValidatorDirectoryBuilder {
     amount: Option<u64>,
     deposit_data: Option<Vec<u8>>,
     spec: Option<ChainSpec>,
+    kdf: Option<Kdf>,
+    voting_keystore: Option<(Keystore, PlainText)>,
+    withdrawal_keystore: Option<(Keystore, PlainText)>,
 }
 
 impl ValidatorDirectoryBuilder {
@@ -172,10 +490,41 @@ impl ValidatorDirectoryBuilder {
         self
     }
 
+    /// Use pre-existing keystores (e.g., those produced by `eth2_wallet::Wallet::next_validator`),
+    /// which are encrypted with `voting_password` and `withdrawal_password` respectively.
+    ///
+    /// The keystores are written to disk as-is, preserving their derivation paths.
+    pub fn keystores(
+        mut self,
+        voting_keystore: Keystore,
+        voting_password: PlainText,
+        withdrawal_keystore: Keystore,
+        withdrawal_password: PlainText,
+    ) -> Result<Self, String> {
+        self.voting_keypair = Some(
+            voting_keystore
+                .decrypt_keypair(voting_password.as_bytes())
+                .map_err(|e| format!("Unable to decrypt voting keystore: {:?}", e))?,
+        );
+        self.withdrawal_keypair = Some(
+            withdrawal_keystore
+                .decrypt_keypair(withdrawal_password.as_bytes())
+                .map_err(|e| format!("Unable to decrypt withdrawal keystore: {:?}", e))?,
+        );
+        self.voting_keystore = Some((voting_keystore, voting_password));
+        self.withdrawal_keystore = Some((withdrawal_keystore, withdrawal_password));
+        Ok(self)
+    }
+
+    /// Use the deterministic, insecure keypair for `index`.
+    ///
+    /// Since these keys are public knowledge, their keystores are encrypted using a fast, insecure
+    /// KDF.
     pub fn insecure_keypairs(mut self, index: usize) -> Self {
         let keypair = generate_deterministic_keypair(index);
         self.voting_keypair = Some(keypair.clone());
         self.withdrawal_keypair = Some(keypair);
+        self.kdf = Some(insecure_kdf());
         self
     }
 
@@ -203,49 +552,56 @@ impl ValidatorDirectoryBuilder {
         Ok(self)
     }
 
-    pub fn write_keypair_files(self) -> Result<Self, String> {
-        let voting_keypair = self
-            .voting_keypair
-            .clone()
-            .ok_or_else(|| "write_keypair_files requires a voting_keypair")?;
-        let withdrawal_keypair = self
-            .withdrawal_keypair
+    /// Writes the voting and withdrawal keypairs as EIP-2335 keystores, storing the keystore
+    /// passwords in `secrets_dir`.
+    ///
+    /// Keystores supplied via `Self::keystores` are written as-is, otherwise each keypair is
+    /// encrypted with a new random password.
+    pub fn write_keystore_files(mut self, secrets_dir: PathBuf) -> Result<Self, String> {
+        let directory = self
+            .directory
             .clone()
-            .ok_or_else(|| "write_keypair_files requires a withdrawal_keypair")?;
+            .ok_or_else(|| "write_keystore_files requires a directory")?;
+
+        let (voting_keystore, voting_password) = match self.voting_keystore.take() {
+            Some(keystore) => keystore,
+            None => encrypt_keypair(
+                self.voting_keypair
+                    .as_ref()
+                    .ok_or_else(|| "write_keystore_files requires a voting_keypair")?,
+                &secrets_dir,
+                self.kdf.clone(),
+            )?,
+        };
+        save_keystore(
+            &voting_keystore,
+            &voting_password,
+            &directory,
+            VOTING_KEYSTORE_FILE,
+            &secrets_dir,
+        )?;
+
+        let (withdrawal_keystore, withdrawal_password) = match self.withdrawal_keystore.take() {
+            Some(keystore) => keystore,
+            None => encrypt_keypair(
+                self.withdrawal_keypair
+                    .as_ref()
+                    .ok_or_else(|| "write_keystore_files requires a withdrawal_keypair")?,
+                &secrets_dir,
+                self.kdf.clone(),
+            )?,
+        };
+        save_keystore(
+            &withdrawal_keystore,
+            &withdrawal_password,
+            &directory,
+            WITHDRAWAL_KEYSTORE_FILE,
+            &secrets_dir,
+        )?;
 
-        self.save_keypair(voting_keypair, VOTING_KEY_PREFIX)?;
-        self.save_keypair(withdrawal_keypair, WITHDRAWAL_KEY_PREFIX)?;
         Ok(self)
     }
 
-    fn save_keypair(&self, keypair: Keypair, file_prefix: &str) -> Result<(), String> {
-        let path = self
-            .directory
-            .as_ref()
-            .map(|directory| directory.join(keypair_file(file_prefix)))
-            .ok_or_else(|| "save_keypair requires a directory")?;
-
-        if path.exists() {
-            return Err(format!("Keypair file already exists at: {:?}", path));
-        }
-
-        let mut file = File::create(&path).map_err(|e| format!("Unable to create file: {}", e))?;
-
-        // Ensure file has correct permissions.
-        let mut perm = file
-            .metadata()
-            .map_err(|e| format!("Unable to get file metadata: {}", e))?
-            .permissions();
-        perm.set_mode((libc::S_IWUSR | libc::S_IRUSR) as u32);
-        file.set_permissions(perm)
-            .map_err(|e| format!("Unable to set file permissions: {}", e))?;
-
-        file.write_all(&SszEncodableKeypair::from(keypair).as_ssz_bytes())
-            .map_err(|e| format!("Unable to write keypair to file: {}", e))?;
-
-        Ok(())
-    }
-
     fn get_deposit_data(&self) -> Result<(Vec<u8>, u64), String> {
         let voting_keypair = self
             .voting_keypair
@@ -332,6 +688,7 @@ impl ValidatorDirectoryBuilder {
         Ok(ValidatorDirectory {
             directory: self.directory.ok_or_else(|| "build requires a directory")?,
             voting_keypair: self.voting_keypair,
+            remote_signer: None,
             withdrawal_keypair: self.withdrawal_keypair,
             deposit_data: self.deposit_data,
         })
@@ -355,6 +712,7 @@ mod tests {
     fn random_keypairs_round_trip() {
         let spec = E::default_spec();
         let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
 
         let created_dir = ValidatorDirectoryBuilder::default()
             .spec(spec)
@@ -363,15 +721,16 @@ mod tests {
             .thread_random_keypairs()
             .create_directory(temp_dir.path().into())
             .expect("should create directory")
-            .write_keypair_files()
-            .expect("should write keypair files")
+            .write_keystore_files(secrets_dir.path().into())
+            .expect("should write keystore files")
             .write_eth1_data_file()
             .expect("should write eth1 data file")
             .build()
             .expect("should build dir");
 
-        let loaded_dir = ValidatorDirectory::load_for_signing(created_dir.directory.clone())
-            .expect("should load directory");
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), secrets_dir.path())
+                .expect("should load directory");
 
         assert_eq!(
             created_dir, loaded_dir,
@@ -383,6 +742,7 @@ mod tests {
     fn deterministic_keypairs_round_trip() {
         let spec = E::default_spec();
         let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
         let index = 42;
 
         let created_dir = ValidatorDirectoryBuilder::default()
@@ -392,8 +752,8 @@ mod tests {
             .insecure_keypairs(index)
             .create_directory(temp_dir.path().into())
             .expect("should create directory")
-            .write_keypair_files()
-            .expect("should write keypair files")
+            .write_keystore_files(secrets_dir.path().into())
+            .expect("should write keystore files")
             .write_eth1_data_file()
             .expect("should write eth1 data file")
             .build()
@@ -432,12 +792,321 @@ mod tests {
             "should have some deposit data"
         );
 
-        let loaded_dir = ValidatorDirectory::load_for_signing(created_dir.directory.clone())
-            .expect("should load directory");
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), secrets_dir.path())
+                .expect("should load directory");
 
         assert_eq!(
             created_dir, loaded_dir,
             "the directory created should match the one loaded"
         );
     }
+
+    #[test]
+    fn keystores_round_trip() {
+        let spec = E::default_spec();
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let voting_keypair = Keypair::random();
+        let withdrawal_keypair = Keypair::random();
+        let voting_path = "m/12381/3600/0/0/0";
+
+        let keystore = |keypair: &Keypair, password: &PlainText, path: &str| {
+            KeystoreBuilder::new(keypair, password.as_bytes(), path.into())
+                .expect("should create keystore builder")
+                .kdf(insecure_kdf())
+                .build()
+                .expect("should build keystore")
+        };
+        let voting_password = random_password();
+        let withdrawal_password = random_password();
+        let voting_keystore = keystore(&voting_keypair, &voting_password, voting_path);
+        let withdrawal_keystore = keystore(
+            &withdrawal_keypair,
+            &withdrawal_password,
+            "m/12381/3600/0/0",
+        );
+
+        assert!(
+            ValidatorDirectoryBuilder::default()
+                .keystores(
+                    voting_keystore.clone(),
+                    withdrawal_password.clone(),
+                    withdrawal_keystore.clone(),
+                    withdrawal_password.clone(),
+                )
+                .is_err(),
+            "should not accept the wrong keystore password"
+        );
+
+        let created_dir = ValidatorDirectoryBuilder::default()
+            .spec(spec)
+            .full_deposit_amount()
+            .expect("should set full deposit amount")
+            .keystores(
+                voting_keystore,
+                voting_password.clone(),
+                withdrawal_keystore,
+                withdrawal_password,
+            )
+            .expect("should use keystores")
+            .create_directory(temp_dir.path().into())
+            .expect("should create directory")
+            .write_keystore_files(secrets_dir.path().into())
+            .expect("should write keystore files")
+            .build()
+            .expect("should build dir");
+
+        let saved = File::open(created_dir.directory.join(VOTING_KEYSTORE_FILE))
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|file| Keystore::from_json_reader(file).map_err(|e| format!("{:?}", e)))
+            .expect("should read voting keystore");
+        assert_eq!(
+            saved.path(),
+            voting_path,
+            "should preserve the derivation path"
+        );
+        assert!(
+            read_password(&secrets_dir.path().join(password_file_name(saved.pubkey())))
+                == Ok(voting_password),
+            "should store the keystore password"
+        );
+
+        assert_eq!(
+            load_keystore(
+                &created_dir.directory,
+                WITHDRAWAL_KEYSTORE_FILE,
+                secrets_dir.path()
+            ),
+            Ok(withdrawal_keypair.clone()),
+            "should store the withdrawal keystore"
+        );
+
+        let withdrawal_password_path = secrets_dir.path().join(password_file_name(&hex::encode(
+            withdrawal_keypair.pk.as_ssz_bytes(),
+        )));
+        fs::remove_file(&withdrawal_password_path).expect("should remove withdrawal password");
+
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(created_dir.directory.clone(), secrets_dir.path())
+                .expect("should load directory without the withdrawal password");
+
+        assert_eq!(loaded_dir.voting_keypair, Some(voting_keypair));
+        assert_eq!(
+            loaded_dir.withdrawal_keypair, None,
+            "should not decrypt the withdrawal keystore"
+        );
+    }
+
+    #[test]
+    fn load_without_password() {
+        let spec = E::default_spec();
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let other_secrets_dir =
+            TempDir::new("acc_manager_secrets").expect("should create test dir");
+
+        let created_dir = ValidatorDirectoryBuilder::default()
+            .spec(spec)
+            .full_deposit_amount()
+            .expect("should set full deposit amount")
+            .insecure_keypairs(1)
+            .create_directory(temp_dir.path().into())
+            .expect("should create directory")
+            .write_keystore_files(secrets_dir.path().into())
+            .expect("should write keystore files")
+            .build()
+            .expect("should build dir");
+
+        assert!(
+            ValidatorDirectory::load_for_signing(
+                created_dir.directory.clone(),
+                other_secrets_dir.path()
+            )
+            .is_err(),
+            "should not load without the password"
+        );
+    }
+
+    #[test]
+    fn load_remote_signer() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let voting_keypair = Keypair::random();
+
+        let directory = temp_dir.path().join(dir_name(&voting_keypair.pk));
+        fs::create_dir_all(&directory).expect("should create directory");
+
+        let definition = RemoteSignerDefinition {
+            voting_public_key: voting_keypair.pk,
+            url: "http://localhost:9000".to_string(),
+        };
+        definition
+            .write_to_directory(&directory)
+            .expect("should write remote signer file");
+
+        let loaded_dir = ValidatorDirectory::load_for_signing(directory, secrets_dir.path())
+            .expect("should load directory without a keystore");
+
+        assert_eq!(loaded_dir.remote_signer, Some(definition));
+        assert_eq!(loaded_dir.voting_keypair, None);
+    }
+
+    #[test]
+    fn migrate_unencrypted_keypairs() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let voting_keypair = Keypair::random();
+        let withdrawal_keypair = Keypair::random();
+
+        let directory = temp_dir.path().join(dir_name(&voting_keypair.pk));
+        fs::create_dir_all(&directory).expect("should create directory");
+        for (keypair, prefix) in &[
+            (&voting_keypair, VOTING_KEY_PREFIX),
+            (&withdrawal_keypair, WITHDRAWAL_KEY_PREFIX),
+        ] {
+            File::create(directory.join(keypair_file(prefix)))
+                .expect("should create keypair file")
+                .write_all(&SszEncodableKeypair::from((*keypair).clone()).as_ssz_bytes())
+                .expect("should write keypair file");
+        }
+
+        assert!(
+            ValidatorDirectory::load_for_signing(directory.clone(), secrets_dir.path()).is_err(),
+            "should not load an unencrypted directory"
+        );
+
+        assert_eq!(
+            ValidatorDirectory::migrate_to_keystores(&directory, secrets_dir.path()),
+            Ok(true),
+            "should migrate directory"
+        );
+        assert_eq!(
+            ValidatorDirectory::migrate_to_keystores(&directory, secrets_dir.path()),
+            Ok(false),
+            "should not migrate directory twice"
+        );
+        assert!(
+            !directory.join(keypair_file(VOTING_KEY_PREFIX)).exists(),
+            "should remove unencrypted voting keypair"
+        );
+        assert!(
+            !directory.join(keypair_file(WITHDRAWAL_KEY_PREFIX)).exists(),
+            "should remove unencrypted withdrawal keypair"
+        );
+
+        assert_eq!(
+            load_keystore(&directory, WITHDRAWAL_KEYSTORE_FILE, secrets_dir.path()),
+            Ok(withdrawal_keypair),
+            "should migrate the withdrawal keypair"
+        );
+
+        let loaded_dir = ValidatorDirectory::load_for_signing(directory, secrets_dir.path())
+            .expect("should load directory");
+
+        assert_eq!(loaded_dir.voting_keypair, Some(voting_keypair));
+    }
+
+    #[test]
+    fn resume_interrupted_migration() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let voting_keypair = Keypair::random();
+        let withdrawal_keypair = Keypair::random();
+
+        let directory = temp_dir.path().join(dir_name(&voting_keypair.pk));
+        fs::create_dir_all(&directory).expect("should create directory");
+        for (keypair, prefix) in &[
+            (&voting_keypair, VOTING_KEY_PREFIX),
+            (&withdrawal_keypair, WITHDRAWAL_KEY_PREFIX),
+        ] {
+            File::create(directory.join(keypair_file(prefix)))
+                .expect("should create keypair file")
+                .write_all(&SszEncodableKeypair::from((*keypair).clone()).as_ssz_bytes())
+                .expect("should write keypair file");
+        }
+
+        // Simulate a migration that was interrupted after writing the voting keystore but before
+        // writing the withdrawal keystore, leaving both unencrypted keypair files in place.
+        let (keystore, password) = encrypt_keypair(&voting_keypair, secrets_dir.path(), None)
+            .expect("should encrypt keypair");
+        save_keystore(
+            &keystore,
+            &password,
+            &directory,
+            VOTING_KEYSTORE_FILE,
+            secrets_dir.path(),
+        )
+        .expect("should save keystore");
+
+        assert_eq!(
+            ValidatorDirectory::migrate_to_keystores(&directory, secrets_dir.path()),
+            Ok(true),
+            "should resume migration"
+        );
+
+        assert_eq!(
+            load_keystore(&directory, WITHDRAWAL_KEYSTORE_FILE, secrets_dir.path()),
+            Ok(withdrawal_keypair),
+            "should migrate the withdrawal keypair"
+        );
+
+        let loaded_dir =
+            ValidatorDirectory::load_for_signing(directory.clone(), secrets_dir.path())
+                .expect("should load directory");
+
+        assert_eq!(loaded_dir.voting_keypair, Some(voting_keypair));
+
+        // Simulate a directory in which the voting keypair has been migrated and removed, but the
+        // withdrawal keystore has not yet been written.
+        fs::remove_file(directory.join(WITHDRAWAL_KEYSTORE_FILE)).expect("should remove keystore");
+        File::create(directory.join(keypair_file(WITHDRAWAL_KEY_PREFIX)))
+            .expect("should create keypair file")
+            .write_all(&SszEncodableKeypair::from(Keypair::random()).as_ssz_bytes())
+            .expect("should write keypair file");
+
+        assert_eq!(
+            ValidatorDirectory::migrate_to_keystores(&directory, secrets_dir.path()),
+            Ok(true),
+            "should migrate the remaining keypair"
+        );
+        assert!(
+            !directory.join(keypair_file(WITHDRAWAL_KEY_PREFIX)).exists(),
+            "should remove unencrypted withdrawal keypair"
+        );
+    }
+
+    #[test]
+    fn migration_rejects_mismatched_keystore() {
+        let temp_dir = TempDir::new("acc_manager").expect("should create test dir");
+        let secrets_dir = TempDir::new("acc_manager_secrets").expect("should create test dir");
+        let voting_keypair = Keypair::random();
+
+        let directory = temp_dir.path().join(dir_name(&voting_keypair.pk));
+        fs::create_dir_all(&directory).expect("should create directory");
+        File::create(directory.join(keypair_file(VOTING_KEY_PREFIX)))
+            .expect("should create keypair file")
+            .write_all(&SszEncodableKeypair::from(voting_keypair).as_ssz_bytes())
+            .expect("should write keypair file");
+
+        let (keystore, password) = encrypt_keypair(&Keypair::random(), secrets_dir.path(), None)
+            .expect("should encrypt keypair");
+        save_keystore(
+            &keystore,
+            &password,
+            &directory,
+            VOTING_KEYSTORE_FILE,
+            secrets_dir.path(),
+        )
+        .expect("should save keystore");
+
+        assert!(
+            ValidatorDirectory::migrate_to_keystores(&directory, secrets_dir.path()).is_err(),
+            "should not migrate over a different keystore"
+        );
+        assert!(
+            directory.join(keypair_file(VOTING_KEY_PREFIX)).exists(),
+            "should keep unencrypted voting keypair"
+        );
+    }
 }
@@ -1,24 +1,29 @@
 use crate::fork_service::ForkService;
+use crate::signing_method::{SignableMessage, SigningMethod};
 use crate::validator_directory::{ValidatorDirectory, ValidatorDirectoryBuilder};
 use parking_lot::RwLock;
 use rayon::prelude::*;
-use slog::{error, Logger};
+use slashing_protection::{NotSafe, Safe, SlashingDatabase, SLASHING_PROTECTION_FILENAME};
+use slog::{crit, error, warn, Logger};
 use slot_clock::SlotClock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::read_dir;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tempdir::TempDir;
 use types::{
-    Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Hash256, PublicKey,
-    SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
+    AggregateAndProof, Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Hash256,
+    PublicKey, SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, Slot,
 };
 
 #[derive(Clone)]
 pub struct ValidatorStore<T, E: EthSpec> {
-    validators: Arc<RwLock<HashMap<PublicKey, ValidatorDirectory>>>,
+    validators: Arc<RwLock<HashMap<PublicKey, Arc<SigningMethod>>>>,
+    /// Validators which must not sign any message, see `DoppelgangerService`.
+    silenced_validators: Arc<RwLock<HashSet<PublicKey>>>,
+    slashing_protection: SlashingDatabase,
     genesis_validators_root: Hash256,
     spec: Arc<ChainSpec>,
     log: Logger,
@@ -30,6 +35,7 @@ pub struct ValidatorStore<T, E: EthSpec> {
 impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     pub fn load_from_disk(
         base_dir: PathBuf,
+        secrets_dir: PathBuf,
         genesis_validators_root: Hash256,
         spec: ChainSpec,
         fork_service: ForkService<T, E>,
@@ -43,7 +49,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 let path = validator_dir.ok()?.path();
 
                 if path.is_dir() {
-                    match ValidatorDirectory::load_for_signing(path.clone()) {
+                    match ValidatorDirectory::load_for_signing(path.clone(), &secrets_dir) {
                         Ok(validator_directory) => Some(validator_directory),
                         Err(e) => {
                             error!(
@@ -60,14 +66,31 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 }
             })
             .filter_map(|validator_directory| {
-                validator_directory
-                    .voting_keypair
-                    .clone()
-                    .map(|voting_keypair| (voting_keypair.pk, validator_directory))
+                match SigningMethod::from_validator_directory(&validator_directory) {
+                    Ok(signing_method) => Some((
+                        signing_method.voting_public_key().clone(),
+                        Arc::new(signing_method),
+                    )),
+                    Err(e) => {
+                        error!(
+                            log,
+                            "Failed to initialize validator signing method";
+                            "error" => e,
+                            "path" => validator_directory.directory.to_str(),
+                        );
+                        None
+                    }
+                }
             });
 
+        let validators = HashMap::from_par_iter(validator_key_values);
+
+        let slashing_protection = open_slashing_protection(&base_dir, validators.keys())?;
+
         Ok(Self {
-            validators: Arc::new(RwLock::new(HashMap::from_par_iter(validator_key_values))),
+            validators: Arc::new(RwLock::new(validators)),
+            silenced_validators: Arc::new(RwLock::new(HashSet::new())),
+            slashing_protection,
             genesis_validators_root,
             spec: Arc::new(spec),
             log,
@@ -87,6 +110,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         let temp_dir = TempDir::new("insecure_validator")
             .map_err(|e| format!("Unable to create temp dir: {:?}", e))?;
         let data_dir = PathBuf::from(temp_dir.path());
+        let secrets_dir = data_dir.join("secrets");
 
         let validators = validator_indices
             .par_iter()
@@ -96,21 +120,28 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     .full_deposit_amount()?
                     .insecure_keypairs(*index)
                     .create_directory(data_dir.clone())?
-                    .write_keypair_files()?
+                    .write_keystore_files(secrets_dir.clone())?
                     .write_eth1_data_file()?
                     .build()
             })
             .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .filter_map(|validator_directory| {
-                validator_directory
-                    .voting_keypair
-                    .clone()
-                    .map(|voting_keypair| (voting_keypair.pk, validator_directory))
+                validator_directory.voting_keypair.map(|voting_keypair| {
+                    (
+                        voting_keypair.pk.clone(),
+                        Arc::new(SigningMethod::LocalKeystore { voting_keypair }),
+                    )
+                })
             });
+        let validators = HashMap::from_iter(validators);
+
+        let slashing_protection = open_slashing_protection(&data_dir, validators.keys())?;
 
         Ok(Self {
-            validators: Arc::new(RwLock::new(HashMap::from_iter(validators))),
+            validators: Arc::new(RwLock::new(validators)),
+            silenced_validators: Arc::new(RwLock::new(HashSet::new())),
+            slashing_protection,
             genesis_validators_root,
             spec: Arc::new(spec),
             log,
@@ -120,14 +151,39 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         })
     }
 
+    /// Returns the pubkeys of the validators which are permitted to sign messages.
     pub fn voting_pubkeys(&self) -> Vec<PublicKey> {
+        let silenced_validators = self.silenced_validators.read();
         self.validators
             .read()
             .iter()
-            .map(|(pubkey, _dir)| pubkey.clone())
+            .filter(|(pubkey, _signing_method)| !silenced_validators.contains(pubkey))
+            .map(|(pubkey, _signing_method)| pubkey.clone())
             .collect()
     }
 
+    /// Prevents all validators from signing any message until they are unsilenced.
+    pub fn silence_all(&self) {
+        let validators = self.validators.read();
+        self.silenced_validators
+            .write()
+            .extend(validators.keys().cloned());
+    }
+
+    /// Permits the given validator to sign messages.
+    pub fn unsilence(&self, validator_pubkey: &PublicKey) {
+        self.silenced_validators.write().remove(validator_pubkey);
+    }
+
+    /// Returns the pubkeys of the validators which are not permitted to sign messages.
+    pub fn silenced_pubkeys(&self) -> Vec<PublicKey> {
+        self.silenced_validators.read().iter().cloned().collect()
+    }
+
+    fn is_silenced(&self, validator_pubkey: &PublicKey) -> bool {
+        self.silenced_validators.read().contains(validator_pubkey)
+    }
+
     pub fn num_voting_validators(&self) -> usize {
         self.validators.read().len()
     }
@@ -142,23 +198,47 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.fork_service.fork()
     }
 
+    /// Signs `message` with the signing method of `validator_pubkey`, logging any failure.
+    fn sign(
+        &self,
+        validator_pubkey: &PublicKey,
+        message: SignableMessage<E>,
+        domain: Hash256,
+        fork: &Fork,
+    ) -> Option<Signature> {
+        // Clone the signing method so the lock is not held whilst waiting for a remote signer.
+        let signing_method = self.validators.read().get(validator_pubkey)?.clone();
+
+        signing_method
+            .get_signature(message, domain, fork, self.genesis_validators_root)
+            .map_err(|e| {
+                error!(
+                    self.log,
+                    "Unable to sign message";
+                    "validator" => format!("{:?}", validator_pubkey),
+                    "error" => e,
+                )
+            })
+            .ok()
+    }
+
     pub fn randao_reveal(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<Signature> {
+        if self.is_silenced(validator_pubkey) {
+            return None;
+        }
+
         // TODO: check this against the slot clock to make sure it's not an early reveal?
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|validator_dir| {
-                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
-                let domain = self.spec.get_domain(
-                    epoch,
-                    Domain::Randao,
-                    &self.fork()?,
-                    self.genesis_validators_root,
-                );
-                let message = epoch.signing_root(domain);
-
-                Some(Signature::new(message.as_bytes(), &voting_keypair.sk))
-            })
+        let fork = self.fork()?;
+        let domain =
+            self.spec
+                .get_domain(epoch, Domain::Randao, &fork, self.genesis_validators_root);
+
+        self.sign(
+            validator_pubkey,
+            SignableMessage::RandaoReveal(epoch),
+            domain,
+            &fork,
+        )
     }
 
     pub fn sign_block(
@@ -166,19 +246,52 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         block: BeaconBlock<E>,
     ) -> Option<SignedBeaconBlock<E>> {
-        // TODO: check for slashing.
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|validator_dir| {
-                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
-                Some(block.sign(
-                    &voting_keypair.sk,
-                    &self.fork()?,
-                    self.genesis_validators_root,
-                    &self.spec,
-                ))
-            })
+        if self.is_silenced(validator_pubkey) {
+            return None;
+        }
+
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            block.epoch(),
+            Domain::BeaconProposer,
+            &fork,
+            self.genesis_validators_root,
+        );
+
+        // Check for slashing conditions, recording the block if it is safe to sign.
+        let slashing_status = self.slashing_protection.check_and_insert_block_proposal(
+            validator_pubkey,
+            &block.block_header(),
+            domain,
+        );
+
+        match slashing_status {
+            Ok(Safe::Valid) => {}
+            // Signing the exact same block twice is not slashable. It happens if the VC restarts
+            // after signing a block but before publishing it.
+            Ok(Safe::SameData) => warn!(
+                self.log,
+                "Signing previously signed block";
+                "slot" => block.slot,
+                "validator" => format!("{:?}", validator_pubkey),
+            ),
+            Err(e) => {
+                self.log_slashing_refusal("block", validator_pubkey, e);
+                return None;
+            }
+        }
+
+        let signature = self.sign(
+            validator_pubkey,
+            SignableMessage::BeaconBlock(&block),
+            domain,
+            &fork,
+        )?;
+
+        Some(SignedBeaconBlock {
+            message: block,
+            signature,
+        })
     }
 
     pub fn sign_attestation(
@@ -187,38 +300,91 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_committee_position: usize,
         attestation: &mut Attestation<E>,
     ) -> Option<()> {
-        // TODO: check for slashing.
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|validator_dir| {
-                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
-
-                attestation
-                    .sign(
-                        &voting_keypair.sk,
-                        validator_committee_position,
-                        &self.fork()?,
-                        self.genesis_validators_root,
-                        &self.spec,
-                    )
-                    .map_err(|e| {
-                        error!(
-                            self.log,
-                            "Error whilst signing attestation";
-                            "error" => format!("{:?}", e)
-                        )
-                    })
-                    .ok()?;
-
-                Some(())
+        if self.is_silenced(validator_pubkey) {
+            return None;
+        }
+
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            attestation.data.target.epoch,
+            Domain::BeaconAttester,
+            &fork,
+            self.genesis_validators_root,
+        );
+
+        // Check for slashing conditions, recording the attestation if it is safe to sign.
+        let slashing_status = self.slashing_protection.check_and_insert_attestation(
+            validator_pubkey,
+            &attestation.data,
+            domain,
+        );
+
+        match slashing_status {
+            Ok(Safe::Valid) => {}
+            Ok(Safe::SameData) => warn!(
+                self.log,
+                "Signing previously signed attestation";
+                "slot" => attestation.data.slot,
+                "validator" => format!("{:?}", validator_pubkey),
+            ),
+            Err(e) => {
+                self.log_slashing_refusal("attestation", validator_pubkey, e);
+                return None;
+            }
+        }
+
+        let signature = self.sign(
+            validator_pubkey,
+            SignableMessage::AttestationData(&attestation.data),
+            domain,
+            &fork,
+        )?;
+
+        attestation
+            .add_signature(&signature, validator_committee_position)
+            .map_err(|e| {
+                error!(
+                    self.log,
+                    "Error whilst signing attestation";
+                    "error" => format!("{:?}", e)
+                )
             })
+            .ok()
+    }
+
+    /// Logs the reason why the slashing protection database refused to sign a message.
+    fn log_slashing_refusal(&self, kind: &str, validator_pubkey: &PublicKey, e: NotSafe) {
+        match e {
+            NotSafe::UnregisteredValidator(pk) => warn!(
+                self.log,
+                "Not signing for unregistered validator";
+                "msg" => kind,
+                "public_key" => format!("{:?}", pk),
+            ),
+            NotSafe::InvalidBlock(_) | NotSafe::InvalidAttestation(_) => crit!(
+                self.log,
+                "Not signing slashable message";
+                "msg" => kind,
+                "validator" => format!("{:?}", validator_pubkey),
+                "error" => format!("{:?}", e),
+            ),
+            _ => error!(
+                self.log,
+                "Slashing protection check failed";
+                "msg" => kind,
+                "validator" => format!("{:?}", validator_pubkey),
+                "error" => format!("{:?}", e),
+            ),
+        }
     }
 
     /// Signs an `AggregateAndProof` for a given validator.
     ///
     /// The resulting `SignedAggregateAndProof` is sent on the aggregation channel and cannot be
     /// modified by actors other than the signing validator.
+    ///
+    /// Signing an aggregate is not a slashable action (the validator signs the `AggregateAndProof`,
+    /// not the `AttestationData`) so the slashing protection database is not consulted.
     pub fn produce_signed_aggregate_and_proof(
         &self,
         validator_pubkey: &PublicKey,
@@ -226,18 +392,32 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         aggregate: Attestation<E>,
         selection_proof: SelectionProof,
     ) -> Option<SignedAggregateAndProof<E>> {
-        let validators = self.validators.read();
-        let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
+        if self.is_silenced(validator_pubkey) {
+            return None;
+        }
 
-        Some(SignedAggregateAndProof::from_aggregate(
-            validator_index,
-            aggregate,
-            Some(selection_proof),
-            &voting_keypair.sk,
-            &self.fork()?,
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            aggregate.data.slot.epoch(E::slots_per_epoch()),
+            Domain::AggregateAndProof,
+            &fork,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+
+        let message = AggregateAndProof {
+            aggregator_index: validator_index,
+            aggregate,
+            selection_proof: selection_proof.into(),
+        };
+
+        let signature = self.sign(
+            validator_pubkey,
+            SignableMessage::SignedAggregateAndProof(&message),
+            domain,
+            &fork,
+        )?;
+
+        Some(SignedAggregateAndProof { message, signature })
     }
 
     /// Produces a `SelectionProof` for the `slot`, signed by with corresponding secret key to
@@ -247,15 +427,51 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         slot: Slot,
     ) -> Option<SelectionProof> {
-        let validators = self.validators.read();
-        let voting_keypair = validators.get(validator_pubkey)?.voting_keypair.as_ref()?;
+        if self.is_silenced(validator_pubkey) {
+            return None;
+        }
 
-        Some(SelectionProof::new::<E>(
-            slot,
-            &voting_keypair.sk,
-            &self.fork()?,
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            slot.epoch(E::slots_per_epoch()),
+            Domain::SelectionProof,
+            &fork,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+
+        self.sign(
+            validator_pubkey,
+            SignableMessage::SelectionProof(slot),
+            domain,
+            &fork,
+        )
+        .map(SelectionProof::from)
     }
 }
+
+/// Opens (or creates) the slashing protection database in `validators_dir` and registers each of
+/// the `voting_pubkeys` with it.
+fn open_slashing_protection<'a>(
+    validators_dir: &Path,
+    voting_pubkeys: impl Iterator<Item = &'a PublicKey>,
+) -> Result<SlashingDatabase, String> {
+    let path = validators_dir.join(SLASHING_PROTECTION_FILENAME);
+
+    let slashing_protection = SlashingDatabase::open_or_create(&path).map_err(|e| {
+        format!(
+            "Failed to open or create slashing protection database at {:?}: {:?}",
+            path, e
+        )
+    })?;
+
+    slashing_protection
+        .register_validators(voting_pubkeys)
+        .map_err(|e| {
+            format!(
+                "Failed to register validators for slashing protection: {:?}",
+                e
+            )
+        })?;
+
+    Ok(slashing_protection)
+}